@@ -83,6 +83,12 @@ class CheckFile:
                 if line == directive[1]:
                     return state
             raise Exception("No matching line found")
+        elif dirname == "CHECK-NOT":
+            for line in copy(state):
+                line = self.ansi_escape.sub("", line)
+                if directive[1] in line:
+                    raise Exception("Unexpected line found: {}".format(line.strip()))
+            return state
         else:
             raise Exception("Unknown directive `{}`".format(directive[0]))
 
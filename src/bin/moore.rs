@@ -65,6 +65,28 @@ fn main() {
                 ])
                 .global(true),
         )
+        .arg(
+            Arg::with_name("warning-opts")
+                .short("W")
+                .value_name("LINT")
+                .help(
+                    "Enables a lint, disables it with a `no-` prefix, or turns it into an error \
+                     with an `error=` prefix; `all` refers to every lint",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("lint-config")
+                .long("lint-config")
+                .value_name("FILE")
+                .help("Read the levels of lints from FILE")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
         .arg(
             Arg::with_name("inc")
                 .short("I")
@@ -112,6 +134,34 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("coverage-map")
+                .long("coverage-map")
+                .value_name("FILE")
+                .help("Write the counter layout of covergroup instances to FILE")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("cdc-report")
+                .long("cdc-report")
+                .value_name("FILE")
+                .help(
+                    "Write the clock domains and the signals crossing between them to FILE, or \
+                     to stdout if FILE is `-`",
+                )
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seed the random number generators, also accepted as +seed=SEED")
+                .default_value("0")
+                .takes_value(true)
+                .number_of_values(1),
+        )
         .arg(
             Arg::with_name("lib")
                 .short("l")
@@ -137,7 +187,7 @@ fn main() {
                 .multiple(true)
                 .required(true),
         )
-        .get_matches();
+        .get_matches_from(std::env::args().map(map_plusarg));
 
     // Configure the session.
     let mut session = Session::new();
@@ -160,12 +210,71 @@ fn main() {
             _ => unreachable!(),
         };
     }
+    if let Some(path) = matches.value_of("lint-config") {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                session.emit(DiagBuilder2::fatal(format!(
+                    "unable to read lint configuration `{}`: {}",
+                    path, e
+                )));
+                std::process::exit(1);
+            }
+        };
+        if let Err(e) = session.opts.lints.load_config(&text) {
+            session.emit(DiagBuilder2::fatal(format!(
+                "invalid lint configuration `{}`: {}",
+                path, e
+            )));
+            std::process::exit(1);
+        }
+    }
+    for w in matches
+        .values_of("warning-opts")
+        .into_iter()
+        .flat_map(|w| w)
+    {
+        let (level, name) = if w.starts_with("no-") {
+            (lint::Level::Allow, &w[3..])
+        } else if w.starts_with("error=") {
+            (lint::Level::Deny, &w[6..])
+        } else {
+            (lint::Level::Warn, w)
+        };
+        if let Err(e) = session.opts.lints.set_by_name(name, level) {
+            session.emit(DiagBuilder2::fatal(format!(
+                "invalid option `-W {}`: {}",
+                w, e
+            )));
+            std::process::exit(1);
+        }
+    }
     session.opts.opt_level = matches.value_of("opt-level").unwrap().parse().unwrap();
+    session.opts.coverage_map = matches.value_of("coverage-map").map(String::from);
+    session.opts.cdc_report = matches.value_of("cdc-report").map(String::from);
+    session.opts.seed = match matches.value_of("seed").unwrap().parse() {
+        Ok(seed) => seed,
+        Err(_) => {
+            session.emit(DiagBuilder2::fatal(
+                "seed must be an unsigned 32 bit integer",
+            ));
+            std::process::exit(1);
+        }
+    };
 
     // Invoke the compiler.
     score(&session, &matches);
 }
 
+/// Map simulator-style `+seed=N` arguments to `--seed=N`.
+fn map_plusarg(arg: String) -> String {
+    if arg.starts_with("+seed=") {
+        format!("--seed={}", &arg[6..])
+    } else {
+        arg
+    }
+}
+
 fn score(sess: &Session, matches: &ArgMatches) {
     use crate::name::get_name_table;
     let svlog_arenas = svlog::GlobalArenas::default();
@@ -257,7 +366,7 @@ fn score(sess: &Session, matches: &ArgMatches) {
                 }
 
                 let lexer = svlog::lexer::Lexer::new(preproc);
-                match svlog::parser::parse(lexer, &svlog_arenas.ast) {
+                match svlog::parser::parse(lexer, &svlog_arenas.ast, &sess.opts.lints) {
                     Ok(x) => asts.push(score::Ast::Svlog(x)),
                     Err(()) => failed = true,
                 }
@@ -469,6 +578,27 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
 
             let mut cg = svlog::CodeGenerator::new(ctx.svlog);
             cg.emit_module(m)?;
+            if let Some(ref path) = ctx.sess.opts.coverage_map {
+                if let Err(e) = std::fs::write(path, cg.coverage_map()) {
+                    ctx.sess.emit(DiagBuilder2::error(format!(
+                        "unable to write coverage map `{}`: {}",
+                        path, e
+                    )));
+                    return Err(());
+                }
+            }
+            if let Some(ref path) = ctx.sess.opts.cdc_report {
+                let report = cg.cdc_report();
+                if path == "-" {
+                    print!("{}", report);
+                } else if let Err(e) = std::fs::write(path, report) {
+                    ctx.sess.emit(DiagBuilder2::error(format!(
+                        "unable to write clock domain crossing report `{}`: {}",
+                        path, e
+                    )));
+                    return Err(());
+                }
+            }
             let mut module = cg.finalize();
             let pass_ctx = PassContext;
             if ctx.sess.opts.opt_level > 0 {
@@ -2,6 +2,7 @@
 
 //! Utilities to implement diagnostics and error reporting facilities.
 
+use crate::lint::Lint;
 use crate::source::Span;
 use std::fmt;
 
@@ -75,6 +76,8 @@ pub struct DiagBuilder2 {
     pub severity: Severity,
     pub message: String,
     pub segments: Vec<DiagSegment>,
+    /// The lint that produced this diagnostic, if any.
+    pub lint: Option<&'static Lint>,
 }
 
 #[derive(Clone, Debug)]
@@ -93,6 +96,7 @@ impl DiagBuilder2 {
             severity: severity,
             message: message.into(),
             segments: Vec::new(),
+            lint: None,
         }
     }
 
@@ -116,6 +120,17 @@ impl DiagBuilder2 {
         DiagBuilder2::new(Severity::Note, message)
     }
 
+    /// Create a warning reported by a lint.
+    ///
+    /// The warning is dropped or promoted to an error according to the level
+    /// of the lint when it is emitted.
+    pub fn lint<S: Into<String>>(lint: &'static Lint, message: S) -> DiagBuilder2 {
+        DiagBuilder2 {
+            lint: Some(lint),
+            ..DiagBuilder2::new(Severity::Warning, message)
+        }
+    }
+
     pub fn segment(self, segment: DiagSegment) -> DiagBuilder2 {
         let mut segments = self.segments;
         segments.push(segment);
@@ -12,6 +12,7 @@ pub mod errors;
 pub mod grind;
 pub mod id;
 pub mod lexer;
+pub mod lint;
 pub mod name;
 pub mod score;
 pub mod source;
@@ -19,6 +20,7 @@ pub mod util;
 
 pub use self::id::NodeId;
 use crate::errors::{DiagBuilder2, DiagEmitter, Severity};
+use crate::lint::{Level, Lint, LintLevels};
 use std::cell::Cell;
 
 pub struct Session {
@@ -39,10 +41,19 @@ impl Session {
     pub fn failed(&self) -> bool {
         self.failed.get()
     }
+
+    /// Check if a lint is enabled.
+    pub fn has_lint(&self, lint: &Lint) -> bool {
+        self.opts.lints.level(lint) != Level::Allow
+    }
 }
 
 impl DiagEmitter for Session {
     fn emit(&self, diag: DiagBuilder2) {
+        let diag = match self.opts.lints.apply(diag) {
+            Some(diag) => diag,
+            None => return,
+        };
         if diag.severity >= Severity::Error {
             self.failed.set(true);
         }
@@ -73,8 +84,17 @@ pub struct SessionOptions {
     pub trace_scoreboard: bool,
     /// The verbosity options.
     pub verbosity: Verbosity,
+    /// The levels of the lint warnings.
+    pub lints: LintLevels,
     /// The optimization level.
     pub opt_level: usize,
+    /// The file to write the covergroup counter layout to.
+    pub coverage_map: Option<String>,
+    /// The file to write the clock domain crossing report to.
+    pub cdc_report: Option<String>,
+    /// The seed of the random number generators used by `randomize()`,
+    /// `$random`, and `$urandom`.
+    pub seed: u32,
 }
 
 bitflags! {
@@ -0,0 +1,417 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! A registry of lint warnings.
+//!
+//! Every warning about code that is legal, but likely to be a mistake, is a
+//! lint with a stable name and a default level. The level of each lint can be
+//! changed on the command line or in a configuration file, and individual
+//! occurrences can be suppressed with a `// moore: allow(<name>)` comment in
+//! the source code.
+
+use crate::errors::{DiagBuilder2, DiagSegment, Severity};
+use crate::source::{Source, Span};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// The level of a lint.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum Level {
+    /// Do not report the lint.
+    Allow,
+    /// Report the lint as a warning.
+    Warn,
+    /// Report the lint as an error.
+    Deny,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Level, String> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            _ => Err(format!(
+                "unknown level `{}`; expected `allow`, `warn`, or `deny`",
+                s
+            )),
+        }
+    }
+}
+
+impl Level {
+    /// Get the name of the level.
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+        }
+    }
+}
+
+/// A lint.
+#[derive(Debug)]
+pub struct Lint {
+    /// The stable name of the lint, used on the command line, in configuration
+    /// files, and in `allow` comments.
+    pub name: &'static str,
+    /// The level of the lint if it is not configured otherwise.
+    pub default: Level,
+    /// A short description of what the lint warns about.
+    pub desc: &'static str,
+    /// The options of the lint and their default values.
+    pub options: &'static [(&'static str, &'static str)],
+}
+
+macro_rules! declare_lints {
+    ($(
+        $(#[$attr:meta])*
+        $id:ident: $name:expr, $level:ident, $desc:expr
+        $(, { $($key:expr => $value:expr),* $(,)? })?;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub static $id: Lint = Lint {
+                name: $name,
+                default: Level::$level,
+                desc: $desc,
+                options: &[$($(($key, $value)),*)?],
+            };
+        )*
+
+        /// All lints known to the compiler.
+        pub static LINTS: &[&Lint] = &[$(&$id),*];
+    };
+}
+
+declare_lints! {
+    /// Implicit truncation of a value that does not fit.
+    TRUNCATION: "truncation", Allow,
+        "a value implicitly truncated to fewer bits";
+    /// Implicit extension of a non-constant value.
+    EXTENSION: "extension", Allow,
+        "a non-constant value implicitly extended to more bits";
+    /// Implicit conversion between signed and unsigned values.
+    SIGN: "sign", Allow,
+        "a non-constant value implicitly converted between signed and unsigned";
+    /// Unsized literals that do not fit into the assigned type.
+    LITERAL_OVERFLOW: "literal-overflow", Allow,
+        "an unsized literal that does not fit into the assigned type";
+    /// Nets that are never driven.
+    UNDRIVEN: "undriven", Allow,
+        "a net or output port net that has no driver";
+    /// Signals that are never read.
+    UNREAD: "unread", Allow,
+        "a variable, net, or input port that is never read";
+    /// Instance inputs that are not connected.
+    UNCONNECTED: "unconnected", Allow,
+        "an instance input that is not connected and has no default";
+    /// Superfluous commas at the end of a list.
+    TRAILING_COMMA: "trailing-comma", Warn,
+        "a superfluous comma at the end of a list";
+    /// Positional parameter or port connections after named ones.
+    POSITIONAL_AFTER_NAMED: "positional-after-named", Warn,
+        "a positional parameter or port connection after a named one";
+    /// Inconsistent block labels.
+    BLOCK_LABEL: "block-label", Warn,
+        "a block with two labels, or a trailing label without a leading one";
+    /// Deprecated language constructs.
+    DEPRECATED: "deprecated", Warn,
+        "a deprecated language construct";
+    /// Sized literals whose value does not fit into their size.
+    LITERAL_SIZE: "literal-size", Warn,
+        "a sized literal whose value does not fit into its size";
+    /// Assignment patterns that assign the same element twice.
+    PATTERN_OVERWRITE: "pattern-overwrite", Warn,
+        "an assignment pattern that assigns the same element twice";
+    /// Constraints that can never be satisfied.
+    UNSATISFIABLE_CONSTRAINT: "unsatisfiable-constraint", Warn,
+        "a constraint that can never be satisfied";
+    /// Latches inferred in `always_comb` procedures.
+    INFERRED_LATCH: "inferred-latch", Warn,
+        "a variable not assigned on all paths through an `always_comb` procedure";
+    /// `always_ff` procedures without a clock edge.
+    ALWAYS_FF_CLOCK: "always-ff-clock", Warn,
+        "an `always_ff` procedure that is not triggered by a clock edge";
+    /// Blocking assignments in `always_ff` procedures.
+    BLOCKING_IN_FF: "blocking-in-ff", Warn,
+        "a blocking assignment to a variable in an `always_ff` procedure";
+    /// Nonblocking assignments in `always_comb` procedures.
+    NONBLOCKING_IN_COMB: "nonblocking-in-comb", Warn,
+        "a nonblocking assignment in an `always_comb` procedure";
+    /// Variables with conflicting continuous drivers.
+    MULTIPLE_DRIVERS: "multiple-drivers", Warn,
+        "a variable driven by a continuous assignment and any other driver";
+    /// Combinational loops.
+    COMB_LOOP: "comb-loop", Warn,
+        "a signal that depends on its own value without any delay";
+    /// Unsynchronized clock domain crossings.
+    UNSYNCHRONIZED_CDC: "unsynchronized-cdc", Warn,
+        "a register that samples another clock domain without a synchronizer";
+    /// Case statements that do not handle all values of their expression.
+    INCOMPLETE_CASE: "incomplete-case", Warn,
+        "a case statement without a `default` item that misses values";
+    /// Case items that can never be taken.
+    UNREACHABLE_CASE_ITEM: "unreachable-case-item", Warn,
+        "a case item whose values are all matched by earlier items";
+    /// Overlapping items in `unique` case statements.
+    CASE_OVERLAP: "case-overlap", Warn,
+        "overlapping items in a `unique` or `unique0` case statement";
+    /// Constructs that are not supported and ignored by the compiler.
+    UNSUPPORTED: "unsupported", Warn,
+        "a construct that is not supported by the compiler and ignored";
+    /// Names that do not follow the naming conventions.
+    ///
+    /// Each option is a regular expression that the names of one kind of
+    /// declaration must match entirely.
+    NAMING: "naming", Allow,
+        "a name that does not match the naming convention of its declaration kind", {
+        "module" => "[a-z][a-z0-9_]*",
+        "input" => "[a-z][a-z0-9_]*_n?i",
+        "output" => "[a-z][a-z0-9_]*_n?o",
+        "inout" => "[a-z][a-z0-9_]*_n?io",
+        "parameter" => "[A-Z]([a-z0-9]+[A-Z]?)*",
+        "localparam" => "[A-Z]([a-z0-9]+[A-Z]?)*",
+        "type" => "[a-z][a-z0-9_]*_t",
+        "register" => "[a-z][a-z0-9_]*_n?q",
+    };
+}
+
+/// Find a lint by its name.
+pub fn find(name: &str) -> Option<&'static Lint> {
+    LINTS.iter().cloned().find(|lint| lint.name == name)
+}
+
+/// The configured levels of the lints.
+#[derive(Debug, Default, Clone)]
+pub struct LintLevels {
+    levels: HashMap<&'static str, Level>,
+    options: HashMap<(&'static str, &'static str), String>,
+    /// The lints that have already been reported at least once.
+    reported: RefCell<HashSet<&'static str>>,
+}
+
+impl LintLevels {
+    /// Get the level of a lint.
+    pub fn level(&self, lint: &Lint) -> Level {
+        self.levels.get(lint.name).cloned().unwrap_or(lint.default)
+    }
+
+    /// Set the level of a lint.
+    pub fn set(&mut self, lint: &'static Lint, level: Level) {
+        self.levels.insert(lint.name, level);
+    }
+
+    /// Set the level of a lint by its name, or of all lints if the name is
+    /// `all`.
+    pub fn set_by_name(&mut self, name: &str, level: Level) -> Result<(), String> {
+        if name == "all" {
+            for &lint in LINTS {
+                self.set(lint, level);
+            }
+            return Ok(());
+        }
+        match find(name) {
+            Some(lint) => {
+                self.set(lint, level);
+                Ok(())
+            }
+            None => Err(format!("unknown lint `{}`", name)),
+        }
+    }
+
+    /// Get the value of an option of a lint.
+    ///
+    /// Panics if the lint has no such option.
+    pub fn option(&self, lint: &'static Lint, key: &str) -> &str {
+        let &(key, default) = lint
+            .options
+            .iter()
+            .find(|&&(k, _)| k == key)
+            .expect("lint has no such option");
+        match self.options.get(&(lint.name, key)) {
+            Some(value) => value,
+            None => default,
+        }
+    }
+
+    /// Set the value of an option of a lint.
+    pub fn set_option(
+        &mut self,
+        lint: &'static Lint,
+        key: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        match lint.options.iter().find(|&&(k, _)| k == key) {
+            Some(&(key, _)) => {
+                self.options.insert((lint.name, key), value.to_string());
+                Ok(())
+            }
+            None => Err(format!("lint `{}` has no option `{}`", lint.name, key)),
+        }
+    }
+
+    /// Apply the levels and options in a lint configuration file.
+    ///
+    /// Each line of the file has the form `<name> = <level>`, where the name
+    /// is a lint or `all`, and the level is `allow`, `warn`, or `deny`, or the
+    /// form `<name>.<option> = <value>` to set an option of a lint. Empty
+    /// lines and lines starting with `#` are ignored.
+    pub fn load_config(&mut self, text: &str) -> Result<(), String> {
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: String| format!("line {}: {}", index + 1, msg);
+            let (name, level) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => {
+                    return Err(err(format!(
+                        "expected `<lint> = <level>`, found `{}`",
+                        line
+                    )))
+                }
+            };
+            if let Some(dot) = name.find('.') {
+                let lint = find(&name[..dot])
+                    .ok_or_else(|| err(format!("unknown lint `{}`", &name[..dot])))?;
+                self.set_option(lint, &name[dot + 1..], level)
+                    .map_err(err)?;
+                continue;
+            }
+            let level = level.parse().map_err(err)?;
+            self.set_by_name(name, level).map_err(err)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the configured level to a diagnostic.
+    ///
+    /// Returns `None` if the diagnostic is a lint that is allowed, either by
+    /// its level or by an `allow` comment in the source code. Promotes denied
+    /// lints to errors. Diagnostics that are not lints are returned unchanged.
+    pub fn apply(&self, mut diag: DiagBuilder2) -> Option<DiagBuilder2> {
+        let lint = match diag.lint {
+            Some(lint) => lint,
+            None => return Some(diag),
+        };
+        let level = self.level(lint);
+        if level == Level::Allow {
+            return None;
+        }
+        let span = diag.segments.iter().find_map(|seg| match *seg {
+            DiagSegment::Span(span) => Some(span),
+            _ => None,
+        });
+        if let Some(span) = span {
+            if get_allow_table().is_allowed(lint, span) {
+                return None;
+            }
+        }
+        if level == Level::Deny {
+            diag.severity = Severity::Error;
+        }
+        if self.reported.borrow_mut().insert(lint.name) {
+            diag = diag.add_note(match level {
+                Level::Deny => format!("Denied by lint `{}`", lint.name),
+                _ => format!(
+                    "Reported by lint `{0}`, which can be disabled with `-W no-{0}` or \
+                     `// moore: allow({0})`",
+                    lint.name
+                ),
+            });
+        }
+        Some(diag)
+    }
+}
+
+/// The lines on which lints are allowed by comments in the source code.
+#[derive(Debug, Default)]
+pub struct AllowTable {
+    allows: RefCell<Vec<AllowEntry>>,
+}
+
+/// An `allow` comment in the source code.
+#[derive(Debug)]
+struct AllowEntry {
+    source: Source,
+    /// The first line on which the lints are allowed.
+    first: usize,
+    /// The last line on which the lints are allowed.
+    last: usize,
+    /// The names of the allowed lints.
+    names: Vec<String>,
+}
+
+impl AllowTable {
+    /// Record a comment.
+    ///
+    /// If the comment has the form `// moore: allow(<name>, ...)`, the named
+    /// lints are allowed on the lines covered by the comment and on the line
+    /// immediately after it.
+    pub fn add_comment(&self, span: Span) {
+        let text = span.extract();
+        let body = if text.starts_with("//") {
+            &text[2..]
+        } else if text.starts_with("/*") && text.ends_with("*/") && text.len() >= 4 {
+            &text[2..text.len() - 2]
+        } else {
+            return;
+        };
+        let body = body.trim();
+        if !body.starts_with("moore:") {
+            return;
+        }
+        let body = body["moore:".len()..].trim_start();
+        if !body.starts_with("allow") {
+            return;
+        }
+        let body = body["allow".len()..].trim_start();
+        if !body.starts_with('(') {
+            return;
+        }
+        let names = match body.find(')') {
+            Some(end) => body[1..end]
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => return,
+        };
+        self.allows.borrow_mut().push(AllowEntry {
+            source: span.source,
+            first: span.begin().human_line(),
+            last: span.end().human_line() + 1,
+            names,
+        });
+    }
+
+    /// Check whether a lint is allowed at a location in the source code.
+    pub fn is_allowed(&self, lint: &Lint, span: Span) -> bool {
+        let allows = self.allows.borrow();
+        if !allows.iter().any(|allow| allow.source == span.source) {
+            return false;
+        }
+        let line = span.begin().human_line();
+        allows.iter().any(|allow| {
+            allow.source == span.source
+                && allow.first <= line
+                && line <= allow.last
+                && allow
+                    .names
+                    .iter()
+                    .any(|name| name == lint.name || name == "all")
+        })
+    }
+}
+
+/// Get the table of `allow` comments recorded by the lexer.
+pub fn get_allow_table() -> Rc<AllowTable> {
+    thread_local!(static TBL: Rc<AllowTable> = Rc::new(AllowTable::default()));
+    TBL.with(|x| x.clone())
+}
@@ -101,8 +101,29 @@ pub enum AstNode<'ast> {
     Import(&'ast ast::ImportItem<'ast>),
     /// A subroutine declaration.
     SubroutineDecl(&'ast ast::SubroutineDecl<'ast>),
+    /// A DPI import or export.
+    DpiDecl(&'ast ast::DpiDecl<'ast>),
+    LetDecl(&'ast ast::LetDecl<'ast>),
+    LetArg(&'ast ast::LetArg<'ast>),
+    Covergroup(&'ast ast::Covergroup<'ast>),
+    Coverpoint(&'ast ast::Coverpoint<'ast>),
+    ClockingDecl(&'ast ast::ClockingDecl<'ast>),
+    /// A clocking block signal, given as `(signal, item, block)`.
+    ClockingSignal(
+        &'ast ast::ClockingSignal<'ast>,
+        &'ast ast::ClockingItem<'ast>,
+        &'ast ast::ClockingDecl<'ast>,
+    ),
+    /// A gate or switch primitive instance, given as `(inst, gate)`.
+    Gate(&'ast ast::GateInstName<'ast>, &'ast ast::GateInst<'ast>),
+    /// A class declaration.
+    ClassDecl(&'ast ast::ClassDecl<'ast>),
+    /// A function or task argument.
+    SubroutinePort(&'ast ast::SubroutinePort<'ast>),
     /// An interface.
     Interface(&'ast ast::Interface<'ast>),
+    /// A loop variable of a `foreach` constraint.
+    ForeachIndex(&'ast ast::ForeachIndex<'ast>),
 }
 
 impl<'a> AstNode<'a> {
@@ -134,7 +155,18 @@ impl<'a> AstNode<'a> {
             AstNode::EnumVariant(x, _, _) => Some(x),
             AstNode::Import(x) => Some(x),
             AstNode::SubroutineDecl(x) => Some(x),
+            AstNode::DpiDecl(x) => Some(x),
+            AstNode::LetDecl(x) => Some(x),
+            AstNode::LetArg(x) => Some(x),
+            AstNode::Covergroup(x) => Some(x),
+            AstNode::Coverpoint(x) => Some(x),
+            AstNode::ClockingDecl(x) => Some(x),
+            AstNode::ClockingSignal(x, ..) => Some(x),
+            AstNode::Gate(x, _) => Some(x),
+            AstNode::ClassDecl(x) => Some(x),
+            AstNode::SubroutinePort(x) => Some(x),
             AstNode::Interface(x) => Some(x),
+            AstNode::ForeachIndex(x) => Some(x),
             _ => None,
         }
     }
@@ -191,7 +223,28 @@ impl<'a> AstNode<'a> {
             })),
             AllNode::ImportItem(x) => Box::new(Some(AstNode::Import(x)).into_iter()),
             AllNode::SubroutineDecl(x) => Box::new(Some(AstNode::SubroutineDecl(x)).into_iter()),
+            AllNode::DpiDecl(x) => Box::new(Some(AstNode::DpiDecl(x)).into_iter()),
+            AllNode::LetDecl(x) => Box::new(Some(AstNode::LetDecl(x)).into_iter()),
+            AllNode::LetArg(x) => Box::new(Some(AstNode::LetArg(x)).into_iter()),
+            AllNode::Covergroup(x) => Box::new(Some(AstNode::Covergroup(x)).into_iter()),
+            AllNode::Coverpoint(x) => Box::new(Some(AstNode::Coverpoint(x)).into_iter()),
+            AllNode::ClockingDecl(x) => Box::new(Some(AstNode::ClockingDecl(x)).into_iter()),
+            AllNode::ClockingSignal(x) => Box::new(
+                x.get_parent()
+                    .and_then(|p| p.as_all().get_clocking_decl())
+                    .and_then(|decl| {
+                        decl.items
+                            .iter()
+                            .find(|item| item.signals.iter().any(|s| s.id() == x.id()))
+                            .map(|item| AstNode::ClockingSignal(x, item, decl))
+                    })
+                    .into_iter(),
+            ),
+            AllNode::GateInst(x) => Box::new(x.names.iter().map(move |n| AstNode::Gate(n, x))),
+            AllNode::ClassDecl(x) => Box::new(Some(AstNode::ClassDecl(x)).into_iter()),
+            AllNode::SubroutinePort(x) => Box::new(Some(AstNode::SubroutinePort(x)).into_iter()),
             AllNode::Interface(x) => Box::new(Some(AstNode::Interface(x)).into_iter()),
+            AllNode::ForeachIndex(x) => Box::new(Some(AstNode::ForeachIndex(x)).into_iter()),
             _ => Box::new(None.into_iter()),
         }
     }
@@ -225,7 +278,18 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::EnumVariant(x, _, _) => x.span(),
             AstNode::Import(x) => x.span(),
             AstNode::SubroutineDecl(x) => x.span(),
+            AstNode::DpiDecl(x) => x.span(),
+            AstNode::LetDecl(x) => x.span(),
+            AstNode::LetArg(x) => x.span(),
+            AstNode::Covergroup(x) => x.span(),
+            AstNode::Coverpoint(x) => x.span(),
+            AstNode::ClockingDecl(x) => x.span(),
+            AstNode::ClockingSignal(x, ..) => x.span(),
+            AstNode::Gate(x, _) => x.span(),
+            AstNode::ClassDecl(x) => x.span(),
+            AstNode::SubroutinePort(x) => x.span(),
             AstNode::Interface(x) => x.span(),
+            AstNode::ForeachIndex(x) => x.span(),
         }
     }
 
@@ -256,7 +320,18 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::EnumVariant(x, _, _) => x.human_span(),
             AstNode::Import(x) => x.human_span(),
             AstNode::SubroutineDecl(x) => x.human_span(),
+            AstNode::DpiDecl(x) => x.human_span(),
+            AstNode::LetDecl(x) => x.human_span(),
+            AstNode::LetArg(x) => x.human_span(),
+            AstNode::Covergroup(x) => x.human_span(),
+            AstNode::Coverpoint(x) => x.human_span(),
+            AstNode::ClockingDecl(x) => x.human_span(),
+            AstNode::ClockingSignal(x, ..) => x.human_span(),
+            AstNode::Gate(x, _) => x.human_span(),
+            AstNode::ClassDecl(x) => x.human_span(),
+            AstNode::SubroutinePort(x) => x.human_span(),
             AstNode::Interface(x) => x.human_span(),
+            AstNode::ForeachIndex(x) => x.human_span(),
         }
     }
 }
@@ -290,7 +365,18 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::EnumVariant(x, _, _) => "enum variant",
             AstNode::Import(x) => "import",
             AstNode::SubroutineDecl(x) => "subroutine declaration",
+            AstNode::DpiDecl(x) => "DPI declaration",
+            AstNode::LetDecl(x) => "let declaration",
+            AstNode::LetArg(x) => "let argument",
+            AstNode::Covergroup(x) => "covergroup",
+            AstNode::Coverpoint(x) => "coverpoint",
+            AstNode::ClockingDecl(x) => "clocking block",
+            AstNode::ClockingSignal(..) => "clocking signal",
+            AstNode::Gate(x, _) => "primitive instance",
+            AstNode::ClassDecl(x) => "class declaration",
+            AstNode::SubroutinePort(x) => "subroutine port",
             AstNode::Interface(x) => "interface",
+            AstNode::ForeachIndex(x) => "index variable",
         }
     }
 
@@ -321,7 +407,18 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::EnumVariant(x, _, _) => x.to_definite_string(),
             AstNode::Import(x) => x.to_definite_string(),
             AstNode::SubroutineDecl(x) => x.to_definite_string(),
+            AstNode::DpiDecl(x) => x.to_definite_string(),
+            AstNode::LetDecl(x) => x.to_definite_string(),
+            AstNode::LetArg(x) => x.to_definite_string(),
+            AstNode::Covergroup(x) => x.to_definite_string(),
+            AstNode::Coverpoint(x) => x.to_definite_string(),
+            AstNode::ClockingDecl(x) => x.to_definite_string(),
+            AstNode::ClockingSignal(x, ..) => x.to_definite_string(),
+            AstNode::Gate(x, _) => x.to_definite_string(),
+            AstNode::ClassDecl(x) => x.to_definite_string(),
+            AstNode::SubroutinePort(x) => x.to_definite_string(),
             AstNode::Interface(x) => x.to_definite_string(),
+            AstNode::ForeachIndex(x) => x.to_definite_string(),
         }
     }
 }
@@ -0,0 +1,446 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Completeness and overlap checks on case statements.
+//!
+//! The items of a `case`, `casez`, or `casex` statement are compared against
+//! the values of the case expression. If the expression is of an enum type or
+//! a small bit vector, all of its values are enumerated to find the ones not
+//! handled by any item. Otherwise only the items themselves are compared
+//! against each other. Only the 2-state values of the expression are
+//! considered, since `x` and `z` are rarely handled explicitly.
+
+use crate::crate_prelude::*;
+use crate::hir::HirNode;
+use crate::ty::UnpackedType;
+use crate::value::{Value, ValueKind};
+use moore_common::lint::{CASE_OVERLAP, INCOMPLETE_CASE, UNREACHABLE_CASE_ITEM};
+use num::{BigInt, One, Zero};
+use std::collections::HashMap;
+
+/// The widest bit vector whose values are enumerated.
+const MAX_ENUM_WIDTH: usize = 8;
+
+/// The number of unhandled values listed in a warning.
+const MAX_LISTED: usize = 4;
+
+/// Check the items of a case statement.
+///
+/// Warns about case statements without a `default` item that do not handle
+/// all values of an enum or small bit vector, about items that are never
+/// taken because earlier items already match all their values, and about
+/// overlapping items in `unique` and `unique0` case statements.
+#[moore_derive::query]
+pub(crate) fn check_case<'a>(cx: &impl Context<'a>, stmt_id: NodeId, env: ParamEnv) -> Result<()> {
+    let hir = match cx.hir_of(stmt_id)? {
+        HirNode::Stmt(x) => x,
+        _ => unreachable!(),
+    };
+    let (up, expr, ways, default, kind) = match hir.kind {
+        hir::StmtKind::Case {
+            up,
+            expr,
+            ref ways,
+            default,
+            kind,
+        } => (up, expr, ways, default, kind),
+        _ => unreachable!(),
+    };
+    let CaseItems {
+        ty,
+        cmp,
+        items,
+        domain,
+    } = match case_items(cx, expr, ways, kind, env) {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let parallel = up.map(|up| up.is_parallel()).unwrap_or(false);
+
+    match domain {
+        Some(domain) => {
+            // Determine which values each item matches.
+            let matched: Vec<Vec<bool>> = items
+                .iter()
+                .map(|item| domain.iter().map(|(v, _)| item.matches(v)).collect())
+                .collect();
+            let mut covered = vec![false; domain.len()];
+            for (i, item) in items.iter().enumerate() {
+                let overlap =
+                    |j: usize| (0..domain.len()).find(|&k| matched[i][k] && matched[j][k]);
+                let earlier = (0..i).find(|&j| overlap(j).is_some());
+                let reachable = (0..domain.len()).any(|k| matched[i][k] && !covered[k]);
+                if !reachable {
+                    let d = DiagBuilder2::lint(&UNREACHABLE_CASE_ITEM, "case item is unreachable")
+                        .span(item.span);
+                    let d = match earlier {
+                        Some(j) => d
+                            .add_note("Earlier items already match all its values:")
+                            .span(items[j].span),
+                        None => d.add_note(format!("The item matches no value of type `{}`", ty)),
+                    };
+                    cx.emit(d);
+                } else if parallel {
+                    if let Some(j) = earlier {
+                        let value = &domain[overlap(j).unwrap()].1;
+                        cx.emit(overlap_diag(item, &items[j], value));
+                    }
+                }
+                for k in 0..domain.len() {
+                    covered[k] |= matched[i][k];
+                }
+            }
+
+            // Report the values not handled by any item.
+            let full = up.map(|up| up.is_full()).unwrap_or(true);
+            if default.is_none() && full {
+                let missing: Vec<_> = (0..domain.len())
+                    .filter(|&k| !covered[k])
+                    .map(|k| format!("`{}`", domain[k].1))
+                    .collect();
+                if !missing.is_empty() {
+                    let mut listed = missing[..missing.len().min(MAX_LISTED)].join(", ");
+                    if missing.len() > MAX_LISTED {
+                        listed += &format!(", and {} more", missing.len() - MAX_LISTED);
+                    }
+                    cx.emit(
+                        DiagBuilder2::lint(
+                            &INCOMPLETE_CASE,
+                            format!(
+                                "case statement does not handle all values of `{}`",
+                                cx.span(expr).extract()
+                            ),
+                        )
+                        .span(cx.span(expr))
+                        .add_note(format!("Unhandled values: {}", listed))
+                        .add_note("Add items for these values or a `default` item"),
+                    );
+                }
+            }
+        }
+        None => {
+            // Compare the labels of each item against the earlier ones.
+            for (i, item) in items.iter().enumerate() {
+                let subsumed = item.labels.iter().all(|label| {
+                    items[..i]
+                        .iter()
+                        .any(|earlier| earlier.labels.iter().any(|e| e.contains(label)))
+                });
+                let earlier = items[..i].iter().find(|earlier| {
+                    earlier
+                        .labels
+                        .iter()
+                        .any(|e| item.labels.iter().any(|l| e.intersects(l)))
+                });
+                if subsumed {
+                    cx.emit(
+                        DiagBuilder2::lint(&UNREACHABLE_CASE_ITEM, "case item is unreachable")
+                            .span(item.span)
+                            .add_note("Earlier items already match all its values:")
+                            .span(earlier.unwrap().span),
+                    );
+                } else if parallel {
+                    if let Some(earlier) = earlier {
+                        let value = earlier
+                            .labels
+                            .iter()
+                            .flat_map(|e| item.labels.iter().filter_map(move |l| e.common(l)))
+                            .next()
+                            .unwrap();
+                        let value = format!("{}'h{:x}", cmp.width, value);
+                        cx.emit(overlap_diag(item, earlier, &value));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check whether the items of a case statement handle all values of its
+/// expression, such that the statement always takes one of them.
+pub(crate) fn handles_all_values<'a>(
+    cx: &impl Context<'a>,
+    stmt_id: NodeId,
+    env: ParamEnv,
+) -> bool {
+    let hir = match cx.hir_of(stmt_id) {
+        Ok(HirNode::Stmt(x)) => x,
+        _ => return false,
+    };
+    let case = match hir.kind {
+        hir::StmtKind::Case {
+            expr,
+            ref ways,
+            kind,
+            ..
+        } => case_items(cx, expr, ways, kind, env),
+        _ => return false,
+    };
+    let case = match case {
+        Some(x) => x,
+        None => return false,
+    };
+    // A label consisting only of wildcards matches every value.
+    let wildcard = case
+        .items
+        .iter()
+        .any(|item| item.labels.iter().any(|l| l.mask.is_zero()));
+    wildcard
+        || match case.domain {
+            Some(ref domain) => domain
+                .iter()
+                .all(|(v, _)| case.items.iter().any(|item| item.matches(v))),
+            None => false,
+        }
+}
+
+/// The items of a case statement, and the values of its expression.
+struct CaseItems<'a> {
+    /// The self-determined type of the case expression.
+    ty: &'a UnpackedType<'a>,
+    /// The width and sign at which the expression and labels are compared.
+    cmp: Extension,
+    /// The items whose labels can match a 2-state value.
+    items: Vec<Item>,
+    /// The values of the expression and their names, if they can be
+    /// enumerated.
+    domain: Option<Vec<(BigInt, String)>>,
+}
+
+/// Determine the items of a case statement and the values of its
+/// expression.
+///
+/// Returns `None` if the expression or any of the labels is not a constant
+/// bit vector.
+fn case_items<'a>(
+    cx: &impl Context<'a>,
+    expr: NodeId,
+    ways: &[(Vec<NodeId>, NodeId)],
+    kind: ast::CaseKind,
+    env: ParamEnv,
+) -> Option<CaseItems<'a>> {
+    let ty = cx.self_determined_type(expr, env)?;
+    let width = match ty.get_bit_size() {
+        Some(w) if w > 0 => w,
+        _ => return None,
+    };
+
+    // The case expression and the labels are compared at the width of the
+    // widest of them, and are only sign-extended if all of them are signed
+    // (see IEEE 1800-2017 §12.5).
+    let mut values = HashMap::new();
+    let mut cmp = Extension {
+        width,
+        signed: is_signed(ty),
+    };
+    for &way_expr in ways.iter().flat_map(|(way_exprs, _)| way_exprs) {
+        let value = self_determined_value(cx, way_expr, env)?;
+        match value.ty.get_bit_size() {
+            Some(w) if w > 0 => cmp.width = cmp.width.max(w),
+            _ => return None,
+        }
+        cmp.signed &= is_signed(value.ty);
+        values.insert(way_expr, value);
+    }
+
+    // Determine the labels of each item. Skip items whose labels cannot match
+    // any 2-state value.
+    let mut items = vec![];
+    for &(ref way_exprs, _) in ways {
+        let mut labels = vec![];
+        for &way_expr in way_exprs {
+            if let Some(label) = case_label(values[&way_expr], cmp, kind).ok()? {
+                labels.push(label);
+            }
+        }
+        if labels.is_empty() {
+            continue;
+        }
+        let span = Span::union(cx.span(way_exprs[0]), cx.span(*way_exprs.last().unwrap()));
+        items.push(Item { labels, span });
+    }
+
+    // Enumerate the values of the expression, if feasible.
+    let domain: Option<Vec<(BigInt, String)>> = match ty.get_enum() {
+        Some(enm) => {
+            let mut values = vec![];
+            for &(name, variant) in &enm.variants {
+                let value = cx.constant_value_of(variant.id(), env);
+                values.push((cmp.extend(value.get_int()?, width), name.value.to_string()));
+            }
+            Some(values)
+        }
+        None if width <= MAX_ENUM_WIDTH && ty.get_simple_bit_vector().is_some() => Some(
+            (0..1u32 << width)
+                .map(|v| {
+                    let name = format!("{}'b{:0w$b}", width, v, w = width);
+                    (cmp.extend(&BigInt::from(v), width), name)
+                })
+                .collect(),
+        ),
+        None => None,
+    };
+    Some(CaseItems {
+        ty,
+        cmp,
+        items,
+        domain,
+    })
+}
+
+/// A case item.
+struct Item {
+    /// The labels of the item.
+    labels: Vec<Label>,
+    /// The location of the labels in the source code.
+    span: Span,
+}
+
+impl Item {
+    /// Check whether any label of the item matches a value.
+    fn matches(&self, value: &BigInt) -> bool {
+        self.labels.iter().any(|l| l.matches(value))
+    }
+}
+
+/// A case item label.
+///
+/// The label matches all values which agree with `value` in the bits set in
+/// `mask`. The other bits are wildcards of a `casez` or `casex` statement.
+struct Label {
+    value: BigInt,
+    mask: BigInt,
+}
+
+impl Label {
+    /// Check whether the label matches a value.
+    fn matches(&self, value: &BigInt) -> bool {
+        (value & &self.mask) == (&self.value & &self.mask)
+    }
+
+    /// Check whether the label matches all values another label matches.
+    fn contains(&self, other: &Label) -> bool {
+        (&self.mask & &other.mask) == self.mask && self.matches(&other.value)
+    }
+
+    /// Check whether the label matches any value another label matches.
+    fn intersects(&self, other: &Label) -> bool {
+        ((&self.value ^ &other.value) & &self.mask & &other.mask).is_zero()
+    }
+
+    /// Find a value matched by both this and another label.
+    fn common(&self, other: &Label) -> Option<BigInt> {
+        if self.intersects(other) {
+            Some((&self.value & &self.mask) | (&other.value & &other.mask))
+        } else {
+            None
+        }
+    }
+}
+
+/// The width and sign at which the case expression and the labels are
+/// compared.
+#[derive(Clone, Copy)]
+struct Extension {
+    width: usize,
+    signed: bool,
+}
+
+impl Extension {
+    /// Extend a value of a number of bits to the width of the comparison, and
+    /// map it to its unsigned representation.
+    fn extend(&self, value: &BigInt, width: usize) -> BigInt {
+        let value = normalize(value, width);
+        if self.signed && !(&value >> (width - 1)).is_zero() {
+            value | (mask(self.width) ^ mask(width))
+        } else {
+            value
+        }
+    }
+}
+
+/// Check whether a type is a signed bit vector.
+fn is_signed(ty: &UnpackedType) -> bool {
+    ty.get_simple_bit_vector()
+        .map(|sbv| sbv.is_signed())
+        .unwrap_or(false)
+}
+
+/// Determine the value of a case item expression in its self-determined type,
+/// before it is cast to the type of the case expression.
+fn self_determined_value<'a>(
+    cx: &impl Context<'a>,
+    expr: NodeId,
+    env: ParamEnv,
+) -> Option<Value<'a>> {
+    let init = cx.cast_type(expr, env)?.init;
+    let mut mir = cx.mir_rvalue(expr, env);
+    while !mir.ty.is_identical(init) {
+        mir = match mir.kind {
+            mir::RvalueKind::CastValueDomain { value, .. }
+            | mir::RvalueKind::Transmute(value)
+            | mir::RvalueKind::CastSign(_, value)
+            | mir::RvalueKind::Truncate(_, value)
+            | mir::RvalueKind::ZeroExtend(_, value)
+            | mir::RvalueKind::SignExtend(_, value) => value,
+            _ => return None,
+        };
+    }
+    Some(cx.const_mir_rvalue(mir.into()))
+}
+
+/// Determine the label of a case item expression.
+///
+/// Returns `None` if the label contains `x` or `z` bits that do not act as
+/// wildcards, such that it matches no 2-state value.
+fn case_label(value: Value, cmp: Extension, kind: ast::CaseKind) -> Result<Option<Label>> {
+    let width = value.ty.get_bit_size().unwrap();
+    let (value, special_bits, x_bits) = match &value.kind {
+        ValueKind::Int(v, s, x) => (v, s, x),
+        _ => return Err(()),
+    };
+    let mut wildcards = special_bits.clone();
+    match kind {
+        ast::CaseKind::Normal => wildcards.clear(),
+        ast::CaseKind::DontCareZ => {
+            wildcards.difference(x_bits);
+        }
+        ast::CaseKind::DontCareXZ => (),
+    }
+    if wildcards != *special_bits {
+        return Ok(None);
+    }
+    let mut wild = BigInt::zero();
+    for b in &wildcards {
+        wild <<= 1;
+        if b {
+            wild |= BigInt::one();
+        }
+    }
+    let mask = mask(cmp.width) ^ cmp.extend(&wild, width);
+    Ok(Some(Label {
+        value: cmp.extend(value, width) & &mask,
+        mask,
+    }))
+}
+
+/// Map a value to its unsigned representation in a number of bits.
+fn normalize(value: &BigInt, width: usize) -> BigInt {
+    value & mask(width)
+}
+
+/// Create a mask of a number of bits.
+fn mask(width: usize) -> BigInt {
+    (BigInt::one() << width) - 1
+}
+
+/// Create the warning for two overlapping case items.
+fn overlap_diag(item: &Item, earlier: &Item, value: &str) -> DiagBuilder2 {
+    DiagBuilder2::lint(
+        &CASE_OVERLAP,
+        "case item overlaps with an earlier item in `unique` case statement",
+    )
+    .span(item.span)
+    .add_note(format!("Both items match `{}`:", value))
+    .span(earlier.span)
+}
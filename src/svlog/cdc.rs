@@ -0,0 +1,451 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Clock domain crossing analysis.
+//!
+//! While a module is elaborated, the code generator records the flip-flops of
+//! the module and the clock that triggers them, and how the signals of the
+//! module depend on each other through combinational logic and the port
+//! connections of instances. Once the design has been elaborated, the clocks
+//! are traced through the hierarchy to their source, which determines the
+//! clock domain of each flip-flop. Data paths from a flip-flop in one domain
+//! to a flip-flop in another domain are reported, and flagged unless they
+//! pass through a synchronizer.
+
+use crate::crate_prelude::*;
+use crate::{drivers::procedure_dependencies, hir::HirNode};
+use moore_common::lint::UNSYNCHRONIZED_CDC;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Write,
+    rc::Rc,
+};
+
+/// The clocking structure of a module.
+#[derive(Default)]
+pub(crate) struct CdcModule {
+    /// The flip-flops of the module.
+    flops: Vec<Flop>,
+    /// The signals which depend on another signal without any delay, as
+    /// triples of driven signal, read signal, and whether the driven signal
+    /// is computed by logic rather than copied.
+    comb: Vec<(NodeId, NodeId, bool)>,
+    /// The module instances.
+    insts: Vec<CdcInst>,
+    /// The port connections of the instance currently being emitted.
+    pending: Vec<Connection>,
+}
+
+/// A register written by a clocked procedure.
+struct Flop {
+    /// The register variable.
+    reg: NodeId,
+    /// The signal whose edge triggers the procedure.
+    clock: NodeId,
+    /// The signals the next value of the register depends on, excluding the
+    /// clock and asynchronous resets.
+    data: Vec<NodeId>,
+    /// The first assignment to the register.
+    span: Span,
+}
+
+/// A module instance.
+struct CdcInst {
+    name: String,
+    module: Rc<CdcModule>,
+    conns: Vec<Connection>,
+}
+
+/// A connection between a port of an instance and the signals of the parent
+/// module.
+struct Connection {
+    port: NodeId,
+    output: bool,
+    signals: Vec<NodeId>,
+}
+
+impl CdcModule {
+    /// Add a procedure.
+    ///
+    /// Procedures triggered by the edges of signals, such as `always_ff
+    /// @(posedge clk)`, define a flip-flop for each variable they write. If a
+    /// procedure is triggered by multiple edges, the signals tested by its
+    /// outermost `if` statement are asynchronous resets, and the remaining
+    /// edge is the clock.
+    pub fn add_procedure<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        proc_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<()> {
+        let hir = match cx.hir_of(proc_id)? {
+            HirNode::Proc(x) => x,
+            _ => unreachable!(),
+        };
+        match hir.kind {
+            ast::ProcedureKind::Always | ast::ProcedureKind::AlwaysFf => (),
+            _ => return Ok(()),
+        }
+        let (event_id, body) = match cx.hir_of(hir.stmt)? {
+            HirNode::Stmt(hir::Stmt {
+                kind:
+                    hir::StmtKind::Timed {
+                        control: hir::TimingControl::ExplicitEvent(event_id),
+                        stmt,
+                    },
+                ..
+            }) => (*event_id, *stmt),
+            _ => return Ok(()),
+        };
+        let events = match cx.hir_of(event_id)? {
+            HirNode::EventExpr(x) => x,
+            _ => return Ok(()),
+        };
+        let mut edges = vec![];
+        for event in &events.events {
+            if event.edge == ast::EdgeIdent::Implicit {
+                return Ok(());
+            }
+            match cx.mir_rvalue(event.expr, env).kind {
+                mir::RvalueKind::Var(id) | mir::RvalueKind::Port(id) => edges.push(id),
+                _ => return Ok(()),
+            }
+        }
+        let tested = tested_signals(cx, body, env)?;
+        let clock = match edges.len() {
+            1 => edges[0],
+            _ => match edges.iter().find(|id| !tested.contains(id)) {
+                Some(&id) => id,
+                None => return Ok(()),
+            },
+        };
+
+        let deps: BTreeMap<_, _> = procedure_dependencies(cx, hir, env).into_iter().collect();
+        for (reg, (span, reads)) in deps {
+            if cx.is_parent_of(proc_id, reg) {
+                continue;
+            }
+            self.flops.push(Flop {
+                reg,
+                clock,
+                data: reads
+                    .into_iter()
+                    .filter(|&id| !edges.contains(&id) && !cx.is_parent_of(proc_id, id))
+                    .collect(),
+                span,
+            });
+        }
+        Ok(())
+    }
+
+    /// Add the expression connected to a port of the instance currently being
+    /// emitted.
+    pub fn add_connection<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        port: NodeId,
+        expr_id: NodeId,
+        env: ParamEnv,
+        output: bool,
+    ) -> Result<()> {
+        let acc = cx.accessed_nodes(expr_id, env)?;
+        self.pending.push(Connection {
+            port,
+            output,
+            signals: acc.read.iter().map(|node| node.id()).collect(),
+        });
+        Ok(())
+    }
+
+    /// Add an instance, connected through the ports added since the previous
+    /// instance.
+    pub fn add_inst(&mut self, name: String, module: Rc<CdcModule>) {
+        let conns = std::mem::replace(&mut self.pending, vec![]);
+        self.insts.push(CdcInst {
+            name,
+            module,
+            conns,
+        });
+    }
+
+    /// Add the signals which depend on another signal without any delay.
+    pub fn add_comb(&mut self, edges: impl IntoIterator<Item = (NodeId, NodeId, bool)>) {
+        self.comb.extend(edges);
+    }
+
+    /// The registers written by the clocked procedures of the module.
+    pub fn registers(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.flops.iter().map(|flop| flop.reg)
+    }
+}
+
+/// Determine the signals tested by the outermost `if` statement of a
+/// procedure body.
+fn tested_signals<'a>(
+    cx: &impl Context<'a>,
+    stmt_id: NodeId,
+    env: ParamEnv,
+) -> Result<HashSet<NodeId>> {
+    match cx.hir_of(stmt_id)? {
+        HirNode::Stmt(hir::Stmt {
+            kind: hir::StmtKind::Block(ref stmts),
+            ..
+        }) if !stmts.is_empty() => tested_signals(cx, stmts[0], env),
+        HirNode::Stmt(hir::Stmt {
+            kind: hir::StmtKind::If { cond, .. },
+            ..
+        }) => Ok(cx
+            .accessed_nodes(*cond, env)?
+            .read
+            .iter()
+            .map(|node| node.id())
+            .collect()),
+        _ => Ok(HashSet::new()),
+    }
+}
+
+/// A signal in a specific instance of the flattened design.
+type Signal = (usize, NodeId);
+
+/// The flattened design, with the connections between signals across the
+/// entire hierarchy.
+#[derive(Default)]
+struct Design<'m> {
+    /// The hierarchical name of each instance.
+    insts: Vec<String>,
+    /// The flip-flops, together with the instance they are in.
+    flops: Vec<(usize, &'m Flop)>,
+    /// The signals each signal depends on, and whether through logic.
+    preds: HashMap<Signal, Vec<(Signal, bool)>>,
+    /// The signals that depend on each signal, and whether through logic.
+    succs: HashMap<Signal, Vec<(Signal, bool)>>,
+    /// The flip-flop writing each register.
+    regs: HashMap<Signal, usize>,
+    /// The flip-flops reading each signal.
+    readers: HashMap<Signal, Vec<usize>>,
+}
+
+impl<'m> Design<'m> {
+    /// Add an instance of a module and all its subinstances.
+    fn add_inst(&mut self, name: String, module: &'m CdcModule) -> usize {
+        let index = self.insts.len();
+        self.insts.push(name.clone());
+        for &(write, read, logic) in &module.comb {
+            self.add_edge((index, read), (index, write), logic);
+        }
+        for flop in &module.flops {
+            let id = self.flops.len();
+            self.flops.push((index, flop));
+            self.regs.insert((index, flop.reg), id);
+            for &data in &flop.data {
+                self.readers.entry((index, data)).or_default().push(id);
+            }
+        }
+        for inst in &module.insts {
+            let child = self.add_inst(format!("{}.{}", name, inst.name), &inst.module);
+            for conn in &inst.conns {
+                for &signal in &conn.signals {
+                    match conn.output {
+                        true => self.add_edge((child, conn.port), (index, signal), false),
+                        false => self.add_edge((index, signal), (child, conn.port), false),
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    fn add_edge(&mut self, from: Signal, to: Signal, logic: bool) {
+        self.preds.entry(to).or_default().push((from, logic));
+        self.succs.entry(from).or_default().push((to, logic));
+    }
+
+    /// Determine the source of a clock, by following the signal through port
+    /// connections and assignments until it has no single driver, or it is
+    /// driven by a flip-flop.
+    fn clock_source(&self, mut signal: Signal) -> Signal {
+        let mut seen = HashSet::new();
+        while seen.insert(signal) && !self.regs.contains_key(&signal) {
+            match self.preds.get(&signal).map(|p| p.as_slice()) {
+                Some(&[(pred, _)]) => signal = pred,
+                _ => break,
+            }
+        }
+        signal
+    }
+
+    /// Determine the flip-flops a signal depends on, and whether it depends
+    /// on them through combinational logic.
+    fn source_flops(&self, signal: Signal) -> BTreeMap<usize, bool> {
+        let mut sources = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let mut todo = vec![(signal, false)];
+        while let Some((signal, logic)) = todo.pop() {
+            if !seen.insert((signal, logic)) {
+                continue;
+            }
+            if let Some(&flop) = self.regs.get(&signal) {
+                *sources.entry(flop).or_insert(false) |= logic;
+                continue;
+            }
+            for &(pred, through_logic) in self.preds.get(&signal).into_iter().flatten() {
+                todo.push((pred, logic || through_logic));
+            }
+        }
+        sources
+    }
+
+    /// Check whether a flip-flop is the first stage of a synchronizer, with
+    /// its output only feeding into another flip-flop in the same domain.
+    fn is_synchronizer(&self, flop: usize, domains: &[Signal]) -> bool {
+        let (inst, f) = self.flops[flop];
+        let mut seen = HashSet::new();
+        let mut todo = vec![(inst, f.reg)];
+        let mut has_second_stage = false;
+        while let Some(signal) = todo.pop() {
+            if !seen.insert(signal) {
+                continue;
+            }
+            for &reader in self.readers.get(&signal).into_iter().flatten() {
+                if reader != flop && domains[reader] == domains[flop] {
+                    has_second_stage = true;
+                }
+            }
+            for &(succ, logic) in self.succs.get(&signal).into_iter().flatten() {
+                if logic {
+                    return false;
+                }
+                todo.push(succ);
+            }
+        }
+        has_second_stage
+    }
+
+    /// Determine the hierarchical name of a signal.
+    fn name<'a>(&self, cx: &impl Context<'a>, (inst, id): Signal) -> String {
+        let name = match cx.hir_of(id) {
+            Ok(HirNode::VarDecl(x)) => x.name.value.to_string(),
+            Ok(HirNode::IntPort(x)) => x.name.value.to_string(),
+            Ok(x) => x.desc_full(),
+            Err(()) => "<error>".to_string(),
+        };
+        format!("{}.{}", self.insts[inst], name)
+    }
+}
+
+/// Trace the clocks through a design, group its flip-flops by clock domain,
+/// and report the data paths between domains.
+///
+/// Emits a warning for each path that does not pass through a synchronizer,
+/// and returns a report listing the domains and all crossings.
+pub(crate) fn cdc_report<'a>(cx: &impl Context<'a>, tops: &[(String, Rc<CdcModule>)]) -> String {
+    let mut design = Design::default();
+    for (name, module) in tops {
+        design.add_inst(name.clone(), module);
+    }
+    let domains: Vec<Signal> = design
+        .flops
+        .iter()
+        .map(|&(inst, flop)| design.clock_source((inst, flop.clock)))
+        .collect();
+
+    // Group the registers by the name of their clock domain.
+    let mut groups: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (flop, &domain) in domains.iter().enumerate() {
+        let (inst, f) = design.flops[flop];
+        groups
+            .entry(design.name(cx, domain))
+            .or_default()
+            .insert(design.name(cx, (inst, f.reg)));
+    }
+    let mut report = String::new();
+    writeln!(report, "Clock domains:").unwrap();
+    for (domain, regs) in &groups {
+        writeln!(report, "  {}", domain).unwrap();
+        for reg in regs {
+            writeln!(report, "    {}", reg).unwrap();
+        }
+    }
+
+    // Determine the registers each register samples, and recognize the
+    // first and second stages of synchronizers.
+    let sources: Vec<BTreeMap<usize, bool>> = design
+        .flops
+        .iter()
+        .map(|&(inst, flop)| {
+            let mut sources = BTreeMap::new();
+            for &data in &flop.data {
+                for (src, logic) in design.source_flops((inst, data)) {
+                    *sources.entry(src).or_insert(false) |= logic;
+                }
+            }
+            sources
+        })
+        .collect();
+    let first_stage: Vec<bool> = (0..design.flops.len())
+        .map(|flop| {
+            sources[flop]
+                .iter()
+                .any(|(&src, &logic)| !logic && domains[src] != domains[flop])
+                && design.is_synchronizer(flop, &domains)
+        })
+        .collect();
+    let second_stage: Vec<bool> = (0..design.flops.len())
+        .map(|flop| {
+            sources[flop]
+                .iter()
+                .any(|(&src, &logic)| !logic && first_stage[src] && domains[src] == domains[flop])
+        })
+        .collect();
+
+    // Report the registers which sample a register from another domain.
+    // Such a register is synchronized if it is the first stage of a
+    // synchronizer, or if it is enabled by the output of a synchronizer such
+    // that it only samples a stable value.
+    writeln!(report, "Crossings:").unwrap();
+    for (dst, &(inst, flop)) in design.flops.iter().enumerate() {
+        for (&src, &logic) in &sources[dst] {
+            if domains[src] == domains[dst] {
+                continue;
+            }
+            let (src_inst, src_flop) = design.flops[src];
+            let src_name = design.name(cx, (src_inst, src_flop.reg));
+            let dst_name = design.name(cx, (inst, flop.reg));
+            let src_domain = design.name(cx, domains[src]);
+            let dst_domain = design.name(cx, domains[dst]);
+            let enabled = sources[dst]
+                .keys()
+                .any(|&g| second_stage[g] && domains[g] == domains[dst]);
+            let status = match (logic, first_stage[dst], enabled) {
+                (false, true, _) => "synchronized",
+                (false, false, true) => "synchronized enable",
+                _ => "unsynchronized",
+            };
+            writeln!(
+                report,
+                "  {} ({}) -> {} ({}): {}",
+                src_name, src_domain, dst_name, dst_domain, status
+            )
+            .unwrap();
+            if status == "unsynchronized" {
+                cx.emit(
+                    DiagBuilder2::lint(
+                        &UNSYNCHRONIZED_CDC,
+                        format!(
+                        "`{}` in clock domain `{}` samples `{}` from clock domain `{}` without a \
+                         synchronizer",
+                        dst_name, dst_domain, src_name, src_domain
+                    ),
+                    )
+                    .span(flop.span)
+                    .add_note("Source register assigned here:")
+                    .span(src_flop.span)
+                    .add_note(
+                        "Sample the signal with two flip-flops in the destination domain, \
+                         without any logic in between",
+                    ),
+                );
+            }
+        }
+    }
+    report
+}
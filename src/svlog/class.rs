@@ -0,0 +1,305 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Classes and their members.
+//!
+//! A class type is identified by its declaration and the parameter environment
+//! of its specialization. Member lookup walks up the chain of base classes
+//! named in `extends` clauses, each base being specialized in the environment
+//! of the class that extends it (see IEEE 1800-2017 §8).
+
+use crate::crate_prelude::*;
+use crate::{hir::HirNode, ty::ClassType, ty::UnpackedType};
+use moore_common::name::get_name_table;
+
+/// A member of a class, together with the class that declares it.
+#[derive(Debug, Clone)]
+pub struct ClassMember<'a, T> {
+    /// The class declaring the member.
+    pub class: ClassType<'a>,
+    /// The member itself.
+    pub member: &'a T,
+}
+
+/// Get the HIR of a class declaration.
+pub fn class_hir<'a>(
+    cx: &impl Context<'a>,
+    ast: &'a ast::ClassDecl<'a>,
+) -> Result<&'a hir::Class<'a>> {
+    match cx.hir_of(ast.id())? {
+        HirNode::Class(x) => Ok(x),
+        _ => unreachable!(),
+    }
+}
+
+/// Determine the base class a class extends, if any.
+pub fn base_class<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+) -> Result<Option<ClassType<'a>>> {
+    let hir = class_hir(cx, class.ast)?;
+    let (ty_id, _) = match hir.extends {
+        Some(ref x) => x,
+        None => return Ok(None),
+    };
+    let ast = cx.ast_for_id(*ty_id).as_all().get_type().unwrap();
+    let ty = cx.packed_type_from_ast(Ref(ast), class.env, None);
+    if ty.is_error() {
+        return Err(());
+    }
+    match ty.get_class() {
+        Some(base) => Ok(Some(base.clone())),
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` is not a class", ast.span().extract()))
+                    .span(ast.span())
+                    .add_note(format!("{} can only extend another class", class.ast)),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Determine a class and all its base classes, starting with the class itself.
+pub fn class_chain<'a>(cx: &impl Context<'a>, class: &ClassType<'a>) -> Result<Vec<ClassType<'a>>> {
+    let mut chain = vec![class.clone()];
+    let mut next = base_class(cx, class)?;
+    while let Some(base) = next {
+        if chain.iter().any(|c| c.ast.id() == base.ast.id()) {
+            cx.emit(
+                DiagBuilder2::error(format!("{} extends itself", class.ast))
+                    .span(class.ast.name.span),
+            );
+            return Err(());
+        }
+        next = base_class(cx, &base)?;
+        chain.push(base);
+    }
+    Ok(chain)
+}
+
+/// Check whether a class is the same as or derived from another class.
+pub fn is_derived_from<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+    base: &ClassType<'a>,
+) -> bool {
+    class_chain(cx, class)
+        .map(|chain| chain.contains(base))
+        .unwrap_or(false)
+}
+
+/// Find the specialization of a class declaration among a class and its bases.
+pub fn find_ancestor<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+    ancestor: NodeId,
+) -> Result<Option<ClassType<'a>>> {
+    Ok(class_chain(cx, class)?
+        .into_iter()
+        .find(|c| c.ast.id() == ancestor))
+}
+
+/// Look up a property of a class or one of its bases.
+pub fn find_property<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+    name: Name,
+) -> Result<Option<ClassMember<'a, hir::ClassProperty>>> {
+    for class in class_chain(cx, class)? {
+        let hir = class_hir(cx, class.ast)?;
+        if let Some(member) = hir.props.iter().find(|p| p.name.value == name) {
+            return Ok(Some(ClassMember { class, member }));
+        }
+    }
+    Ok(None)
+}
+
+/// Look up a method of a class or one of its bases.
+///
+/// Constructors are not inherited, so `new` is only looked up in the class
+/// itself.
+pub fn find_method<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+    name: Name,
+) -> Result<Option<ClassMember<'a, hir::ClassMethod>>> {
+    let chain = class_chain(cx, class)?;
+    let limit = if &*name.as_str() == "new" {
+        1
+    } else {
+        chain.len()
+    };
+    for class in chain.into_iter().take(limit) {
+        let hir = class_hir(cx, class.ast)?;
+        if let Some(member) = hir.methods.iter().find(|m| m.name.value == name) {
+            return Ok(Some(ClassMember { class, member }));
+        }
+    }
+    Ok(None)
+}
+
+/// Look up the constructor declared in a class, if any.
+pub fn find_constructor<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+) -> Result<Option<ClassMember<'a, hir::ClassMethod>>> {
+    find_method(cx, class, get_name_table().intern("new", true))
+}
+
+/// Check whether calls to a method of a class are dispatched dynamically.
+///
+/// A method is virtual if it or any method of the same name in a base class is
+/// declared `virtual`.
+pub fn is_virtual_method<'a>(
+    cx: &impl Context<'a>,
+    class: &ClassType<'a>,
+    name: Name,
+) -> Result<bool> {
+    for class in class_chain(cx, class)? {
+        let hir = class_hir(cx, class.ast)?;
+        if hir.methods.iter().any(|m| m.name.value == name && m.virt) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Find the class declaration a node is nested in, if any.
+pub fn enclosing_class<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+) -> Option<&'a ast::ClassDecl<'a>> {
+    let mut node = cx.ast_for_id(node_id);
+    loop {
+        if let Some(class) = node.as_all().get_class_decl() {
+            return Some(class);
+        }
+        node = node.get_parent()?;
+    }
+}
+
+/// Find the subroutine declaration a node is nested in, if any.
+pub fn enclosing_subroutine<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+) -> Option<&'a ast::SubroutineDecl<'a>> {
+    let mut node = cx.ast_for_id(node_id);
+    loop {
+        if let Some(sub) = node.as_all().get_subroutine_decl() {
+            return Some(sub);
+        }
+        node = node.get_parent()?;
+    }
+}
+
+/// Check whether a node is a method of a class.
+pub fn is_method<'a>(cx: &impl Context<'a>, node_id: NodeId) -> bool {
+    let ast = cx.ast_for_id(node_id);
+    ast.as_all().is_subroutine_decl()
+        && ast
+            .get_parent()
+            .map(|p| p.as_all().is_class_decl())
+            .unwrap_or(false)
+}
+
+/// Check whether a node is a property of a class.
+pub fn is_property<'a>(cx: &impl Context<'a>, node_id: NodeId) -> bool {
+    let ast = cx.ast_for_id(node_id);
+    ast.as_all().is_var_decl_name()
+        && ast
+            .get_parent()
+            .and_then(|p| p.get_parent())
+            .map(|p| p.as_all().is_class_decl())
+            .unwrap_or(false)
+}
+
+/// Determine the type of `this` within a node nested in a class.
+///
+/// The `env` is the parameter environment of the class specialization the node
+/// is being analyzed in.
+pub fn this_type<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Option<&'a UnpackedType<'a>> {
+    let ast = enclosing_class(cx, node_id)?;
+    Some(UnpackedType::make(
+        cx,
+        ty::UnpackedCore::Class(ClassType { ast, env }),
+    ))
+}
+
+/// Determine the class a method or property declaration belongs to, as
+/// specialized from within the class `within`.
+pub fn declaring_class<'a>(
+    cx: &impl Context<'a>,
+    member: NodeId,
+    within: &ClassType<'a>,
+) -> Result<ClassType<'a>> {
+    let ast = match enclosing_class(cx, member) {
+        Some(x) => x,
+        None => bug_span!(cx.span(member), cx, "member not in a class"),
+    };
+    match find_ancestor(cx, within, ast.id())? {
+        Some(x) => Ok(x),
+        None => bug_span!(
+            cx.span(member),
+            cx,
+            "{} is not declared in `{}` or its bases",
+            cx.ast_for_id(member),
+            within.ast.name
+        ),
+    }
+}
+
+/// Determine the name under which a class is emitted.
+///
+/// Parametrized classes have the parameter environment appended to their name
+/// to distinguish the different specializations.
+pub fn class_name<'a>(cx: &impl Context<'a>, class: &ClassType<'a>) -> String {
+    let has_params = class_hir(cx, class.ast)
+        .map(|hir| !hir.params.is_empty())
+        .unwrap_or(false);
+    if has_params {
+        format!("{}.param{}", class.ast.name, class.env.0)
+    } else {
+        format!("{}", class.ast.name)
+    }
+}
+
+/// Determine the parameter environment of a method called by name.
+///
+/// Methods inherited from a base class are analyzed in the environment of the
+/// base class specialization. Calls to anything other than a method, and calls
+/// to static methods of unrelated classes, retain the environment of the call
+/// site.
+pub fn callee_env<'a>(
+    cx: &impl Context<'a>,
+    call_site: NodeId,
+    callee: NodeId,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    if !is_method(cx, callee) {
+        return Ok(env);
+    }
+    let this = match this_type(cx, call_site, env).and_then(|ty| ty.get_class()) {
+        Some(x) => x.clone(),
+        None => return Ok(env),
+    };
+    let decl = enclosing_class(cx, callee).unwrap();
+    Ok(find_ancestor(cx, &this, decl.id())?
+        .map(|class| class.env)
+        .unwrap_or(env))
+}
+
+/// Check whether a method is static.
+pub fn is_static_method<'a>(cx: &impl Context<'a>, method: NodeId) -> Result<bool> {
+    let class = match enclosing_class(cx, method) {
+        Some(x) => x,
+        None => return Ok(false),
+    };
+    Ok(class_hir(cx, class)?
+        .methods
+        .iter()
+        .any(|m| m.id == method && m.statik))
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Clocking blocks.
+//!
+//! A clocking block samples its inputs and drives its outputs synchronously to
+//! a clocking event (see IEEE 1800-2017 §14). The block itself behaves like an
+//! event which is triggered after the inputs have been sampled, such that
+//! `@(cb)` observes the new samples. The sampled values are accessed as
+//! `cb.signal`, and outputs are driven with synchronous drives such as
+//! `cb.signal <= value`.
+
+use crate::crate_prelude::*;
+use crate::hir::HirNode;
+
+/// Get the HIR of a clocking block.
+pub fn clocking_hir<'a>(cx: &impl Context<'a>, id: NodeId) -> Result<&'a hir::ClockingBlock> {
+    match cx.hir_of(id)? {
+        HirNode::ClockingBlock(x) => Ok(x),
+        _ => unreachable!(),
+    }
+}
+
+/// Look up a signal of a clocking block by name.
+pub fn find_clock_var<'a>(
+    cx: &impl Context<'a>,
+    block: &'a hir::ClockingBlock,
+    name: Name,
+) -> Result<Option<&'a hir::ClockVar>> {
+    for &id in &block.signals {
+        match cx.hir_of(id)? {
+            HirNode::ClockVar(var) if var.name.value == name => return Ok(Some(var)),
+            _ => (),
+        }
+    }
+    Ok(None)
+}
+
+/// Determine the clocking block an expression refers to, if any.
+pub fn clocking_block_of_expr<'a>(
+    cx: &impl Context<'a>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Option<&'a hir::ClockingBlock> {
+    match cx.hir_of(expr_id).ok()? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        })
+        | HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Scope(..),
+            ..
+        }) => (),
+        _ => return None,
+    }
+    match cx.hir_of(cx.resolve_node(expr_id, env).ok()?).ok()? {
+        HirNode::ClockingBlock(x) => Some(x),
+        _ => None,
+    }
+}
+
+/// Determine the clocking block signal an expression such as `cb.a` refers
+/// to, if any.
+pub fn clock_var_of_expr<'a>(
+    cx: &impl Context<'a>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Option<&'a hir::ClockVar> {
+    let (target, name) = match cx.hir_of(expr_id).ok()? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target, name),
+            ..
+        }) => (*target, *name),
+        _ => return None,
+    };
+    let block = clocking_block_of_expr(cx, target, env)?;
+    find_clock_var(cx, block, name.value).ok()?
+}
+
+/// Resolve a field access `cb.a` into a clocking block, or emit a diagnostic
+/// if the block has no such signal.
+pub fn resolve_clock_var<'a>(
+    cx: &impl Context<'a>,
+    block: &'a hir::ClockingBlock,
+    name: Spanned<Name>,
+) -> Result<&'a hir::ClockVar> {
+    match find_clock_var(cx, block, name.value)? {
+        Some(var) => Ok(var),
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "clocking block `{}` has no signal `{}`",
+                    block.name, name
+                ))
+                .span(name.span)
+                .add_note(format!("`{}` declared here:", block.name))
+                .span(block.name.span),
+            );
+            Err(())
+        }
+    }
+}
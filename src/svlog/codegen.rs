@@ -3,22 +3,49 @@
 //! This module implements LLHD code generation.
 
 use crate::{
+    cdc::{self, CdcModule},
+    class, clocking,
+    constraint::{self, Check, CheckKind, DistEntry, Distribution, RandVar, RandomizeModel},
+    coverage::{CoverModel, CoverPointModel},
     crate_prelude::*,
-    hir::{AccessedNode, HirNode},
+    drivers::DriverTable,
+    hir::{AccessedNode, HierPath, HirNode},
     port_list::PortList,
     resolver::InstTarget,
+    timescale,
     ty::UnpackedType,
     value::{Value, ValueKind},
     ParamEnv,
 };
+use moore_common::lint::{NAMING, UNCONNECTED};
 use num::{BigInt, One, ToPrimitive, Zero};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     iter::{once, repeat},
     ops::{Deref, DerefMut},
     rc::Rc,
+    sync::Arc,
 };
 
+/// The number of times a stage of a call to `randomize()` is drawn before
+/// giving up and drawing the previous stage again.
+const RANDOMIZE_ATTEMPTS: usize = 1000;
+
+/// The violation reported by the runtime if no branch of a `unique` or
+/// `priority` statement matches.
+const VIOLATION_NO_MATCH: usize = 0;
+
+/// The violation reported by the runtime if multiple branches of a `unique` or
+/// `unique0` statement match.
+const VIOLATION_MULTIPLE_MATCHES: usize = 1;
+
+/// The granularity in seconds at which the time of the last trigger of an
+/// event is recorded. This is finer than any valid time precision, such that
+/// distinct time steps always have distinct stamps.
+fn event_time_granularity() -> num::BigRational {
+    num::BigRational::new(One::one(), num::pow(BigInt::from(10), 15))
+}
+
 /// A code generator.
 ///
 /// Use this struct to emit LLHD code for nodes in a [`Context`].
@@ -42,16 +69,130 @@ impl<'gcx, C> CodeGenerator<'gcx, C> {
     }
 
     /// Finalize code generation and return the generated LLHD module.
-    pub fn finalize(self) -> llhd::ir::Module {
+    pub fn finalize(mut self) -> llhd::ir::Module {
+        self.emit_dispatchers();
         self.into
     }
+
+    /// Emit the functions dispatching calls to virtual methods.
+    ///
+    /// Each dispatcher compares the class id of the object against the ids of
+    /// all constructed classes derived from the class declaring the method,
+    /// and calls the implementation of the matching class. Objects of any
+    /// other class use the implementation of the declaring class. See
+    /// `docs/classes.md`.
+    fn emit_dispatchers(&mut self) {
+        for disp in std::mem::replace(&mut self.tables.dispatchers, Vec::new()) {
+            let mut func = llhd::ir::UnitData::new(
+                llhd::ir::UnitKind::Function,
+                disp.name.clone(),
+                disp.sig.clone(),
+            );
+            let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut func);
+            let args: Vec<_> = builder.input_args().collect();
+            builder.set_name(args[0], "this".to_string());
+            let entry_blk = builder.block();
+            builder.append_to(entry_blk);
+            let mut id_sig = llhd::ir::Signature::new();
+            id_sig.add_input(llhd::int_ty(64));
+            id_sig.set_return_type(llhd::int_ty(32));
+            let id_unit =
+                builder.add_extern(llhd::ir::UnitName::Global("moore.class.id".into()), id_sig);
+            let inst = builder.ins().call(id_unit, vec![args[0]]);
+            let id = builder.unit().get_inst_result(inst).unwrap();
+            let sig = &disp.sig;
+            let call = |builder: &mut llhd::ir::UnitBuilder, target: llhd::ir::UnitName| {
+                let unit = builder.add_extern(target, sig.clone());
+                let inst = builder.ins().call(unit, args.clone());
+                match builder.unit().get_inst_result(inst) {
+                    Some(value) => builder.ins().ret_value(value),
+                    None => builder.ins().ret(),
+                };
+            };
+            for (index, constructed) in self.tables.constructed_classes.iter().enumerate() {
+                if !constructed.chain.contains(&disp.class) {
+                    continue;
+                }
+                let target = match constructed.methods.get(&disp.method) {
+                    Some(x) if *x != disp.default => x.clone(),
+                    _ => continue,
+                };
+                let class_id = builder.ins().const_int((32, index + 1));
+                let matches = builder.ins().eq(id, class_id);
+                let taken_blk = builder.block();
+                let next_blk = builder.block();
+                builder.ins().br_cond(matches, next_blk, taken_blk);
+                builder.append_to(taken_blk);
+                call(&mut builder, target);
+                builder.append_to(next_blk);
+            }
+            call(&mut builder, disp.default.clone());
+            self.into.add_unit(func);
+        }
+    }
+
+    /// Describe the counter layout of all emitted covergroup instances.
+    ///
+    /// Each instance is emitted as an array of hit counters. The returned text
+    /// maps the array indices of each instance signal back to the coverpoint
+    /// and cross bins they count. See `docs/coverage.md`.
+    pub fn coverage_map(&self) -> String {
+        let mut map = String::new();
+        for (entity, signal, model) in &self.tables.cover_instances {
+            map.push_str(&format!(
+                "{}.{}: covergroup {}\n{}",
+                entity, signal, model.name, model
+            ));
+        }
+        map
+    }
 }
 
 #[derive(Default)]
 struct Tables<'gcx> {
-    module_defs: HashMap<NodeEnvId, Result<Rc<EmittedModule<'gcx>>>>,
+    module_defs: HashMap<(NodeEnvId, Vec<(HierPath, NodeId)>), Result<Rc<EmittedModule<'gcx>>>>,
     module_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
     interned_types: HashMap<&'gcx UnpackedType<'gcx>, Result<llhd::Type>>,
+    dpi_imports: HashMap<NodeEnvId, Result<(llhd::ir::UnitName, llhd::ir::Signature)>>,
+    dpi_symbols: HashMap<String, (NodeId, llhd::ir::Signature)>,
+    cover_instances: Vec<(String, Name, Arc<CoverModel<'gcx>>)>,
+    class_methods: HashMap<
+        (ty::ClassType<'gcx>, Option<NodeId>),
+        Result<(llhd::ir::UnitName, llhd::ir::Signature)>,
+    >,
+    constructed_classes: Vec<ConstructedClass<'gcx>>,
+    dispatchers: Vec<Dispatcher<'gcx>>,
+    /// The tasks currently being inlined into a process.
+    inlined_tasks: Vec<NodeId>,
+    /// The top-level modules emitted, for the clock domain crossing report.
+    cdc_tops: Vec<(String, Rc<CdcModule>)>,
+    /// The modules whose names have been checked.
+    naming_checked: HashSet<NodeId>,
+}
+
+/// A class of which objects are constructed.
+///
+/// The position of the class in the code generator's table, plus one, is the
+/// class id passed to the runtime when constructing objects.
+struct ConstructedClass<'gcx> {
+    /// The class and all its bases.
+    chain: Vec<ty::ClassType<'gcx>>,
+    /// The functions implementing the virtual methods of the class.
+    methods: HashMap<Name, llhd::ir::UnitName>,
+}
+
+/// A function dispatching calls to a virtual method.
+struct Dispatcher<'gcx> {
+    /// The class declaring the called method.
+    class: ty::ClassType<'gcx>,
+    /// The name of the called method.
+    method: Name,
+    /// The name of the dispatcher function.
+    name: llhd::ir::UnitName,
+    /// The signature shared by the dispatcher and all implementations.
+    sig: llhd::ir::Signature,
+    /// The implementation in the declaring class.
+    default: llhd::ir::UnitName,
 }
 
 impl<'gcx, C> Deref for CodeGenerator<'gcx, C> {
@@ -68,13 +209,43 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         self.emit_module_with_env(id, self.default_param_env())
     }
 
+    /// Report the clock domains of the emitted modules, and the signals
+    /// crossing between them.
+    ///
+    /// Emits a warning for each crossing without a synchronizer.
+    pub fn cdc_report(&self) -> String {
+        cdc::cdc_report(self.cx, &self.tables.cdc_tops)
+    }
+
     /// Emit the code for a module and all its dependent modules.
     pub fn emit_module_with_env(
         &mut self,
         id: NodeId,
         env: ParamEnv,
     ) -> Result<Rc<EmittedModule<'gcx>>> {
-        if let Some(x) = self.tables.module_defs.get(&id.env(env)) {
+        let module = self.emit_module_with_exports(id, env, &[])?;
+        let name = match self.hir_of(id)? {
+            HirNode::Module(m) => m.name.value.to_string(),
+            _ => unreachable!(),
+        };
+        self.tables.cdc_tops.push((name, module.cdc.clone()));
+        Ok(module)
+    }
+
+    /// Emit the code for a module, exporting additional signals.
+    ///
+    /// Each entry in `exports` is a signal within the module or one of its
+    /// subinstances which is referred to hierarchically from outside. These
+    /// signals are threaded out of the entity as additional hidden outputs,
+    /// after the regular ports.
+    fn emit_module_with_exports(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+        exports: &[(HierPath, NodeId)],
+    ) -> Result<Rc<EmittedModule<'gcx>>> {
+        let key = (id.env(env), exports.to_vec());
+        if let Some(x) = self.tables.module_defs.get(&key) {
             return x.clone();
         }
         let hir = match self.hir_of(id)? {
@@ -96,15 +267,32 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         if env != self.default_param_env() {
             entity_name.push_str(&format!(".param{}", env.0));
         }
+        if !exports.is_empty() {
+            let variant = self
+                .tables
+                .module_defs
+                .keys()
+                .filter(|(k, e)| *k == id.env(env) && !e.is_empty())
+                .count();
+            entity_name.push_str(&format!(".hier{}", variant));
+        }
         let name = llhd::ir::UnitName::Global(entity_name.clone());
 
+        // Add the exported signals to the signature.
+        let mut sig = ports.sig.clone();
+        for &(path, decl) in exports {
+            let ty = self.hier_signal_type(path, decl, env)?;
+            sig.add_output(llhd::signal_ty(self.emit_type(ty)?));
+        }
+
         // Create entity.
-        let mut ent =
-            llhd::ir::UnitData::new(llhd::ir::UnitKind::Entity, name.clone(), ports.sig.clone());
+        let mut ent = llhd::ir::UnitData::new(llhd::ir::UnitKind::Entity, name.clone(), sig);
         let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut ent);
-        self.tables
-            .module_signatures
-            .insert(id.env(env), (name, ports.sig.clone()));
+        if exports.is_empty() {
+            self.tables
+                .module_signatures
+                .insert(id.env(env), (name, ports.sig.clone()));
+        }
         let mut values = HashMap::new();
         let mut gen = UnitGenerator {
             gen: self,
@@ -114,6 +302,10 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            resolved_nets: Default::default(),
+            drivers: Default::default(),
+            clocks: Default::default(),
+            this: None,
         };
 
         // Assign proper port names and collect ports into a lookup table.
@@ -127,6 +319,31 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             gen.builder.set_name(arg, port.name.clone());
             gen.values.insert(port.accnode, arg);
         }
+        for (index, &(path, decl)) in exports.iter().enumerate() {
+            let arg = gen.builder.output_arg(ports.outputs.len() + index);
+            let name = gen.hier_signal_name(path, decl)?;
+            gen.builder.set_name(arg, name);
+            gen.values.insert(AccessedNode::Hier(path, decl), arg);
+        }
+
+        // Emit signals for the hierarchical references made from within the
+        // module. These are connected to the hidden outputs of the referenced
+        // instances.
+        let mut refs = BTreeSet::new();
+        gen.collect_hier_refs(env, &hir.block, &mut refs)?;
+        for (path, decl) in refs {
+            let accnode = AccessedNode::Hier(path, decl);
+            if gen.values.contains_key(&accnode) {
+                continue;
+            }
+            let ty = gen.hier_signal_type(path, decl, env)?;
+            let init = gen.type_default_value(ty);
+            let init = gen.emit_const(init, env, gen.span(decl))?;
+            let value = gen.builder.ins().sig(init);
+            let name = gen.hier_signal_name(path, decl)?;
+            gen.builder.set_name(value, name);
+            gen.values.insert(accnode, value);
+        }
 
         debug!("  Ports:");
         for (node, value) in gen.values.iter() {
@@ -139,7 +356,37 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         }
 
         // Emit the actual contents of the entity.
+        for port in ports.inputs.iter().chain(ports.outputs.iter()) {
+            if let ModulePortKind::Port = port.kind {
+                gen.drivers.add_port(port.port, port.ty, env);
+            }
+        }
         gen.emit_module_block(id, env, &hir.block, &entity_name)?;
+        gen.emit_net_resolution();
+
+        // Connect the exported signals declared in this module to their
+        // hidden outputs.
+        for &(path, decl) in exports {
+            if gen.hier_path_insts(path).is_empty() {
+                let output = gen.values[&AccessedNode::Hier(path, decl)];
+                let value = gen.emitted_value(decl);
+                gen.builder.ins().con(output, value);
+                gen.drivers.add_external(decl);
+            }
+        }
+
+        // Check the drivers of the signals declared in the module.
+        let cx = gen.cx;
+        gen.drivers.check(cx)?;
+        let mut clocks = std::mem::replace(&mut gen.clocks, Default::default());
+        clocks.add_comb(gen.drivers.comb_edges());
+
+        // Check the names declared in the module.
+        if cx.sess().has_lint(&NAMING) && gen.tables.naming_checked.insert(id) {
+            if let Ok(rules) = cx.naming_rules() {
+                rules.check_module(cx, hir, clocks.registers());
+            }
+        }
 
         // Assign default values to undriven output ports.
         for port in ports.outputs.iter() {
@@ -172,11 +419,115 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         }
 
         let unit = self.into.add_unit(ent);
-        let result = Ok(Rc::new(EmittedModule { unit, ports }));
-        self.tables.module_defs.insert(id.env(env), result.clone());
+        let result = Ok(Rc::new(EmittedModule {
+            unit,
+            ports,
+            cdc: Rc::new(clocks),
+        }));
+        self.tables.module_defs.insert(key, result.clone());
         result
     }
 
+    /// Determine the type of a signal referred to hierarchically.
+    fn hier_signal_type(
+        &mut self,
+        path: HierPath,
+        decl: NodeId,
+        env: ParamEnv,
+    ) -> Result<&'gcx UnpackedType<'gcx>> {
+        let mut env = env;
+        for &inst_id in self.hier_path_insts(path) {
+            let inst = match self.hir_of(inst_id)? {
+                HirNode::Inst(x) => x,
+                _ => unreachable!(),
+            };
+            env = self.inst_details(Ref(inst), env)?.inner_env;
+        }
+        self.type_of(decl, env)
+    }
+
+    /// Determine the name of a signal referred to hierarchically.
+    ///
+    /// This is the dot-separated list of instance names, followed by the name
+    /// of the signal, e.g. `dut.u_core.pc_q`.
+    fn hier_signal_name(&self, path: HierPath, decl: NodeId) -> Result<String> {
+        let mut names = vec![];
+        for &id in self.hier_path_insts(path).iter().chain(once(&decl)) {
+            names.push(match self.hir_of(id)? {
+                HirNode::Inst(x) => x.name.value,
+                HirNode::VarDecl(x) => x.name.value,
+                HirNode::IntPort(x) => x.name.value,
+                x => bug_span!(x.span(), self.cx, "{} in hierarchical path", x.desc_full()),
+            });
+        }
+        Ok(names
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+
+    /// Collect the hierarchical references made within a module block.
+    fn collect_hier_refs(
+        &mut self,
+        env: ParamEnv,
+        hir: &hir::ModuleBlock,
+        refs: &mut BTreeSet<(HierPath, NodeId)>,
+    ) -> Result<()> {
+        let mut add_refs = |acc: &hir::AccessTable| {
+            for &node in acc.read.iter().chain(acc.written.iter()) {
+                if let AccessedNode::Hier(path, decl) = node {
+                    refs.insert((path, decl));
+                }
+            }
+        };
+        for &proc_id in &hir.procs {
+            if let HirNode::Proc(prok) = self.hir_of(proc_id)? {
+                add_refs(&*self.accessed_nodes(prok.stmt, env)?);
+            }
+        }
+        for &id in hir.assigns.iter().chain(&hir.insts).chain(&hir.gates) {
+            add_refs(&*self.accessed_nodes(id, env)?);
+        }
+        for &gen_id in &hir.gens {
+            let hir = match self.hir_of(gen_id)? {
+                HirNode::Gen(x) => x,
+                _ => unreachable!(),
+            };
+            match hir.kind {
+                hir::GenKind::If {
+                    cond,
+                    ref main_body,
+                    ref else_body,
+                } => {
+                    if self.constant_value_of(cond, env).is_false() {
+                        if let Some(else_body) = else_body {
+                            self.collect_hier_refs(env, else_body, refs)?;
+                        }
+                    } else {
+                        self.collect_hier_refs(env, main_body, refs)?;
+                    }
+                }
+                hir::GenKind::For {
+                    ref init,
+                    cond,
+                    step,
+                    ref body,
+                } => {
+                    let mut local_env = env;
+                    for &i in init {
+                        local_env = self.execute_genvar_init(i, local_env)?;
+                    }
+                    while self.constant_value_of(cond, local_env).is_true() {
+                        self.collect_hier_refs(local_env, body, refs)?;
+                        local_env = self.execute_genvar_step(step, local_env)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn determine_module_ports(
         &mut self,
         ports: &'gcx [port_list::IntPort<'gcx>],
@@ -323,6 +674,21 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             _ => unreachable!(),
         };
 
+        // Check the restrictions of the procedure kind.
+        self.check_procedure(id, env)?;
+
+        // Final procedures execute in zero time.
+        if hir.kind == ast::ProcedureKind::Final {
+            let mut checker = FinalTimingChecker {
+                cx: self.cx,
+                failed: false,
+            };
+            hir::Visitor::visit_node_with_id(&mut checker, hir.stmt, false);
+            if checker.failed {
+                return Err(());
+            }
+        }
+
         // Find the accessed nodes.
         let acc = self.accessed_nodes(hir.stmt, env)?;
         trace!("Process accesses {:#?}", acc);
@@ -330,29 +696,13 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         let mut inputs = vec![];
         let mut outputs = vec![];
         for &id in acc.read.iter().filter(|id| !acc.written.contains(id)) {
-            sig.add_input(llhd::signal_ty(self.emit_type(match id {
-                AccessedNode::Regular(id) => self.type_of(id, env)?,
-                AccessedNode::Intf(intf, id) => {
-                    let intf_ty = self.type_of(intf, env)?;
-                    let intf_ty_inner = intf_ty.resolve_full().core.get_interface().unwrap();
-                    let mut sig_ty = self.type_of(id, intf_ty_inner.env)?.clone();
-                    sig_ty.dims.extend(&intf_ty.dims);
-                    sig_ty.intern(self.cx)
-                }
-            })?));
+            let ty = self.accessed_node_type(id, env)?;
+            sig.add_input(llhd::signal_ty(self.emit_type(ty)?));
             inputs.push(id);
         }
         for &id in acc.written.iter() {
-            sig.add_output(llhd::signal_ty(self.emit_type(match id {
-                AccessedNode::Regular(id) => self.type_of(id, env)?,
-                AccessedNode::Intf(intf, id) => {
-                    let intf_ty = self.type_of(intf, env)?;
-                    let intf_ty_inner = intf_ty.resolve_full().core.get_interface().unwrap();
-                    let mut sig_ty = self.type_of(id, intf_ty_inner.env)?.clone();
-                    sig_ty.dims.extend(&intf_ty.dims);
-                    sig_ty.intern(self.cx)
-                }
-            })?));
+            let ty = self.accessed_node_type(id, env)?;
+            sig.add_output(llhd::signal_ty(self.emit_type(ty)?));
             outputs.push(id);
         }
         trace!("Process Inputs: {:?}", inputs);
@@ -383,37 +733,14 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut prok);
 
         // Assign names to inputs and outputs.
-        let guess_name = |id| {
-            let (prefix, id) = match id {
-                AccessedNode::Regular(id) => (None, id),
-                AccessedNode::Intf(inst_id, id) => {
-                    let inst_name = match self.hir_of(inst_id).ok()? {
-                        HirNode::IntPort(x) => Some(x.name),
-                        HirNode::Inst(x) => Some(x.name),
-                        _ => None,
-                    };
-                    (inst_name, id)
-                }
-            };
-            let name = match self.hir_of(id).ok()? {
-                HirNode::VarDecl(x) => Some(x.name),
-                HirNode::IntPort(x) => Some(x.name),
-                _ => None,
-            };
-            match (prefix, name) {
-                (Some(prefix), Some(name)) => Some(format!("{}.{}", prefix, name)),
-                (None, Some(name)) => Some(format!("{}", name)),
-                _ => None,
-            }
-        };
         for (i, &id) in inputs.iter().enumerate() {
-            if let Some(name) = guess_name(id) {
+            if let Some(name) = self.accessed_node_name(id) {
                 let value = builder.input_arg(i);
                 builder.set_name(value, name);
             }
         }
         for (i, &id) in outputs.iter().enumerate() {
-            if let Some(name) = guess_name(id) {
+            if let Some(name) = self.accessed_node_name(id) {
                 let value = builder.output_arg(i);
                 builder.set_name(value, name);
             }
@@ -436,6 +763,10 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            resolved_nets: Default::default(),
+            drivers: Default::default(),
+            clocks: Default::default(),
+            this: None,
         };
         let entry_blk = pg.add_nameless_block();
         pg.builder.append_to(entry_blk);
@@ -476,15 +807,12 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 check_blk
             }
             ast::ProcedureKind::Final => {
-                // TODO(fschuiki): Replace this with a cleverer way to implement a trigger-on-end.
+                // Register the process with the runtime, which resumes it
+                // exactly once at the end of the simulation. Until then, the
+                // process waits without any sensitivity.
                 let body_blk = pg.add_named_block("body");
-                let endtimes = pg.builder.ins().const_time(llhd::value::TimeValue::new(
-                    "9001".parse().unwrap(),
-                    0,
-                    0,
-                ));
-                pg.builder.set_name(endtimes, "endtimes".to_string());
-                pg.builder.ins().wait_time(body_blk, endtimes, vec![]);
+                pg.emit_runtime_call("moore.final", vec![], None);
+                pg.builder.ins().wait(body_blk, vec![]);
                 pg.builder.append_to(body_blk);
                 pg.flush_mir(); // ensure we don't reuse earlier expr probe
                 pg.emit_shadow_update();
@@ -519,92 +847,812 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         })
     }
 
-    /// Map a type to an LLHD type (interned).
-    fn emit_type(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
-        if let Some(x) = self.tables.interned_types.get(&ty) {
-            x.clone()
-        } else {
-            let x = self.emit_type_uninterned(ty);
-            self.tables.interned_types.insert(ty, x.clone());
-            x
-        }
+    /// Determine the type of a node accessed by a process.
+    fn accessed_node_type(
+        &mut self,
+        id: AccessedNode,
+        env: ParamEnv,
+    ) -> Result<&'gcx UnpackedType<'gcx>> {
+        Ok(match id {
+            AccessedNode::Regular(id) => self.type_of(id, env)?,
+            AccessedNode::Intf(intf, id) => {
+                let intf_ty = self.type_of(intf, env)?;
+                let intf_ty_inner = intf_ty.resolve_full().core.get_interface().unwrap();
+                let mut sig_ty = self.type_of(id, intf_ty_inner.env)?.clone();
+                sig_ty.dims.extend(&intf_ty.dims);
+                sig_ty.intern(self.cx)
+            }
+            AccessedNode::Hier(path, id) => self.hier_signal_type(path, id, env)?,
+        })
     }
 
-    /// Map a type to an LLHD type.
-    fn emit_type_uninterned(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
-        if ty.is_error() {
-            return Err(());
+    /// Guess a human-readable name for a node accessed by a process.
+    fn accessed_node_name(&self, id: AccessedNode) -> Option<String> {
+        let (prefix, id) = match id {
+            AccessedNode::Regular(id) => (None, id),
+            AccessedNode::Intf(inst_id, id) => {
+                let inst_name = match self.hir_of(inst_id).ok()? {
+                    HirNode::IntPort(x) => Some(x.name),
+                    HirNode::Inst(x) => Some(x.name),
+                    _ => None,
+                };
+                (inst_name, id)
+            }
+            AccessedNode::Hier(path, id) => return self.hier_signal_name(path, id).ok(),
+        };
+        let name = match self.hir_of(id).ok()? {
+            HirNode::VarDecl(x) => Some(x.name),
+            HirNode::IntPort(x) => Some(x.name),
+            HirNode::ClockingBlock(x) => Some(x.name),
+            HirNode::ClockVar(x) => {
+                let block = clocking::clocking_hir(self.cx, x.block).ok()?;
+                return Some(format!("{}.{}", block.name, x.name));
+            }
+            _ => None,
+        };
+        match (prefix, name) {
+            (Some(prefix), Some(name)) => Some(format!("{}.{}", prefix, name)),
+            (None, Some(name)) => Some(format!("{}", name)),
+            _ => None,
         }
-        let ty = ty.resolve_full();
+    }
 
-        // Handle things that coalesce easily to scalars.
-        if ty.coalesces_to_llhd_scalar() {
-            return Ok(llhd::int_ty(ty.get_bit_size().unwrap()));
-        }
+    /// Emit a process that samples a covergroup instance whenever the
+    /// covergroup's sampling event triggers.
+    ///
+    /// Returns `None` if the declaration is not a covergroup instance, or the
+    /// covergroup has no sampling event.
+    fn emit_cover_sampler(
+        &mut self,
+        decl_id: NodeId,
+        env: ParamEnv,
+        name_prefix: &str,
+    ) -> Result<Option<EmittedProcedure>> {
+        let ty = self.type_of(decl_id, env)?;
+        let cg = match ty.get_covergroup() {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let hir = match self.hir_of(cg.ast.id())? {
+            HirNode::Covergroup(x) => x,
+            _ => unreachable!(),
+        };
+        let event = match hir.event {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let model = self.cover_model(Ref(cg.ast), cg.env)?;
 
-        // Handle arrays.
-        if let Some(dim) = ty.outermost_dim() {
-            let size = match dim.get_size() {
-                Some(size) => size,
-                None => panic!("cannot map unsized array `{}` to LLHD", ty),
-            };
-            let inner = ty.pop_dim(self.cx).unwrap();
-            return Ok(llhd::array_ty(size, self.emit_type(inner)?));
+        // The sampler reads everything the event and the coverpoints refer
+        // to, and drives the hit counters of the instance.
+        let acc = self.accessed_nodes(hir.id, cg.env)?;
+        let mut sig = llhd::ir::Signature::new();
+        let inputs: Vec<_> = acc.read.iter().cloned().collect();
+        for &id in &inputs {
+            let ty = self.accessed_node_type(id, cg.env)?;
+            sig.add_input(llhd::signal_ty(self.emit_type(ty)?));
         }
+        let outputs = vec![AccessedNode::Regular(decl_id)];
+        sig.add_output(llhd::signal_ty(self.emit_type(ty)?));
 
-        // Handle structs.
-        if let Some(strukt) = ty.get_struct() {
-            let mut types = vec![];
-            for member in &strukt.members {
-                types.push(self.emit_type(member.ty)?);
+        let proc_name = format!("{}.cover.{}.{}", name_prefix, decl_id.as_usize(), env.0);
+        let mut prok = llhd::ir::UnitData::new(
+            llhd::ir::UnitKind::Process,
+            llhd::ir::UnitName::Local(proc_name),
+            sig,
+        );
+        let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut prok);
+        for (i, &id) in inputs.iter().enumerate() {
+            if let Some(name) = self.accessed_node_name(id) {
+                let value = builder.input_arg(i);
+                builder.set_name(value, name);
             }
-            return Ok(llhd::struct_ty(types));
         }
+        let output = builder.output_arg(0);
+        if let Some(name) = self.accessed_node_name(outputs[0]) {
+            builder.set_name(output, name);
+        }
+        let mut values = HashMap::new();
+        for (&id, arg) in inputs.iter().zip(builder.input_args()) {
+            values.insert(id, arg);
+        }
+        values.insert(outputs[0], output);
+        let mut pg = UnitGenerator {
+            gen: self,
+            builder: &mut builder,
+            values: &mut values,
+            interned_consts: Default::default(),
+            interned_lvalues: Default::default(),
+            interned_rvalues: Default::default(),
+            shadows: Default::default(),
+            resolved_nets: Default::default(),
+            drivers: Default::default(),
+            clocks: Default::default(),
+            this: None,
+        };
 
-        // Handle packed types.
-        if let Some(packed) = ty.get_packed() {
-            let packed = packed.resolve_full();
-            return match packed.core {
-                ty::PackedCore::Error => Ok(llhd::void_ty()),
-                ty::PackedCore::Void => Ok(llhd::void_ty()),
-                ty::PackedCore::IntAtom(ty::IntAtomType::Time) => Ok(llhd::time_ty()),
-                ty::PackedCore::Enum(ref enm) => self.emit_type(enm.base.to_unpacked(self.cx)),
-                _ => unreachable!("emitting `{}` should have been handled above", packed),
+        // Keep a copy of the counters in a variable, since the process only
+        // drives the instance signal.
+        let entry_blk = pg.add_nameless_block();
+        pg.builder.append_to(entry_blk);
+        let init = pg.builder.ins().prb(output);
+        let state = pg.builder.ins().var(init);
+        pg.builder.set_name(state, "counters".to_string());
+        let loop_blk = pg.add_named_block("loop");
+        pg.builder.ins().br(loop_blk);
+        pg.builder.append_to(loop_blk);
+        pg.emit_event_wait(event, cg.env)?;
+
+        // Sample the coverpoints and update the counters.
+        let mut points = vec![];
+        let mut point_guards = vec![];
+        for &id in &hir.points {
+            let cp = match pg.hir_of(id)? {
+                HirNode::Coverpoint(x) => x,
+                _ => unreachable!(),
             };
+            points.push(pg.emit_rvalue(cp.expr, cg.env)?);
+            point_guards.push(match cp.iff {
+                Some(iff) => pg.emit_rvalue_bool(iff, cg.env)?,
+                None => pg.builder.ins().const_int((1, 1)),
+            });
         }
-
-        // Everything else we cannot do.
-        error!("Cannot map type {:#?}", ty);
-        panic!("cannot map `{}` to LLHD", ty);
+        let mut cross_guards = vec![];
+        for cross in &hir.crosses {
+            cross_guards.push(match cross.iff {
+                Some(iff) => pg.emit_rvalue_bool(iff, cg.env)?,
+                None => pg.builder.ins().const_int((1, 1)),
+            });
+        }
+        let current = pg.builder.ins().ld(state);
+        let next = pg.emit_cover_update(&model, current, &points, &point_guards, &cross_guards);
+        pg.builder.ins().st(state, next);
+        let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
+        let one_epsilon = pg.builder.ins().const_time(one_epsilon);
+        pg.builder.ins().drv(output, next, one_epsilon);
+        pg.builder.ins().br(loop_blk);
+
+        Ok(Some(EmittedProcedure {
+            unit: self.into.add_unit(prok),
+            inputs,
+            outputs,
+        }))
     }
 
-    /// Execute the initialization step of a generate loop.
-    fn execute_genvar_init(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        match hir {
-            HirNode::GenvarDecl(_) => Ok(env),
-            HirNode::Stmt(stmt) => match stmt.kind {
-                hir::StmtKind::Assign {
-                    lhs,
-                    rhs,
-                    kind: hir::AssignKind::Block(ast::AssignOp::Identity),
-                } => {
-                    let target_id = self.resolve_node(lhs, env)?;
-                    let init_value = self.constant_value_of(rhs, env);
-                    let mut env_data = self.param_env_data(env).clone();
-                    env_data.set_value(target_id, init_value);
-                    Ok(self.intern_param_env(env_data))
-                }
+    /// Emit a process that samples the inputs of a clocking block and then
+    /// triggers the block itself whenever the clocking event occurs.
+    ///
+    /// The process takes the signals read by the clocking event and the inputs
+    /// as arguments, followed by delayed copies of the inputs with an explicit
+    /// skew in declaration order. It drives the block and its sampled inputs.
+    fn emit_clocking_sampler(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+        name_prefix: &str,
+    ) -> Result<EmittedProcedure> {
+        let hir = clocking::clocking_hir(self.cx, id)?;
+        let mut vars = vec![];
+        for &var_id in &hir.signals {
+            match self.hir_of(var_id)? {
+                HirNode::ClockVar(x) if x.dir.is_input() => vars.push(x),
+                HirNode::ClockVar(_) => (),
                 _ => unreachable!(),
-            },
-            _ => unreachable!(),
+            }
         }
-    }
 
-    /// Execute the iteration step of a generate loop.
-    fn execute_genvar_step(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        let mut env_data = self.param_env_data(env).clone();
+        // Determine the signals read by the clocking event and the inputs
+        // without explicit skew.
+        let mut inputs = vec![];
+        let mut accessed = vec![self.accessed_nodes(hir.event, env)?];
+        for var in &vars {
+            if var.input_delay().is_none() {
+                accessed.push(self.accessed_nodes(var.expr, env)?);
+            }
+        }
+        for acc in accessed {
+            for &id in &acc.read {
+                if !inputs.contains(&id) {
+                    inputs.push(id);
+                }
+            }
+        }
+        let outputs: Vec<_> = once(AccessedNode::Regular(id))
+            .chain(vars.iter().map(|var| AccessedNode::Regular(var.id)))
+            .collect();
+
+        let mut sig = llhd::ir::Signature::new();
+        for &id in &inputs {
+            let ty = self.accessed_node_type(id, env)?;
+            sig.add_input(llhd::signal_ty(self.emit_type(ty)?));
+        }
+        for var in vars.iter().filter(|var| var.input_delay().is_some()) {
+            let ty = self.type_of(var.id, env)?;
+            sig.add_input(llhd::signal_ty(self.emit_type(ty)?));
+        }
+        for &id in &outputs {
+            let ty = self.accessed_node_type(id, env)?;
+            sig.add_output(llhd::signal_ty(self.emit_type(ty)?));
+        }
+
+        let proc_name = format!("{}.clocking.{}.{}", name_prefix, id.as_usize(), env.0);
+        let mut prok = llhd::ir::UnitData::new(
+            llhd::ir::UnitKind::Process,
+            llhd::ir::UnitName::Local(proc_name),
+            sig,
+        );
+        let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut prok);
+        let mut values = HashMap::new();
+        for (i, &id) in inputs.iter().enumerate() {
+            let value = builder.input_arg(i);
+            if let Some(name) = self.accessed_node_name(id) {
+                builder.set_name(value, name);
+            }
+            values.insert(id, value);
+        }
+        let mut delayed = vec![];
+        for (i, var) in vars
+            .iter()
+            .filter(|var| var.input_delay().is_some())
+            .enumerate()
+        {
+            let value = builder.input_arg(inputs.len() + i);
+            builder.set_name(value, format!("{}.{}.skewed", hir.name, var.name));
+            delayed.push(value);
+        }
+        for (i, &id) in outputs.iter().enumerate() {
+            let value = builder.output_arg(i);
+            if let Some(name) = self.accessed_node_name(id) {
+                builder.set_name(value, name);
+            }
+            values.insert(id, value);
+        }
+        let mut pg = UnitGenerator {
+            gen: self,
+            builder: &mut builder,
+            values: &mut values,
+            interned_consts: Default::default(),
+            interned_lvalues: Default::default(),
+            interned_rvalues: Default::default(),
+            shadows: Default::default(),
+            resolved_nets: Default::default(),
+            drivers: Default::default(),
+            clocks: Default::default(),
+            this: None,
+        };
+        let entry_blk = pg.add_nameless_block();
+        pg.builder.append_to(entry_blk);
+        let loop_blk = pg.add_named_block("loop");
+        pg.builder.ins().br(loop_blk);
+        pg.builder.append_to(loop_blk);
+        pg.emit_event_wait(hir.event, env)?;
+
+        // Sample the inputs. Since the clocking event has just occurred, the
+        // signals still carry the values they had right before it, which is
+        // the `#1step` sampling semantics.
+        let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
+        let one_epsilon = pg.builder.ins().const_time(one_epsilon);
+        let mut delayed = delayed.into_iter();
+        for var in &vars {
+            let value = match var.input_delay() {
+                Some(_) => {
+                    let copy = delayed.next().unwrap();
+                    pg.builder.ins().prb(copy)
+                }
+                None => pg.emit_rvalue(var.expr, env)?,
+            };
+            let sample = pg.values[&AccessedNode::Regular(var.id)];
+            pg.builder.ins().drv(sample, value, one_epsilon);
+        }
+
+        // Trigger the clocking block, such that `@(cb)` observes the samples.
+        let event = pg.values[&AccessedNode::Regular(id)];
+        let current = pg.builder.ins().prb(event);
+        let count = pg.builder.ins().ext_field(current, 0);
+        let one = pg.builder.ins().const_int((32, 1));
+        let count = pg.builder.ins().add(count, one);
+        let now = pg.emit_sim_time(event_time_granularity());
+        let value = pg.builder.ins().strukt(vec![count, now]);
+        pg.builder.ins().drv(event, value, one_epsilon);
+        pg.builder.ins().br(loop_blk);
+
+        Ok(EmittedProcedure {
+            unit: self.into.add_unit(prok),
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Declare a subroutine imported via DPI.
+    ///
+    /// The import is emitted as an external function declaration named after
+    /// the C function implementing it. See `docs/dpi.md` for the calling
+    /// convention.
+    fn emit_dpi_import(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+    ) -> Result<(llhd::ir::UnitName, llhd::ir::Signature)> {
+        if let Some(x) = self.tables.dpi_imports.get(&id.env(env)) {
+            return x.clone();
+        }
+        let x = self.emit_dpi_import_uninterned(id, env);
+        self.tables.dpi_imports.insert(id.env(env), x.clone());
+        x
+    }
+
+    fn emit_dpi_import_uninterned(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+    ) -> Result<(llhd::ir::UnitName, llhd::ir::Signature)> {
+        let hir = match self.hir_of(id)? {
+            HirNode::Subroutine(x) => x,
+            _ => unreachable!(),
+        };
+        let dpi = hir.dpi.as_ref().expect("subroutine should be a DPI import");
+
+        // Map the argument and return types.
+        let mut sig = llhd::ir::Signature::new();
+        let mut failed = false;
+        for arg in &hir.args {
+            let ty = typeck::type_of_subroutine_arg(self.cx, arg, env);
+            match self.emit_dpi_type(ty, hir, arg.span) {
+                Ok(ty) => {
+                    sig.add_input(ty);
+                }
+                Err(()) => failed = true,
+            }
+        }
+        let retty = match hir.retty {
+            Some(retty) => {
+                let ty = self.packed_type_from_ast(
+                    Ref(self.ast_for_id(retty).as_all().get_type().unwrap()),
+                    env,
+                    None,
+                );
+                if ty.is_void() {
+                    llhd::void_ty()
+                } else {
+                    self.emit_dpi_type(ty, hir, self.span(retty))?
+                }
+            }
+            None => llhd::void_ty(),
+        };
+        sig.set_return_type(retty);
+        if failed {
+            return Err(());
+        }
+
+        // Declare the function, making sure that imports which bind to the
+        // same C function agree on the signature.
+        let symbol = String::from(&*dpi.cname.value.as_str());
+        let name = llhd::ir::UnitName::Global(symbol.clone());
+        match self.tables.dpi_symbols.get(&symbol) {
+            Some((_, other_sig)) if *other_sig == sig => (),
+            Some(&(other, _)) => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "DPI import `{}` conflicts with an earlier import of C function `{}`",
+                        hir.name, symbol
+                    ))
+                    .span(hir.span)
+                    .add_note("Imports of the same C function must have the same signature")
+                    .add_note("Previous import here:")
+                    .span(self.span(other)),
+                );
+                return Err(());
+            }
+            None => {
+                self.into.declare(name.clone(), sig.clone());
+                self.tables
+                    .dpi_symbols
+                    .insert(symbol, (hir.id, sig.clone()));
+            }
+        }
+        Ok((name, sig))
+    }
+
+    /// Map the type of a DPI import argument or return value to an LLHD type.
+    ///
+    /// Only the integral subset of the C-compatible types is supported. Packed
+    /// vectors are widened to the smallest C integer type that can hold them.
+    fn emit_dpi_type(
+        &mut self,
+        ty: &'gcx UnpackedType<'gcx>,
+        hir: &hir::Subroutine,
+        span: Span,
+    ) -> Result<llhd::Type> {
+        if ty.is_error() {
+            return Err(());
+        }
+        match ty.get_simple_bit_vector() {
+            Some(sbv) if sbv.size <= 64 => {
+                let width = [8, 16, 32, 64]
+                    .iter()
+                    .cloned()
+                    .find(|&w| w >= sbv.size)
+                    .unwrap();
+                Ok(llhd::int_ty(width))
+            }
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "type `{}` cannot cross the DPI boundary of `{}`",
+                        ty, hir.name
+                    ))
+                    .span(span)
+                    .add_note(
+                        "Only `byte`, `shortint`, `int`, `longint`, and `bit`/`logic` scalars and \
+                         packed vectors of up to 64 bits are supported in DPI imports",
+                    ),
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Emit the function implementing a class method (interned).
+    ///
+    /// If `method` is `None`, emits the constructor of the class. Methods are
+    /// emitted as functions taking the object handle as first argument, unless
+    /// they are static. See `docs/classes.md` for the calling convention.
+    fn emit_class_method(
+        &mut self,
+        class: &ty::ClassType<'gcx>,
+        method: Option<NodeId>,
+    ) -> Result<(llhd::ir::UnitName, llhd::ir::Signature)> {
+        let key = (class.clone(), method);
+        if let Some(x) = self.tables.class_methods.get(&key) {
+            return x.clone();
+        }
+        let x = self.emit_class_method_uninterned(class, method);
+        self.tables.class_methods.insert(key, x.clone());
+        x
+    }
+
+    fn emit_class_method_uninterned(
+        &mut self,
+        class: &ty::ClassType<'gcx>,
+        method: Option<NodeId>,
+    ) -> Result<(llhd::ir::UnitName, llhd::ir::Signature)> {
+        let hir = class::class_hir(self.cx, class.ast)?;
+        let sub_id = match method {
+            Some(id) => Some(id),
+            None => class::find_constructor(self.cx, class)?.map(|m| m.member.id),
+        };
+        let sub = match sub_id {
+            Some(id) => match self.hir_of(id)? {
+                HirNode::Subroutine(x) => Some(x),
+                _ => unreachable!(),
+            },
+            None => None,
+        };
+        let statik = hir.methods.iter().any(|m| Some(m.id) == method && m.statik);
+        if let Some(sub) = sub {
+            if sub.kind == ast::SubroutineKind::Task {
+                self.emit(
+                    DiagBuilder2::error(format!("unsupported: task `{}` in a class", sub.name))
+                        .span(sub.name.span)
+                        .add_note("Only functions are supported as class methods"),
+                );
+                return Err(());
+            }
+        }
+
+        // Map the argument and return types.
+        let mut sig = llhd::ir::Signature::new();
+        if !statik {
+            sig.add_input(llhd::int_ty(64));
+        }
+        let mut retty = llhd::void_ty();
+        if let Some(sub) = sub {
+            for arg in &sub.args {
+                let ty = typeck::type_of_subroutine_arg(self.cx, arg, class.env);
+                sig.add_input(self.emit_type(ty)?);
+            }
+            let ty = typeck::subroutine_return_type(self.cx, sub, class.env);
+            if !ty.is_void() {
+                retty = self.emit_type(ty)?;
+            }
+        }
+        sig.set_return_type(retty.clone());
+        let name = llhd::ir::UnitName::Global(format!(
+            "{}.{}",
+            class::class_name(self.cx, class),
+            sub.map(|s| s.name.value.to_string())
+                .unwrap_or_else(|| "new".to_string())
+        ));
+
+        // Register the function before emitting its body, such that the
+        // method may call itself.
+        self.tables
+            .class_methods
+            .insert((class.clone(), method), Ok((name.clone(), sig.clone())));
+
+        // Emit the function body.
+        let mut func =
+            llhd::ir::UnitData::new(llhd::ir::UnitKind::Function, name.clone(), sig.clone());
+        let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut func);
+        let this = if statik {
+            None
+        } else {
+            let this = builder.input_arg(0);
+            builder.set_name(this, "this".to_string());
+            Some(this)
+        };
+        let mut values = HashMap::new();
+        let mut pg = UnitGenerator {
+            gen: self,
+            builder: &mut builder,
+            values: &mut values,
+            interned_consts: Default::default(),
+            interned_lvalues: Default::default(),
+            interned_rvalues: Default::default(),
+            shadows: Default::default(),
+            resolved_nets: Default::default(),
+            drivers: Default::default(),
+            clocks: Default::default(),
+            this,
+        };
+        let entry_blk = pg.add_nameless_block();
+        pg.builder.append_to(entry_blk);
+
+        // Arguments are stored in variables, since the body may assign to
+        // them.
+        let mut body: &[NodeId] = &[];
+        if let Some(sub) = sub {
+            let offset = if statik { 0 } else { 1 };
+            for (index, arg) in sub.args.iter().enumerate() {
+                let value = pg.builder.input_arg(index + offset);
+                let var = pg.builder.ins().var(value);
+                if let Some(name) = arg.name {
+                    pg.builder.set_name(value, name.value.to_string());
+                    pg.builder.set_name(var, format!("{}.var", name));
+                }
+                pg.set_emitted_value(arg.id, var);
+            }
+            body = &sub.body;
+        }
+
+        // Constructors first construct the base class, either by an explicit
+        // `super.new(...)` as their first statement, or with the arguments of
+        // the `extends` clause. Then they initialize the properties.
+        if method.is_none() {
+            match body.first() {
+                Some(&first) if pg.is_super_new(first)? => {
+                    pg.emit_stmt(first, class.env)?;
+                    body = &body[1..];
+                }
+                _ => {
+                    if let Some(call) = mir::lower::rvalue::lower_base_constructor(pg.cx, class)? {
+                        pg.emit_mir_method_call(call)?;
+                    }
+                }
+            }
+            pg.emit_property_init(class)?;
+        }
+        for &stmt in body {
+            pg.emit_stmt(stmt, class.env)?;
+        }
+
+        // Falling off the end of a function returns the zero value.
+        if retty.is_void() {
+            pg.builder.ins().ret();
+        } else {
+            let zero = pg.emit_zero_for_type(&retty);
+            pg.builder.ins().ret_value(zero);
+        }
+        self.into.add_unit(func);
+        Ok((name, sig))
+    }
+
+    /// Assign a class id to a class of which objects are constructed.
+    ///
+    /// Also emits the implementations of the class' virtual methods, such that
+    /// calls can be dispatched to them.
+    fn constructed_class_id(&mut self, class: &ty::ClassType<'gcx>) -> Result<usize> {
+        let index = self
+            .tables
+            .constructed_classes
+            .iter()
+            .position(|c| c.chain[0] == *class);
+        if let Some(index) = index {
+            return Ok(index + 1);
+        }
+        let chain = class::class_chain(self.cx, class)?;
+        let index = self.tables.constructed_classes.len();
+        self.tables.constructed_classes.push(ConstructedClass {
+            chain: chain.clone(),
+            methods: Default::default(),
+        });
+        for base in &chain {
+            for method in &class::class_hir(self.cx, base.ast)?.methods {
+                let name = method.name.value;
+                if self.tables.constructed_classes[index]
+                    .methods
+                    .contains_key(&name)
+                    || !class::is_virtual_method(self.cx, class, name)?
+                {
+                    continue;
+                }
+                let imp = class::find_method(self.cx, class, name)?.unwrap();
+                let (unit, _) = self.emit_class_method(&imp.class, Some(imp.member.id))?;
+                self.tables.constructed_classes[index]
+                    .methods
+                    .insert(name, unit);
+            }
+        }
+        Ok(index + 1)
+    }
+
+    /// Determine the dispatcher function for calls to a virtual method.
+    ///
+    /// The dispatcher itself is emitted once all classes are known, during
+    /// `finalize`.
+    fn emit_dispatcher(
+        &mut self,
+        class: &ty::ClassType<'gcx>,
+        method: NodeId,
+        default: llhd::ir::UnitName,
+        sig: llhd::ir::Signature,
+    ) -> Result<llhd::ir::UnitName> {
+        let method = match self.hir_of(method)? {
+            HirNode::Subroutine(x) => x.name.value,
+            _ => unreachable!(),
+        };
+        if let Some(disp) = self
+            .tables
+            .dispatchers
+            .iter()
+            .find(|d| d.class == *class && d.method == method)
+        {
+            return Ok(disp.name.clone());
+        }
+        let name = llhd::ir::UnitName::Global(format!(
+            "{}.{}.virtual",
+            class::class_name(self.cx, class),
+            method
+        ));
+        self.tables.dispatchers.push(Dispatcher {
+            class: class.clone(),
+            method,
+            name: name.clone(),
+            sig,
+            default,
+        });
+        Ok(name)
+    }
+
+    /// Check whether a statement is a `super.new(...)` call.
+    fn is_super_new(&self, stmt_id: NodeId) -> Result<bool> {
+        let expr_id = match self.hir_of(stmt_id)? {
+            HirNode::Stmt(hir::Stmt {
+                kind: hir::StmtKind::Expr(x),
+                ..
+            }) => *x,
+            _ => return Ok(false),
+        };
+        let (target, name) = match self.hir_of(expr_id)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::MethodCall(target, name, _),
+                ..
+            }) => (*target, *name),
+            _ => return Ok(false),
+        };
+        Ok(&*name.value.as_str() == "new"
+            && match self.hir_of(target)? {
+                HirNode::Expr(hir::Expr {
+                    kind: hir::ExprKind::Super,
+                    ..
+                }) => true,
+                _ => false,
+            })
+    }
+
+    /// Map a type to an LLHD type (interned).
+    fn emit_type(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
+        if let Some(x) = self.tables.interned_types.get(&ty) {
+            x.clone()
+        } else {
+            let x = self.emit_type_uninterned(ty);
+            self.tables.interned_types.insert(ty, x.clone());
+            x
+        }
+    }
+
+    /// Map a type to an LLHD type.
+    fn emit_type_uninterned(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
+        if ty.is_error() {
+            return Err(());
+        }
+        let ty = ty.resolve_full();
+
+        // Handle things that coalesce easily to scalars.
+        if ty.coalesces_to_llhd_scalar() {
+            return Ok(llhd::int_ty(ty.get_bit_size().unwrap()));
+        }
+
+        // Handle arrays.
+        if let Some(dim) = ty.outermost_dim() {
+            let size = match dim.get_size() {
+                Some(size) => size,
+                None => panic!("cannot map unsized array `{}` to LLHD", ty),
+            };
+            let inner = ty.pop_dim(self.cx).unwrap();
+            return Ok(llhd::array_ty(size, self.emit_type(inner)?));
+        }
+
+        // Handle class handles, which are represented as 64 bit integers.
+        if ty.get_class().is_some() {
+            return Ok(llhd::int_ty(64));
+        }
+
+        // Handle events, which are represented by the number of times they
+        // have been triggered and the time of the last trigger.
+        if ty.is_event() {
+            return Ok(llhd::struct_ty(vec![llhd::int_ty(32), llhd::time_ty()]));
+        }
+
+        // Handle covergroups, which are represented by their hit counters.
+        if let Some(cg) = ty.get_covergroup() {
+            let model = self.cover_model(Ref(cg.ast), cg.env)?;
+            return self.emit_type(model.state_type(self.cx));
+        }
+
+        // Handle structs.
+        if let Some(strukt) = ty.get_struct() {
+            let mut types = vec![];
+            for member in &strukt.members {
+                types.push(self.emit_type(member.ty)?);
+            }
+            return Ok(llhd::struct_ty(types));
+        }
+
+        // Handle packed types.
+        if let Some(packed) = ty.get_packed() {
+            let packed = packed.resolve_full();
+            return match packed.core {
+                ty::PackedCore::Error => Ok(llhd::void_ty()),
+                ty::PackedCore::Void => Ok(llhd::void_ty()),
+                ty::PackedCore::IntAtom(ty::IntAtomType::Time) => Ok(llhd::time_ty()),
+                ty::PackedCore::Enum(ref enm) => self.emit_type(enm.base.to_unpacked(self.cx)),
+                _ => unreachable!("emitting `{}` should have been handled above", packed),
+            };
+        }
+
+        // Everything else we cannot do.
+        error!("Cannot map type {:#?}", ty);
+        panic!("cannot map `{}` to LLHD", ty);
+    }
+
+    /// Execute the initialization step of a generate loop.
+    fn execute_genvar_init(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
+        let hir = self.hir_of(id)?;
+        match hir {
+            HirNode::GenvarDecl(_) => Ok(env),
+            HirNode::Stmt(stmt) => match stmt.kind {
+                hir::StmtKind::Assign {
+                    lhs,
+                    rhs,
+                    kind: hir::AssignKind::Block(ast::AssignOp::Identity),
+                } => {
+                    let target_id = self.resolve_node(lhs, env)?;
+                    let init_value = self.constant_value_of(rhs, env);
+                    let mut env_data = self.param_env_data(env).clone();
+                    env_data.set_value(target_id, init_value);
+                    Ok(self.intern_param_env(env_data))
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Execute the iteration step of a generate loop.
+    fn execute_genvar_step(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
+        let hir = self.hir_of(id)?;
+        let mut env_data = self.param_env_data(env).clone();
         let next = match hir {
             HirNode::Expr(expr) => match expr.kind {
                 hir::ExprKind::Unary(op, target_id) => {
@@ -677,6 +1725,28 @@ struct UnitGenerator<'a, 'gcx, C> {
     /// The shadow variables introduced to handle signals which are both read
     /// and written in a process.
     shadows: HashMap<AccessedNode, llhd::ir::Value>,
+    /// The nets with a resolution function declared in the unit, together
+    /// with the signals of their drivers.
+    resolved_nets: BTreeMap<llhd::ir::Value, ResolvedNet>,
+    /// The drivers and readers of the signals declared in the unit.
+    drivers: DriverTable,
+    /// The flip-flops and clocks of the unit, for the clock domain crossing
+    /// report.
+    clocks: CdcModule,
+    /// The handle of the object whose method is being emitted.
+    this: Option<llhd::ir::Value>,
+}
+
+/// A net whose drivers are combined by a resolution function, such as `wand`.
+struct ResolvedNet {
+    /// The net type, which determines the resolution function.
+    kind: ast::NetType,
+    /// The name of the net.
+    name: Name,
+    /// The location of the net declaration.
+    span: Span,
+    /// The signals driven by each of the net's drivers.
+    drivers: Vec<llhd::ir::Value>,
 }
 
 impl<'a, 'gcx, C> Deref for UnitGenerator<'a, 'gcx, C> {
@@ -705,6 +1775,7 @@ where
                 self.span(match src {
                     AccessedNode::Regular(id) => id,
                     AccessedNode::Intf(_, id) => id,
+                    AccessedNode::Hier(_, id) => id,
                 }),
                 self.cx,
                 "no value emitted for {:?}",
@@ -713,6 +1784,25 @@ where
         }
     }
 
+    /// Check that a function only accesses its own arguments and variables.
+    ///
+    /// Class methods are emitted as functions, which have no access to the
+    /// signals of the module they are called from.
+    fn check_function_access(&self, id: NodeId, span: Span) -> Result<()> {
+        if !self.builder.unit().is_function() || self.values.contains_key(&id.into()) {
+            return Ok(());
+        }
+        self.emit(
+            DiagBuilder2::error(format!(
+                "unsupported: method accesses {} outside of its class",
+                self.hir_of(id)?.desc_full()
+            ))
+            .span(span)
+            .add_note("Methods can only access properties, arguments, and local variables"),
+        );
+        Err(())
+    }
+
     fn set_emitted_value(&mut self, src: impl Into<AccessedNode>, value: llhd::ir::Value) {
         let src = src.into();
         self.values.insert(src, value);
@@ -735,6 +1825,8 @@ where
         hir: &hir::ModuleBlock,
         name_prefix: &str,
     ) -> Result<()> {
+        let cx = self.cx;
+
         // Emit declarations.
         for &decl_id in &hir.decls {
             let hir = match self.hir_of(decl_id)? {
@@ -742,9 +1834,36 @@ where
                 _ => unreachable!(),
             };
             let ty = self.type_of(decl_id, env)?;
+            self.drivers.add_decl(cx, hir, ty, env)?;
+            if ty.get_virtual_interface().is_some() {
+                // Virtual interfaces are bound during elaboration and have
+                // no runtime representation.
+                continue;
+            }
             let value = self.emit_varnet_decl(decl_id, ty, env, hir.init)?;
             self.builder.set_name(value, hir.name.value.into());
             self.values.insert(decl_id.into(), value.into());
+            if let ast::VarKind::Net { ty: kind, .. } = hir.kind {
+                self.declare_resolved_net(value, kind, hir.name, hir.span)?;
+            }
+            if let Some(cg) = ty.get_covergroup() {
+                let model = self.cover_model(Ref(cg.ast), cg.env)?;
+                self.tables
+                    .cover_instances
+                    .push((name_prefix.to_string(), hir.name.value, model));
+            }
+        }
+
+        // Nets with a `pullup` or `pulldown` are resolved like `tri1` and
+        // `tri0` nets.
+        for &gate_id in &hir.gates {
+            let hir = match self.hir_of(gate_id)? {
+                HirNode::Gate(x) => x,
+                _ => unreachable!(),
+            };
+            if hir.kind.is_pull() {
+                self.declare_pulled_nets(hir, env)?;
+            }
         }
 
         // Emit interface instances.
@@ -817,6 +1936,13 @@ where
             }
         }
 
+        // Emit clocking blocks.
+        for &cb_id in &hir.clockings {
+            self.emit_clocking_block(cb_id, env, name_prefix)?;
+            self.drivers
+                .add_reads(&self.accessed_nodes(cb_id, env)?.read);
+        }
+
         // Emit assignments.
         for &assign_id in &hir.assigns {
             let hir = match self.hir_of(assign_id)? {
@@ -829,11 +1955,43 @@ where
                 continue;
             }
             assert_type!(rhs.ty, lhs.ty, rhs.span, self.cx);
+            self.check_implicit_conversion(hir.rhs, env)?;
+            self.drivers.add_assign(cx, hir, env)?;
+
+            // Assignments of the form `en ? value : 'z` release the net while
+            // the enable is low, rather than driving it.
+            let (rhs, enable) = match self.split_high_impedance(hir.rhs, env)? {
+                Some((value, cond, invert)) if value.ty.is_identical(lhs.ty) => {
+                    (value, Some((cond, invert)))
+                }
+                _ => (rhs, None),
+            };
             let lhs = self.emit_mir_lvalue(lhs)?.0;
             let rhs = self.emit_mir_rvalue(rhs)?;
+            let enable = match enable {
+                Some((cond, invert)) => {
+                    let en = self.emit_rvalue_bool(cond, env)?;
+                    Some(if invert {
+                        self.builder.ins().not(en)
+                    } else {
+                        en
+                    })
+                }
+                None => None,
+            };
             let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
             let one_epsilon = self.builder.ins().const_time(one_epsilon);
-            self.builder.ins().drv(lhs, rhs, one_epsilon);
+            self.emit_net_drive(lhs, rhs, one_epsilon, enable, hir.span);
+        }
+
+        // Emit gate and switch primitives.
+        for &gate_id in &hir.gates {
+            let hir = match self.hir_of(gate_id)? {
+                HirNode::Gate(x) => x,
+                _ => unreachable!(),
+            };
+            self.drivers.add_gate(cx, hir, env)?;
+            self.emit_gate(hir, env)?;
         }
 
         // Emit module instantiations.
@@ -849,16 +2007,37 @@ where
                 _ => continue,
             };
 
+            // Determine the signals referred to hierarchically through this
+            // instance. These are exported by the instance as hidden outputs.
+            let mut hier = vec![];
+            for (&accnode, &value) in self.values.iter() {
+                if let AccessedNode::Hier(path, decl) = accnode {
+                    let insts = self.hier_path_insts(path);
+                    if insts.first() == Some(&inst_id) {
+                        hier.push(((self.intern_hier_path(&insts[1..]), decl), value));
+                    }
+                }
+            }
+            hier.sort();
+            let exports: Vec<_> = hier.iter().map(|&(export, _)| export).collect();
+
             // Emit the instantiated module.
-            let target = self.emit_module_with_env(target_module.id, inst.inner_env)?;
+            let target =
+                self.emit_module_with_exports(target_module.id, inst.inner_env, &exports)?;
 
             // Prepare the port assignments.
-            let (inputs, outputs) = self.emit_port_connections(
+            let (inputs, mut outputs) = self.emit_port_connections(
                 target_module.ports_new,
                 inst.as_ref(),
                 &target.ports.inputs,
                 &target.ports.outputs,
             )?;
+            for output in &mut outputs {
+                *output = self.net_driver_signal(*output, inst.hir.ast.span());
+            }
+            self.clocks
+                .add_inst(inst.hir.name.to_string(), target.cdc.clone());
+            outputs.extend(hier.into_iter().map(|(_, value)| value));
 
             // Instantiate the module.
             let ext_unit = self.builder.add_extern(
@@ -917,9 +2096,24 @@ where
             }
         }
 
-        // Emit and instantiate procedures.
-        for &proc_id in &hir.procs {
-            let prok = self.emit_procedure(proc_id, env, name_prefix)?;
+        // Emit and instantiate procedures, and the processes that sample
+        // covergroup instances on their sampling event.
+        let samplers = hir.decls.iter().map(|&id| (id, true));
+        let procs = hir.procs.iter().map(|&id| (id, false));
+        for (proc_id, is_sampler) in samplers.chain(procs) {
+            let prok = if is_sampler {
+                match self.emit_cover_sampler(proc_id, env, name_prefix)? {
+                    Some(x) => {
+                        self.drivers.add_reads(&x.inputs);
+                        x
+                    }
+                    None => continue,
+                }
+            } else {
+                self.drivers.add_procedure(cx, proc_id, env)?;
+                self.clocks.add_procedure(cx, proc_id, env)?;
+                self.emit_procedure(proc_id, env, name_prefix)?
+            };
             let lookup_value = |&id: &AccessedNode| match self.values.get(&id) {
                 Some(v) => v.clone(),
                 None => {
@@ -946,6 +2140,534 @@ where
         Ok(())
     }
 
+    /// Emit the signals of a clocking block and instantiate the process that
+    /// samples its inputs.
+    fn emit_clocking_block(&mut self, id: NodeId, env: ParamEnv, name_prefix: &str) -> Result<()> {
+        let hir = clocking::clocking_hir(self.cx, id)?;
+
+        // The block itself is an event, triggered after every sample.
+        let ty = self.type_of(id, env)?;
+        let init = self.emit_const(self.type_default_value(ty), env, hir.span)?;
+        let value = self.builder.ins().sig(init);
+        self.builder.set_name(value, hir.name.value.into());
+        self.values.insert(id.into(), value);
+
+        // Each input holds the value sampled at the last clocking event.
+        // Inputs with an explicit skew are sampled from a copy of the signal
+        // delayed by the skew.
+        let mut delayed = vec![];
+        for &var_id in &hir.signals {
+            let var = match self.hir_of(var_id)? {
+                HirNode::ClockVar(x) => x,
+                _ => unreachable!(),
+            };
+            if !var.dir.is_input() {
+                continue;
+            }
+            let ty = self.type_of(var_id, env)?;
+            let init = self.emit_const(self.type_default_value(ty), env, var.span)?;
+            let value = self.builder.ins().sig(init);
+            self.builder
+                .set_name(value, format!("{}.{}", hir.name, var.name));
+            self.values.insert(var_id.into(), value);
+            if let Some(skew) = var.input_delay() {
+                let copy = self.builder.ins().sig(init);
+                self.builder
+                    .set_name(copy, format!("{}.{}.skewed", hir.name, var.name));
+                let current = self.emit_rvalue(var.expr, env)?;
+                let delay = self.emit_delay(skew, env)?;
+                self.builder.ins().drv(copy, current, delay);
+                delayed.push(copy);
+            }
+        }
+
+        // Instantiate the sampler.
+        let prok = self.emit_clocking_sampler(id, env, name_prefix)?;
+        let mut inputs: Vec<_> = prok
+            .inputs
+            .iter()
+            .map(|&id| self.emitted_value(id))
+            .collect();
+        inputs.extend(delayed);
+        let outputs = prok
+            .outputs
+            .iter()
+            .map(|&id| self.emitted_value(id))
+            .collect();
+        let ext_unit = self.builder.add_extern(
+            self.into.unit(prok.unit).name().clone(),
+            self.into.unit(prok.unit).sig().clone(),
+        );
+        self.builder.ins().inst(ext_unit, inputs, outputs);
+        Ok(())
+    }
+
+    /// Emit the logic of a gate or switch primitive instance.
+    ///
+    /// LLHD signals are two-valued, so an output in the high-impedance state
+    /// is modelled by not driving it, leaving the net at its previous value.
+    /// Pulls drive the identity of the net's resolution function, see
+    /// `declare_pulled_nets`.
+    fn emit_gate(&mut self, gate: &hir::Gate, env: ParamEnv) -> Result<()> {
+        use ast::GateKind::*;
+        if gate.kind.is_bidirectional() {
+            self.emit(
+                DiagBuilder2::error(format!("unsupported: bidirectional switch `{}`", gate.kind))
+                    .span(gate.span),
+            );
+            return Err(());
+        }
+        let released = self.gate_strength(gate)?;
+        let inputs = gate
+            .inputs
+            .iter()
+            .map(|&id| self.emit_rvalue_bool(id, env))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Determine the driven value, and the condition under which the output
+        // is driven for tri-state gates and switches.
+        let (value, enable) = match gate.kind {
+            And | Nand | Or | Nor | Xor | Xnor => {
+                let mut value = inputs[0];
+                for &input in &inputs[1..] {
+                    value = match gate.kind {
+                        And | Nand => self.builder.ins().and(value, input),
+                        Or | Nor => self.builder.ins().or(value, input),
+                        _ => self.builder.ins().xor(value, input),
+                    };
+                }
+                (value, None)
+            }
+            Buf | Not => (inputs[0], None),
+            Bufif1 | Notif1 | Nmos | Rnmos => (inputs[0], Some(inputs[1])),
+            Bufif0 | Notif0 | Pmos | Rpmos => (inputs[0], Some(self.builder.ins().not(inputs[1]))),
+            Cmos | Rcmos => {
+                let p = self.builder.ins().not(inputs[2]);
+                (inputs[0], Some(self.builder.ins().or(inputs[1], p)))
+            }
+            Pullup => (self.builder.ins().const_int((1, 1)), None),
+            Pulldown => (self.builder.ins().const_int((1, 0)), None),
+            Tran | Rtran | Tranif0 | Tranif1 | Rtranif0 | Rtranif1 => unreachable!(),
+        };
+        let value = match gate.kind {
+            Nand | Nor | Xnor | Not | Notif0 | Notif1 => self.builder.ins().not(value),
+            _ => value,
+        };
+
+        // A `highz0` or `highz1` strength releases the output while it would
+        // be driven to 0 or 1, respectively.
+        let enable = match released {
+            Some(one) => {
+                let en = if one {
+                    self.builder.ins().not(value)
+                } else {
+                    value
+                };
+                Some(match enable {
+                    Some(enable) => self.builder.ins().and(enable, en),
+                    None => en,
+                })
+            }
+            None => enable,
+        };
+
+        // Determine the propagation delay.
+        let delay = match gate.delay {
+            Some(delay_id) => self.emit_delay(delay_id, env)?,
+            None => {
+                let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
+                self.builder.ins().const_time(one_epsilon)
+            }
+        };
+
+        // Drive the outputs.
+        for &output_id in &gate.outputs {
+            let lhs = self.mir_lvalue(output_id, env);
+            if lhs.is_error() {
+                continue;
+            }
+            if lhs.ty.get_bit_size() != Some(1) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` output must be a single bit, but has type `{}`",
+                        gate.kind, lhs.ty
+                    ))
+                    .span(lhs.span),
+                );
+                continue;
+            }
+            let lhs = self.emit_mir_lvalue(lhs)?.0;
+            self.emit_net_drive(lhs, value, delay, enable, gate.span);
+        }
+        Ok(())
+    }
+
+    /// Register a net declaration that requires a resolution function.
+    ///
+    /// Plain `wire`, `tri`, and `trireg` nets are driven directly. Since LLHD
+    /// signals are two-valued, a net whose drivers are all released keeps its
+    /// previous value, which models the charge storage of `trireg`.
+    fn declare_resolved_net(
+        &mut self,
+        net: llhd::ir::Value,
+        kind: ast::NetType,
+        name: Spanned<Name>,
+        span: Span,
+    ) -> Result<()> {
+        use ast::NetType::*;
+        match kind {
+            Wire | Tri | TriReg => return Ok(()),
+            _ => (),
+        }
+        if !self.llhd_type(net).unwrap_signal().is_int() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: `{}` net `{}` of type `{}`",
+                    kind,
+                    name,
+                    self.llhd_type(net).unwrap_signal()
+                ))
+                .span(span)
+                .add_note("Resolved nets must be single bits or bit vectors"),
+            );
+            return Err(());
+        }
+        self.resolved_nets.insert(
+            net,
+            ResolvedNet {
+                kind,
+                name: name.value,
+                span,
+                drivers: vec![],
+            },
+        );
+        Ok(())
+    }
+
+    /// Check the drive strength of a gate or switch primitive.
+    ///
+    /// Nets are resolved without regard to strength, so primitives other than
+    /// pulls may only drive with the default `strong` strength, or with
+    /// `highz`, which releases the output. Pulls are weaker than all other
+    /// drivers and may have a `pull` or `weak` strength. Returns whether the
+    /// output is released while driving 1 or 0, if any.
+    fn gate_strength(&mut self, gate: &hir::Gate) -> Result<Option<bool>> {
+        use ast::DriveStrength::*;
+        let strengths: Vec<_> = match gate.strength {
+            Some((a, b)) => std::iter::once(a).chain(b).collect(),
+            None => vec![],
+        };
+        // A `(highz0, highz1)` strength is invalid (see IEEE 1800-2017
+        // §28.3.2).
+        if strengths.contains(&HighZ0) && strengths.contains(&HighZ1) {
+            self.emit(
+                DiagBuilder2::error("drive strength `(highz0, highz1)` is invalid").span(gate.span),
+            );
+            return Err(());
+        }
+        let mut released = None;
+        for strength in strengths {
+            let is_one = match strength {
+                Supply1 | Strong1 | Pull1 | Weak1 | HighZ1 => true,
+                _ => false,
+            };
+            let supported = match (gate.kind, strength) {
+                // A pull only considers the strength of the value it drives
+                // (see IEEE 1800-2017 §28.10).
+                (ast::GateKind::Pullup, _) if !is_one => true,
+                (ast::GateKind::Pulldown, _) if is_one => true,
+                (_, Pull0) | (_, Pull1) | (_, Weak0) | (_, Weak1) => gate.kind.is_pull(),
+                (_, Strong0) | (_, Strong1) => !gate.kind.is_pull(),
+                (_, HighZ0) | (_, HighZ1) if !gate.kind.is_pull() => {
+                    released = Some(is_one);
+                    true
+                }
+                _ => false,
+            };
+            if !supported {
+                let note = if gate.kind.is_pull() {
+                    "Pulls are weaker than all other drivers and only support the `pull` and \
+                     `weak` strengths"
+                } else {
+                    "Nets are resolved without regard to strength, so primitives only support \
+                     the `strong` and `highz` strengths"
+                };
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "unsupported: `{}` strength of `{}`",
+                        strength, gate.kind
+                    ))
+                    .span(gate.span)
+                    .add_note(note),
+                );
+                return Err(());
+            }
+        }
+        Ok(released)
+    }
+
+    /// Resolve the nets driven by a `pullup` or `pulldown` like `tri1` or
+    /// `tri0` nets.
+    ///
+    /// The pull then drives the identity of the resolution function, such
+    /// that it is weaker than all other drivers and only determines the value
+    /// of the net while they are released. Nets whose resolution already
+    /// yields the pulled value are left as they are.
+    fn declare_pulled_nets(&mut self, gate: &hir::Gate, env: ParamEnv) -> Result<()> {
+        use ast::NetType::*;
+        let (pulled, compatible): (_, &[_]) = match gate.kind {
+            ast::GateKind::Pullup => (Tri1, &[Tri1, WireAnd, TriAnd, Supply0, Supply1, Uwire]),
+            _ => (Tri0, &[Tri0, WireOr, TriOr, Supply0, Supply1, Uwire]),
+        };
+        for &output_id in &gate.outputs {
+            let lhs = self.mir_lvalue(output_id, env);
+            if lhs.is_error() {
+                continue;
+            }
+            let target = match lhs.kind {
+                mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => match self.hir_of(id)? {
+                    HirNode::VarDecl(decl) => Some((id, decl.name, decl.span, decl.kind)),
+                    HirNode::IntPort(port) if port.dir == ast::PortDir::Output => {
+                        Some((id, port.name, port.span, port.kind))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            let (id, name, span, kind) = match target {
+                Some((id, name, span, ast::VarKind::Net { ty, .. })) => (id, name, span, ty),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "unsupported: `{}` driving `{}`",
+                            gate.kind,
+                            lhs.span.extract()
+                        ))
+                        .span(lhs.span)
+                        .add_note(
+                            "Pulls are only supported on entire nets declared in the module or as \
+                             output ports",
+                        ),
+                    );
+                    return Err(());
+                }
+            };
+            let net = self.emitted_value(id);
+            let resolved = self.resolved_nets.get(&net).map(|rn| rn.kind);
+            let kind = match resolved.unwrap_or(kind) {
+                Wire | Tri | TriReg => pulled,
+                kind => kind,
+            };
+            if !compatible.contains(&kind) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "unsupported: `{}` driving `{}` net `{}`",
+                        gate.kind, kind, name
+                    ))
+                    .span(lhs.span)
+                    .add_note(format!(
+                        "The net resolves to a value other than the one pulled to if all \
+                         drivers are released; `{}` declared here:",
+                        name
+                    ))
+                    .span(span),
+                );
+                return Err(());
+            }
+            if resolved.is_none() {
+                self.declare_resolved_net(net, kind, name, span)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Split an assignment of the form `en ? value : 'z` into the driven value
+    /// and the enable condition.
+    ///
+    /// Returns the value, the condition, and whether the condition is inverted
+    /// (as in `en ? 'z : value`).
+    fn split_high_impedance(
+        &mut self,
+        expr_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<Option<(&'gcx mir::Rvalue<'gcx>, NodeId, bool)>> {
+        let is_high_z = |cx: &Self, id: NodeId| match cx.hir_of(id) {
+            Ok(HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::UnsizedConst('z'),
+                ..
+            })) => true,
+            Ok(HirNode::Expr(&hir::Expr {
+                kind:
+                    hir::ExprKind::IntConst {
+                        ref special_bits,
+                        ref x_bits,
+                        ..
+                    },
+                ..
+            })) => special_bits.all() && x_bits.none(),
+            _ => false,
+        };
+        let (cond, value, invert) = match self.hir_of(expr_id)? {
+            HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::Ternary(cond, true_expr, false_expr),
+                ..
+            }) => {
+                if is_high_z(self, false_expr) {
+                    (cond, true_expr, false)
+                } else if is_high_z(self, true_expr) {
+                    (cond, false_expr, true)
+                } else {
+                    return Ok(None);
+                }
+            }
+            _ => return Ok(None),
+        };
+        let value = self.mir_rvalue(value, env);
+        if value.is_error() {
+            return Err(());
+        }
+        Ok(Some((value, cond, invert)))
+    }
+
+    /// Emit a continuous driver of a net.
+    ///
+    /// If an enable is given, the driver is released while the enable is low.
+    /// Drivers of resolved nets drive a separate signal instead, which is
+    /// later combined with the other drivers by `emit_net_resolution`.
+    fn emit_net_drive(
+        &mut self,
+        net: llhd::ir::Value,
+        value: llhd::ir::Value,
+        delay: llhd::ir::Value,
+        enable: Option<llhd::ir::Value>,
+        span: Span,
+    ) {
+        use ast::NetType::*;
+        let kind = match self.resolved_nets.get(&net) {
+            Some(x) => x.kind,
+            None => {
+                match enable {
+                    Some(en) => self.builder.ins().drv_cond(net, value, delay, en),
+                    None => self.builder.ins().drv(net, value, delay),
+                };
+                return;
+            }
+        };
+        match kind {
+            // Supply nets are not affected by their drivers.
+            Supply0 | Supply1 => (),
+            // Unresolved nets may only have a single driver.
+            Uwire => {
+                self.check_uwire_driver(net, span);
+                match enable {
+                    Some(en) => self.builder.ins().drv_cond(net, value, delay, en),
+                    None => self.builder.ins().drv(net, value, delay),
+                };
+            }
+            // Released drivers contribute the identity of the resolution
+            // function, such that they do not affect the net's value.
+            _ => {
+                let driver = self.net_driver_signal(net, span);
+                let value = match enable {
+                    Some(en) => {
+                        let identity = self.emit_net_identity(net);
+                        let array = self.builder.ins().array(vec![identity, value]);
+                        self.builder.ins().mux(array, en)
+                    }
+                    None => value,
+                };
+                self.builder.ins().drv(driver, value, delay);
+            }
+        }
+    }
+
+    /// Determine the signal a driver of a net should drive.
+    ///
+    /// For resolved nets this allocates a separate signal for the driver.
+    /// Otherwise the net itself is returned.
+    fn net_driver_signal(&mut self, net: llhd::ir::Value, span: Span) -> llhd::ir::Value {
+        let kind = match self.resolved_nets.get(&net) {
+            Some(x) => x.kind,
+            None => return net,
+        };
+        if kind == ast::NetType::Uwire {
+            self.check_uwire_driver(net, span);
+            return net;
+        }
+        let identity = self.emit_net_identity(net);
+        let driver = self.builder.ins().sig(identity);
+        let rn = self.resolved_nets.get_mut(&net).unwrap();
+        let name = format!("{}.drv{}", rn.name, rn.drivers.len());
+        rn.drivers.push(driver);
+        self.builder.set_name(driver, name);
+        driver
+    }
+
+    /// Emit an error if a `uwire` net already has a driver.
+    fn check_uwire_driver(&mut self, net: llhd::ir::Value, span: Span) {
+        let rn = self.resolved_nets.get_mut(&net).unwrap();
+        rn.drivers.push(net);
+        if rn.drivers.len() == 2 {
+            let (name, decl_span) = (rn.name, rn.span);
+            self.emit(
+                DiagBuilder2::error(format!("`uwire` net `{}` has multiple drivers", name))
+                    .span(span)
+                    .add_note(format!("`{}` declared here:", name))
+                    .span(decl_span),
+            );
+        }
+    }
+
+    /// Emit the value a released driver contributes to a resolved net.
+    ///
+    /// This is all ones for wired-AND nets and nets pulled high, and zero
+    /// otherwise.
+    fn emit_net_identity(&mut self, net: llhd::ir::Value) -> llhd::ir::Value {
+        use ast::NetType::*;
+        let width = self.llhd_type(net).unwrap_signal().unwrap_int();
+        match self.resolved_nets[&net].kind {
+            WireAnd | TriAnd | Tri1 | Supply1 => self
+                .builder
+                .ins()
+                .const_int(llhd::value::IntValue::all_ones(width)),
+            _ => self.builder.ins().const_int((width, 0)),
+        }
+    }
+
+    /// Combine the drivers of the resolved nets in the unit.
+    ///
+    /// Wired-AND nets and nets pulled high combine their drivers with a
+    /// bitwise AND, and wired-OR nets and nets pulled low with a bitwise OR.
+    /// Supply nets and nets without drivers retain their initial value.
+    fn emit_net_resolution(&mut self) {
+        use ast::NetType::*;
+        let nets: Vec<_> = self
+            .resolved_nets
+            .iter()
+            .filter(|(_, rn)| match rn.kind {
+                Uwire | Supply0 | Supply1 => false,
+                _ => !rn.drivers.is_empty(),
+            })
+            .map(|(&net, rn)| (net, rn.kind, rn.drivers.clone()))
+            .collect();
+        for (net, kind, drivers) in nets {
+            let mut value = None;
+            for driver in drivers {
+                let driven = self.builder.ins().prb(driver);
+                value = Some(match value {
+                    None => driven,
+                    Some(v) => match kind {
+                        WireAnd | TriAnd | Tri1 => self.builder.ins().and(v, driven),
+                        _ => self.builder.ins().or(v, driven),
+                    },
+                });
+            }
+            let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
+            let one_epsilon = self.builder.ins().const_time(one_epsilon);
+            self.builder.ins().drv(net, value.unwrap(), one_epsilon);
+        }
+    }
+
     /// Emit code for the connections made in a port list.
     fn emit_port_connections(
         &mut self,
@@ -996,6 +2718,7 @@ where
 
         // Connect to the actual internal ports emitted as the module's port
         // interface.
+        let cx = self.cx;
         let mut map_port = |port: &ModulePort<'gcx>, lvalue: bool| {
             trace!(
                 "Mapping port `{}` of type `{}` as {}",
@@ -1015,7 +2738,24 @@ where
                         return Err(());
                     }
                     let mir = match port.kind {
-                        ModulePortKind::Port => mir,
+                        ModulePortKind::Port => {
+                            let desc =
+                                format!("port `{}` of instance `{}`", port.name, inst.hir.name);
+                            self.drivers.add_port_connection(
+                                cx,
+                                mapping.id(),
+                                mapping.env(),
+                                Some(desc),
+                            )?;
+                            self.clocks.add_connection(
+                                cx,
+                                port.port.id,
+                                mapping.id(),
+                                mapping.env(),
+                                true,
+                            )?;
+                            mir
+                        }
                         ModulePortKind::IntfSignal { decl_id, env, .. } => {
                             self.arena().alloc_mir_lvalue(mir::Lvalue {
                                 id: NodeId::alloc(),
@@ -1034,7 +2774,23 @@ where
                         return Err(());
                     }
                     let mir = match port.kind {
-                        ModulePortKind::Port => mir,
+                        ModulePortKind::Port => {
+                            self.check_implicit_conversion(mapping.id(), mapping.env())?;
+                            self.drivers.add_port_connection(
+                                cx,
+                                mapping.id(),
+                                mapping.env(),
+                                None,
+                            )?;
+                            self.clocks.add_connection(
+                                cx,
+                                port.port.id,
+                                mapping.id(),
+                                mapping.env(),
+                                false,
+                            )?;
+                            mir
+                        }
                         ModulePortKind::IntfSignal { decl_id, env, .. } => {
                             self.arena().alloc_mir_rvalue(mir::Rvalue {
                                 id: NodeId::alloc(),
@@ -1053,7 +2809,22 @@ where
                 // Emit an auxiliary signal with the default value for this
                 // port or type.
                 let ty = self.type_of_int_port(Ref(port.port), inst.inner_env);
-                let value = match port.port.data.as_ref().and_then(|d| d.default) {
+                let default = port.port.data.as_ref().and_then(|d| d.default);
+                if !lvalue && default.is_none() && self.sess().has_lint(&UNCONNECTED) {
+                    self.emit(
+                        DiagBuilder2::lint(
+                            &UNCONNECTED,
+                            format!(
+                                "input `{}` of instance `{}` is unconnected",
+                                port.name, inst.hir.name
+                            ),
+                        )
+                        .span(inst.hir.span())
+                        .add_note("Port declared here:")
+                        .span(port.port.span),
+                    );
+                }
+                let value = match default {
                     Some(default) => {
                         self.emit_rvalue_mode(default, inst.inner_env, Mode::Signal)?
                     }
@@ -1106,6 +2877,9 @@ where
             return Err(());
         }
         match value.kind {
+            ValueKind::Int(ref k, ..) if value.ty.get_class().is_some() => {
+                Ok(self.builder.ins().const_int((64, k.clone())))
+            }
             ValueKind::Int(ref k, ..) => {
                 let size = value.ty.simple_bit_vector(self.cx, span).size;
                 Ok(self.builder.ins().const_int((size, k.clone())))
@@ -1121,7 +2895,7 @@ where
                         .map(|v| self.emit_const(v, env, span).map(Into::into))
                         .collect();
                     Ok(self.builder.ins().array(fields?))
-                } else if let Some(_strukt) = value.ty.get_struct() {
+                } else if value.ty.get_struct().is_some() || value.ty.is_event() {
                     let fields: Result<Vec<_>> = v
                         .iter()
                         .map(|v| self.emit_const(v, env, span).map(Into::into))
@@ -1183,6 +2957,47 @@ where
         self.emit_rvalue_mode(expr_id, env, Mode::Value)
     }
 
+    /// Emit the code for a delay.
+    ///
+    /// Delays without a time unit are scaled by the time unit in effect, and
+    /// constant delays are rounded to the time precision.
+    fn emit_delay(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
+        let mir = self.mir_rvalue(expr_id, env);
+        if mir.is_error() {
+            return Err(());
+        }
+        let is_time = mir.ty.get_packed().map(|ty| ty.is_time()).unwrap_or(false);
+        if is_time && !mir.is_const() {
+            return self.emit_mir_rvalue(mir);
+        }
+        if !mir.is_const() {
+            self.emit(
+                DiagBuilder2::error("unsupported: non-constant delay without time unit")
+                    .span(mir.span)
+                    .add_note("Use a value of type `time`, such as `10ns`"),
+            );
+            return Err(());
+        }
+        let scale = timescale::time_scale_at(self.cx, self.ast_for_id(expr_id));
+        let value = self.const_mir_rvalue(mir.into());
+        let time = match value.kind {
+            ValueKind::Time(ref x) => scale.round(x),
+            ValueKind::Int(ref x, ..) => scale.scale(&num::BigRational::from_integer(x.clone())),
+            ValueKind::Error => return Err(()),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!("`{}` is not a valid delay", mir.span.extract()))
+                        .span(mir.span),
+                );
+                return Err(());
+            }
+        };
+        Ok(self
+            .builder
+            .ins()
+            .const_time(llhd::value::TimeValue::new(time, 0, 0)))
+    }
+
     /// Emit the code for an rvalue.
     fn emit_rvalue_mode(
         &mut self,
@@ -1285,6 +3100,7 @@ where
 
         let value = match mir.kind {
             mir::RvalueKind::Var(id) | mir::RvalueKind::Port(id) => {
+                self.check_function_access(id, mir.span)?;
                 let sig = self
                     .shadows
                     .get(&id.into())
@@ -1297,9 +3113,30 @@ where
                 }
             }
 
-            mir::RvalueKind::Intf(_) => {
+            mir::RvalueKind::Intf(_) => {
+                self.emit(
+                    DiagBuilder2::error("interface cannot be used in an expression").span(mir.span),
+                );
+                Err(())
+            }
+
+            mir::RvalueKind::HierSignal(path, decl) => {
+                let id = AccessedNode::Hier(path, decl);
+                let sig = self
+                    .shadows
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| self.emitted_value(id));
+                if mode_hint == Mode::Signal && self.llhd_type(sig).is_signal() {
+                    return Ok((sig, Mode::Signal));
+                } else {
+                    Ok(self.emit_prb_or_var(sig))
+                }
+            }
+
+            mir::RvalueKind::Inst(_) => {
                 self.emit(
-                    DiagBuilder2::error("interface cannot be used in an expression").span(mir.span),
+                    DiagBuilder2::error("instance cannot be used in an expression").span(mir.span),
                 );
                 Err(())
             }
@@ -1517,127 +3354,1061 @@ where
                     }
                 })
             }
-
-            mir::RvalueKind::Concat(ref values) => {
-                let mut offset = 0;
-                let llty = self.emit_type(mir.ty)?;
-                let mut result = self.emit_zero_for_type(&llty);
-                trace!(
-                    "Concatenating {} values into `{}` (as `{}`)",
-                    values.len(),
-                    mir.ty,
-                    llty
-                );
-                for value in values.iter().rev() {
-                    let width = value.ty.simple_bit_vector(self.cx, value.span).size;
-                    let llval = self.emit_mir_rvalue(value)?;
-                    trace!(
-                        " - Value has width {}, type `{}`, in LLHD `{}`",
-                        width,
-                        value.ty,
-                        self.llhd_type(llval)
-                    );
-                    result = self.builder.ins().ins_slice(result, llval, offset, width);
-                    offset += width;
+
+            mir::RvalueKind::Concat(ref values) => {
+                let mut offset = 0;
+                let llty = self.emit_type(mir.ty)?;
+                let mut result = self.emit_zero_for_type(&llty);
+                trace!(
+                    "Concatenating {} values into `{}` (as `{}`)",
+                    values.len(),
+                    mir.ty,
+                    llty
+                );
+                for value in values.iter().rev() {
+                    let width = value.ty.simple_bit_vector(self.cx, value.span).size;
+                    let llval = self.emit_mir_rvalue(value)?;
+                    trace!(
+                        " - Value has width {}, type `{}`, in LLHD `{}`",
+                        width,
+                        value.ty,
+                        self.llhd_type(llval)
+                    );
+                    result = self.builder.ins().ins_slice(result, llval, offset, width);
+                    offset += width;
+                }
+                self.builder.set_name(result, "concat".to_string());
+                Ok(result)
+            }
+
+            mir::RvalueKind::Repeat(times, value) => {
+                let width = value.ty.simple_bit_vector(self.cx, value.span).size;
+                let value = self.emit_mir_rvalue(value)?;
+                let llty = self.emit_type(mir.ty)?;
+                let mut result = self.emit_zero_for_type(&llty);
+                for i in 0..times {
+                    result = self
+                        .builder
+                        .ins()
+                        .ins_slice(result, value, i * width, width);
+                }
+                self.builder.set_name(result, "repeat".to_string());
+                Ok(result)
+            }
+
+            mir::RvalueKind::Shift {
+                op,
+                arith,
+                value,
+                amount,
+            } => {
+                let value = self.emit_mir_rvalue(value)?;
+                let amount = self.emit_mir_rvalue(amount)?;
+                let value_ty = self.builder.unit().value_type(value);
+                let hidden = self.emit_zero_for_type(&value_ty);
+                let hidden = if arith && op == mir::ShiftOp::Right {
+                    let ones = self.builder.ins().not(hidden);
+                    let sign = self
+                        .builder
+                        .ins()
+                        .ext_slice(value, value_ty.unwrap_int() - 1, 1);
+                    let mux = self.builder.ins().array(vec![hidden, ones]);
+                    self.builder.ins().mux(mux, sign)
+                } else {
+                    hidden
+                };
+                Ok(match op {
+                    mir::ShiftOp::Left => self.builder.ins().shl(value, hidden, amount),
+                    mir::ShiftOp::Right => self.builder.ins().shr(value, hidden, amount),
+                })
+            }
+
+            mir::RvalueKind::Ternary {
+                cond,
+                true_value,
+                false_value,
+            } => {
+                let cond = self.emit_mir_rvalue(cond)?;
+                let true_value = self.emit_mir_rvalue(true_value)?;
+                let false_value = self.emit_mir_rvalue(false_value)?;
+                let array = self.builder.ins().array(vec![false_value, true_value]);
+                Ok(self.builder.ins().mux(array, cond))
+            }
+
+            mir::RvalueKind::Reduction { op, arg } => {
+                let width = arg.ty.simple_bit_vector(self.cx, arg.span).size;
+                let arg = self.emit_mir_rvalue(arg)?;
+                let mut value = self.builder.ins().ext_slice(arg, 0, 1);
+                for i in 1..width {
+                    let bit = self.builder.ins().ext_slice(arg, i, 1);
+                    value = match op {
+                        mir::BinaryBitwiseOp::And => self.builder.ins().and(value, bit),
+                        mir::BinaryBitwiseOp::Or => self.builder.ins().or(value, bit),
+                        mir::BinaryBitwiseOp::Xor => self.builder.ins().xor(value, bit),
+                    };
+                }
+                Ok(value)
+            }
+
+            mir::RvalueKind::Assignment {
+                lvalue,
+                rvalue,
+                result,
+            } => {
+                self.emit_mir_blocking_assign(lvalue, rvalue)?;
+                self.emit_mir_rvalue(result)
+            }
+
+            mir::RvalueKind::PackString(value) | mir::RvalueKind::UnpackString(value) => bug_span!(
+                value.span,
+                self.cx,
+                "codegen for string packing/unpacking not implemented"
+            ),
+
+            mir::RvalueKind::Call { .. } => match self.emit_mir_call(mir)? {
+                Some(value) => Ok(value),
+                None => {
+                    self.emit(
+                        DiagBuilder2::error("void function call cannot be used as a value")
+                            .span(mir.span),
+                    );
+                    Err(())
+                }
+            },
+
+            mir::RvalueKind::StringComp { .. } => bug_span!(
+                mir.span,
+                self.cx,
+                "runtime string comparisons not implemented"
+            ),
+
+            mir::RvalueKind::CoverSample { .. } => {
+                self.emit(
+                    DiagBuilder2::error("void method call cannot be used as a value")
+                        .span(mir.span),
+                );
+                Err(())
+            }
+
+            mir::RvalueKind::Finish(..) => {
+                self.emit(
+                    DiagBuilder2::error("`$finish` cannot be used as a value").span(mir.span),
+                );
+                Err(())
+            }
+
+            mir::RvalueKind::CoverCoverage(state) => {
+                let cg = state.ty.get_covergroup().unwrap();
+                let model = self.cover_model(Ref(cg.ast), cg.env)?;
+                let state = self.emit_mir_rvalue(state)?;
+                Ok(self.emit_cover_coverage(&model, state))
+            }
+
+            mir::RvalueKind::SimTime(ref granularity) => {
+                if self.builder.unit().is_entity() {
+                    self.emit(
+                        DiagBuilder2::error("simulation time used outside of a procedure")
+                            .span(mir.span),
+                    );
+                    return Err(());
+                }
+                Ok(self.emit_sim_time(granularity.clone()))
+            }
+
+            mir::RvalueKind::EventTriggered(event) => {
+                if self.builder.unit().is_entity() {
+                    self.emit(
+                        DiagBuilder2::error("event state used outside of a procedure")
+                            .span(mir.span),
+                    );
+                    return Err(());
+                }
+                // An event is triggered in the current time step if it has
+                // been triggered at all, and the last trigger happened now.
+                let event = self.emit_mir_rvalue(event)?;
+                let count = self.builder.ins().ext_field(event, 0);
+                let stamp = self.builder.ins().ext_field(event, 1);
+                let zero = self.builder.ins().const_int((32, 0));
+                let any = self.builder.ins().neq(count, zero);
+                let now = self.emit_sim_time(event_time_granularity());
+                let recent = self.builder.ins().eq(stamp, now);
+                Ok(self.builder.ins().and(any, recent))
+            }
+
+            mir::RvalueKind::Random
+            | mir::RvalueKind::Urandom(..)
+            | mir::RvalueKind::UrandomRange { .. } => self.emit_sys_random(mir),
+
+            mir::RvalueKind::This => match self.this {
+                Some(this) => Ok(this),
+                None => {
+                    self.emit(
+                        DiagBuilder2::error("`this` is not available in a static method")
+                            .span(mir.span),
+                    );
+                    Err(())
+                }
+            },
+
+            mir::RvalueKind::ClassNew(ref args) => self.emit_class_new(mir, args),
+
+            mir::RvalueKind::ClassProperty {
+                object,
+                ref class,
+                prop,
+            } => {
+                let handle = self.emit_class_handle(object)?;
+                Ok(self
+                    .emit_class_property(handle, class, prop, None)?
+                    .unwrap())
+            }
+
+            mir::RvalueKind::MethodCall { .. } => match self.emit_mir_method_call(mir)? {
+                Some(value) => Ok(value),
+                None => {
+                    self.emit(
+                        DiagBuilder2::error("void method call cannot be used as a value")
+                            .span(mir.span),
+                    );
+                    Err(())
+                }
+            },
+
+            mir::RvalueKind::Randomize { .. } => self.emit_randomize(mir),
+
+            mir::RvalueKind::Error => Err(()),
+        };
+
+        value.map(|v| (v, Mode::Value))
+    }
+
+    /// Emit the code for an MIR call.
+    ///
+    /// Returns `None` if the called subroutine produces no value.
+    fn emit_mir_call(&mut self, mir: &'gcx mir::Rvalue<'gcx>) -> Result<Option<llhd::ir::Value>> {
+        let (target, args) = match mir.kind {
+            mir::RvalueKind::Call { target, ref args } => (target, args),
+            _ => unreachable!(),
+        };
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "call to {} outside of a procedure",
+                    self.hir_of(target)?.desc_full()
+                ))
+                .span(mir.span)
+                .add_note("DPI imports and tasks can only be called from within procedures"),
+            );
+            return Err(());
+        }
+        let sub = match self.hir_of(target)? {
+            HirNode::Subroutine(x) => x,
+            _ => unreachable!(),
+        };
+        if sub.dpi.is_none() {
+            self.emit_inlined_task(sub, args, mir)?;
+            return Ok(None);
+        }
+        let (name, sig) = self.emit_dpi_import(target, mir.env)?;
+        let mut values = vec![];
+        for (&arg, c_arg) in args.iter().zip(sig.inputs()) {
+            let value = self.emit_mir_rvalue(arg)?;
+            let width = sig.arg_type(c_arg).unwrap_int();
+            values.push(self.emit_dpi_extend(value, arg.ty, width));
+        }
+        let ext_unit = self.builder.add_extern(name, sig);
+        let inst = self.builder.ins().call(ext_unit, values);
+
+        // Narrow the result from the C integer type to the return type.
+        let result = self.builder.unit().get_inst_result(inst);
+        match (result, mir.ty.get_simple_bit_vector()) {
+            (Some(result), Some(sbv)) if sbv.size < self.llhd_type(result).unwrap_int() => {
+                Ok(Some(self.builder.ins().ext_slice(result, 0, sbv.size)))
+            }
+            _ => Ok(result),
+        }
+    }
+
+    /// Extend a DPI import argument to the width of the C integer type that
+    /// carries it. Signed values are sign-extended, others zero-extended.
+    fn emit_dpi_extend(
+        &mut self,
+        value: llhd::ir::Value,
+        ty: &'gcx UnpackedType<'gcx>,
+        width: usize,
+    ) -> llhd::ir::Value {
+        let size = self.llhd_type(value).unwrap_int();
+        if size == width {
+            return value;
+        }
+        let zeros = self.builder.ins().const_int((width, 0));
+        let signed = ty
+            .get_simple_bit_vector()
+            .map(|sbv| sbv.sign.is_signed())
+            .unwrap_or(false);
+        let fill = if signed {
+            let sign = self.builder.ins().ext_slice(value, size - 1, 1);
+            let ones = self.builder.ins().not(zeros);
+            let mux = self.builder.ins().array(vec![zeros, ones]);
+            self.builder.ins().mux(mux, sign)
+        } else {
+            zeros
+        };
+        self.builder.ins().ins_slice(fill, value, 0, size)
+    }
+
+    /// Emit a call to a task declared in a module.
+    ///
+    /// The task body is inlined into the calling process, such that it may
+    /// wait and access the signals of the module.
+    fn emit_inlined_task(
+        &mut self,
+        sub: &'gcx hir::Subroutine,
+        args: &[&'gcx mir::Rvalue<'gcx>],
+        mir: &'gcx mir::Rvalue<'gcx>,
+    ) -> Result<()> {
+        if self.tables.inlined_tasks.contains(&sub.id) {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: recursive call to task `{}`",
+                    sub.name
+                ))
+                .span(mir.span),
+            );
+            return Err(());
+        }
+
+        // Arguments are stored in variables, since the body may assign to
+        // them.
+        for (arg, &value) in sub.args.iter().zip(args) {
+            let value = self.emit_mir_rvalue(value)?;
+            let var = self.builder.ins().var(value);
+            if let Some(name) = arg.name {
+                self.builder.set_name(var, format!("{}.{}", sub.name, name));
+            }
+            self.set_emitted_value(arg.id, var);
+        }
+        self.tables.inlined_tasks.push(sub.id);
+        let result = sub
+            .body
+            .iter()
+            .map(|&stmt| self.emit_stmt(stmt, mir.env))
+            .collect::<Result<()>>();
+        self.tables.inlined_tasks.pop();
+        result
+    }
+
+    /// Emit the code for a call to a class method or constructor.
+    ///
+    /// Returns `None` if the called method produces no value.
+    fn emit_mir_method_call(
+        &mut self,
+        mir: &'gcx mir::Rvalue<'gcx>,
+    ) -> Result<Option<llhd::ir::Value>> {
+        let (object, class, method, args, dispatch) = match mir.kind {
+            mir::RvalueKind::MethodCall {
+                object,
+                ref class,
+                method,
+                ref args,
+                dispatch,
+            } => (object, class, method, args, dispatch),
+            _ => unreachable!(),
+        };
+        if self.builder.unit().is_entity() {
+            self.emit(DiagBuilder2::error("method called outside of a procedure").span(mir.span));
+            return Err(());
+        }
+        let (mut name, sig) = self.emit_class_method(class, method)?;
+        if dispatch {
+            name = self.emit_dispatcher(class, method.unwrap(), name, sig.clone())?;
+        }
+        let mut values = vec![];
+        if let Some(object) = object {
+            values.push(self.emit_mir_rvalue(object)?);
+        }
+        for &arg in args {
+            values.push(self.emit_mir_rvalue(arg)?);
+        }
+        let unit = self.builder.add_extern(name, sig);
+        let inst = self.builder.ins().call(unit, values);
+        Ok(self.builder.unit().get_inst_result(inst))
+    }
+
+    /// Emit the code for a call to `randomize()`.
+    ///
+    /// The random variables are drawn stage by stage, and each stage is drawn
+    /// again until its constraints are satisfied. If a stage cannot be
+    /// satisfied in `RANDOMIZE_ATTEMPTS` attempts, the previous stage is drawn
+    /// again. If the first stage cannot be satisfied, the variables retain
+    /// their previous values and the call yields 0. See
+    /// `docs/randomization.md`.
+    fn emit_randomize(&mut self, mir: &'gcx mir::Rvalue<'gcx>) -> Result<llhd::ir::Value> {
+        let (object, class, vars, constraints, pre, post) = match mir.kind {
+            mir::RvalueKind::Randomize {
+                object,
+                ref class,
+                ref vars,
+                constraints,
+                pre,
+                post,
+            } => (object, class, vars, constraints, pre, post),
+            _ => unreachable!(),
+        };
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error("unsupported: `randomize()` outside of a procedure")
+                    .span(mir.span),
+            );
+            return Err(());
+        }
+        let decls: Vec<_> = vars
+            .iter()
+            .map(|lv| match lv.kind {
+                mir::LvalueKind::Var(id) => id,
+                _ => unreachable!(),
+            })
+            .collect();
+        let model = constraint::randomize_model(
+            self.cx,
+            mir.span,
+            class.as_ref(),
+            &decls,
+            constraints,
+            mir.env,
+        )?;
+
+        // The constraints access the properties of the randomized object
+        // through `this`.
+        let handle = match object {
+            Some(object) => Some(self.emit_mir_rvalue(object)?),
+            None => None,
+        };
+        let saved_this = self.this;
+        if handle.is_some() {
+            self.this = handle;
+        }
+        let result = self.emit_randomize_stages(&model, handle, vars, pre, post);
+        self.this = saved_this;
+        self.flush_mir();
+        result
+    }
+
+    /// Emit the stages of a call to `randomize()`.
+    fn emit_randomize_stages(
+        &mut self,
+        model: &RandomizeModel<'gcx>,
+        handle: Option<llhd::ir::Value>,
+        vars: &[&'gcx mir::Lvalue<'gcx>],
+        pre: Option<&'gcx mir::Rvalue<'gcx>>,
+        post: Option<&'gcx mir::Rvalue<'gcx>>,
+    ) -> Result<llhd::ir::Value> {
+        let zero = self.builder.ins().const_int((32, 0));
+        let one = self.builder.ins().const_int((32, 1));
+        let result = self.builder.ins().var(zero);
+        self.builder.set_name(result, "randomize".into());
+        if let Some(pre) = pre {
+            self.emit_mir_method_call(pre)?;
+        }
+        let exit_blk = self.add_named_block("randomize_exit");
+        if model.infeasible {
+            self.builder.ins().br(exit_blk);
+            self.builder.append_to(exit_blk);
+            return Ok(self.builder.ins().ld(result));
+        }
+
+        // Save the current value of the properties, such that they can be
+        // restored if randomization fails. The variables passed to
+        // `std::randomize` are drawn into temporaries instead.
+        let mut saved = vec![];
+        let mut temps = vec![];
+        for var in &model.vars {
+            match var.class {
+                Some(ref class) => {
+                    let h = self.randomize_handle(var, handle);
+                    let value = self.emit_class_property(h, class, var.decl, None)?.unwrap();
+                    saved.push(value);
+                }
+                None => {
+                    let sig = self
+                        .shadows
+                        .get(&var.decl.into())
+                        .cloned()
+                        .unwrap_or_else(|| self.emitted_value(var.decl));
+                    let value = self.emit_prb_or_var(sig);
+                    let temp = self.builder.ins().var(value);
+                    let prev = self.shadows.insert(var.decl.into(), temp);
+                    temps.push((var.decl, temp, prev));
+                }
+            }
+        }
+
+        // Draw and check each stage.
+        let limit = self.builder.ins().const_int((32, RANDOMIZE_ATTEMPTS));
+        let counters: Vec<_> = model
+            .stages
+            .iter()
+            .map(|_| self.builder.ins().var(zero))
+            .collect();
+        let try_blks: Vec<_> = model
+            .stages
+            .iter()
+            .map(|_| self.add_named_block("randomize_try"))
+            .collect();
+        let success_blk = self.add_named_block("randomize_success");
+        let failure_blk = self.add_named_block("randomize_failure");
+        self.builder.ins().br(try_blks[0]);
+        for (i, stage) in model.stages.iter().enumerate() {
+            self.builder.append_to(try_blks[i]);
+            self.flush_mir();
+            let count = self.builder.ins().ld(counters[i]);
+            let exhausted = self.builder.ins().eq(count, limit);
+            let draw_blk = self.add_named_block("randomize_draw");
+            let retry_blk = if i == 0 { failure_blk } else { try_blks[i - 1] };
+            self.builder.ins().br_cond(exhausted, draw_blk, retry_blk);
+            self.builder.append_to(draw_blk);
+            let count = self.builder.ins().add(count, one);
+            self.builder.ins().st(counters[i], count);
+            for &index in &stage.vars {
+                self.emit_randomize_var(&model.vars[index], handle)?;
+            }
+            self.flush_mir();
+            let ok = self.emit_randomize_checks(&stage.checks)?;
+            let next_blk = match try_blks.get(i + 1) {
+                Some(&next_blk) => {
+                    let enter_blk = self.add_named_block("randomize_next");
+                    self.builder.append_to(enter_blk);
+                    self.builder.ins().st(counters[i + 1], zero);
+                    self.builder.ins().br(next_blk);
+                    self.builder.append_to(draw_blk);
+                    enter_blk
                 }
-                self.builder.set_name(result, "concat".to_string());
-                Ok(result)
+                None => success_blk,
+            };
+            self.builder.ins().br_cond(ok, try_blks[i], next_blk);
+        }
+
+        // On success, commit the temporaries and call `post_randomize()`.
+        self.builder.append_to(success_blk);
+        self.flush_mir();
+        for ((decl, temp, prev), &lvalue) in temps.into_iter().zip(vars) {
+            let value = self.builder.ins().ld(temp);
+            match prev {
+                Some(prev) => self.shadows.insert(decl.into(), prev),
+                None => self.shadows.remove(&decl.into()),
+            };
+            let lvalue = self.emit_mir_lvalue(lvalue)?;
+            self.emit_blocking_assign_llhd(lvalue, value)?;
+        }
+        self.builder.ins().st(result, one);
+        if let Some(post) = post {
+            self.emit_mir_method_call(post)?;
+        }
+        self.builder.ins().br(exit_blk);
+
+        // On failure, restore the previous values of the properties.
+        self.builder.append_to(failure_blk);
+        self.flush_mir();
+        let props = model.vars.iter().filter(|v| v.class.is_some());
+        for (var, value) in props.zip(saved) {
+            let h = self.randomize_handle(var, handle);
+            self.emit_class_property(h, var.class.as_ref().unwrap(), var.decl, Some(value))?;
+        }
+        self.builder.ins().br(exit_blk);
+
+        self.builder.append_to(exit_blk);
+        Ok(self.builder.ins().ld(result))
+    }
+
+    /// Emit the handle through which a random property is accessed.
+    fn randomize_handle(
+        &mut self,
+        var: &RandVar<'gcx>,
+        handle: Option<llhd::ir::Value>,
+    ) -> llhd::ir::Value {
+        match handle {
+            Some(handle) if !var.statik => handle,
+            _ => self.builder.ins().const_int((64, 0)),
+        }
+    }
+
+    /// Emit the code to draw a new value for a random variable.
+    fn emit_randomize_var(
+        &mut self,
+        var: &RandVar<'gcx>,
+        handle: Option<llhd::ir::Value>,
+    ) -> Result<()> {
+        let mut values = vec![];
+        for dist in &var.dists {
+            values.push(self.emit_random_value(var, dist, handle));
+        }
+        let value = match var.length {
+            Some(_) => self.builder.ins().array(values),
+            None => values[0],
+        };
+        match var.class {
+            Some(ref class) => {
+                let h = self.randomize_handle(var, handle);
+                self.emit_class_property(h, class, var.decl, Some(value))?;
+            }
+            None => {
+                let temp = self.shadows[&var.decl.into()];
+                self.builder.ins().st(temp, value);
             }
+        }
+        Ok(())
+    }
 
-            mir::RvalueKind::Repeat(times, value) => {
-                let width = value.ty.simple_bit_vector(self.cx, value.span).size;
-                let value = self.emit_mir_rvalue(value)?;
-                let llty = self.emit_type(mir.ty)?;
-                let mut result = self.emit_zero_for_type(&llty);
-                for i in 0..times {
-                    result = self
-                        .builder
-                        .ins()
-                        .ins_slice(result, value, i * width, width);
+    /// Emit the code to draw a value from a distribution.
+    fn emit_random_value(
+        &mut self,
+        var: &RandVar<'gcx>,
+        dist: &Distribution,
+        handle: Option<llhd::ir::Value>,
+    ) -> llhd::ir::Value {
+        let width = var.sbv.size;
+        let limit = BigInt::one() << 64;
+
+        // Wide variables without constraints are drawn in chunks of 64 bits.
+        if dist.entries.len() == 1 && dist.entries[0].size > limit {
+            let mut value = self.builder.ins().const_int((width, 0));
+            for offset in (0..width).step_by(64) {
+                let length = std::cmp::min(64, width - offset);
+                let mut chunk = self.emit_random_below(&limit);
+                if length < 64 {
+                    chunk = self.builder.ins().ext_slice(chunk, 0, length);
                 }
-                self.builder.set_name(result, "repeat".to_string());
-                Ok(result)
+                value = self.builder.ins().ins_slice(value, chunk, offset, length);
             }
+            return value;
+        }
 
-            mir::RvalueKind::Shift {
-                op,
-                arith,
-                value,
-                amount,
-            } => {
-                let value = self.emit_mir_rvalue(value)?;
-                let amount = self.emit_mir_rvalue(amount)?;
-                let value_ty = self.builder.unit().value_type(value);
-                let hidden = self.emit_zero_for_type(&value_ty);
-                let hidden = if arith && op == mir::ShiftOp::Right {
-                    let ones = self.builder.ins().not(hidden);
-                    let sign = self
-                        .builder
-                        .ins()
-                        .ext_slice(value, value_ty.unwrap_int() - 1, 1);
-                    let mux = self.builder.ins().array(vec![hidden, ones]);
-                    self.builder.ins().mux(mux, sign)
-                } else {
-                    hidden
-                };
-                Ok(match op {
-                    mir::ShiftOp::Left => self.builder.ins().shl(value, hidden, amount),
-                    mir::ShiftOp::Right => self.builder.ins().shr(value, hidden, amount),
+        // Draw a value from each group, then pick one group based on the
+        // weights.
+        let calc_width = std::cmp::max(width, 64);
+        let values: Vec<_> = dist
+            .entries
+            .iter()
+            .map(|entry| self.emit_random_entry(var, entry, handle, calc_width))
+            .collect();
+        let mut value = *values.last().unwrap();
+        if values.len() > 1 {
+            let pick = self.emit_random_below(&BigInt::from(dist.total));
+            let bounds: Vec<u64> = dist
+                .entries
+                .iter()
+                .scan(0, |sum, entry| {
+                    *sum += entry.weight;
+                    Some(*sum)
                 })
+                .collect();
+            for (i, &entry_value) in values.iter().enumerate().rev().skip(1) {
+                let bound = self.builder.ins().const_int((64, BigInt::from(bounds[i])));
+                let sel = self.builder.ins().ult(pick, bound);
+                let choices = self.builder.ins().array(vec![value, entry_value]);
+                value = self.builder.ins().mux(choices, sel);
             }
+        }
+        if calc_width > width {
+            value = self.builder.ins().ext_slice(value, 0, width);
+        }
+        value
+    }
 
-            mir::RvalueKind::Ternary {
-                cond,
-                true_value,
-                false_value,
-            } => {
-                let cond = self.emit_mir_rvalue(cond)?;
-                let true_value = self.emit_mir_rvalue(true_value)?;
-                let false_value = self.emit_mir_rvalue(false_value)?;
-                let array = self.builder.ins().array(vec![false_value, true_value]);
-                Ok(self.builder.ins().mux(array, cond))
-            }
+    /// Emit the code to draw a value uniformly from a group of values.
+    ///
+    /// The value is computed in `calc_width` bits.
+    fn emit_random_entry(
+        &mut self,
+        var: &RandVar<'gcx>,
+        entry: &DistEntry,
+        handle: Option<llhd::ir::Value>,
+        calc_width: usize,
+    ) -> llhd::ir::Value {
+        let mut offset = if var.cyclic {
+            self.emit_random_cyclic(var, handle, &entry.size)
+        } else {
+            self.emit_random_below(&entry.size)
+        };
+        if calc_width > 64 {
+            let zero = self.builder.ins().const_int((calc_width, 0));
+            offset = self.builder.ins().ins_slice(zero, offset, 0, 64);
+        }
 
-            mir::RvalueKind::Reduction { op, arg } => {
-                let width = arg.ty.simple_bit_vector(self.cx, arg.span).size;
-                let arg = self.emit_mir_rvalue(arg)?;
-                let mut value = self.builder.ins().ext_slice(arg, 0, 1);
-                for i in 1..width {
-                    let bit = self.builder.ins().ext_slice(arg, i, 1);
-                    value = match op {
-                        mir::BinaryBitwiseOp::And => self.builder.ins().and(value, bit),
-                        mir::BinaryBitwiseOp::Or => self.builder.ins().or(value, bit),
-                        mir::BinaryBitwiseOp::Xor => self.builder.ins().xor(value, bit),
-                    };
+        // Map the offset onto the value ranges of the group.
+        let modulus = BigInt::one() << calc_width;
+        let wrap = |v: BigInt| ((v % &modulus) + &modulus) % &modulus;
+        let mut base = BigInt::zero();
+        let mut candidates = vec![];
+        for (lo, hi) in &entry.ranges {
+            candidates.push((base.clone(), wrap(lo - &base)));
+            base += hi - lo + 1;
+        }
+        let mut value = None;
+        for (i, (_, delta)) in candidates.iter().enumerate().rev() {
+            let delta = self.builder.ins().const_int((calc_width, delta.clone()));
+            let candidate = self.builder.ins().add(offset, delta);
+            value = Some(match value {
+                None => candidate,
+                Some(later) => {
+                    let bound = candidates[i + 1].0.clone();
+                    let bound = self.builder.ins().const_int((calc_width, bound));
+                    let sel = self.builder.ins().ult(offset, bound);
+                    let choices = self.builder.ins().array(vec![later, candidate]);
+                    self.builder.ins().mux(choices, sel)
                 }
-                Ok(value)
+            });
+        }
+        value.unwrap()
+    }
+
+    /// Emit a call to the runtime to draw a number below `n`, or a full 64
+    /// bit number if `n` is 2^64.
+    fn emit_random_below(&mut self, n: &BigInt) -> llhd::ir::Value {
+        let n = if n.bits() > 64 {
+            BigInt::zero()
+        } else {
+            n.clone()
+        };
+        let mut sig = llhd::ir::Signature::new();
+        sig.add_input(llhd::int_ty(32));
+        sig.add_input(llhd::int_ty(64));
+        sig.set_return_type(llhd::int_ty(64));
+        let unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global("moore.rand.below".into()), sig);
+        let seed = self.sess().opts.seed as usize;
+        let seed = self.builder.ins().const_int((32, seed));
+        let n = self.builder.ins().const_int((64, n));
+        let inst = self.builder.ins().call(unit, vec![seed, n]);
+        self.builder.unit().get_inst_result(inst).unwrap()
+    }
+
+    /// Emit a call to the simulator to obtain the current simulation time,
+    /// rounded to the given granularity in seconds.
+    fn emit_sim_time(&mut self, granularity: num::BigRational) -> llhd::ir::Value {
+        let mut sig = llhd::ir::Signature::new();
+        sig.add_input(llhd::time_ty());
+        sig.set_return_type(llhd::time_ty());
+        let ext_unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global("moore.time".into()), sig);
+        let granularity =
+            self.builder
+                .ins()
+                .const_time(llhd::value::TimeValue::new(granularity, 0, 0));
+        let inst = self.builder.ins().call(ext_unit, vec![granularity]);
+        self.builder.unit().get_inst_result(inst).unwrap()
+    }
+
+    /// Emit the code for a call to `$random`, `$urandom`, or `$urandom_range`.
+    ///
+    /// The runtime keeps the state of the generators. It initializes the global
+    /// `$random` generator and the `$urandom` generator of each process from
+    /// the seed passed as first argument the first time they are used.
+    fn emit_sys_random(&mut self, mir: &'gcx mir::Rvalue<'gcx>) -> Result<llhd::ir::Value> {
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error("random number generator used outside of a procedure")
+                    .span(mir.span),
+            );
+            return Err(());
+        }
+        let seed = self.sess().opts.seed as usize;
+        let seed = self.builder.ins().const_int((32, seed));
+        let i32_ty = llhd::int_ty(32);
+        let i64_ty = llhd::int_ty(64);
+        match mir.kind {
+            mir::RvalueKind::Random => Ok(self
+                .emit_runtime_call("moore.random", vec![seed], Some(i32_ty))
+                .unwrap()),
+            mir::RvalueKind::Urandom(reseed) => {
+                if let Some(reseed) = reseed {
+                    let reseed = self.emit_mir_rvalue(reseed)?;
+                    self.emit_runtime_call("moore.urandom.seed", vec![reseed], None);
+                }
+                Ok(self
+                    .emit_runtime_call("moore.urandom", vec![seed], Some(i32_ty))
+                    .unwrap())
             }
+            mir::RvalueKind::UrandomRange { max, min } => {
+                // The bounds may be given in either order.
+                let max = self.emit_mir_rvalue(max)?;
+                let min = self.emit_mir_rvalue(min)?;
+                let swap = self.builder.ins().ult(max, min);
+                let lo = self.builder.ins().array(vec![min, max]);
+                let lo = self.builder.ins().mux(lo, swap);
+                let hi = self.builder.ins().array(vec![max, min]);
+                let hi = self.builder.ins().mux(hi, swap);
+
+                // Draw an offset from the range, which may span all 2^32
+                // values.
+                let diff = self.builder.ins().sub(hi, lo);
+                let zero = self.builder.ins().const_int((64, 0));
+                let diff = self.builder.ins().ins_slice(zero, diff, 0, 32);
+                let one = self.builder.ins().const_int((64, 1));
+                let n = self.builder.ins().add(diff, one);
+                let offset = self
+                    .emit_runtime_call("moore.urandom.below", vec![seed, n], Some(i64_ty))
+                    .unwrap();
+                let offset = self.builder.ins().ext_slice(offset, 0, 32);
+                Ok(self.builder.ins().add(offset, lo))
+            }
+            _ => unreachable!(),
+        }
+    }
 
-            mir::RvalueKind::Assignment {
-                lvalue,
-                rvalue,
-                result,
-            } => {
-                self.emit_mir_blocking_assign(lvalue, rvalue)?;
-                self.emit_mir_rvalue(result)
+    /// Emit a call to `$finish`, which ends the simulation after running the
+    /// final procedures.
+    fn emit_finish(&mut self, level: &'gcx mir::Rvalue<'gcx>) -> Result<()> {
+        let level = self.emit_mir_rvalue(level)?;
+        self.emit_runtime_call("moore.finish", vec![level], None);
+
+        // The calling process never resumes, since the simulation has ended.
+        if self.builder.unit().is_process() {
+            self.builder.ins().halt();
+            let finished_blk = self.add_named_block("finished");
+            self.builder.append_to(finished_blk);
+        }
+        Ok(())
+    }
+
+    /// Emit a call to a function of the runtime.
+    fn emit_runtime_call(
+        &mut self,
+        name: &str,
+        args: Vec<llhd::ir::Value>,
+        ret: Option<llhd::Type>,
+    ) -> Option<llhd::ir::Value> {
+        let mut sig = llhd::ir::Signature::new();
+        for &arg in &args {
+            sig.add_input(self.llhd_type(arg));
+        }
+        sig.set_return_type(ret.clone().unwrap_or_else(llhd::void_ty));
+        let unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global(name.into()), sig);
+        let inst = self.builder.ins().call(unit, args);
+        ret.map(|_| self.builder.unit().get_inst_result(inst).unwrap())
+    }
+
+    /// Emit a call to the runtime to draw the next number below `n` in the
+    /// cycle of a `randc` variable.
+    fn emit_random_cyclic(
+        &mut self,
+        var: &RandVar<'gcx>,
+        handle: Option<llhd::ir::Value>,
+        n: &BigInt,
+    ) -> llhd::ir::Value {
+        let mut sig = llhd::ir::Signature::new();
+        sig.add_input(llhd::int_ty(32));
+        sig.add_input(llhd::int_ty(64));
+        sig.add_input(llhd::int_ty(64));
+        sig.set_return_type(llhd::int_ty(64));
+        let name = format!(
+            "moore.rand.cyclic.{}.{}",
+            class::class_name(self.cx, var.class.as_ref().unwrap()),
+            var.name
+        );
+        let unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global(name), sig);
+        let seed = self.sess().opts.seed as usize;
+        let seed = self.builder.ins().const_int((32, seed));
+        let handle = self.randomize_handle(var, handle);
+        let n = self.builder.ins().const_int((64, n.clone()));
+        let inst = self.builder.ins().call(unit, vec![seed, handle, n]);
+        self.builder.unit().get_inst_result(inst).unwrap()
+    }
+
+    /// Emit the code to evaluate a constraint checked after randomization.
+    fn emit_randomize_check(&mut self, check: &Check) -> Result<llhd::ir::Value> {
+        match check.kind {
+            CheckKind::Expr(expr_id) => self.emit_randomize_cond(expr_id, check.env),
+            CheckKind::Implication(cond, ref checks) => {
+                let cond = self.emit_randomize_cond(cond, check.env)?;
+                let not_cond = self.builder.ins().not(cond);
+                let all = self.emit_randomize_checks(checks)?;
+                Ok(self.builder.ins().or(not_cond, all))
             }
+            CheckKind::If(cond, ref main, ref alt) => {
+                let cond = self.emit_randomize_cond(cond, check.env)?;
+                let main = self.emit_randomize_checks(main)?;
+                let alt = self.emit_randomize_checks(alt)?;
+                let choices = self.builder.ins().array(vec![alt, main]);
+                Ok(self.builder.ins().mux(choices, cond))
+            }
+        }
+    }
 
-            mir::RvalueKind::PackString(value) | mir::RvalueKind::UnpackString(value) => bug_span!(
-                value.span,
-                self.cx,
-                "codegen for string packing/unpacking not implemented"
-            ),
+    /// Emit the code to evaluate a list of constraints that must all hold.
+    fn emit_randomize_checks(&mut self, checks: &[Check]) -> Result<llhd::ir::Value> {
+        let mut all = self.builder.ins().const_int((1, 1));
+        for check in checks {
+            let check = self.emit_randomize_check(check)?;
+            all = self.builder.ins().and(all, check);
+        }
+        Ok(all)
+    }
 
-            mir::RvalueKind::StringComp { .. } => bug_span!(
-                mir.span,
-                self.cx,
-                "runtime string comparisons not implemented"
-            ),
+    /// Emit the code to evaluate a constraint expression to a boolean.
+    fn emit_randomize_cond(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
+        let value = self.emit_rvalue(expr_id, env)?;
+        match *self.llhd_type(value) {
+            llhd::IntType(1) => Ok(value),
+            llhd::IntType(w) => {
+                let zero = self.builder.ins().const_int((w, 0));
+                Ok(self.builder.ins().neq(value, zero))
+            }
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "constraint `{}` is not a boolean expression",
+                        self.span(expr_id).extract()
+                    ))
+                    .span(self.span(expr_id)),
+                );
+                Err(())
+            }
+        }
+    }
 
-            mir::RvalueKind::Error => Err(()),
-        };
+    /// Emit the code to construct an object of a class.
+    ///
+    /// The runtime allocates the object, which is then initialized by calling
+    /// the constructor.
+    fn emit_class_new(
+        &mut self,
+        mir: &'gcx mir::Rvalue<'gcx>,
+        args: &[&'gcx mir::Rvalue<'gcx>],
+    ) -> Result<llhd::ir::Value> {
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error("unsupported: object constructed outside of a procedure")
+                    .span(mir.span)
+                    .add_note("Construct objects in an `initial` block or a class method"),
+            );
+            return Err(());
+        }
+        let class = mir.ty.get_class().unwrap();
+        let id = self.constructed_class_id(class)?;
+        let id = self.builder.ins().const_int((32, id));
+        let mut sig = llhd::ir::Signature::new();
+        sig.add_input(llhd::int_ty(32));
+        sig.set_return_type(llhd::int_ty(64));
+        let unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global("moore.class.new".into()), sig);
+        let inst = self.builder.ins().call(unit, vec![id]);
+        let handle = self.builder.unit().get_inst_result(inst).unwrap();
+        let (name, sig) = self.emit_class_method(class, None)?;
+        let mut values = vec![handle];
+        for &arg in args {
+            values.push(self.emit_mir_rvalue(arg)?);
+        }
+        let unit = self.builder.add_extern(name, sig);
+        self.builder.ins().call(unit, values);
+        Ok(handle)
+    }
 
-        value.map(|v| (v, Mode::Value))
+    /// Initialize the properties of the object being constructed.
+    fn emit_property_init(&mut self, class: &ty::ClassType<'gcx>) -> Result<()> {
+        let this = self.this.unwrap();
+        for prop in &class::class_hir(self.cx, class.ast)?.props {
+            let decl = match self.hir_of(prop.id)? {
+                HirNode::VarDecl(x) => x,
+                _ => unreachable!(),
+            };
+            if prop.statik {
+                if let Some(init) = decl.init {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "unsupported: initializer of static property `{}`",
+                            decl.name
+                        ))
+                        .span(self.span(init)),
+                    );
+                    return Err(());
+                }
+                continue;
+            }
+            let value = match decl.init {
+                Some(init) => self.emit_rvalue(init, class.env)?,
+                None => {
+                    let ty = self.type_of(prop.id, class.env)?;
+                    let ty = self.emit_type(ty)?;
+                    self.emit_zero_for_type(&ty)
+                }
+            };
+            self.emit_class_property(this, class, prop.id, Some(value))?;
+        }
+        Ok(())
+    }
+
+    /// Emit the handle of the object a property or method is accessed
+    /// through. Static members use the null handle.
+    fn emit_class_handle(
+        &mut self,
+        object: Option<&'gcx mir::Rvalue<'gcx>>,
+    ) -> Result<llhd::ir::Value> {
+        match object {
+            Some(object) => self.emit_mir_rvalue(object),
+            None => Ok(self.builder.ins().const_int((64, 0))),
+        }
+    }
+
+    /// Emit the code to read or write a class property.
+    ///
+    /// Reads the property if `value` is `None`, and writes `value` to it
+    /// otherwise. Properties are stored by the runtime, and accessed through
+    /// functions named after the class and property. See `docs/classes.md`.
+    fn emit_class_property(
+        &mut self,
+        handle: llhd::ir::Value,
+        class: &ty::ClassType<'gcx>,
+        prop: NodeId,
+        value: Option<llhd::ir::Value>,
+    ) -> Result<Option<llhd::ir::Value>> {
+        let name = match self.hir_of(prop)? {
+            HirNode::VarDecl(x) => x.name,
+            _ => unreachable!(),
+        };
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "property `{}` accessed outside of a procedure",
+                    name
+                ))
+                .span(self.span(prop)),
+            );
+            return Err(());
+        }
+        let ty = self.type_of(prop, class.env)?;
+        let ty = self.emit_type(ty)?;
+        let mut sig = llhd::ir::Signature::new();
+        sig.add_input(llhd::int_ty(64));
+        let (access, args) = match value {
+            Some(value) => {
+                sig.add_input(ty);
+                sig.set_return_type(llhd::void_ty());
+                ("set", vec![handle, value])
+            }
+            None => {
+                sig.set_return_type(ty);
+                ("get", vec![handle])
+            }
+        };
+        let unit = self.builder.add_extern(
+            llhd::ir::UnitName::Global(format!(
+                "moore.class.{}.{}.{}",
+                access,
+                class::class_name(self.cx, class),
+                name
+            )),
+            sig,
+        );
+        let inst = self.builder.ins().call(unit, args);
+        Ok(self.builder.unit().get_inst_result(inst))
     }
 
     fn emit_prb_or_var(&mut self, sig: llhd::ir::Value) -> llhd::ir::Value {
@@ -1821,10 +4592,20 @@ where
             // Variables and ports trivially return their declaration value.
             // This is either the `var` or `sig` instruction which introduced
             // them.
-            mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => Ok((
-                self.emitted_value(id).clone(),
-                self.shadows.get(&id.into()).cloned(),
-            )),
+            mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => {
+                self.check_function_access(id, mir.span)?;
+                Ok((
+                    self.emitted_value(id).clone(),
+                    self.shadows.get(&id.into()).cloned(),
+                ))
+            }
+
+            // Hierarchical references resolve to the signal threaded out of
+            // the referenced instance.
+            mir::LvalueKind::HierSignal(path, decl) => {
+                let id = AccessedNode::Hier(path, decl);
+                Ok((self.emitted_value(id), self.shadows.get(&id).cloned()))
+            }
 
             // Interface signals require special care, because they are emitted
             // in a transposed fashion.
@@ -1852,6 +4633,16 @@ where
                 self.emit_lvalue_index(value.ty, inner, base, length)
             }
 
+            // Class properties are only assigned as a whole, which is handled
+            // by the assignments themselves.
+            mir::LvalueKind::ClassProperty { .. } => {
+                self.emit(
+                    DiagBuilder2::error("unsupported: assignment to part of a class property")
+                        .span(mir.span),
+                );
+                Err(())
+            }
+
             // Errors from MIR lowering have already been reported. Just abort.
             mir::LvalueKind::Error => Err(()),
 
@@ -2024,6 +4815,53 @@ where
                     return Err(());
                 }
                 assert_type!(rhs_mir.ty, lhs_mir.ty, rhs_mir.span, self.cx);
+                self.check_implicit_conversion(rhs, env)?;
+                if lhs_mir.ty.get_virtual_interface().is_some() {
+                    // Virtual interfaces are bound during elaboration.
+                    return Ok(());
+                }
+                if let mir::LvalueKind::ClassProperty { .. } = lhs_mir.kind {
+                    if kind != hir::AssignKind::Block(ast::AssignOp::Identity) {
+                        self.emit(
+                            DiagBuilder2::error(
+                                "unsupported: compound or nonblocking assignment to a class \
+                                 property",
+                            )
+                            .span(hir.span)
+                            .add_note("Use a blocking assignment `=` instead"),
+                        );
+                        return Err(());
+                    }
+                    return self.emit_mir_blocking_assign(lhs_mir, rhs_mir);
+                }
+
+                // Synchronous drives through a clocking block take effect
+                // after the output skew of the signal.
+                if let Some(var) = clocking::clock_var_of_expr(self.cx, lhs, env) {
+                    if kind != hir::AssignKind::Nonblock {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "synchronous drive to `{}` must use `<=` without delay",
+                                lhs_mir.span.extract()
+                            ))
+                            .span(hir.span)
+                            .add_note("The output skew of the clocking block determines the delay"),
+                        );
+                        return Err(());
+                    }
+                    let delay = match var.output_skew {
+                        hir::ClockingSkew::Delay(skew) => self.emit_delay(skew, env)?,
+                        _ => {
+                            let delay = llhd::value::TimeValue::new(num::zero(), 1, 0);
+                            self.builder.ins().const_time(delay)
+                        }
+                    };
+                    let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+                    let rhs_rv = self.emit_mir_rvalue(rhs_mir)?;
+                    self.builder.ins().drv(lhs_lv.0, rhs_rv, delay);
+                    return Ok(());
+                }
+
                 let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
                 let rhs_rv = self.emit_mir_rvalue(rhs_mir)?;
 
@@ -2064,7 +4902,7 @@ where
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay_const);
                     }
                     hir::AssignKind::NonblockDelay(delay) => {
-                        let delay = self.emit_rvalue(delay, env)?;
+                        let delay = self.emit_delay(delay, env)?;
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay);
                     }
                     _ => {
@@ -2080,8 +4918,8 @@ where
                 control: hir::TimingControl::Delay(expr_id),
                 stmt,
             } => {
+                let duration = self.emit_delay(expr_id, env)?;
                 let resume_blk = self.add_nameless_block();
-                let duration = self.emit_rvalue(expr_id, env)?.into();
                 self.builder.ins().wait_time(resume_blk, duration, vec![]);
                 self.builder.append_to(resume_blk);
                 self.flush_mir(); // ensure we don't reuse earlier expr probe
@@ -2092,70 +4930,7 @@ where
                 control: hir::TimingControl::ExplicitEvent(expr_id),
                 stmt,
             } => {
-                let expr_hir = match self.hir_of(expr_id)? {
-                    HirNode::EventExpr(x) => x,
-                    _ => unreachable!(),
-                };
-                trace!("would now emit event checking code for {:#?}", expr_hir);
-
-                // Store initial values of the expressions the event is
-                // sensitive to.
-                let init_blk = self.add_named_block("init");
-                self.builder.ins().br(init_blk);
-                self.builder.append_to(init_blk);
-                let mut init_values = vec![];
-                for event in &expr_hir.events {
-                    init_values.push(self.emit_rvalue(event.expr, env)?);
-                }
-
-                // Wait for any of the inputs to those expressions to change.
-                let check_blk = self.add_named_block("check");
-                let mut trigger_on = vec![];
-                for event in &expr_hir.events {
-                    let acc = self.accessed_nodes(event.expr, env)?;
-                    for &id in &acc.read {
-                        trigger_on.push(self.emitted_value(id).clone());
-                    }
-                }
-                self.builder.ins().wait(check_blk, trigger_on);
-                self.builder.append_to(check_blk);
-                self.flush_mir(); // ensure we don't reuse earlier expr probe
-                self.emit_shadow_update();
-
-                // Check if any of the events happened and produce a single bit
-                // value that represents this.
-                let mut event_cond = None;
-                for (event, init_value) in expr_hir.events.iter().zip(init_values.into_iter()) {
-                    trace!(
-                        "would now emit check if {:?} changed according to {:#?}",
-                        init_value,
-                        event
-                    );
-                    let now_value = self.emit_rvalue(event.expr, env)?;
-                    let mut trigger = self.emit_event_trigger(event.edge, init_value, now_value)?;
-                    for &iff in &event.iff {
-                        let iff_value = self.emit_rvalue_bool(iff, env)?;
-                        trigger = self.builder.ins().and(trigger, iff_value);
-                        self.builder.set_name(trigger, "iff".to_string());
-                    }
-                    event_cond = Some(match event_cond {
-                        Some(chain) => {
-                            let value = self.builder.ins().or(chain, trigger);
-                            self.builder.set_name(value, "event_or".to_string());
-                            value
-                        }
-                        None => trigger,
-                    });
-                }
-
-                // If the event happened, branch to a new block which will
-                // contain the subsequent statements. Otherwise jump back up to
-                // the initial block.
-                if let Some(event_cond) = event_cond {
-                    let event_blk = self.add_named_block("event");
-                    self.builder.ins().br_cond(event_cond, init_blk, event_blk);
-                    self.builder.append_to(event_blk);
-                }
+                self.emit_event_wait(expr_id, env)?;
 
                 // Emit the actual statement.
                 self.emit_stmt(stmt, env)?;
@@ -2180,12 +4955,36 @@ where
                 self.emit_stmt(stmt, env)?;
             }
             hir::StmtKind::Expr(expr_id) => {
-                self.emit_rvalue(expr_id, env)?;
+                let mir = self.mir_rvalue(expr_id, env);
+                match mir.kind {
+                    mir::RvalueKind::Call { .. } => {
+                        self.emit_mir_call(mir)?;
+                    }
+                    mir::RvalueKind::MethodCall { .. } => {
+                        self.emit_mir_method_call(mir)?;
+                    }
+                    mir::RvalueKind::CoverSample { .. } => {
+                        self.emit_mir_cover_sample(mir)?;
+                    }
+                    mir::RvalueKind::Randomize { .. } => {
+                        self.emit_randomize(mir)?;
+                    }
+                    mir::RvalueKind::Finish(level) => {
+                        self.emit_finish(level)?;
+                    }
+                    _ => {
+                        self.emit_mir_rvalue(mir)?;
+                    }
+                }
+            }
+            hir::StmtKind::If { up: Some(up), .. } => {
+                self.emit_unique_if(hir, up, env)?;
             }
             hir::StmtKind::If {
                 cond,
                 main_stmt,
                 else_stmt,
+                ..
             } => {
                 let main_blk = self.add_named_block("if_true");
                 let else_blk = self.add_named_block("if_false");
@@ -2283,64 +5082,35 @@ where
             }
 
             hir::StmtKind::Case {
+                up,
                 expr,
                 ref ways,
                 default,
                 kind,
             } => {
+                self.check_case(hir.id, env)?;
                 let expr = self.emit_rvalue(expr, env)?;
-                let final_blk = self.add_named_block("case_exit");
-                for &(ref way_exprs, stmt) in ways {
-                    let mut last_check = self.builder.ins().const_int((1, 0));
-                    for &way_expr in way_exprs {
-                        // Determine the constant value of the label.
-                        let way_const = self.constant_value_of(way_expr, env);
-                        let (_, special_bits, x_bits) = match &way_const.kind {
-                            ValueKind::Int(v, s, x) => (v, s, x),
-                            _ => panic!("case constant evaluates to non-integer"),
-                        };
-                        let way_expr = self.emit_const(way_const, env, self.span(way_expr))?;
-                        let way_width = self.llhd_type(way_expr).unwrap_int();
-
-                        // Generate the comparison mask based on the case kind.
-                        let mask = match kind {
-                            ast::CaseKind::Normal => None,
-                            ast::CaseKind::DontCareZ => {
-                                let mut mask = special_bits.clone();
-                                mask.difference(x_bits);
-                                mask.negate();
-                                Some(mask)
-                            }
-                            ast::CaseKind::DontCareXZ => {
-                                let mut mask = special_bits.clone();
-                                mask.negate();
-                                Some(mask)
-                            }
-                        };
-                        let mask = mask.map(|bits| {
-                            let mut mask = BigInt::zero();
-                            for b in &bits {
-                                mask <<= 1;
-                                if b {
-                                    mask |= BigInt::one();
-                                }
-                            }
-                            self.builder.ins().const_int((way_width, mask))
-                        });
-
-                        // Filter the comparison values through the mask.
-                        let (lhs, rhs) = match mask {
-                            Some(mask) => (
-                                self.builder.ins().and(expr, mask),
-                                self.builder.ins().and(way_expr, mask),
-                            ),
-                            None => (expr, way_expr),
-                        };
 
-                        // Perform the comparison and branch.
-                        let check = self.builder.ins().eq(lhs, rhs);
-                        last_check = self.builder.ins().or(last_check, check);
+                // Qualified case statements evaluate all labels upfront, such
+                // that multiple matches can be detected.
+                let checks = match up {
+                    Some(up) => {
+                        let mut checks = vec![];
+                        for &(ref way_exprs, _) in ways {
+                            checks.push(self.emit_case_way_check(expr, way_exprs, kind, env)?);
+                        }
+                        self.emit_unique_checks(up, &checks, default.is_some(), hir.span);
+                        Some(checks)
                     }
+                    None => None,
+                };
+
+                let final_blk = self.add_named_block("case_exit");
+                for (i, &(ref way_exprs, stmt)) in ways.iter().enumerate() {
+                    let last_check = match checks {
+                        Some(ref checks) => checks[i],
+                        None => self.emit_case_way_check(expr, way_exprs, kind, env)?,
+                    };
                     let taken_blk = self.add_named_block("case_body");
                     let untaken_blk = self.add_nameless_block();
                     self.builder
@@ -2358,6 +5128,108 @@ where
                 self.builder.append_to(final_blk);
             }
 
+            hir::StmtKind::Trigger { target, kind } => {
+                let lhs_mir = self.mir_lvalue(target, env);
+                let rhs_mir = self.mir_rvalue(target, env);
+                if lhs_mir.is_error() || rhs_mir.is_error() {
+                    return Err(());
+                }
+                if !rhs_mir.ty.is_event() {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not an event",
+                            rhs_mir.span.extract()
+                        ))
+                        .span(rhs_mir.span)
+                        .add_note(format!(
+                            "`{}` is of type `{}`",
+                            rhs_mir.span.extract(),
+                            rhs_mir.ty
+                        )),
+                    );
+                    return Err(());
+                }
+                if self.builder.unit().is_entity() {
+                    self.emit(
+                        DiagBuilder2::error("event triggered outside of a procedure")
+                            .span(hir.span),
+                    );
+                    return Err(());
+                }
+                let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+                let current = self.emit_mir_rvalue(rhs_mir)?;
+
+                // Bump the trigger count and record the time of the trigger.
+                let count = self.builder.ins().ext_field(current, 0);
+                let one = self.builder.ins().const_int((32, 1));
+                let count = self.builder.ins().add(count, one);
+                let now = self.emit_sim_time(event_time_granularity());
+
+                match kind {
+                    hir::AssignKind::Block(ast::AssignOp::Identity) => {
+                        let value = self.builder.ins().strukt(vec![count, now]);
+                        self.emit_blocking_assign_llhd(lhs_lv, value)?;
+                    }
+                    hir::AssignKind::Nonblock => {
+                        let value = self.builder.ins().strukt(vec![count, now]);
+                        let delay = llhd::value::TimeValue::new(num::zero(), 1, 0);
+                        let delay_const = self.builder.ins().const_time(delay);
+                        self.builder.ins().drv(lhs_lv.0, value, delay_const);
+                    }
+                    hir::AssignKind::NonblockDelay(delay) => {
+                        let delay = self.emit_delay(delay, env)?;
+                        let then = self.builder.ins().add(now, delay);
+                        let value = self.builder.ins().strukt(vec![count, then]);
+                        self.builder.ins().drv(lhs_lv.0, value, delay);
+                    }
+                    _ => unreachable!("invalid event trigger {:?}", kind),
+                }
+            }
+
+            hir::StmtKind::Wait { cond, stmt } => {
+                // Check the condition, and if it does not hold, wait for any
+                // of the signals it reads to change and check again.
+                let check_blk = self.add_named_block("wait_check");
+                let wait_blk = self.add_named_block("wait");
+                let done_blk = self.add_named_block("wait_done");
+                self.builder.ins().br(check_blk);
+                self.builder.append_to(check_blk);
+                self.flush_mir(); // ensure we don't reuse earlier expr probe
+                self.emit_shadow_update();
+                let value = self.emit_rvalue_bool(cond, env)?;
+                self.builder.ins().br_cond(value, wait_blk, done_blk);
+                self.builder.append_to(wait_blk);
+                let mut trigger_on = vec![];
+                for &id in &self.accessed_nodes(cond, env)?.read {
+                    trigger_on.push(self.emitted_value(id).clone());
+                }
+                self.builder.ins().wait(check_blk, trigger_on);
+                self.builder.append_to(done_blk);
+                self.emit_stmt(stmt, env)?;
+            }
+
+            hir::StmtKind::Return(expr) => {
+                if !self.builder.unit().is_function() {
+                    self.emit(
+                        DiagBuilder2::error("unsupported: `return` outside of a function")
+                            .span(hir.span),
+                    );
+                    return Err(());
+                }
+                match expr {
+                    Some(expr) => {
+                        let value = self.emit_rvalue(expr, env)?;
+                        self.builder.ins().ret_value(value);
+                    }
+                    None => {
+                        self.builder.ins().ret();
+                    }
+                }
+                // Any statements after the return are unreachable.
+                let blk = self.add_nameless_block();
+                self.builder.append_to(blk);
+            }
+
             _ => {
                 error!("{:#?}", hir);
                 return self.unimp_msg("code generation for", hir);
@@ -2366,6 +5238,184 @@ where
         Ok(())
     }
 
+    /// Emit a `unique`, `unique0`, or `priority` if statement, together with
+    /// the runtime checks of its qualifier.
+    ///
+    /// The `else if` chain is flattened, and all conditions are evaluated
+    /// upfront such that multiple matching branches can be detected.
+    fn emit_unique_if(
+        &mut self,
+        stmt: &hir::Stmt,
+        up: ast::UniquePriority,
+        env: ParamEnv,
+    ) -> Result<()> {
+        // Collect the branches of the chain.
+        let mut ways = vec![];
+        let mut default = None;
+        let mut next = stmt;
+        while let hir::StmtKind::If {
+            cond,
+            main_stmt,
+            else_stmt,
+            ..
+        } = next.kind
+        {
+            ways.push((cond, main_stmt));
+            let else_stmt = match else_stmt {
+                Some(x) => x,
+                None => break,
+            };
+            match self.hir_of(else_stmt)? {
+                HirNode::Stmt(
+                    x @ hir::Stmt {
+                        kind: hir::StmtKind::If { up: None, .. },
+                        ..
+                    },
+                ) => next = x,
+                _ => {
+                    default = Some(else_stmt);
+                    break;
+                }
+            }
+        }
+
+        // Evaluate the conditions and check for violations.
+        let mut checks = vec![];
+        for &(cond, _) in &ways {
+            checks.push(self.emit_rvalue_bool(cond, env)?);
+        }
+        self.emit_unique_checks(up, &checks, default.is_some(), stmt.span);
+
+        // Emit the branches.
+        let final_blk = self.add_named_block("if_exit");
+        for (&(_, main_stmt), check) in ways.iter().zip(checks) {
+            let main_blk = self.add_named_block("if_true");
+            let else_blk = self.add_named_block("if_false");
+            self.builder.ins().br_cond(check, else_blk, main_blk);
+            self.builder.append_to(main_blk);
+            self.emit_stmt(main_stmt, env)?;
+            self.builder.ins().br(final_blk);
+            self.builder.append_to(else_blk);
+        }
+        if let Some(default) = default {
+            self.emit_stmt(default, env)?;
+        }
+        self.builder.ins().br(final_blk);
+        self.builder.append_to(final_blk);
+        Ok(())
+    }
+
+    /// Emit the check whether the value of a case statement matches any of
+    /// the labels of a case item.
+    fn emit_case_way_check(
+        &mut self,
+        expr: llhd::ir::Value,
+        way_exprs: &[NodeId],
+        kind: ast::CaseKind,
+        env: ParamEnv,
+    ) -> Result<llhd::ir::Value> {
+        let mut last_check = self.builder.ins().const_int((1, 0));
+        for &way_expr in way_exprs {
+            // Determine the constant value of the label.
+            let way_const = self.constant_value_of(way_expr, env);
+            let (_, special_bits, x_bits) = match &way_const.kind {
+                ValueKind::Int(v, s, x) => (v, s, x),
+                _ => panic!("case constant evaluates to non-integer"),
+            };
+            let way_expr = self.emit_const(way_const, env, self.span(way_expr))?;
+            let way_width = self.llhd_type(way_expr).unwrap_int();
+
+            // Generate the comparison mask based on the case kind.
+            let mask = match kind {
+                ast::CaseKind::Normal => None,
+                ast::CaseKind::DontCareZ => {
+                    let mut mask = special_bits.clone();
+                    mask.difference(x_bits);
+                    mask.negate();
+                    Some(mask)
+                }
+                ast::CaseKind::DontCareXZ => {
+                    let mut mask = special_bits.clone();
+                    mask.negate();
+                    Some(mask)
+                }
+            };
+            let mask = mask.map(|bits| {
+                let mut mask = BigInt::zero();
+                for b in &bits {
+                    mask <<= 1;
+                    if b {
+                        mask |= BigInt::one();
+                    }
+                }
+                self.builder.ins().const_int((way_width, mask))
+            });
+
+            // Filter the comparison values through the mask.
+            let (lhs, rhs) = match mask {
+                Some(mask) => (
+                    self.builder.ins().and(expr, mask),
+                    self.builder.ins().and(way_expr, mask),
+                ),
+                None => (expr, way_expr),
+            };
+
+            // Perform the comparison and branch.
+            let check = self.builder.ins().eq(lhs, rhs);
+            last_check = self.builder.ins().or(last_check, check);
+        }
+        Ok(last_check)
+    }
+
+    /// Emit the runtime checks of a `unique`, `unique0`, or `priority`
+    /// qualifier, given the conditions under which each branch is taken.
+    ///
+    /// Reports a violation if no branch is taken and there is no `else` or
+    /// `default` branch (`unique` and `priority`), or if multiple branches
+    /// could be taken (`unique` and `unique0`).
+    fn emit_unique_checks(
+        &mut self,
+        up: ast::UniquePriority,
+        checks: &[llhd::ir::Value],
+        has_default: bool,
+        span: Span,
+    ) {
+        let mut any = self.builder.ins().const_int((1, 0));
+        let mut multiple = any;
+        for &check in checks {
+            if up.is_parallel() {
+                let overlap = self.builder.ins().and(any, check);
+                multiple = self.builder.ins().or(multiple, overlap);
+            }
+            any = self.builder.ins().or(any, check);
+        }
+        if up.is_full() && !has_default {
+            let none = self.builder.ins().not(any);
+            self.emit_violation(none, VIOLATION_NO_MATCH, span);
+        }
+        if up.is_parallel() {
+            self.emit_violation(multiple, VIOLATION_MULTIPLE_MATCHES, span);
+        }
+    }
+
+    /// Emit a call to the runtime that reports a violation at the given
+    /// location if `cond` holds.
+    fn emit_violation(&mut self, cond: llhd::ir::Value, kind: usize, span: Span) {
+        let report_blk = self.add_named_block("violation");
+        let exit_blk = self.add_named_block("violation_exit");
+        self.builder.ins().br_cond(cond, exit_blk, report_blk);
+        self.builder.append_to(report_blk);
+        let (line, column, _) = span.begin().human();
+        let args = vec![
+            self.builder.ins().const_int((32, kind)),
+            self.builder.ins().const_int((32, line)),
+            self.builder.ins().const_int((32, column)),
+        ];
+        self.emit_runtime_call("moore.violation", args, None);
+        self.builder.ins().br(exit_blk);
+        self.builder.append_to(exit_blk);
+    }
+
     /// Emit the code for a variable declaration statement, given its HIR.
     fn emit_stmt_var_decl(
         &mut self,
@@ -2381,9 +5431,16 @@ where
                 .unwrap()),
             env,
         );
+        if ty.get_virtual_interface().is_some() {
+            // Virtual interfaces are bound during elaboration.
+            return Ok(());
+        }
         let ty = self.emit_type(ty)?;
         let init = match hir.init {
-            Some(expr) => self.emit_rvalue(expr, env)?,
+            Some(expr) => {
+                self.check_implicit_conversion(expr, env)?;
+                self.emit_rvalue(expr, env)?
+            }
             None => self.emit_zero_for_type(&ty),
         };
         let value = self.builder.ins().var(init);
@@ -2393,6 +5450,76 @@ where
     }
 
     /// Emit the code to check if a certain edge occurred between two values.
+    /// Emit code that waits for an event expression such as `@(posedge clk)`
+    /// to trigger.
+    fn emit_event_wait(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<()> {
+        let expr_hir = match self.hir_of(expr_id)? {
+            HirNode::EventExpr(x) => x,
+            _ => unreachable!(),
+        };
+        trace!("would now emit event checking code for {:#?}", expr_hir);
+
+        // Store initial values of the expressions the event is
+        // sensitive to.
+        let init_blk = self.add_named_block("init");
+        self.builder.ins().br(init_blk);
+        self.builder.append_to(init_blk);
+        let mut init_values = vec![];
+        for event in &expr_hir.events {
+            init_values.push(self.emit_rvalue(event.expr, env)?);
+        }
+
+        // Wait for any of the inputs to those expressions to change.
+        let check_blk = self.add_named_block("check");
+        let mut trigger_on = vec![];
+        for event in &expr_hir.events {
+            let acc = self.accessed_nodes(event.expr, env)?;
+            for &id in &acc.read {
+                trigger_on.push(self.emitted_value(id).clone());
+            }
+        }
+        self.builder.ins().wait(check_blk, trigger_on);
+        self.builder.append_to(check_blk);
+        self.flush_mir(); // ensure we don't reuse earlier expr probe
+        self.emit_shadow_update();
+
+        // Check if any of the events happened and produce a single bit
+        // value that represents this.
+        let mut event_cond = None;
+        for (event, init_value) in expr_hir.events.iter().zip(init_values.into_iter()) {
+            trace!(
+                "would now emit check if {:?} changed according to {:#?}",
+                init_value,
+                event
+            );
+            let now_value = self.emit_rvalue(event.expr, env)?;
+            let mut trigger = self.emit_event_trigger(event.edge, init_value, now_value)?;
+            for &iff in &event.iff {
+                let iff_value = self.emit_rvalue_bool(iff, env)?;
+                trigger = self.builder.ins().and(trigger, iff_value);
+                self.builder.set_name(trigger, "iff".to_string());
+            }
+            event_cond = Some(match event_cond {
+                Some(chain) => {
+                    let value = self.builder.ins().or(chain, trigger);
+                    self.builder.set_name(value, "event_or".to_string());
+                    value
+                }
+                None => trigger,
+            });
+        }
+
+        // If the event happened, branch to a new block which will
+        // contain the subsequent statements. Otherwise jump back up to
+        // the initial block.
+        if let Some(event_cond) = event_cond {
+            let event_blk = self.add_named_block("event");
+            self.builder.ins().br_cond(event_cond, init_blk, event_blk);
+            self.builder.append_to(event_blk);
+        }
+        Ok(())
+    }
+
     fn emit_event_trigger(
         &mut self,
         edge: ast::EdgeIdent,
@@ -2451,11 +5578,239 @@ where
         lvalue: &'gcx mir::Lvalue<'gcx>,
         rvalue: &'gcx mir::Rvalue<'gcx>,
     ) -> Result<()> {
+        if let mir::LvalueKind::ClassProperty {
+            object,
+            ref class,
+            prop,
+        } = lvalue.kind
+        {
+            let rv = self.emit_mir_rvalue(rvalue)?;
+            let handle = self.emit_class_handle(object)?;
+            self.emit_class_property(handle, class, prop, Some(rv))?;
+            return Ok(());
+        }
         let lv = self.emit_mir_lvalue(lvalue)?;
         let rv = self.emit_mir_rvalue(rvalue)?;
         self.emit_blocking_assign_llhd(lv, rv)
     }
 
+    /// Emit the code for sampling a covergroup.
+    fn emit_mir_cover_sample(&mut self, mir: &'gcx mir::Rvalue<'gcx>) -> Result<()> {
+        let (group, state, points, point_guards, cross_guards) = match mir.kind {
+            mir::RvalueKind::CoverSample {
+                group,
+                state,
+                ref points,
+                ref point_guards,
+                ref cross_guards,
+            } => (group, state, points, point_guards, cross_guards),
+            _ => unreachable!(),
+        };
+        if self.builder.unit().is_entity() {
+            self.emit(
+                DiagBuilder2::error("covergroup sampled outside of a procedure").span(mir.span),
+            );
+            return Err(());
+        }
+        let cg = state.ty.get_covergroup().unwrap();
+        let model = self.cover_model(Ref(cg.ast), cg.env)?;
+        let lvalue = self.emit_mir_lvalue(group)?;
+        let state = self.emit_mir_rvalue(state)?;
+        let mut emit_all = |values: &[&'gcx mir::Rvalue<'gcx>]| {
+            values
+                .iter()
+                .map(|&v| self.emit_mir_rvalue(v))
+                .collect::<Result<Vec<_>>>()
+        };
+        let points = emit_all(points)?;
+        let point_guards = emit_all(point_guards)?;
+        let cross_guards = emit_all(cross_guards)?;
+        let next = self.emit_cover_update(&model, state, &points, &point_guards, &cross_guards);
+        self.emit_blocking_assign_llhd(lvalue, next)
+    }
+
+    /// Emit the code that increments the hit counters of a covergroup.
+    ///
+    /// Returns the updated counters.
+    fn emit_cover_update(
+        &mut self,
+        model: &CoverModel<'gcx>,
+        state: llhd::ir::Value,
+        points: &[llhd::ir::Value],
+        point_guards: &[llhd::ir::Value],
+        cross_guards: &[llhd::ir::Value],
+    ) -> llhd::ir::Value {
+        let mut hits = vec![None; model.num_counters];
+
+        // Determine which bins of each coverpoint are hit.
+        for ((point, &value), &guard) in model.points.iter().zip(points).zip(point_guards) {
+            let mut enable = guard;
+            if let Some(ignored) = self.emit_cover_ranges(point, value, &point.ignore) {
+                let not_ignored = self.builder.ins().not(ignored);
+                enable = self.builder.ins().and(enable, not_ignored);
+            }
+            let mut any = None;
+            for bin in point.bins.iter().chain(point.illegal.iter()) {
+                let hit = match self.emit_cover_ranges(point, value, &bin.ranges) {
+                    Some(hit) => hit,
+                    None => continue,
+                };
+                if point.default.is_some() {
+                    any = Some(match any {
+                        Some(any) => self.builder.ins().or(any, hit),
+                        None => hit,
+                    });
+                }
+                let hit = self.builder.ins().and(hit, enable);
+                self.builder
+                    .set_name(hit, format!("{}.{}", point.name, bin.name));
+                hits[bin.counter] = Some(hit);
+            }
+            if let Some(ref bin) = point.default {
+                let hit = match any {
+                    Some(any) => {
+                        let none = self.builder.ins().not(any);
+                        self.builder.ins().and(none, enable)
+                    }
+                    None => enable,
+                };
+                hits[bin.counter] = Some(hit);
+            }
+        }
+
+        // Determine which combinations of bins of each cross are hit.
+        for (cross, &guard) in model.crosses.iter().zip(cross_guards) {
+            let mut combos = vec![Some(guard)];
+            for &point in &cross.points {
+                let mut next = vec![];
+                for &combo in &combos {
+                    for bin in &model.points[point].bins {
+                        next.push(match (combo, hits[bin.counter]) {
+                            (Some(a), Some(b)) => Some(self.builder.ins().and(a, b)),
+                            _ => None,
+                        });
+                    }
+                }
+                combos = next;
+            }
+            for (i, hit) in combos.into_iter().enumerate() {
+                hits[cross.offset + i] = hit;
+            }
+        }
+
+        // Increment the counters of the bins that were hit.
+        let zero = self.builder.ins().const_int((32, 0));
+        let counters = hits
+            .into_iter()
+            .enumerate()
+            .map(|(i, hit)| {
+                let count = self.builder.ins().ext_field(state, i);
+                match hit {
+                    Some(hit) => {
+                        let inc = self.builder.ins().ins_slice(zero, hit, 0, 1);
+                        self.builder.ins().add(count, inc)
+                    }
+                    None => count,
+                }
+            })
+            .collect();
+        self.builder.ins().array(counters)
+    }
+
+    /// Emit a check whether a coverpoint value lies within any of a list of
+    /// value ranges.
+    ///
+    /// Returns `None` if the list is empty.
+    fn emit_cover_ranges(
+        &mut self,
+        point: &CoverPointModel<'gcx>,
+        value: llhd::ir::Value,
+        ranges: &[(BigInt, BigInt)],
+    ) -> Option<llhd::ir::Value> {
+        let sbv = point.ty.get_simple_bit_vector().unwrap();
+        let signed = sbv.sign == ty::Sign::Signed;
+        let modulus = BigInt::one() << sbv.size;
+        let mut result = None;
+        for (lo, hi) in ranges {
+            let mut konst = |v: &BigInt| {
+                let v = ((v % &modulus) + &modulus) % &modulus;
+                self.builder.ins().const_int((sbv.size, v))
+            };
+            let hit = if lo == hi {
+                let k = konst(lo);
+                self.builder.ins().eq(value, k)
+            } else {
+                let lo = konst(lo);
+                let hi = konst(hi);
+                let (above, below) = if signed {
+                    (
+                        self.builder.ins().sge(value, lo),
+                        self.builder.ins().sle(value, hi),
+                    )
+                } else {
+                    (
+                        self.builder.ins().uge(value, lo),
+                        self.builder.ins().ule(value, hi),
+                    )
+                };
+                self.builder.ins().and(above, below)
+            };
+            result = Some(match result {
+                Some(r) => self.builder.ins().or(r, hit),
+                None => hit,
+            });
+        }
+        result
+    }
+
+    /// Emit the code that computes the coverage of a covergroup as an integer
+    /// percentage.
+    ///
+    /// The coverage is the average over the coverage of each coverpoint and
+    /// cross, which in turn is the percentage of its regular bins that have
+    /// been hit at least once.
+    fn emit_cover_coverage(
+        &mut self,
+        model: &CoverModel<'gcx>,
+        state: llhd::ir::Value,
+    ) -> llhd::ir::Value {
+        let groups = model
+            .points
+            .iter()
+            .map(|p| p.bins.iter().map(|b| b.counter).collect::<Vec<_>>())
+            .chain(
+                model
+                    .crosses
+                    .iter()
+                    .map(|c| (c.offset..c.offset + c.size).collect()),
+            )
+            .filter(|g| !g.is_empty())
+            .collect::<Vec<_>>();
+        let zero = self.builder.ins().const_int((32, 0));
+        let hundred = self.builder.ins().const_int((32, 100));
+        let mut total = zero;
+        for group in &groups {
+            let mut covered = zero;
+            for &counter in group {
+                let count = self.builder.ins().ext_field(state, counter);
+                let hit = self.builder.ins().neq(count, zero);
+                let hit = self.builder.ins().ins_slice(zero, hit, 0, 1);
+                covered = self.builder.ins().add(covered, hit);
+            }
+            let covered = self.builder.ins().umul(covered, hundred);
+            let size = self.builder.ins().const_int((32, group.len()));
+            let pct = self.builder.ins().udiv(covered, size);
+            total = self.builder.ins().add(total, pct);
+        }
+        if groups.is_empty() {
+            return zero;
+        }
+        let num = self.builder.ins().const_int((32, groups.len()));
+        let value = self.builder.ins().udiv(total, num);
+        self.builder.set_name(value, "coverage".to_string());
+        value
+    }
+
     /// Emit a blocking assignment to a variable or signal.
     fn emit_blocking_assign_llhd(
         &mut self,
@@ -2505,11 +5860,15 @@ where
         default: Option<NodeId>,
     ) -> Result<llhd::ir::Value> {
         // Check if this is a variable or a net declaration.
-        let is_var = match self.hir_of(decl_id)? {
-            HirNode::VarDecl(x) => x.kind.is_var(),
-            HirNode::IntPort(x) => x.kind.is_var(),
+        let kind = match self.hir_of(decl_id)? {
+            HirNode::VarDecl(x) => x.kind,
+            HirNode::IntPort(x) => x.kind,
             x => unreachable!("emit_varnet_decl on HIR {:?}", x),
         };
+        let is_var = kind.is_var();
+        if let Some(default) = default {
+            self.check_implicit_conversion(default, env)?;
+        }
 
         // Differentiate between variable and net declarations, which have
         // slightly different semantics regarding their initial value.
@@ -2527,8 +5886,27 @@ where
             Ok(self.builder.ins().sig(init))
         } else {
             // For nets we simply emit the initial value as a signal, then
-            // short-circuit it with the net declaration.
-            let zero = self.emit_const(self.type_default_value(ty), env, self.span(decl_id))?;
+            // short-circuit it with the net declaration. Nets pulled high
+            // start out as all ones.
+            let mut zero = self.emit_const(self.type_default_value(ty), env, self.span(decl_id))?;
+            match kind {
+                ast::VarKind::Net {
+                    ty: ast::NetType::Supply1,
+                    ..
+                }
+                | ast::VarKind::Net {
+                    ty: ast::NetType::Tri1,
+                    ..
+                } => {
+                    if let llhd::IntType(width) = *self.llhd_type(zero) {
+                        zero = self
+                            .builder
+                            .ins()
+                            .const_int(llhd::value::IntValue::all_ones(width));
+                    }
+                }
+                _ => (),
+            }
             let net = self.builder.ins().sig(zero);
             if let Some(default) = default {
                 let init = self.emit_rvalue_mode(default, env, Mode::Signal)?;
@@ -2626,6 +6004,8 @@ pub struct EmittedModule<'a> {
     unit: llhd::ir::UnitId,
     /// The module's ports.
     ports: ModuleIntf<'a>,
+    /// The flip-flops and clocks of the module.
+    cdc: Rc<CdcModule>,
 }
 
 /// Result of emitting a procedure.
@@ -2693,3 +6073,35 @@ pub struct IntfSignal<'a> {
     /// The expression assigned as default to the signal.
     pub default: Option<NodeId>,
 }
+
+/// A visitor that rejects timing controls in a `final` procedure.
+struct FinalTimingChecker<'a, C> {
+    cx: &'a C,
+    failed: bool,
+}
+
+impl<'a, 'gcx: 'a, C> hir::Visitor<'gcx> for FinalTimingChecker<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        match stmt.kind {
+            hir::StmtKind::Timed { .. } | hir::StmtKind::Wait { .. } => {
+                self.cx.emit(
+                    DiagBuilder2::error("timing control in `final` procedure")
+                        .span(stmt.human_span())
+                        .add_note("Final procedures execute in zero time at the end of simulation"),
+                );
+                self.failed = true;
+            }
+            _ => (),
+        }
+        hir::walk_stmt(self, stmt);
+    }
+}
@@ -0,0 +1,1303 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Constrained randomization.
+//!
+//! This module analyzes the random variables and constraints involved in a
+//! call to `randomize()` (see IEEE 1800-2017 §18). Rather than a general
+//! constraint solver, it implements the common subset as follows:
+//!
+//! - Comparisons of a random variable against a constant, `inside` sets, and
+//!   `dist` distributions that hold unconditionally are absorbed into the
+//!   variable's domain. The variable is drawn from its domain directly.
+//! - Comparisons between two random variables that hold unconditionally
+//!   narrow the bounds of both domains, such that few draws are rejected.
+//! - All other constraints are checked after drawing the variables, and the
+//!   variables are drawn again if a check fails.
+//! - `solve ... before` orders the variables into stages. Each stage is drawn
+//!   and checked on its own, such that later stages do not disturb the values
+//!   chosen for earlier ones.
+//! - `foreach` constraints over fixed-size arrays are unrolled.
+//!
+//! See `docs/randomization.md` for how the model is executed at runtime.
+
+use crate::crate_prelude::*;
+use crate::{
+    hir::{self, HirNode},
+    ty::{ClassType, SbvType, Sign, UnpackedType},
+};
+use moore_common::lint::UNSATISFIABLE_CONSTRAINT;
+use num::{BigInt, One, ToPrimitive, Zero};
+
+/// The maximum number of values a `randc` variable may take on.
+const RANDC_MAX: usize = 1 << 16;
+
+/// The maximum number of passes over the comparisons between random variables
+/// when narrowing their domains.
+const NARROW_PASSES: usize = 64;
+
+/// The random variables and constraints of a call to `randomize()`.
+#[derive(Debug)]
+pub struct RandomizeModel<'a> {
+    /// The random variables.
+    pub vars: Vec<RandVar<'a>>,
+    /// The stages in which the variables are drawn and checked.
+    pub stages: Vec<RandStage>,
+    /// Whether the domain of any variable is empty, such that randomization
+    /// always fails.
+    pub infeasible: bool,
+}
+
+/// A random variable.
+#[derive(Debug)]
+pub struct RandVar<'a> {
+    /// The declaration of the variable.
+    pub decl: NodeId,
+    /// The name of the variable.
+    pub name: Name,
+    /// The class declaring the variable, if it is a property.
+    pub class: Option<ClassType<'a>>,
+    /// Whether the variable is a static property.
+    pub statik: bool,
+    /// Whether the variable is declared `randc`.
+    pub cyclic: bool,
+    /// The type of the variable.
+    pub ty: &'a UnpackedType<'a>,
+    /// The type of the variable or its elements.
+    pub sbv: SbvType,
+    /// The number of elements, if the variable is an array.
+    pub length: Option<usize>,
+    /// The distribution of the variable, or of each of its elements.
+    pub dists: Vec<Distribution>,
+}
+
+/// The distribution of the values of a random variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distribution {
+    /// The groups of values, each with a weight.
+    pub entries: Vec<DistEntry>,
+    /// The sum of the weights of all groups.
+    pub total: u64,
+}
+
+/// A group of values that are drawn uniformly once the group is chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistEntry {
+    /// The weight of the group.
+    pub weight: u64,
+    /// The sorted, disjoint, and inclusive value ranges of the group.
+    pub ranges: Vec<(BigInt, BigInt)>,
+    /// The number of values in the group.
+    pub size: BigInt,
+}
+
+/// A set of variables drawn together, and the constraints checked after.
+#[derive(Debug, Default)]
+pub struct RandStage {
+    /// The indices of the variables drawn in this stage.
+    pub vars: Vec<usize>,
+    /// The constraints checked once the variables are drawn.
+    pub checks: Vec<Check>,
+}
+
+/// A constraint checked after the variables are drawn.
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// The environment in which the constraint is evaluated.
+    pub env: ParamEnv,
+    /// The constraint.
+    pub kind: CheckKind,
+}
+
+/// The different constraints that may be checked.
+#[derive(Debug, Clone)]
+pub enum CheckKind {
+    /// An expression that must be true.
+    Expr(NodeId),
+    /// Constraints that must hold if a condition is true.
+    Implication(NodeId, Vec<Check>),
+    /// Constraints that must hold if a condition is true or false,
+    /// respectively.
+    If(NodeId, Vec<Check>, Vec<Check>),
+}
+
+/// Check whether a method name refers to the built-in `randomize()`.
+pub fn is_randomize(name: Name) -> bool {
+    &*name.as_str() == "randomize"
+}
+
+/// Check whether an expression refers to the `std::randomize` function.
+pub fn is_std_randomize(expr: &ast::Expr) -> bool {
+    match expr.data {
+        ast::ScopeExpr(ref scope, name) => match scope.data {
+            ast::IdentExpr(pkg) => {
+                &*pkg.value.as_str() == "std" && &*name.value.as_str() == "randomize"
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Find the object randomized by the inline constraint a node is nested in.
+///
+/// Returns the expression `obj` of an enclosing `obj.randomize() with {...}`,
+/// if any. Names within the inline constraint refer to the properties of this
+/// object first.
+pub fn inline_constraint_object<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+) -> Option<&'a ast::Expr<'a>> {
+    let mut node = cx.ast_for_id(node_id);
+    loop {
+        let parent = node.get_parent()?;
+        if let Some(expr) = parent.as_all().get_expr() {
+            if let ast::InlineConstraintExpr(ref call, _) = expr.data {
+                if node.as_all().is_constraint_item() {
+                    return match call.data {
+                        ast::CallExpr(ref callee, _) => match callee.data {
+                            ast::MemberExpr { ref expr, .. } => Some(expr),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                }
+            }
+        }
+        node = parent;
+    }
+}
+
+/// Find the class of the object randomized by the inline constraint a node is
+/// nested in.
+pub fn inline_constraint_class<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Option<ClassType<'a>> {
+    let object = inline_constraint_object(cx, node_id)?;
+    cx.type_of(object.id(), env).ok()?.get_class().cloned()
+}
+
+/// Find the property of the randomized object a name in an inline constraint
+/// refers to, if any.
+pub fn resolve_inline_property<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+    name: Name,
+    env: ParamEnv,
+) -> Option<NodeId> {
+    let class = inline_constraint_class(cx, node_id, env)?;
+    crate::class::find_property(cx, &class, name)
+        .ok()?
+        .map(|prop| prop.member.id)
+}
+
+/// Analyze the random variables and constraints of a call to `randomize()`.
+///
+/// The variables are the `rand` and `randc` properties of `class` and its
+/// bases, or the variables `std_vars` passed to `std::randomize`. The
+/// constraints are the constraint blocks of `class` and its bases, together
+/// with the inline constraint `inline`, if any.
+pub fn randomize_model<'a>(
+    cx: &impl Context<'a>,
+    span: Span,
+    class: Option<&ClassType<'a>>,
+    std_vars: &[NodeId],
+    inline: Option<NodeId>,
+    env: ParamEnv,
+) -> Result<RandomizeModel<'a>> {
+    let mut builder = ModelBuilder {
+        cx,
+        vars: vec![],
+        domains: vec![],
+        dists: vec![],
+        checks: vec![],
+        relations: vec![],
+        order: vec![],
+    };
+
+    // Collect the random variables and the constraint blocks. Blocks of a
+    // derived class override the blocks of the same name in its bases.
+    let mut blocks = vec![];
+    if let Some(class) = class {
+        let chain = crate::class::class_chain(cx, class)?;
+        let mut seen = vec![];
+        for class in &chain {
+            let hir = crate::class::class_hir(cx, class.ast)?;
+            for constraint in &hir.constraints {
+                if seen.contains(&constraint.name.value) {
+                    continue;
+                }
+                seen.push(constraint.name.value);
+                blocks.push((&constraint.items, class.env));
+            }
+        }
+        for class in chain.iter().rev() {
+            let hir = crate::class::class_hir(cx, class.ast)?;
+            for prop in &hir.props {
+                if let Some(qual) = prop.rand {
+                    builder.add_var(
+                        prop.id,
+                        Some(class.clone()),
+                        prop.statik,
+                        qual == ast::RandomQualifier::Randc,
+                    )?;
+                }
+            }
+        }
+    }
+    for &decl in std_vars {
+        builder.add_var(decl, None, false, false)?;
+    }
+    if let Some(inline) = inline {
+        match cx.hir_of(inline)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::InlineConstraint(_, ref items),
+                ..
+            }) => blocks.push((items, env)),
+            _ => unreachable!(),
+        }
+    }
+
+    // Absorb the constraints into the domains of the variables.
+    let mut failed = false;
+    for (items, env) in blocks.into_iter().rev() {
+        failed |= builder.add_items(items, env).is_err();
+    }
+    if failed {
+        return Err(());
+    }
+    builder.finish(span)
+}
+
+/// A helper to gather the random variables and constraints of a call to
+/// `randomize()`.
+struct ModelBuilder<'a, 'c, C> {
+    cx: &'c C,
+    vars: Vec<RandVar<'a>>,
+    /// The permitted value ranges of each variable, or each of its elements.
+    domains: Vec<Vec<Vec<(BigInt, BigInt)>>>,
+    /// The `dist` constraints on each variable, or each of its elements.
+    dists: Vec<Vec<Option<(Span, Vec<(BigInt, BigInt, ast::DistWeight, BigInt)>)>>>,
+    checks: Vec<Check>,
+    /// The comparisons between variables, or elements thereof.
+    relations: Vec<Relation>,
+    /// The `solve ... before` orderings between variables.
+    order: Vec<(usize, usize)>,
+}
+
+/// A comparison between two random variables, or elements thereof, that
+/// holds unconditionally.
+///
+/// The operands are pairs of variable and element index, where scalar
+/// variables have the single element `0`. The operator is `<`, `<=`, or `==`.
+#[derive(Debug, Clone)]
+struct Relation {
+    lhs: (usize, usize),
+    op: hir::BinaryOp,
+    rhs: (usize, usize),
+}
+
+impl<'a, 'c, C: Context<'a>> ModelBuilder<'a, 'c, C> {
+    /// Add a random variable.
+    fn add_var(
+        &mut self,
+        decl: NodeId,
+        class: Option<ClassType<'a>>,
+        statik: bool,
+        cyclic: bool,
+    ) -> Result<()> {
+        let cx = self.cx;
+        let name = match cx.hir_of(decl)? {
+            HirNode::VarDecl(x) => x.name,
+            _ => unreachable!(),
+        };
+        let env = class
+            .as_ref()
+            .map(|c| c.env)
+            .unwrap_or(cx.default_param_env());
+        let ty = cx.type_of(decl, env)?;
+        let unsupported = || {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: random variable `{}` of type `{}`",
+                    name.value, ty
+                ))
+                .span(name.span)
+                .add_note(
+                    "Only integral variables and fixed-size arrays thereof can be randomized",
+                ),
+            );
+            Err(())
+        };
+
+        // Determine the number of elements of arrays.
+        let dims: Vec<_> = ty.unpacked_dims().collect();
+        let (elem_ty, length) = match dims.as_slice() {
+            [] => (ty, None),
+            [dim] => match dim.get_size() {
+                Some(size) => (ty.pop_dim(cx).unwrap(), Some(size)),
+                None => return unsupported(),
+            },
+            _ => return unsupported(),
+        };
+        let sbv = match elem_ty.get_simple_bit_vector() {
+            Some(x) => x,
+            None => return unsupported(),
+        };
+        if cyclic && length.is_some() {
+            cx.emit(
+                DiagBuilder2::error(format!("unsupported: `randc` array `{}`", name.value))
+                    .span(name.span),
+            );
+            return Err(());
+        }
+
+        // Enums can only take on the values of their variants.
+        let domain = match elem_ty.get_enum() {
+            Some(enm) => {
+                let mut values = vec![];
+                for &(_, variant) in &enm.variants {
+                    let value = cx.constant_value_of(variant.id(), env);
+                    let value = value.get_int().ok_or(())?;
+                    let value = normalize(value, sbv);
+                    values.push((value.clone(), value));
+                }
+                merge_ranges(values)
+            }
+            None => {
+                let (min, max) = type_range(sbv);
+                vec![(min, max)]
+            }
+        };
+        let count = length.unwrap_or(1);
+        self.vars.push(RandVar {
+            decl,
+            name: name.value,
+            class,
+            statik,
+            cyclic,
+            ty,
+            sbv,
+            length,
+            dists: vec![],
+        });
+        self.domains.push(vec![domain; count]);
+        self.dists.push(vec![None; count]);
+        Ok(())
+    }
+
+    /// Find the random variable, or element thereof, an expression refers to.
+    fn match_var(&self, expr_id: NodeId, env: ParamEnv) -> Option<(usize, Option<usize>)> {
+        let cx = self.cx;
+        let hir = match cx.hir_of(expr_id).ok()? {
+            HirNode::Expr(x) => x,
+            _ => return None,
+        };
+        match hir.kind {
+            hir::ExprKind::Ident(..) => {
+                let decl = cx.resolve_node(expr_id, env).ok()?;
+                self.vars
+                    .iter()
+                    .position(|v| v.decl == decl)
+                    .map(|v| (v, None))
+            }
+            hir::ExprKind::Index(target, hir::IndexMode::One(index)) => {
+                let (var, elem) = self.match_var(target, env)?;
+                let length = self.vars[var].length?;
+                if elem.is_some() {
+                    return None;
+                }
+                let index = const_operand(cx, index, env)?.to_usize()?;
+                if index < length {
+                    Some((var, Some(index)))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Add a list of constraint items that hold unconditionally.
+    fn add_items(&mut self, items: &'a [hir::ConstraintItem], env: ParamEnv) -> Result<()> {
+        let mut failed = false;
+        for item in items {
+            failed |= self.add_item(item, env).is_err();
+        }
+        if failed {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Add a constraint item that holds unconditionally.
+    fn add_item(&mut self, item: &'a hir::ConstraintItem, env: ParamEnv) -> Result<()> {
+        let cx = self.cx;
+        match *item {
+            hir::ConstraintItem::Expr(expr_id) => self.add_expr(expr_id, env),
+            hir::ConstraintItem::Dist(expr_id, ref items) => {
+                let (var, elem) = match self.match_var(expr_id, env) {
+                    Some(x) => x,
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "unsupported: `dist` on `{}`",
+                                cx.span(expr_id).extract()
+                            ))
+                            .span(cx.span(expr_id))
+                            .add_note("Only random variables can have a distribution"),
+                        );
+                        return Err(());
+                    }
+                };
+                let elems = match elem {
+                    Some(elem) => elem..elem + 1,
+                    None => 0..self.dists[var].len(),
+                };
+                let dist = self.eval_dist(items, env)?;
+                for elem in elems {
+                    let slot = &mut self.dists[var][elem];
+                    if let Some((prev, _)) = slot {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "multiple distributions for `{}`",
+                                cx.span(expr_id).extract()
+                            ))
+                            .span(cx.span(expr_id))
+                            .add_note("Previous distribution was here:")
+                            .span(*prev),
+                        );
+                        return Err(());
+                    }
+                    *slot = Some((cx.span(expr_id), dist.clone()));
+                }
+                Ok(())
+            }
+            hir::ConstraintItem::Foreach(array, ref indices, ref items) => {
+                for env in self.unroll_foreach(array, indices, env)? {
+                    self.add_items(items, env)?;
+                }
+                Ok(())
+            }
+            hir::ConstraintItem::SolveBefore(ref before, ref after) => {
+                let before = self.solve_vars(before, env)?;
+                let after = self.solve_vars(after, env)?;
+                for &b in &before {
+                    for &a in &after {
+                        self.order.push((b, a));
+                    }
+                }
+                Ok(())
+            }
+            hir::ConstraintItem::Implication(..) | hir::ConstraintItem::If(..) => {
+                let checks = self.lower_checks(std::slice::from_ref(item), env)?;
+                self.checks.extend(checks);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add an expression that must hold unconditionally.
+    ///
+    /// Conjunctions are split up, and comparisons of a random variable against
+    /// a constant are absorbed into the variable's domain. Everything else is
+    /// checked after the variables are drawn.
+    fn add_expr(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<()> {
+        let hir = match self.cx.hir_of(expr_id)? {
+            HirNode::Expr(x) => x,
+            _ => unreachable!(),
+        };
+        if let hir::ExprKind::Binary(hir::BinaryOp::LogicAnd, lhs, rhs) = hir.kind {
+            self.add_expr(lhs, env)?;
+            return self.add_expr(rhs, env);
+        }
+        if !self.absorb_expr(hir, env)? {
+            self.relate_expr(hir, env)?;
+            self.checks.push(Check {
+                env,
+                kind: CheckKind::Expr(expr_id),
+            });
+        }
+        Ok(())
+    }
+
+    /// Try to absorb an expression into the domain of a random variable.
+    ///
+    /// Returns `false` if the expression cannot be absorbed.
+    fn absorb_expr(&mut self, hir: &'a hir::Expr<'a>, env: ParamEnv) -> Result<bool> {
+        let cx = self.cx;
+        match hir.kind {
+            hir::ExprKind::Binary(op, lhs, rhs) => {
+                // Find the variable and the constant it is compared against.
+                let (var, elem, konst, op) =
+                    match (self.match_var(lhs, env), self.match_var(rhs, env)) {
+                        (Some((var, elem)), _) => match const_operand(cx, rhs, env) {
+                            Some(k) => (var, elem, k, op),
+                            None => return Ok(false),
+                        },
+                        (None, Some((var, elem))) => match const_operand(cx, lhs, env) {
+                            Some(k) => (var, elem, k, mirror_op(op)),
+                            None => return Ok(false),
+                        },
+                        _ => return Ok(false),
+                    };
+                let optype = match self.operation_sbv(hir.id, var, env)? {
+                    Some(x) => x,
+                    None => return Ok(false),
+                };
+                let (min, max) = type_range(self.vars[var].sbv);
+                let konst = normalize(&konst, optype);
+                let ranges = match op {
+                    hir::BinaryOp::Eq => vec![(konst.clone(), konst)],
+                    hir::BinaryOp::Lt => vec![(min, konst - 1)],
+                    hir::BinaryOp::Leq => vec![(min, konst)],
+                    hir::BinaryOp::Gt => vec![(konst + 1, max)],
+                    hir::BinaryOp::Geq => vec![(konst, max)],
+                    hir::BinaryOp::Neq => {
+                        let mut ranges = vec![];
+                        if min < konst {
+                            ranges.push((min, &konst - 1));
+                        }
+                        if konst < max {
+                            ranges.push((konst + 1, max));
+                        }
+                        ranges
+                    }
+                    _ => return Ok(false),
+                };
+                self.restrict(var, elem, &ranges);
+                Ok(true)
+            }
+            hir::ExprKind::Inside(expr, ref values) => {
+                let (var, elem) = match self.match_var(expr, env) {
+                    Some(x) => x,
+                    None => return Ok(false),
+                };
+                let optype = match self.operation_sbv(hir.id, var, env)? {
+                    Some(x) => x,
+                    None => return Ok(false),
+                };
+                let mut ranges = vec![];
+                for value in values {
+                    let (lo, hi) = match value.value {
+                        hir::InsideRange::Single(id) => (id, id),
+                        hir::InsideRange::Range(lo, hi) => (lo, hi),
+                    };
+                    match (const_operand(cx, lo, env), const_operand(cx, hi, env)) {
+                        (Some(lo), Some(hi)) => {
+                            ranges.push((normalize(&lo, optype), normalize(&hi, optype)))
+                        }
+                        _ => return Ok(false),
+                    }
+                }
+                self.restrict(var, elem, &merge_ranges(ranges));
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Record a comparison between two random variables.
+    ///
+    /// The comparison narrows the bounds of the variables' domains before
+    /// they are drawn, but is still checked afterwards.
+    fn relate_expr(&mut self, hir: &'a hir::Expr<'a>, env: ParamEnv) -> Result<()> {
+        let (op, lhs, rhs) = match hir.kind {
+            hir::ExprKind::Binary(op, lhs, rhs) => (op, lhs, rhs),
+            _ => return Ok(()),
+        };
+        let elem = |expr_id| match self.match_var(expr_id, env) {
+            Some((var, Some(elem))) => Some((var, elem)),
+            Some((var, None)) if self.vars[var].length.is_none() => Some((var, 0)),
+            _ => None,
+        };
+        let (lhs, rhs) = match (elem(lhs), elem(rhs)) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return Ok(()),
+        };
+        if self.operation_sbv(hir.id, lhs.0, env)?.is_none()
+            || self.operation_sbv(hir.id, rhs.0, env)?.is_none()
+        {
+            return Ok(());
+        }
+        let relation = match op {
+            hir::BinaryOp::Lt | hir::BinaryOp::Leq | hir::BinaryOp::Eq => Relation { lhs, op, rhs },
+            hir::BinaryOp::Gt | hir::BinaryOp::Geq => Relation {
+                lhs: rhs,
+                op: mirror_op(op),
+                rhs: lhs,
+            },
+            _ => return Ok(()),
+        };
+        self.relations.push(relation);
+        Ok(())
+    }
+
+    /// Determine the type in which a random variable is compared, if the
+    /// comparison preserves the variable's value.
+    fn operation_sbv(&self, expr_id: NodeId, var: usize, env: ParamEnv) -> Result<Option<SbvType>> {
+        let optype = match self.cx.operation_type(expr_id, env) {
+            Some(x) if !x.is_error() => x,
+            Some(_) => return Err(()),
+            None => return Ok(None),
+        };
+        let optype = match optype.get_simple_bit_vector() {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let sbv = self.vars[var].sbv;
+        let preserved = optype.size >= sbv.size
+            && (optype.sign == sbv.sign || (sbv.sign == Sign::Unsigned && optype.size > sbv.size));
+        Ok(if preserved { Some(optype) } else { None })
+    }
+
+    /// Restrict the domain of a random variable, or one of its elements.
+    fn restrict(&mut self, var: usize, elem: Option<usize>, ranges: &[(BigInt, BigInt)]) {
+        for (i, domain) in self.domains[var].iter_mut().enumerate() {
+            if elem.map(|e| e == i).unwrap_or(true) {
+                *domain = intersect_ranges(domain, ranges);
+            }
+        }
+    }
+
+    /// Evaluate the items of a `dist` constraint.
+    fn eval_dist(
+        &self,
+        items: &'a [hir::DistItem],
+        env: ParamEnv,
+    ) -> Result<Vec<(BigInt, BigInt, ast::DistWeight, BigInt)>> {
+        let cx = self.cx;
+        let eval = |id: NodeId| -> Result<BigInt> {
+            let value = cx.constant_value_of(id, env);
+            match value.get_int() {
+                Some(v) => Ok(v.clone()),
+                None => {
+                    if !value.is_error() {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` is not a constant integer",
+                                cx.span(id).extract()
+                            ))
+                            .span(cx.span(id))
+                            .add_note("Distributions must consist of constant values and weights"),
+                        );
+                    }
+                    Err(())
+                }
+            }
+        };
+        let mut dist = vec![];
+        for item in items {
+            let (lo, hi) = match item.range.value {
+                hir::InsideRange::Single(id) => eval(id).map(|x| (x.clone(), x))?,
+                hir::InsideRange::Range(lo, hi) => (eval(lo)?, eval(hi)?),
+            };
+            let (kind, weight) = match item.weight {
+                Some((kind, weight)) => {
+                    let value = eval(weight)?;
+                    if value < BigInt::zero() {
+                        cx.emit(
+                            DiagBuilder2::error(format!("negative weight `{}`", value))
+                                .span(cx.span(weight)),
+                        );
+                        return Err(());
+                    }
+                    (kind, value)
+                }
+                None => (ast::DistWeight::PerValue, BigInt::one()),
+            };
+            dist.push((lo, hi, kind, weight));
+        }
+        Ok(dist)
+    }
+
+    /// Determine the random variables listed in a `solve ... before`.
+    fn solve_vars(&self, exprs: &[NodeId], env: ParamEnv) -> Result<Vec<usize>> {
+        let cx = self.cx;
+        let mut vars = vec![];
+        for &expr_id in exprs {
+            match self.match_var(expr_id, env) {
+                Some((var, None)) if !self.vars[var].cyclic => vars.push(var),
+                Some((var, None)) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`randc` variable `{}` cannot be ordered by `solve ... before`",
+                            self.vars[var].name
+                        ))
+                        .span(cx.span(expr_id)),
+                    );
+                    return Err(());
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a random variable",
+                            cx.span(expr_id).extract()
+                        ))
+                        .span(cx.span(expr_id))
+                        .add_note("`solve ... before` can only order random variables"),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        Ok(vars)
+    }
+
+    /// Determine the environments for each iteration of a `foreach`
+    /// constraint, with the loop variables bound to the indices.
+    fn unroll_foreach(
+        &self,
+        array: NodeId,
+        indices: &[(usize, NodeId)],
+        env: ParamEnv,
+    ) -> Result<Vec<ParamEnv>> {
+        let cx = self.cx;
+        let ty = cx.type_of(array, env)?;
+        let dims: Vec<_> = ty.unpacked_dims().collect();
+        let mut envs = vec![env];
+        for &(dim, index) in indices {
+            let range = match dims.get(dim) {
+                Some(dim) => match (dim.get_range(), dim.get_size()) {
+                    (Some(r), _) => r.low()..r.high() + 1,
+                    (None, Some(size)) => 0..size as isize,
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "unsupported: `foreach` constraint over `{}` of type `{}`",
+                                cx.span(array).extract(),
+                                ty
+                            ))
+                            .span(cx.span(array))
+                            .add_note("Only fixed-size arrays can be iterated over"),
+                        );
+                        return Err(());
+                    }
+                },
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` has no dimension {}",
+                            cx.span(array).extract(),
+                            dim + 1
+                        ))
+                        .span(cx.span(index)),
+                    );
+                    return Err(());
+                }
+            };
+            let index_ty = cx.type_of(index, env)?;
+            let mut next = vec![];
+            for env in envs {
+                for i in range.clone() {
+                    let mut data = cx.param_env_data(env).clone();
+                    let value = value::make_int(index_ty, BigInt::from(i));
+                    data.set_value(index, cx.intern_value(value));
+                    next.push(cx.intern_param_env(data));
+                }
+            }
+            envs = next;
+        }
+        Ok(envs)
+    }
+
+    /// Lower constraint items that are checked after the variables are drawn.
+    fn lower_checks(&self, items: &'a [hir::ConstraintItem], env: ParamEnv) -> Result<Vec<Check>> {
+        let cx = self.cx;
+        let mut checks = vec![];
+        for item in items {
+            let kind = match *item {
+                hir::ConstraintItem::Expr(expr_id) => CheckKind::Expr(expr_id),
+                hir::ConstraintItem::Implication(cond, ref items) => {
+                    CheckKind::Implication(cond, self.lower_checks(items, env)?)
+                }
+                hir::ConstraintItem::If(cond, ref main, ref alt) => CheckKind::If(
+                    cond,
+                    self.lower_checks(main, env)?,
+                    self.lower_checks(alt, env)?,
+                ),
+                hir::ConstraintItem::Foreach(array, ref indices, ref items) => {
+                    for env in self.unroll_foreach(array, indices, env)? {
+                        checks.extend(self.lower_checks(items, env)?);
+                    }
+                    continue;
+                }
+                hir::ConstraintItem::Dist(expr_id, _) => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: conditional `dist` constraint")
+                            .span(cx.span(expr_id))
+                            .add_note("Distributions must hold unconditionally"),
+                    );
+                    return Err(());
+                }
+                hir::ConstraintItem::SolveBefore(ref before, _) => {
+                    cx.emit(
+                        DiagBuilder2::error("`solve ... before` cannot be conditional")
+                            .span(cx.span(before[0])),
+                    );
+                    return Err(());
+                }
+            };
+            checks.push(Check { env, kind });
+        }
+        Ok(checks)
+    }
+
+    /// Determine the random variables a check refers to.
+    fn check_vars(&self, check: &Check, into: &mut Vec<usize>) {
+        let mut exprs = vec![];
+        match check.kind {
+            CheckKind::Expr(id) => exprs.push(id),
+            CheckKind::Implication(cond, ref checks) => {
+                exprs.push(cond);
+                for check in checks {
+                    self.check_vars(check, into);
+                }
+            }
+            CheckKind::If(cond, ref main, ref alt) => {
+                exprs.push(cond);
+                for check in main.iter().chain(alt.iter()) {
+                    self.check_vars(check, into);
+                }
+            }
+        }
+        for id in exprs {
+            let mut collector = IdentCollector {
+                cx: self.cx,
+                env: check.env,
+                decls: vec![],
+            };
+            self.cx.ast_for_id(id).accept(&mut collector);
+            into.extend(
+                collector
+                    .decls
+                    .into_iter()
+                    .flat_map(|d| self.vars.iter().position(|v| v.decl == d)),
+            );
+        }
+    }
+
+    /// Compute the distribution of each variable and order the variables into
+    /// stages.
+    fn finish(mut self, span: Span) -> Result<RandomizeModel<'a>> {
+        let cx = self.cx;
+
+        // Combine the domains and distributions.
+        narrow_domains(&mut self.domains, &self.relations);
+        let mut infeasible = false;
+        for (i, var) in self.vars.iter_mut().enumerate() {
+            for (domain, dist) in self.domains[i].iter().zip(self.dists[i].iter()) {
+                let items = match dist {
+                    Some((_, items)) => items.clone(),
+                    None => vec![],
+                };
+                let dist = make_distribution(cx, var, domain, &items, span)?;
+                infeasible |= dist.entries.is_empty();
+                var.dists.push(dist);
+            }
+        }
+        if infeasible {
+            cx.emit(
+                DiagBuilder2::lint(
+                    &UNSATISFIABLE_CONSTRAINT,
+                    "constraints can never be satisfied",
+                )
+                .span(span)
+                .add_note("The call to `randomize()` always fails"),
+            );
+        }
+
+        // Assign each variable to a stage such that it is drawn after all
+        // variables that are solved before it.
+        let num_vars = self.vars.len();
+        let mut levels = vec![0; num_vars];
+        let mut iterations = 0;
+        loop {
+            let mut changed = false;
+            for &(before, after) in &self.order {
+                if levels[after] <= levels[before] {
+                    levels[after] = levels[before] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+            iterations += 1;
+            if iterations > num_vars {
+                cx.emit(
+                    DiagBuilder2::error("circular `solve ... before` constraints")
+                        .span(span)
+                        .add_note("The variables cannot be ordered"),
+                );
+                return Err(());
+            }
+        }
+        let num_stages = levels.iter().cloned().max().unwrap_or(0) + 1;
+        let ordered: Vec<usize> = self.order.iter().flat_map(|&(a, b)| vec![a, b]).collect();
+        for (i, level) in levels.iter_mut().enumerate() {
+            if !ordered.contains(&i) {
+                *level = num_stages - 1;
+            }
+        }
+        let mut stages: Vec<RandStage> = (0..num_stages).map(|_| Default::default()).collect();
+        for (i, &level) in levels.iter().enumerate() {
+            stages[level].vars.push(i);
+        }
+
+        // Check each constraint as soon as all variables it refers to are
+        // drawn.
+        for check in std::mem::replace(&mut self.checks, vec![]) {
+            let mut vars = vec![];
+            self.check_vars(&check, &mut vars);
+            let level = vars.into_iter().map(|v| levels[v]).max().unwrap_or(0);
+            stages[level].checks.push(check);
+        }
+
+        Ok(RandomizeModel {
+            vars: self.vars,
+            stages,
+            infeasible,
+        })
+    }
+}
+
+/// Narrow the domains of random variables according to the comparisons
+/// between them.
+///
+/// For `a < b`, the values of `a` must be below the largest value of `b`, and
+/// the values of `b` above the smallest value of `a`. The bounds are narrowed
+/// until they no longer change, or for at most `NARROW_PASSES` passes.
+fn narrow_domains(domains: &mut [Vec<Vec<(BigInt, BigInt)>>], relations: &[Relation]) {
+    let bounds = |ranges: &[(BigInt, BigInt)]| match (ranges.first(), ranges.last()) {
+        (Some((lo, _)), Some((_, hi))) => Some((lo.clone(), hi.clone())),
+        _ => None,
+    };
+    for _ in 0..NARROW_PASSES {
+        let mut changed = false;
+        for rel in relations {
+            let (lhs, rhs) = match (
+                bounds(&domains[rel.lhs.0][rel.lhs.1]),
+                bounds(&domains[rel.rhs.0][rel.rhs.1]),
+            ) {
+                (Some(lhs), Some(rhs)) => (lhs, rhs),
+                _ => continue,
+            };
+            let eq = rel.op == hir::BinaryOp::Eq;
+            let offset = match rel.op {
+                hir::BinaryOp::Lt => BigInt::one(),
+                _ => BigInt::zero(),
+            };
+            let lhs_range = (
+                if eq {
+                    (&lhs.0).max(&rhs.0).clone()
+                } else {
+                    lhs.0.clone()
+                },
+                (&lhs.1).min(&(&rhs.1 - &offset)).clone(),
+            );
+            let rhs_range = (
+                (&rhs.0).max(&(&lhs.0 + &offset)).clone(),
+                if eq {
+                    (&rhs.1).min(&lhs.1).clone()
+                } else {
+                    rhs.1.clone()
+                },
+            );
+            for &((var, elem), ref range) in &[(rel.lhs, lhs_range), (rel.rhs, rhs_range)] {
+                let domain = &mut domains[var][elem];
+                let narrowed = intersect_ranges(domain, std::slice::from_ref(range));
+                if narrowed != *domain {
+                    *domain = narrowed;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Compute the distribution of a random variable from its domain and `dist`
+/// constraint.
+fn make_distribution<'a>(
+    cx: &impl Context<'a>,
+    var: &RandVar<'a>,
+    domain: &[(BigInt, BigInt)],
+    items: &[(BigInt, BigInt, ast::DistWeight, BigInt)],
+    span: Span,
+) -> Result<Distribution> {
+    // Without a `dist` constraint, all values are equally likely.
+    let mut groups = vec![];
+    if items.is_empty() {
+        groups.push((BigInt::one(), domain.to_vec()));
+    }
+    for (lo, hi, kind, weight) in items {
+        let ranges = intersect_ranges(domain, &[(lo.clone(), hi.clone())]);
+        let size = ranges_size(&ranges);
+        let weight = match kind {
+            ast::DistWeight::PerValue => weight * &size,
+            ast::DistWeight::PerRange => weight.clone(),
+        };
+        groups.push((weight, ranges));
+    }
+
+    let mut entries = vec![];
+    let mut total = BigInt::zero();
+    for (weight, ranges) in groups {
+        let size = ranges_size(&ranges);
+        if weight.is_zero() || size.is_zero() {
+            continue;
+        }
+        let limit = BigInt::one() << 64;
+        let full = ranges == vec![type_range(var.sbv)];
+        if size > limit && !full {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: constraints on random variable `{}` wider than 64 bits",
+                    var.name
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+        if var.cyclic && size > BigInt::from(RANDC_MAX) {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: `randc` variable `{}` with more than {} values",
+                    var.name, RANDC_MAX
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+        total += &weight;
+        entries.push((weight, ranges, size));
+    }
+    let total = match total.to_u64() {
+        Some(x) => x,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "total weight of the distribution of `{}` is too large",
+                    var.name
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+    Ok(Distribution {
+        entries: entries
+            .into_iter()
+            .map(|(weight, ranges, size)| DistEntry {
+                weight: weight.to_u64().unwrap(),
+                ranges,
+                size,
+            })
+            .collect(),
+        total,
+    })
+}
+
+/// A visitor that collects the declarations identifiers refer to.
+struct IdentCollector<'c, C> {
+    cx: &'c C,
+    env: ParamEnv,
+    decls: Vec<NodeId>,
+}
+
+impl<'a, 'c, C: Context<'a>> ast::Visitor<'a> for IdentCollector<'c, C> {
+    fn pre_visit_expr(&mut self, node: &'a ast::Expr<'a>) -> bool {
+        match node.data {
+            ast::IdentExpr(..) => {
+                if let Ok(decl) = self.cx.resolve_node(node.id(), self.env) {
+                    self.decls.push(decl);
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Evaluate an expression to a constant integer, if it is constant.
+fn const_operand<'a>(cx: &impl Context<'a>, expr_id: NodeId, env: ParamEnv) -> Option<BigInt> {
+    let mir = cx.mir_rvalue(expr_id, env);
+    if mir.is_error() || !mir.is_const() {
+        return None;
+    }
+    cx.const_mir_rvalue(Ref(mir)).get_int().cloned()
+}
+
+/// Swap the operands of a comparison.
+fn mirror_op(op: hir::BinaryOp) -> hir::BinaryOp {
+    match op {
+        hir::BinaryOp::Lt => hir::BinaryOp::Gt,
+        hir::BinaryOp::Leq => hir::BinaryOp::Geq,
+        hir::BinaryOp::Gt => hir::BinaryOp::Lt,
+        hir::BinaryOp::Geq => hir::BinaryOp::Leq,
+        op => op,
+    }
+}
+
+/// Determine the smallest and largest value of a type.
+fn type_range(sbv: SbvType) -> (BigInt, BigInt) {
+    if sbv.sign == Sign::Signed {
+        let half = BigInt::one() << (sbv.size - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << sbv.size) - 1)
+    }
+}
+
+/// Map a value into the range of a type, wrapping around if needed.
+fn normalize(value: &BigInt, sbv: SbvType) -> BigInt {
+    let modulus = BigInt::one() << sbv.size;
+    let mut value = ((value % &modulus) + &modulus) % &modulus;
+    if sbv.sign == Sign::Signed && value >= (&modulus >> 1) {
+        value -= modulus;
+    }
+    value
+}
+
+/// Sort and merge inclusive value ranges, dropping empty ones.
+fn merge_ranges(mut ranges: Vec<(BigInt, BigInt)>) -> Vec<(BigInt, BigInt)> {
+    ranges.retain(|(lo, hi)| lo <= hi);
+    ranges.sort();
+    let mut merged: Vec<(BigInt, BigInt)> = vec![];
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some((_, prev)) if lo <= &*prev + 1 => {
+                if hi > *prev {
+                    *prev = hi;
+                }
+            }
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+/// Intersect two sets of sorted, disjoint value ranges.
+fn intersect_ranges(a: &[(BigInt, BigInt)], b: &[(BigInt, BigInt)]) -> Vec<(BigInt, BigInt)> {
+    let mut result = vec![];
+    for (alo, ahi) in a {
+        for (blo, bhi) in b {
+            let lo = alo.max(blo);
+            let hi = ahi.min(bhi);
+            if lo <= hi {
+                result.push((lo.clone(), hi.clone()));
+            }
+        }
+    }
+    merge_ranges(result)
+}
+
+/// Count the values in a set of disjoint value ranges.
+fn ranges_size(ranges: &[(BigInt, BigInt)]) -> BigInt {
+    ranges.iter().map(|(lo, hi)| hi - lo + 1).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(lo: i64, hi: i64) -> Vec<(BigInt, BigInt)> {
+        vec![(BigInt::from(lo), BigInt::from(hi))]
+    }
+
+    fn relation(lhs: usize, op: hir::BinaryOp, rhs: usize) -> Relation {
+        Relation {
+            lhs: (lhs, 0),
+            op,
+            rhs: (rhs, 0),
+        }
+    }
+
+    /// The domains of `int x, y` under `x < y; x > 0; y < 10;`, after the
+    /// comparisons against constants have been absorbed.
+    fn std_randomize_domains() -> Vec<Vec<Vec<(BigInt, BigInt)>>> {
+        vec![
+            vec![range(1, i32::max_value() as i64)],
+            vec![range(i32::min_value() as i64, 9)],
+        ]
+    }
+
+    #[test]
+    fn narrow_lt() {
+        let mut domains = std_randomize_domains();
+        narrow_domains(&mut domains, &[relation(0, hir::BinaryOp::Lt, 1)]);
+        assert_eq!(domains, vec![vec![range(1, 8)], vec![range(2, 9)]]);
+    }
+
+    #[test]
+    fn narrow_chain() {
+        // a <= b, b == c, c < d, with d in [0, 3]
+        let mut domains = vec![
+            vec![range(0, 100)],
+            vec![range(-50, 50)],
+            vec![range(1, 255)],
+            vec![range(0, 3)],
+        ];
+        narrow_domains(
+            &mut domains,
+            &[
+                relation(0, hir::BinaryOp::Leq, 1),
+                relation(1, hir::BinaryOp::Eq, 2),
+                relation(2, hir::BinaryOp::Lt, 3),
+            ],
+        );
+        assert_eq!(
+            domains,
+            vec![
+                vec![range(0, 2)],
+                vec![range(1, 2)],
+                vec![range(1, 2)],
+                vec![range(2, 3)],
+            ]
+        );
+    }
+
+    #[test]
+    fn narrow_infeasible() {
+        let mut domains = vec![vec![range(5, 10)], vec![range(0, 5)]];
+        narrow_domains(&mut domains, &[relation(0, hir::BinaryOp::Lt, 1)]);
+        assert!(domains[0][0].is_empty() && domains[1][0].is_empty());
+    }
+
+    /// Run the draw-and-check loop that the code generated for `randomize()`
+    /// executes, on the narrowed domains of `std::randomize(x, y) with { x <
+    /// y; x > 0; y < 10; }`. Every call must succeed within the attempt limit
+    /// of the generated code, and yield values that satisfy the constraints.
+    #[test]
+    fn randomize_lt_succeeds() {
+        let mut domains = std_randomize_domains();
+        narrow_domains(&mut domains, &[relation(0, hir::BinaryOp::Lt, 1)]);
+        let mut state: u64 = 1;
+        let mut draw = |ranges: &[(BigInt, BigInt)]| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let (lo, hi) = &ranges[0];
+            let size = (hi - lo + BigInt::one()).to_u64().unwrap();
+            lo + BigInt::from((state >> 33) % size)
+        };
+        for _ in 0..1000 {
+            let solution = (0..1000).find_map(|_| {
+                let x = draw(&domains[0][0]);
+                let y = draw(&domains[1][0]);
+                if x < y {
+                    Some((x, y))
+                } else {
+                    None
+                }
+            });
+            let (x, y) = solution.expect("randomize() failed");
+            assert!(x < y && x > BigInt::zero() && y < BigInt::from(10));
+        }
+    }
+}
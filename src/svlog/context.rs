@@ -27,6 +27,7 @@ use crate::{
     ast_map::{AstMap, AstNode},
     common::{arenas::Alloc, arenas::TypedArena, Session},
     hir::{self, HirNode},
+    naming::NamingRules,
     port_list::PortList,
     resolver::{Scope, StructDef},
     value::{Value, ValueData, ValueKind},
@@ -35,6 +36,7 @@ use crate::{
 use std::{
     cell::RefCell,
     collections::{BTreeSet, HashMap, HashSet},
+    rc::Rc,
     sync::Arc,
 };
 
@@ -113,7 +115,9 @@ impl<'gcx> GlobalContext<'gcx> {
         for file in &root.files {
             for item in &file.items {
                 match &item.data {
-                    ast::ItemData::ModuleDecl(ref n) => {
+                    ast::ItemData::ModuleDecl(ref n)
+                    | ast::ItemData::ProgramDecl(ref n)
+                    | ast::ItemData::CheckerDecl(ref n) => {
                         let id = self.map_ast(AstNode::Module(n));
                         self.modules.borrow_mut().insert(n.name.value, id);
                     }
@@ -359,6 +363,9 @@ pub struct GlobalTables<'t> {
     interned_values: RefCell<HashSet<Value<'t>>>,
     lowering_hints: RefCell<HashMap<NodeId, hir::Hint>>,
     interned_hir: RefCell<HashMap<NodeId, HirNode<'t>>>,
+    interned_hier_paths: RefCell<HashMap<&'t [NodeId], hir::HierPath>>,
+    hier_paths: RefCell<Vec<&'t [NodeId]>>,
+    naming_rules: RefCell<Option<Result<Rc<NamingRules>>>>,
 }
 
 /// The fundamental compiler context.
@@ -509,6 +516,22 @@ pub trait BaseContext<'gcx>:
         }
     }
 
+    /// Get the naming conventions configured for the `naming` lint.
+    ///
+    /// The conventions are compiled on first use, such that an invalid pattern
+    /// is only reported once.
+    fn naming_rules(&self) -> Result<Rc<NamingRules>>
+    where
+        Self: Sized,
+    {
+        if let Some(ref rules) = *self.tables().naming_rules.borrow() {
+            return rules.clone();
+        }
+        let rules = NamingRules::new(self).map(Rc::new);
+        *self.tables().naming_rules.borrow_mut() = Some(rules.clone());
+        rules
+    }
+
     /// Internalize an HIR node.
     fn intern_hir(&self, id: NodeId, hir: HirNode<'gcx>) {
         self.tables().interned_hir.borrow_mut().insert(id, hir);
@@ -564,6 +587,30 @@ pub trait BaseContext<'gcx>:
         self.tables().param_envs.borrow()[env.0 as usize]
     }
 
+    /// Internalize a path of instances through the hierarchy.
+    fn intern_hier_path(&self, insts: &[NodeId]) -> hir::HierPath {
+        if let Some(&x) = self.tables().interned_hier_paths.borrow().get(insts) {
+            return x;
+        }
+        let data = self.arena().alloc_ids(insts.iter().cloned());
+        let id = {
+            let mut vec = self.tables().hier_paths.borrow_mut();
+            let id = hir::HierPath(vec.len() as u32);
+            vec.push(data);
+            id
+        };
+        self.tables()
+            .interned_hier_paths
+            .borrow_mut()
+            .insert(data, id);
+        id
+    }
+
+    /// Get the instances along a [`HierPath`](hir::HierPath).
+    fn hier_path_insts(&self, path: hir::HierPath) -> &'gcx [NodeId] {
+        self.tables().hier_paths.borrow()[path.0 as usize]
+    }
+
     /// Get the default parameter environment.
     ///
     /// This is useful for instantiations without any parameter assignment, e.g.
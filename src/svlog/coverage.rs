@@ -0,0 +1,513 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Functional coverage.
+//!
+//! This module flattens a covergroup into a list of hit counters. Every bin of
+//! every coverpoint and every combination of bins of a cross gets its own
+//! counter. An instance of the covergroup is represented as an unpacked array
+//! of `int` counters, which sampling the covergroup increments.
+//!
+//! The counters are laid out as follows:
+//!
+//! - For each coverpoint in declaration order, the counters of its regular
+//!   bins, followed by the counters of its illegal bins, followed by the
+//!   counter of its `default` bin, if any.
+//! - For each cross in declaration order, the counters of all combinations of
+//!   the regular bins of the crossed coverpoints, with the last coverpoint
+//!   varying fastest.
+//!
+//! See `docs/coverage.md` for a description of the coverage map that can be
+//! emitted alongside the generated code.
+
+use crate::crate_prelude::*;
+use crate::{
+    hir::{self, HirNode},
+    ty::{IntAtomType, PackedType, Sign, UnpackedDim, UnpackedType},
+};
+use num::{BigInt, One, ToPrimitive, Zero};
+use std::{fmt, sync::Arc};
+
+/// The maximum number of automatically created bins per coverpoint.
+const AUTO_BIN_MAX: usize = 64;
+
+/// The maximum number of bins a `b[]` declaration may expand to.
+const ARRAY_BIN_MAX: usize = 4096;
+
+/// The flattened counter layout of a covergroup.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CoverModel<'a> {
+    /// The name of the covergroup.
+    pub name: Name,
+    /// The coverpoints.
+    pub points: Vec<CoverPointModel<'a>>,
+    /// The crosses.
+    pub crosses: Vec<CoverCrossModel>,
+    /// The total number of counters.
+    pub num_counters: usize,
+}
+
+/// The bins of a single coverpoint.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CoverPointModel<'a> {
+    /// The HIR node of the coverpoint.
+    pub id: NodeId,
+    /// The name of the coverpoint.
+    pub name: Name,
+    /// The type of the coverpoint expression.
+    pub ty: &'a UnpackedType<'a>,
+    /// The regular bins, which count towards coverage.
+    pub bins: Vec<CoverBinModel>,
+    /// The illegal bins. These are counted, but do not count towards coverage.
+    pub illegal: Vec<CoverBinModel>,
+    /// The value ranges excluded from coverage.
+    pub ignore: Vec<(BigInt, BigInt)>,
+    /// The bin that counts values not covered by any other bin.
+    pub default: Option<CoverBinModel>,
+}
+
+/// A single bin.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CoverBinModel {
+    /// The name of the bin.
+    pub name: String,
+    /// The inclusive value ranges which hit this bin.
+    pub ranges: Vec<(BigInt, BigInt)>,
+    /// The index of the bin's counter.
+    pub counter: usize,
+}
+
+/// The bins of a cross.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CoverCrossModel {
+    /// The name of the cross.
+    pub name: Name,
+    /// The indices of the crossed coverpoints into `CoverModel::points`.
+    pub points: Vec<usize>,
+    /// The index of the first counter of the cross.
+    pub offset: usize,
+    /// The number of counters of the cross.
+    pub size: usize,
+}
+
+impl<'a> CoverModel<'a> {
+    /// Get the type of the counter array that represents an instance of the
+    /// covergroup.
+    pub fn state_type(&self, cx: &impl Context<'a>) -> &'a UnpackedType<'a> {
+        UnpackedType::make_dims(
+            cx,
+            PackedType::make(cx, IntAtomType::Int),
+            vec![UnpackedDim::Array(self.num_counters)],
+        )
+    }
+
+    /// Get the names of the bins of a cross, in counter order.
+    pub fn cross_bin_names(&self, cross: &CoverCrossModel) -> Vec<String> {
+        let mut names = vec![String::new()];
+        for &point in &cross.points {
+            let point = &self.points[point];
+            names = names
+                .iter()
+                .flat_map(|prefix| {
+                    point.bins.iter().map(move |bin| {
+                        if prefix.is_empty() {
+                            format!("{}.{}", point.name, bin.name)
+                        } else {
+                            format!("{}, {}.{}", prefix, point.name, bin.name)
+                        }
+                    })
+                })
+                .collect();
+        }
+        names
+    }
+}
+
+impl fmt::Display for CoverModel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fmt_ranges = |ranges: &[(BigInt, BigInt)]| {
+            let ranges: Vec<_> = ranges
+                .iter()
+                .map(|(lo, hi)| {
+                    if lo == hi {
+                        format!("{}", lo)
+                    } else {
+                        format!("[{}:{}]", lo, hi)
+                    }
+                })
+                .collect();
+            format!("{{{}}}", ranges.join(", "))
+        };
+        for point in &self.points {
+            writeln!(f, "  coverpoint {}", point.name)?;
+            for bin in &point.bins {
+                let ranges = fmt_ranges(&bin.ranges);
+                writeln!(f, "    [{}] bins {} = {}", bin.counter, bin.name, ranges)?;
+            }
+            for bin in &point.illegal {
+                let ranges = fmt_ranges(&bin.ranges);
+                writeln!(
+                    f,
+                    "    [{}] illegal_bins {} = {}",
+                    bin.counter, bin.name, ranges
+                )?;
+            }
+            if let Some(ref bin) = point.default {
+                writeln!(f, "    [{}] bins {} = default", bin.counter, bin.name)?;
+            }
+        }
+        for cross in &self.crosses {
+            writeln!(f, "  cross {}", cross.name)?;
+            for (i, name) in self.cross_bin_names(cross).into_iter().enumerate() {
+                writeln!(f, "    [{}] <{}>", cross.offset + i, name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compute the counter layout of a covergroup.
+#[moore_derive::query]
+pub(crate) fn cover_model<'a>(
+    cx: &impl Context<'a>,
+    Ref(ast): Ref<'a, ast::Covergroup<'a>>,
+    env: ParamEnv,
+) -> Result<Arc<CoverModel<'a>>> {
+    let hir = match cx.hir_of(ast.id())? {
+        HirNode::Covergroup(x) => x,
+        _ => unreachable!(),
+    };
+    let mut counter = 0;
+    let mut failed = false;
+
+    // Lay out the bins of each coverpoint.
+    let mut points = vec![];
+    for &point_id in &hir.points {
+        match lower_coverpoint(cx, point_id, env, &mut counter) {
+            Ok(x) => points.push(x),
+            Err(()) => failed = true,
+        }
+    }
+    if failed {
+        return Err(());
+    }
+
+    // Lay out the bins of each cross.
+    let mut crosses = vec![];
+    for cross in &hir.crosses {
+        let indices: Vec<usize> = cross
+            .points
+            .iter()
+            .map(|&id| points.iter().position(|p| p.id == id).unwrap())
+            .collect();
+        let size = indices.iter().map(|&i| points[i].bins.len()).product();
+        crosses.push(CoverCrossModel {
+            name: cross.name.value,
+            points: indices,
+            offset: counter,
+            size,
+        });
+        counter += size;
+    }
+
+    Ok(Arc::new(CoverModel {
+        name: hir.name.value,
+        points,
+        crosses,
+        num_counters: counter,
+    }))
+}
+
+/// Compute the bins of a coverpoint and assign counters to them.
+fn lower_coverpoint<'a>(
+    cx: &impl Context<'a>,
+    point_id: NodeId,
+    env: ParamEnv,
+    counter: &mut usize,
+) -> Result<CoverPointModel<'a>> {
+    let hir = match cx.hir_of(point_id)? {
+        HirNode::Coverpoint(x) => x,
+        _ => unreachable!(),
+    };
+    let ty = cx.type_of(hir.expr, env)?;
+    let sbv = match ty.get_simple_bit_vector() {
+        Some(x) => x,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "coverpoint `{}` must be of integral type; has type `{}`",
+                    hir.name, ty
+                ))
+                .span(cx.span(hir.expr)),
+            );
+            return Err(());
+        }
+    };
+    let (min, max): (BigInt, BigInt) = if sbv.sign == Sign::Signed {
+        let half = BigInt::one() << (sbv.size - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << sbv.size) - 1)
+    };
+
+    // Evaluate the value ranges of the explicit bins.
+    let eval = |id: NodeId| -> Result<BigInt> {
+        let value = cx.constant_value_of(id, env);
+        match value.get_int() {
+            Some(v) => Ok(v.clone()),
+            None => {
+                if !value.is_error() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "bin value `{}` is not a constant integer",
+                            cx.span(id).extract()
+                        ))
+                        .span(cx.span(id)),
+                    );
+                }
+                Err(())
+            }
+        }
+    };
+    // Values outside of the coverpoint's range can never be hit, so clamp the
+    // ranges accordingly.
+    let eval_ranges = |values: &[Spanned<hir::InsideRange>]| -> Result<Vec<(BigInt, BigInt)>> {
+        let mut ranges = vec![];
+        for v in values {
+            let (lo, hi) = match v.value {
+                hir::InsideRange::Single(id) => eval(id).map(|x| (x.clone(), x))?,
+                hir::InsideRange::Range(lo, hi) => (eval(lo)?, eval(hi)?),
+            };
+            let lo = lo.max(min.clone());
+            let hi = hi.min(max.clone());
+            if lo <= hi {
+                ranges.push((lo, hi));
+            }
+        }
+        Ok(ranges)
+    };
+
+    let mut bins = vec![];
+    let mut illegal = vec![];
+    let mut ignore = vec![];
+    let mut default = None;
+    for decl in &hir.bins {
+        if let Some(iff) = decl.iff {
+            cx.emit(DiagBuilder2::error("unsupported: `iff` on bins").span(cx.span(iff)));
+            return Err(());
+        }
+        let values = match decl.values {
+            Some(ref values) => eval_ranges(values)?,
+            None => {
+                if decl.kind != ast::CoverBinsKind::Bins || decl.array.is_some() {
+                    cx.emit(
+                        DiagBuilder2::error("`default` is only supported for a single regular bin")
+                            .span(decl.span),
+                    );
+                    return Err(());
+                }
+                default = Some(CoverBinModel {
+                    name: decl.name.value.to_string(),
+                    ranges: vec![],
+                    counter: 0,
+                });
+                continue;
+            }
+        };
+        let expanded = match decl.kind {
+            ast::CoverBinsKind::Ignore => {
+                ignore.extend(values);
+                continue;
+            }
+            ast::CoverBinsKind::Illegal => {
+                illegal.push(CoverBinModel {
+                    name: decl.name.value.to_string(),
+                    ranges: values,
+                    counter: 0,
+                });
+                continue;
+            }
+            ast::CoverBinsKind::Bins => match decl.array {
+                None => vec![CoverBinModel {
+                    name: decl.name.value.to_string(),
+                    ranges: values,
+                    counter: 0,
+                }],
+                Some(size) => {
+                    let size = match size {
+                        Some(size) => Some(eval(size)?.to_usize().unwrap_or(0)),
+                        None => None,
+                    };
+                    expand_array_bins(cx, decl, &values, size)?
+                }
+            },
+        };
+        bins.extend(expanded);
+    }
+
+    // Create the automatic bins if the coverpoint has no explicit ones.
+    if bins.is_empty() && illegal.is_empty() && default.is_none() {
+        bins = auto_bins(cx, ty, env, &min, &max)?;
+    }
+
+    // Assign the counters.
+    for bin in bins
+        .iter_mut()
+        .chain(illegal.iter_mut())
+        .chain(default.iter_mut())
+    {
+        bin.counter = *counter;
+        *counter += 1;
+    }
+
+    Ok(CoverPointModel {
+        id: point_id,
+        name: hir.name.value,
+        ty,
+        bins,
+        illegal,
+        ignore,
+        default,
+    })
+}
+
+/// Expand a `b[]` or `b[N]` bins declaration into individual bins.
+///
+/// `b[]` creates a bin per value. `b[N]` distributes the values evenly across
+/// `N` bins, with the last bin receiving any leftover values.
+fn expand_array_bins<'a>(
+    cx: &impl Context<'a>,
+    decl: &hir::CoverBins,
+    values: &[(BigInt, BigInt)],
+    size: Option<usize>,
+) -> Result<Vec<CoverBinModel>> {
+    let total: BigInt = values
+        .iter()
+        .map(|(lo, hi)| {
+            if hi >= lo {
+                hi - lo + 1
+            } else {
+                BigInt::zero()
+            }
+        })
+        .sum();
+    let limit = size.unwrap_or(ARRAY_BIN_MAX);
+    if size.is_none() && total > BigInt::from(limit) {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`{}[]` would create {} bins; at most {} are supported",
+                decl.name, total, limit
+            ))
+            .span(decl.span),
+        );
+        return Err(());
+    }
+    if size == Some(0) {
+        cx.emit(DiagBuilder2::error("number of bins must be positive").span(decl.span));
+        return Err(());
+    }
+
+    // Walk the values, splitting the ranges at bin boundaries.
+    let count = match size {
+        Some(size) => BigInt::from(size).min(total.clone()),
+        None => total.clone(),
+    };
+    if count.is_zero() {
+        return Ok(vec![]);
+    }
+    let last: BigInt = &count - 1;
+    let per_bin = &total / &count;
+    let mut bins: Vec<CoverBinModel> = vec![];
+    let mut index = BigInt::zero();
+    for (lo, hi) in values {
+        let mut v = lo.clone();
+        while &v <= hi {
+            let bin = (&index / &per_bin).min(last.clone());
+            let remaining: BigInt = hi - &v + 1;
+            let take = if bin == last {
+                remaining
+            } else {
+                remaining.min((&bin + 1) * &per_bin - &index)
+            };
+            let bin = bin.to_usize().unwrap();
+            if bins.len() <= bin {
+                let name = match size {
+                    Some(_) => format!("{}[{}]", decl.name, bin),
+                    None => format!("{}[{}]", decl.name, v),
+                };
+                bins.push(CoverBinModel {
+                    name,
+                    ranges: vec![],
+                    counter: 0,
+                });
+            }
+            let end: BigInt = &v + &take - 1;
+            let ranges = &mut bins[bin].ranges;
+            match ranges.last_mut() {
+                Some((_, prev)) if *prev == &v - 1 => *prev = end.clone(),
+                _ => ranges.push((v.clone(), end.clone())),
+            }
+            v = end + 1;
+            index += take;
+        }
+    }
+    Ok(bins)
+}
+
+/// Create the automatic bins for a coverpoint without explicit bins.
+///
+/// Enums get one bin per variant. Other types get one bin per value, or
+/// `AUTO_BIN_MAX` bins that evenly divide the value range if there are more
+/// values than that.
+fn auto_bins<'a>(
+    cx: &impl Context<'a>,
+    ty: &'a UnpackedType<'a>,
+    env: ParamEnv,
+    min: &BigInt,
+    max: &BigInt,
+) -> Result<Vec<CoverBinModel>> {
+    if let Some(enm) = ty.get_enum() {
+        let mut bins = vec![];
+        for &(name, ast) in &enm.variants {
+            let value = cx.constant_value_of(ast.id(), env);
+            let value = value.get_int().ok_or(())?.clone();
+            bins.push(CoverBinModel {
+                name: name.value.to_string(),
+                ranges: vec![(value.clone(), value)],
+                counter: 0,
+            });
+        }
+        return Ok(bins);
+    }
+
+    let total = max - min + 1;
+    if total <= BigInt::from(AUTO_BIN_MAX) {
+        let mut bins = vec![];
+        let mut v = min.clone();
+        while &v <= max {
+            bins.push(CoverBinModel {
+                name: format!("auto[{}]", v),
+                ranges: vec![(v.clone(), v.clone())],
+                counter: 0,
+            });
+            v += 1;
+        }
+        Ok(bins)
+    } else {
+        let width = &total / AUTO_BIN_MAX;
+        Ok((0..AUTO_BIN_MAX)
+            .map(|i| {
+                let lo = min + &width * i;
+                let hi = if i + 1 == AUTO_BIN_MAX {
+                    max.clone()
+                } else {
+                    &lo + &width - 1
+                };
+                CoverBinModel {
+                    name: format!("auto[{}:{}]", lo, hi),
+                    ranges: vec![(lo, hi)],
+                    counter: 0,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,1156 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Driver analysis.
+//!
+//! While a module is elaborated, the code generator records the continuous
+//! assignments, procedures, primitives, and port connections that drive and
+//! read each signal declared in the module. The checks in this module then
+//! report variables with conflicting drivers (see IEEE 1800-2017 §6.5),
+//! combinational loops, and optionally warn about nets that are never driven
+//! and signals that are never read.
+
+use crate::crate_prelude::*;
+use crate::{
+    hir::{AccessedNode, HirNode, Visitor},
+    mir::WalkVisitor as _,
+    port_list::IntPort,
+    ty::UnpackedType,
+    value::ValueKind,
+};
+use moore_common::lint::{COMB_LOOP, MULTIPLE_DRIVERS, UNDRIVEN, UNREAD};
+use num::ToPrimitive;
+use std::collections::{HashMap, HashSet};
+
+/// The drivers and readers of the signals declared in a module.
+///
+/// Signals declared in the body of a generate loop are elaborated once per
+/// iteration. Each of these instances is tracked separately, identified by
+/// the parameter environment of the iteration.
+#[derive(Default)]
+pub(crate) struct DriverTable {
+    /// The parameter environment of the most recently elaborated instance of
+    /// each signal.
+    scopes: HashMap<NodeId, ParamEnv>,
+    /// The signals, in the order in which they were elaborated.
+    signals: Vec<Signal>,
+    /// The drivers of each signal.
+    drivers: HashMap<(NodeId, ParamEnv), Vec<Driver>>,
+    /// The signals that are read.
+    read: HashSet<(NodeId, ParamEnv)>,
+    /// The signals that are accessed from outside the module through
+    /// hierarchical references.
+    external: HashSet<(NodeId, ParamEnv)>,
+    /// The drivers which propagate changes of the signals they read to the
+    /// signals they drive without any delay.
+    comb: Vec<CombDriver>,
+}
+
+/// A signal declared in a module.
+struct Signal {
+    id: NodeId,
+    env: ParamEnv,
+    /// Whether the signal is a variable or a net.
+    kind: ast::VarKind,
+    /// The direction of the signal, if it is a port.
+    dir: Option<ast::PortDir>,
+    /// Whether the signal carries a value that is expected to be driven and
+    /// read, as opposed to an event, class handle, or similar.
+    is_value: bool,
+}
+
+/// A driver of a signal.
+struct Driver {
+    kind: DriverKind,
+    /// A description of the driver, such as "continuous assignment".
+    desc: String,
+    /// The location where the driver assigns the signal.
+    span: Span,
+    /// The parts of the signal being driven.
+    parts: Vec<Vec<Select>>,
+}
+
+/// A driver without any delay between the signals it reads and the signals
+/// it drives.
+struct CombDriver {
+    /// A description of the driver, such as "continuous assignment".
+    desc: String,
+    /// The location of the driver.
+    span: Span,
+    kind: CombKind,
+    /// The parts of the signals being driven.
+    writes: Vec<((NodeId, ParamEnv), Vec<Select>)>,
+    /// The parts of the signals being read.
+    reads: Vec<((NodeId, ParamEnv), Vec<Select>)>,
+}
+
+/// The different kinds of drivers without delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CombKind {
+    /// An assignment that copies a signal without any logic, like a wire.
+    Wire,
+    /// An assignment or primitive computing a value from the signals it
+    /// reads.
+    Logic,
+    /// A procedure, which may read the signals it drives without forming a
+    /// loop.
+    Procedure,
+}
+
+/// The different kinds of drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DriverKind {
+    /// A continuous assignment, primitive output, or port connection.
+    Continuous,
+    /// A procedure.
+    Procedure(ast::ProcedureKind),
+}
+
+/// A constant select into a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Select {
+    /// The elements from the first index up to but excluding the second.
+    Range(isize, isize),
+    /// A struct field.
+    Field(usize),
+}
+
+impl DriverTable {
+    /// Add a variable or net declaration.
+    ///
+    /// The initial value of a net declaration drives the net.
+    pub fn add_decl<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        hir: &'a hir::VarDecl,
+        ty: &'a UnpackedType<'a>,
+        env: ParamEnv,
+    ) -> Result<()> {
+        self.add_signal(hir.id, env, hir.kind, None, ty);
+        if let Some(init) = hir.init {
+            self.add_reads(&cx.accessed_nodes(init, env)?.read);
+            if hir.kind.is_net() {
+                self.add_driver(
+                    hir.id,
+                    Driver {
+                        kind: DriverKind::Continuous,
+                        desc: "net declaration assignment".to_string(),
+                        span: cx.span(init),
+                        parts: vec![vec![]],
+                    },
+                );
+                self.add_comb(
+                    "net declaration assignment".to_string(),
+                    cx.span(init),
+                    comb_kind(cx, init, env),
+                    vec![(hir.id, vec![])],
+                    read_parts(cx, init, env),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a port of the module.
+    pub fn add_port<'a>(&mut self, port: &IntPort<'a>, ty: &'a UnpackedType<'a>, env: ParamEnv) {
+        self.add_signal(port.id, env, port.kind, Some(port.dir), ty);
+    }
+
+    fn add_signal(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+        kind: ast::VarKind,
+        dir: Option<ast::PortDir>,
+        ty: &UnpackedType,
+    ) {
+        self.scopes.insert(id, env);
+        self.signals.push(Signal {
+            id,
+            env,
+            kind,
+            dir,
+            is_value: !ty.is_event()
+                && !ty.is_string()
+                && ty.get_class().is_none()
+                && ty.get_covergroup().is_none()
+                && ty.get_virtual_interface().is_none(),
+        });
+    }
+
+    /// Add a continuous assignment.
+    pub fn add_assign<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        hir: &'a hir::Assign,
+        env: ParamEnv,
+    ) -> Result<()> {
+        self.add_reads(&cx.accessed_nodes(hir.id, env)?.read);
+        self.add_continuous(cx, hir.lhs, env, "continuous assignment".to_string());
+        let mut writes = vec![];
+        driven_parts(cx, cx.mir_lvalue(hir.lhs, env), &mut writes);
+        self.add_comb(
+            "continuous assignment".to_string(),
+            hir.span,
+            comb_kind(cx, hir.rhs, env),
+            writes,
+            read_parts(cx, hir.id, env),
+        );
+        Ok(())
+    }
+
+    /// Add a gate or switch primitive.
+    pub fn add_gate<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        hir: &'a hir::Gate,
+        env: ParamEnv,
+    ) -> Result<()> {
+        self.add_reads(&cx.accessed_nodes(hir.id, env)?.read);
+        for &output in &hir.outputs {
+            self.add_continuous(cx, output, env, hir.desc_full());
+        }
+        // Primitives with a delay do not form combinational loops.
+        if hir.delay.is_none() {
+            let mut writes = vec![];
+            for &output in &hir.outputs {
+                driven_parts(cx, cx.mir_lvalue(output, env), &mut writes);
+            }
+            self.add_comb(
+                hir.desc_full(),
+                hir.span,
+                CombKind::Logic,
+                writes,
+                read_parts(cx, hir.id, env),
+            );
+        }
+        Ok(())
+    }
+
+    /// Add an expression connected to a port of an instance.
+    ///
+    /// Expressions connected to outputs are driven by the instance, while
+    /// expressions connected to inputs are read.
+    pub fn add_port_connection<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        expr_id: NodeId,
+        env: ParamEnv,
+        output: Option<String>,
+    ) -> Result<()> {
+        match output {
+            Some(desc) => self.add_continuous(cx, expr_id, env, desc),
+            None => self.add_reads(&cx.accessed_nodes(expr_id, env)?.read),
+        }
+        Ok(())
+    }
+
+    /// Add a procedure.
+    pub fn add_procedure<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        proc_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<()> {
+        let hir = match cx.hir_of(proc_id)? {
+            HirNode::Proc(x) => x,
+            _ => unreachable!(),
+        };
+        let acc = cx.accessed_nodes(hir.stmt, env)?;
+        self.add_reads(&acc.read);
+
+        // Variables written by other means than an assignment statement, such
+        // as the task calls inlined into the procedure, count as written in
+        // their entirety.
+        let mut writes = ProcedureWrites {
+            cx,
+            env,
+            writes: HashMap::new(),
+        };
+        writes.visit_node_with_id(hir.stmt, false);
+
+        // In a combinational procedure, each variable only depends on the
+        // signals that flow into its assignments.
+        let mut deps = match is_combinational(cx, hir) {
+            true => Some(procedure_deps(cx, hir, env)),
+            false => None,
+        };
+
+        for node in &acc.written {
+            if let AccessedNode::Regular(id) = *node {
+                let (span, parts) = writes
+                    .writes
+                    .remove(&id)
+                    .unwrap_or_else(|| (hir.span, vec![vec![]]));
+                if let Some(ref mut deps) = deps {
+                    self.add_comb(
+                        hir.desc().to_string(),
+                        span,
+                        CombKind::Procedure,
+                        parts.iter().map(|part| (id, part.clone())).collect(),
+                        deps.remove(&id).unwrap_or_default(),
+                    );
+                }
+                self.add_driver(
+                    id,
+                    Driver {
+                        kind: DriverKind::Procedure(hir.kind),
+                        desc: hir.desc().to_string(),
+                        span,
+                        parts,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark signals as read.
+    pub fn add_reads<'b>(&mut self, nodes: impl IntoIterator<Item = &'b AccessedNode>) {
+        for node in nodes {
+            if let AccessedNode::Regular(id) = *node {
+                if let Some(&env) = self.scopes.get(&id) {
+                    self.read.insert((id, env));
+                }
+            }
+        }
+    }
+
+    /// Mark a signal as accessed from outside the module.
+    pub fn add_external(&mut self, id: NodeId) {
+        if let Some(&env) = self.scopes.get(&id) {
+            self.external.insert((id, env));
+        }
+    }
+
+    /// Determine the signals that depend on another signal without any delay,
+    /// as triples of driven signal, read signal, and whether the driven
+    /// signal is computed by logic rather than copied.
+    pub fn comb_edges(&self) -> Vec<(NodeId, NodeId, bool)> {
+        let mut edges = vec![];
+        for driver in &self.comb {
+            for &((write, _), _) in &driver.writes {
+                for &((read, _), _) in &driver.reads {
+                    edges.push((write, read, driver.kind != CombKind::Wire));
+                }
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    /// Add the signals driven by a continuous lvalue.
+    fn add_continuous<'a>(
+        &mut self,
+        cx: &impl Context<'a>,
+        lvalue_id: NodeId,
+        env: ParamEnv,
+        desc: String,
+    ) {
+        let lv = cx.mir_lvalue(lvalue_id, env);
+        let mut parts = vec![];
+        driven_parts(cx, lv, &mut parts);
+        for (id, part) in parts {
+            self.add_driver(
+                id,
+                Driver {
+                    kind: DriverKind::Continuous,
+                    desc: desc.clone(),
+                    span: lv.span,
+                    parts: vec![part],
+                },
+            );
+        }
+    }
+
+    /// Add a driver without delay, which drives and reads some parts of
+    /// signals.
+    fn add_comb(
+        &mut self,
+        desc: String,
+        span: Span,
+        kind: CombKind,
+        writes: Vec<(NodeId, Vec<Select>)>,
+        reads: Vec<(NodeId, Vec<Select>)>,
+    ) {
+        let scopes = &self.scopes;
+        let resolve =
+            |(id, part): (NodeId, Vec<Select>)| scopes.get(&id).map(|&env| ((id, env), part));
+        let writes: Vec<_> = writes.into_iter().filter_map(resolve).collect();
+        if writes.is_empty() {
+            return;
+        }
+        self.comb.push(CombDriver {
+            desc,
+            span,
+            kind,
+            writes,
+            reads: reads.into_iter().filter_map(resolve).collect(),
+        });
+    }
+
+    fn add_driver(&mut self, id: NodeId, driver: Driver) {
+        if let Some(&env) = self.scopes.get(&id) {
+            self.drivers.entry((id, env)).or_default().push(driver);
+        }
+    }
+
+    /// Report signals with conflicting drivers, and warn about signals that
+    /// are never driven or never read.
+    pub fn check<'a>(&self, cx: &impl Context<'a>) -> Result<()> {
+        let mut failed = false;
+        let mut reported = HashSet::new();
+        for signal in &self.signals {
+            let key = (signal.id, signal.env);
+            let drivers = self.drivers.get(&key).map(|d| d.as_slice()).unwrap_or(&[]);
+            if !reported.insert(signal.id) {
+                continue;
+            }
+            if !signal.kind.is_net() {
+                check_conflicts(cx, signal.id, drivers)?;
+                failed |= check_procedure_writers(cx, signal.id, drivers)?;
+            }
+            if !signal.is_value || self.external.contains(&key) {
+                continue;
+            }
+
+            // Nets which are not driven anywhere remain at their default
+            // value. Pulled nets and supply nets have a defined value.
+            let undriven = match signal.kind {
+                ast::VarKind::Net {
+                    ty: ast::NetType::Supply0,
+                    ..
+                }
+                | ast::VarKind::Net {
+                    ty: ast::NetType::Supply1,
+                    ..
+                }
+                | ast::VarKind::Net {
+                    ty: ast::NetType::Tri0,
+                    ..
+                }
+                | ast::VarKind::Net {
+                    ty: ast::NetType::Tri1,
+                    ..
+                } => false,
+                ast::VarKind::Net { .. } => drivers.is_empty(),
+                ast::VarKind::Var => false,
+            };
+            let undriven = undriven
+                && match signal.dir {
+                    None | Some(ast::PortDir::Output) => true,
+                    _ => false,
+                };
+            if undriven && cx.sess().has_lint(&UNDRIVEN) {
+                let hir = cx.hir_of(signal.id)?;
+                cx.emit(
+                    DiagBuilder2::lint(&UNDRIVEN, format!("{} is never driven", hir.desc_full()))
+                        .span(hir.human_span()),
+                );
+            }
+
+            // Outputs are read by the parent module.
+            let unread = !self.read.contains(&key)
+                && match signal.dir {
+                    None | Some(ast::PortDir::Input) => true,
+                    _ => false,
+                };
+            if unread && cx.sess().has_lint(&UNREAD) {
+                let hir = cx.hir_of(signal.id)?;
+                cx.emit(
+                    DiagBuilder2::lint(&UNREAD, format!("{} is never read", hir.desc_full()))
+                        .span(hir.human_span()),
+                );
+            }
+        }
+        self.check_comb_loops(cx)?;
+        if failed {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Warn about combinational loops, where a signal depends on its own
+    /// value through a chain of drivers without any delay.
+    ///
+    /// Each strongly connected component of the graph of drivers is reported
+    /// once, by showing one of the loops through it.
+    fn check_comb_loops<'a>(&self, cx: &impl Context<'a>) -> Result<()> {
+        // Determine which drivers read the signals driven by each driver.
+        let mut writers: HashMap<(NodeId, ParamEnv), Vec<(usize, &[Select])>> = HashMap::new();
+        for (i, driver) in self.comb.iter().enumerate() {
+            for (key, part) in &driver.writes {
+                writers.entry(*key).or_default().push((i, part));
+            }
+        }
+        let mut edges: Vec<Vec<(usize, NodeId)>> = vec![vec![]; self.comb.len()];
+        for (j, driver) in self.comb.iter().enumerate() {
+            for (key, read) in &driver.reads {
+                for &(i, write) in writers.get(key).map(|w| w.as_slice()).unwrap_or(&[]) {
+                    if i == j && driver.kind == CombKind::Procedure {
+                        continue;
+                    }
+                    if overlaps(write, read) && !edges[i].iter().any(|&(k, _)| k == j) {
+                        edges[i].push((j, key.0));
+                    }
+                }
+            }
+        }
+
+        for scc in strongly_connected(&edges) {
+            let first = scc[0];
+            if scc.len() == 1 && !edges[first].iter().any(|&(j, _)| j == first) {
+                continue;
+            }
+            let cycle = find_cycle(&edges, &scc);
+            let mut signals = vec![];
+            for &(_, id) in &cycle {
+                let desc = cx.hir_of(id)?.desc_full();
+                if !signals.contains(&desc) {
+                    signals.push(desc);
+                }
+            }
+            let mut d = DiagBuilder2::lint(
+                &COMB_LOOP,
+                format!("combinational loop through {}", join_and(&signals)),
+            )
+            .span(self.comb[first].span);
+            for &(i, _) in &cycle[1..] {
+                d = d.add_note(format!("Continues through {} here:", self.comb[i].desc));
+                d = d.span(self.comb[i].span);
+            }
+            cx.emit(d.add_note(
+                "Signals on the loop depend on their own value without any delay, which may \
+                 prevent the simulation from advancing",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Determine whether an assigned expression copies a signal, or computes a
+/// value with logic.
+fn comb_kind<'a>(cx: &impl Context<'a>, expr_id: NodeId, env: ParamEnv) -> CombKind {
+    match static_read(cx, cx.mir_rvalue(expr_id, env)) {
+        Some(_) => CombKind::Wire,
+        None => CombKind::Logic,
+    }
+}
+
+/// Check whether a procedure updates the signals it drives as soon as the
+/// signals it reads change.
+fn is_combinational<'a>(cx: &impl Context<'a>, hir: &hir::Proc) -> bool {
+    match hir.kind {
+        ast::ProcedureKind::AlwaysComb | ast::ProcedureKind::AlwaysLatch => true,
+        ast::ProcedureKind::Always => match cx.hir_of(hir.stmt) {
+            Ok(HirNode::Stmt(hir::Stmt {
+                kind:
+                    hir::StmtKind::Timed {
+                        control: hir::TimingControl::ImplicitEvent,
+                        ..
+                    },
+                ..
+            })) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Determine the strongly connected components of a graph, given as the
+/// successors of each node.
+///
+/// This is Tarjan's algorithm. The nodes of each component are sorted.
+fn strongly_connected(edges: &[Vec<(usize, NodeId)>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<(usize, NodeId)>],
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.lowlink[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+            for &(w, _) in &self.edges[v] {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.lowlink[v] = std::cmp::min(self.lowlink[v], self.lowlink[w]);
+                    }
+                    Some(index) if self.on_stack[w] => {
+                        self.lowlink[v] = std::cmp::min(self.lowlink[v], index);
+                    }
+                    _ => (),
+                }
+            }
+            if Some(self.lowlink[v]) == self.index[v] {
+                let mut component = vec![];
+                loop {
+                    let w = self.stack.pop().unwrap();
+                    self.on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; edges.len()],
+        lowlink: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: vec![],
+        next: 0,
+        components: vec![],
+    };
+    for v in 0..edges.len() {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+    tarjan.components.sort();
+    tarjan.components
+}
+
+/// Find a shortest loop from the first node of a strongly connected component
+/// back to itself.
+///
+/// Returns each node on the loop, together with the signal through which it
+/// drives the next node.
+fn find_cycle(edges: &[Vec<(usize, NodeId)>], scc: &[usize]) -> Vec<(usize, NodeId)> {
+    let start = scc[0];
+    let mut pred: HashMap<usize, (usize, NodeId)> = HashMap::new();
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back(start);
+    'search: while let Some(v) = queue.pop_front() {
+        for &(w, signal) in &edges[v] {
+            if scc.binary_search(&w).is_err() || pred.contains_key(&w) {
+                continue;
+            }
+            pred.insert(w, (v, signal));
+            if w == start {
+                break 'search;
+            }
+            queue.push_back(w);
+        }
+    }
+    let mut cycle = vec![];
+    let mut v = start;
+    loop {
+        let (u, signal) = pred[&v];
+        cycle.push((u, signal));
+        v = u;
+        if v == start {
+            break;
+        }
+    }
+    cycle.reverse();
+    cycle
+}
+
+/// Join descriptions into an enumeration such as "`a`, `b`, and `c`".
+fn join_and(items: &[String]) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        2 => format!("{} and {}", items[0], items[1]),
+        n => format!("{}, and {}", items[..n - 1].join(", "), items[n - 1]),
+    }
+}
+
+/// Warn about a variable that is driven by a continuous assignment, primitive,
+/// or port connection, and by any other driver.
+fn check_conflicts<'a>(cx: &impl Context<'a>, id: NodeId, drivers: &[Driver]) -> Result<()> {
+    let conflicts = conflicting_drivers(drivers, |a, b| {
+        a.kind == DriverKind::Continuous || b.kind == DriverKind::Continuous
+    });
+    let (first, rest) = match conflicts.split_first() {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let mut d = DiagBuilder2::lint(
+        &MULTIPLE_DRIVERS,
+        format!("{} has multiple drivers", cx.hir_of(id)?.desc_full()),
+    )
+    .span(first.span);
+    for driver in rest {
+        d = d.add_note(format!("Also driven by {} here:", driver.desc));
+        d = d.span(driver.span);
+    }
+    cx.emit(d.add_note(
+        "Variables driven by a continuous assignment, primitive, or port connection may not \
+         have any other driver",
+    ));
+    Ok(())
+}
+
+/// Report a variable written by an `always_comb`, `always_latch`, or
+/// `always_ff` procedure that is also written by any other procedure.
+///
+/// Returns whether an error was emitted.
+fn check_procedure_writers<'a>(
+    cx: &impl Context<'a>,
+    id: NodeId,
+    drivers: &[Driver],
+) -> Result<bool> {
+    let conflicts = conflicting_drivers(drivers, |a, b| match (a.kind, b.kind) {
+        (DriverKind::Procedure(a), DriverKind::Procedure(b)) => {
+            is_restricted(a) || is_restricted(b)
+        }
+        _ => false,
+    });
+    let (first, rest) = match conflicts.split_first() {
+        Some(x) => x,
+        None => return Ok(false),
+    };
+    let mut d = DiagBuilder2::error(format!(
+        "{} is written by more than one procedure",
+        cx.hir_of(id)?.desc_full()
+    ))
+    .span(first.span);
+    for driver in rest {
+        d = d.add_note(format!("Also written by {} here:", driver.desc));
+        d = d.span(driver.span);
+    }
+    cx.emit(d.add_note(
+        "Variables written by an `always_comb`, `always_latch`, or `always_ff` procedure \
+         may not be written by any other procedure",
+    ));
+    Ok(true)
+}
+
+/// Check whether a procedure may be the only procedure writing its
+/// variables.
+fn is_restricted(kind: ast::ProcedureKind) -> bool {
+    match kind {
+        ast::ProcedureKind::AlwaysComb
+        | ast::ProcedureKind::AlwaysLatch
+        | ast::ProcedureKind::AlwaysFf => true,
+        _ => false,
+    }
+}
+
+/// Determine the drivers which drive overlapping parts of a signal as
+/// another driver they may not be combined with.
+fn conflicting_drivers(
+    drivers: &[Driver],
+    exclusive: impl Fn(&Driver, &Driver) -> bool,
+) -> Vec<&Driver> {
+    let mut conflicting = vec![false; drivers.len()];
+    for (i, a) in drivers.iter().enumerate() {
+        for (j, b) in drivers.iter().enumerate().skip(i + 1) {
+            if !exclusive(a, b) {
+                continue;
+            }
+            let overlaps = a
+                .parts
+                .iter()
+                .any(|pa| b.parts.iter().any(|pb| overlaps(pa, pb)));
+            if overlaps {
+                conflicting[i] = true;
+                conflicting[j] = true;
+            }
+        }
+    }
+    drivers
+        .iter()
+        .zip(conflicting)
+        .filter(|&(_, c)| c)
+        .map(|(d, _)| d)
+        .collect()
+}
+
+/// Check whether two driven parts of a signal overlap.
+fn overlaps(a: &[Select], b: &[Select]) -> bool {
+    for (&a, &b) in a.iter().zip(b) {
+        match (a, b) {
+            (Select::Range(a0, a1), Select::Range(b0, b1)) => {
+                if a1 <= b0 || b1 <= a0 {
+                    return false;
+                }
+                // Selects into differing ranges are not comparable.
+                if a != b {
+                    return true;
+                }
+            }
+            (Select::Field(a), Select::Field(b)) if a != b => return false,
+            _ => (),
+        }
+    }
+    true
+}
+
+/// Determine the signals driven by an lvalue, and the part of each signal.
+///
+/// Selects with a non-constant index end the part, such that `x[i][0]` drives
+/// all of `x`, the longest static prefix of the lvalue.
+fn driven_parts<'a>(
+    cx: &impl Context<'a>,
+    lv: &'a mir::Lvalue<'a>,
+    into: &mut Vec<(NodeId, Vec<Select>)>,
+) {
+    let mut parts = vec![];
+    collect_parts(cx, lv, &mut parts);
+    into.extend(parts.into_iter().map(|(id, part, _)| (id, part)));
+}
+
+/// Collect the driven parts of an lvalue, together with a flag indicating
+/// whether the part is still static.
+fn collect_parts<'a>(
+    cx: &impl Context<'a>,
+    lv: &'a mir::Lvalue<'a>,
+    into: &mut Vec<(NodeId, Vec<Select>, bool)>,
+) {
+    let (value, select) = match lv.kind {
+        mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => {
+            into.push((id, vec![], true));
+            return;
+        }
+        mir::LvalueKind::Index {
+            value,
+            base,
+            length,
+        } => (value, const_select(cx, base, length)),
+        mir::LvalueKind::Member { value, field } => (value, Some(Select::Field(field))),
+        mir::LvalueKind::DestructArray(ref values) => {
+            for value in values.values() {
+                collect_parts(cx, value, into);
+            }
+            return;
+        }
+        mir::LvalueKind::DestructStruct(ref values) => {
+            for value in values {
+                collect_parts(cx, value, into);
+            }
+            return;
+        }
+        _ => return,
+    };
+    let start = into.len();
+    collect_parts(cx, value, into);
+    for (_, part, is_static) in &mut into[start..] {
+        match select {
+            Some(select) if *is_static => part.push(select),
+            _ => *is_static = false,
+        }
+    }
+}
+
+/// A visitor that collects the parts of the variables assigned in a
+/// procedure, and the first assignment to each variable.
+struct ProcedureWrites<'a, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    writes: HashMap<NodeId, (Span, Vec<Vec<Select>>)>,
+}
+
+impl<'a, 'gcx: 'a, C> Visitor<'gcx> for ProcedureWrites<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        if let hir::StmtKind::Assign { lhs, .. } = stmt.kind {
+            let mut parts = vec![];
+            driven_parts(self.cx, self.cx.mir_lvalue(lhs, self.env), &mut parts);
+            for (id, part) in parts {
+                let entry = self.writes.entry(id).or_insert((stmt.span, vec![]));
+                entry.1.push(part);
+            }
+        }
+        hir::walk_stmt(self, stmt);
+    }
+}
+
+/// Determine the select performed by an index with a constant base.
+fn const_select<'a>(
+    cx: &impl Context<'a>,
+    base: &'a mir::Rvalue<'a>,
+    length: usize,
+) -> Option<Select> {
+    if !base.is_const() {
+        return None;
+    }
+    let lo = match cx.const_mir_rvalue(Ref(base)).kind {
+        ValueKind::Int(ref v, ..) => v.to_isize()?,
+        _ => return None,
+    };
+    Some(Select::Range(lo, lo + std::cmp::max(1, length) as isize))
+}
+
+/// Determine the signal read by an rvalue, and the part of the signal, if the
+/// rvalue only consists of constant selects into the signal.
+fn static_read<'a>(
+    cx: &impl Context<'a>,
+    rv: &'a mir::Rvalue<'a>,
+) -> Option<(NodeId, Vec<Select>)> {
+    let (value, select) = match rv.kind {
+        mir::RvalueKind::Var(id) | mir::RvalueKind::Port(id) => return Some((id, vec![])),
+        mir::RvalueKind::Index {
+            value,
+            base,
+            length,
+        } => (value, const_select(cx, base, length)?),
+        mir::RvalueKind::Member { value, field } => (value, Select::Field(field)),
+        _ => return None,
+    };
+    let (id, mut part) = static_read(cx, value)?;
+    part.push(select);
+    Some((id, part))
+}
+
+/// Determine the parts of the signals read by a node.
+fn read_parts<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Vec<(NodeId, Vec<Select>)> {
+    let mut reads = ReadParts {
+        cx,
+        env,
+        reads: vec![],
+        inlined: vec![],
+        shallow: None,
+    };
+    reads.visit_node_with_id(node_id, false);
+    reads.reads
+}
+
+/// A visitor that collects the parts of the signals read by a node.
+///
+/// Reads are narrowed down to the longest static prefix of each expression,
+/// such that `x[0]` and `x[1]` refer to distinct parts of `x`. Tasks called
+/// by the node read whatever their body reads.
+struct ReadParts<'a, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    reads: Vec<(NodeId, Vec<Select>)>,
+    /// The tasks being inlined at the current point of the analysis.
+    inlined: Vec<NodeId>,
+    /// A statement whose nested statements are skipped, such that only the
+    /// reads of the statement itself are collected.
+    shallow: Option<NodeId>,
+}
+
+impl<'a, 'gcx: 'a, C> Visitor<'gcx> for ReadParts<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        let skip = self.inlined.is_empty() && self.shallow.map(|id| id != stmt.id).unwrap_or(false);
+        if !skip {
+            hir::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx hir::Expr, lvalue: bool) {
+        if lvalue {
+            self.cx.mir_lvalue(expr.id, self.env).walk(self);
+        } else {
+            self.cx.mir_rvalue(expr.id, self.env).walk(self);
+        }
+    }
+}
+
+impl<'a, 'gcx: 'a, C> mir::Visitor<'gcx> for ReadParts<'a, C>
+where
+    C: Context<'gcx>,
+{
+    fn pre_visit_rvalue(&mut self, mir: &'gcx mir::Rvalue<'gcx>) -> bool {
+        if let Some(read) = static_read(self.cx, mir) {
+            self.reads.push(read);
+            return false;
+        }
+        if let mir::RvalueKind::Call { target, .. } = mir.kind {
+            if let Ok(HirNode::Subroutine(sub)) = self.cx.hir_of(target) {
+                if sub.dpi.is_none() && !self.inlined.contains(&sub.id) {
+                    self.inlined.push(sub.id);
+                    for &stmt in &sub.body {
+                        Visitor::visit_node_with_id(self, stmt, false);
+                    }
+                    self.inlined.pop();
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Determine the signals each variable written by a procedure depends on,
+/// together with the first assignment to the variable.
+pub(crate) fn procedure_dependencies<'a>(
+    cx: &impl Context<'a>,
+    hir: &'a hir::Proc,
+    env: ParamEnv,
+) -> HashMap<NodeId, (Span, Vec<NodeId>)> {
+    let mut writes = ProcedureWrites {
+        cx,
+        env,
+        writes: HashMap::new(),
+    };
+    writes.visit_node_with_id(hir.stmt, false);
+    procedure_deps(cx, hir, env)
+        .into_iter()
+        .map(|(id, reads)| {
+            let span = writes.writes.get(&id).map(|w| w.0).unwrap_or(hir.span);
+            let mut reads: Vec<_> = reads.into_iter().map(|(id, _)| id).collect();
+            reads.sort();
+            reads.dedup();
+            (id, (span, reads))
+        })
+        .collect()
+}
+
+/// Determine the parts of the signals each variable written by a procedure
+/// depends on.
+fn procedure_deps<'a>(
+    cx: &impl Context<'a>,
+    hir: &'a hir::Proc,
+    env: ParamEnv,
+) -> HashMap<NodeId, Vec<(NodeId, Vec<Select>)>> {
+    let mut deps = ProcedureDeps {
+        cx,
+        env,
+        control: vec![],
+        deps: HashMap::new(),
+        blocking: HashSet::new(),
+    };
+    deps.visit_node_with_id(hir.stmt, false);
+    deps.deps
+}
+
+/// A visitor that determines the signals each variable written by a
+/// procedure depends on.
+///
+/// A variable depends on the signals read by the right-hand side of its
+/// assignments, and by the conditions of the statements the assignments are
+/// nested in. Reading a variable that has been assigned by a blocking
+/// assignment earlier in the procedure reads the signals that variable depends
+/// on instead.
+struct ProcedureDeps<'a, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    /// The signals read by the statements enclosing the current statement.
+    control: Vec<(NodeId, Vec<Select>)>,
+    /// The dependencies of each variable assigned so far.
+    deps: HashMap<NodeId, Vec<(NodeId, Vec<Select>)>>,
+    /// The variables whose new value is visible to subsequent statements.
+    blocking: HashSet<NodeId>,
+}
+
+impl<'a, 'gcx: 'a, C> ProcedureDeps<'a, C>
+where
+    C: Context<'gcx>,
+{
+    /// Determine the signals read by a node, looking through the variables
+    /// assigned earlier in the procedure.
+    fn reads(&self, node_id: NodeId, shallow: bool) -> Vec<(NodeId, Vec<Select>)> {
+        let mut reads = ReadParts {
+            cx: self.cx,
+            env: self.env,
+            reads: vec![],
+            inlined: vec![],
+            shallow: if shallow { Some(node_id) } else { None },
+        };
+        reads.visit_node_with_id(node_id, false);
+        let mut result = self.control.clone();
+        for (id, part) in reads.reads {
+            match self.deps.get(&id) {
+                Some(deps) if self.blocking.contains(&id) => result.extend(deps.iter().cloned()),
+                _ => result.push((id, part)),
+            }
+        }
+        result
+    }
+
+    /// Add dependencies to a variable.
+    fn add(&mut self, id: NodeId, reads: &[(NodeId, Vec<Select>)], blocking: bool) {
+        if blocking {
+            self.blocking.insert(id);
+        }
+        let deps = self.deps.entry(id).or_default();
+        for read in reads {
+            if !deps.contains(read) {
+                deps.push(read.clone());
+            }
+        }
+    }
+}
+
+impl<'a, 'gcx: 'a, C> Visitor<'gcx> for ProcedureDeps<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        let reads = self.reads(stmt.id, true);
+        if let hir::StmtKind::Assign { lhs, kind, .. } = stmt.kind {
+            let blocking = match kind {
+                hir::AssignKind::Block(_) => true,
+                _ => false,
+            };
+            let mut parts = vec![];
+            driven_parts(self.cx, self.cx.mir_lvalue(lhs, self.env), &mut parts);
+            for (id, _) in parts {
+                self.add(id, &reads, blocking);
+            }
+            return;
+        }
+
+        // Statements without nested statements, such as task calls or
+        // increments, write whatever they access.
+        let is_leaf = match stmt.kind {
+            hir::StmtKind::Expr(_) | hir::StmtKind::Null => true,
+            _ => false,
+        };
+        if is_leaf {
+            if let Ok(acc) = self.cx.accessed_nodes(stmt.id, self.env) {
+                for node in &acc.written {
+                    self.add(node.id(), &reads, true);
+                }
+            }
+            return;
+        }
+
+        let len = self.control.len();
+        self.control = reads;
+        hir::walk_stmt(self, stmt);
+        self.control.truncate(len);
+    }
+
+    fn visit_var_decl(&mut self, decl: &'gcx hir::VarDecl) {
+        if let Some(init) = decl.init {
+            let reads = self.reads(init, false);
+            self.add(decl.id, &reads, true);
+        }
+    }
+
+    fn visit_expr(&mut self, _expr: &'gcx hir::Expr, _lvalue: bool) {}
+}
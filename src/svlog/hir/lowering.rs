@@ -5,7 +5,10 @@
 use crate::crate_prelude::*;
 use crate::{ast_map::AstNode, hir::HirNode};
 use bit_vec::BitVec;
+use moore_common::lint::{DEPRECATED, LITERAL_SIZE, POSITIONAL_AFTER_NAMED, UNSUPPORTED};
+use moore_common::name::get_name_table;
 use num::BigInt;
+use std::collections::HashMap;
 
 /// A hint about how a node should be lowered to HIR.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -48,12 +51,15 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                 } else {
                     if !is_pos {
                         cx.emit(
-                            DiagBuilder2::warning("positional parameters must appear before named")
-                                .span(param.span)
-                                .add_note(format!(
-                                    "assuming this refers to argument #{}",
-                                    pos_params.len() + 1
-                                )),
+                            DiagBuilder2::lint(
+                                &POSITIONAL_AFTER_NAMED,
+                                "positional parameters must appear before named",
+                            )
+                            .span(param.span)
+                            .add_note(format!(
+                                "assuming this refers to argument #{}",
+                                pos_params.len() + 1
+                            )),
                         );
                     }
                     pos_params.push((param.span, Some(value_id)));
@@ -95,12 +101,15 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                     ast::PortConnData::Positional(ref expr) => {
                         if !is_pos {
                             cx.emit(
-                                DiagBuilder2::warning("positional port must appear before named")
-                                    .span(port.span)
-                                    .add_note(format!(
-                                        "assuming this refers to argument #{}",
-                                        pos_ports.len() + 1
-                                    )),
+                                DiagBuilder2::lint(
+                                    &POSITIONAL_AFTER_NAMED,
+                                    "positional port must appear before named",
+                                )
+                                .span(port.span)
+                                .add_note(format!(
+                                    "assuming this refers to argument #{}",
+                                    pos_ports.len() + 1
+                                )),
                             );
                         }
                         let value_id = cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
@@ -239,11 +248,12 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                     }
                 }
                 ast::IfStmt {
+                    up,
                     ref cond,
                     ref main_stmt,
                     ref else_stmt,
-                    ..
                 } => hir::StmtKind::If {
+                    up,
                     cond: cx.map_ast_with_parent(AstNode::Expr(cond), node_id),
                     main_stmt: cx.map_ast_with_parent(AstNode::Stmt(main_stmt), node_id),
                     else_stmt: else_stmt
@@ -304,11 +314,11 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                     },
                 },
                 ast::CaseStmt {
+                    up,
                     ref expr,
                     mode: ast::CaseMode::Normal,
                     ref items,
                     kind,
-                    ..
                 } => {
                     let expr = cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
                     let mut ways = vec![];
@@ -338,6 +348,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                         }
                     }
                     hir::StmtKind::Case {
+                        up,
                         expr,
                         ways,
                         default,
@@ -346,11 +357,36 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                 }
                 ast::AssertionStmt { .. } => {
                     cx.emit(
-                        DiagBuilder2::warning("unsupported: immediate assertion; ignored")
-                            .span(stmt.human_span()),
+                        DiagBuilder2::lint(
+                            &UNSUPPORTED,
+                            "unsupported: immediate assertion; ignored",
+                        )
+                        .span(stmt.human_span()),
                     );
                     hir::StmtKind::Null
                 }
+                ast::ReturnStmt(ref expr) => hir::StmtKind::Return(
+                    expr.as_ref()
+                        .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                ),
+                ast::TriggerStmt {
+                    ref target,
+                    nonblocking,
+                    ref delay,
+                } => hir::StmtKind::Trigger {
+                    target: cx.map_ast_with_parent(AstNode::Expr(target), node_id),
+                    kind: match (nonblocking, delay) {
+                        (false, _) => hir::AssignKind::Block(ast::AssignOp::Identity),
+                        (true, None) => hir::AssignKind::Nonblock,
+                        (true, Some(dc)) => hir::AssignKind::NonblockDelay(
+                            cx.map_ast_with_parent(AstNode::Expr(&dc.expr), node_id),
+                        ),
+                    },
+                },
+                ast::WaitExprStmt(ref cond, ref stmt) => hir::StmtKind::Wait {
+                    cond: cx.map_ast_with_parent(AstNode::Expr(cond), node_id),
+                    stmt: cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id),
+                },
                 _ => {
                     error!("{:#?}", stmt);
                     bug_span!(
@@ -381,11 +417,19 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
         }
         AstNode::GenIf(gen) => {
             let cond = cx.map_ast_with_parent(AstNode::Expr(&gen.cond), node_id);
-            let main_body = lower_module_block(cx, node_id, &gen.main_block.items, false, false)?;
+            let main_body = lower_module_block(
+                cx,
+                node_id,
+                &gen.main_block,
+                &gen.main_block.items,
+                false,
+                false,
+            )?;
             let else_body = match gen.else_block {
                 Some(ref else_block) => Some(lower_module_block(
                     cx,
                     node_id,
+                    else_block,
                     &else_block.items,
                     false,
                     false,
@@ -408,7 +452,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             let rib = *init.last().unwrap();
             let cond = cx.map_ast_with_parent(AstNode::Expr(&gen.cond), rib);
             let step = cx.map_ast_with_parent(AstNode::Expr(&gen.step), rib);
-            let body = lower_module_block(cx, rib, &gen.block.items, false, false)?;
+            let body = lower_module_block(cx, rib, &gen.block, &gen.block.items, false, false)?;
             let hir = hir::Gen {
                 id: node_id,
                 span: gen.span(),
@@ -433,6 +477,17 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::GenvarDecl(cx.arena().alloc_hir(hir)))
         }
+        // The loop variables of `foreach` constraints are unrolled like
+        // genvars, taking on each index of the array in turn.
+        AstNode::ForeachIndex(index) => {
+            let hir = hir::GenvarDecl {
+                id: node_id,
+                span: index.span,
+                name: index.name,
+                init: None,
+            };
+            Ok(HirNode::GenvarDecl(cx.arena().alloc_hir(hir)))
+        }
         AstNode::Typedef(def) => {
             let hir = hir::Typedef {
                 id: node_id,
@@ -495,9 +550,249 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                     .retty
                     .as_ref()
                     .map(|ty| cx.map_ast_with_parent(AstNode::Type(ty), node_id)),
+                args: lower_subroutine_args(cx, node_id, &decl.prototype.args),
+                body: lower_subroutine_body(cx, node_id, decl),
+                dpi: None,
             };
             Ok(HirNode::Subroutine(cx.arena().alloc_hir(hir)))
         }
+        AstNode::SubroutinePort(port) => {
+            let name = match port.name {
+                Some(ref name) => name,
+                None => {
+                    cx.emit(DiagBuilder2::error("argument has no name").span(port.span));
+                    return Err(());
+                }
+            };
+            let hir = hir::VarDecl {
+                id: node_id,
+                name: name.name,
+                span: port.span,
+                ty: cx.map_ast_with_parent(AstNode::Type(&port.ty), node_id),
+                init: name
+                    .expr
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                kind: ast::VarKind::Var,
+            };
+            Ok(HirNode::VarDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::ClassDecl(decl) => lower_class(cx, node_id, decl),
+        AstNode::DpiDecl(decl) => match decl.data {
+            ast::DpiDeclData::Import {
+                spec,
+                ref property,
+                cident,
+                ref prototype,
+            } => {
+                match &*spec.value.as_str() {
+                    "DPI-C" => (),
+                    "DPI" => cx.emit(
+                        DiagBuilder2::lint(
+                            &DEPRECATED,
+                            "`\"DPI\"` is deprecated; treated as `\"DPI-C\"`",
+                        )
+                        .span(spec.span),
+                    ),
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "unknown DPI specifier `\"{}\"`",
+                                spec.value
+                            ))
+                            .span(spec.span)
+                            .add_note("Only `\"DPI-C\"` is supported"),
+                        );
+                        return Err(());
+                    }
+                }
+                for port in &prototype.args {
+                    if let Some(dims) = port.name.as_ref().map(|n| &n.dims) {
+                        if !dims.is_empty() {
+                            cx.emit(
+                                DiagBuilder2::error(
+                                    "unpacked array arguments of DPI imports not supported",
+                                )
+                                .span(port.span),
+                            );
+                            return Err(());
+                        }
+                    }
+                }
+                let hir = hir::Subroutine {
+                    id: node_id,
+                    name: prototype.name,
+                    span: decl.span,
+                    kind: prototype.kind,
+                    retty: prototype
+                        .retty
+                        .as_ref()
+                        .map(|ty| cx.map_ast_with_parent(AstNode::Type(ty), node_id)),
+                    args: lower_subroutine_args(cx, node_id, &prototype.args),
+                    body: vec![],
+                    dpi: Some(hir::DpiImport {
+                        cname: cident.unwrap_or(prototype.name),
+                        property: property.as_ref().map(|p| p.value.clone()),
+                    }),
+                };
+                Ok(HirNode::Subroutine(cx.arena().alloc_hir(hir)))
+            }
+            ast::DpiDeclData::Export { .. } => {
+                unreachable!("DPI export should never be lowered: {:#?}", decl)
+            }
+        },
+        AstNode::LetDecl(decl) => {
+            let hir = hir::LetDecl {
+                id: node_id,
+                name: decl.name,
+                span: decl.span,
+                args: decl
+                    .args
+                    .iter()
+                    .map(|arg| cx.map_ast_with_parent(AstNode::LetArg(arg), node_id))
+                    .collect(),
+                expr: cx.map_ast_with_parent(AstNode::Expr(&decl.expr), node_id),
+            };
+            Ok(HirNode::LetDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::LetArg(arg) => {
+            // Arguments without a type, or declared as `untyped`, take on the
+            // type of the expression bound to them.
+            let untyped =
+                arg.ty.kind.data == ast::ImplicitType && arg.ty.sign == ast::TypeSign::None;
+            let untyped = untyped && arg.ty.dims.is_empty();
+            let hir = hir::LetArg {
+                id: node_id,
+                name: arg.name,
+                span: arg.span,
+                ty: if untyped {
+                    None
+                } else {
+                    Some(cx.map_ast_with_parent(AstNode::Type(&arg.ty), node_id))
+                },
+                default: arg
+                    .default
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+            };
+            Ok(HirNode::LetArg(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::Covergroup(cg) => lower_covergroup(cx, node_id, cg),
+        AstNode::ClockingDecl(decl) => {
+            let event = match decl.event.data {
+                ast::EventControlData::Expr(ref expr) => {
+                    cx.map_ast_with_parent(AstNode::EventExpr(expr), node_id)
+                }
+                ast::EventControlData::Implicit => {
+                    cx.emit(
+                        DiagBuilder2::error("clocking block requires an explicit clocking event")
+                            .span(decl.event.span),
+                    );
+                    return Err(());
+                }
+            };
+            let signals = decl
+                .items
+                .iter()
+                .flat_map(|item| item.signals.iter().map(move |sig| (sig, item)))
+                .map(|(sig, item)| {
+                    cx.map_ast_with_parent(AstNode::ClockingSignal(sig, item, decl), node_id)
+                })
+                .collect();
+            let hir = hir::ClockingBlock {
+                id: node_id,
+                name: decl.name,
+                span: decl.span,
+                default: decl.default,
+                event,
+                signals,
+            };
+            Ok(HirNode::ClockingBlock(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::ClockingSignal(sig, item, decl) => {
+            // Apply the default skews of the block, which are `#1step` for
+            // inputs and `#0` for outputs unless specified otherwise.
+            let lower_skew = |skew: Option<&'gcx ast::ClockingSkew<'gcx>>, default| match skew {
+                Some(ast::ClockingSkew::Step) => hir::ClockingSkew::Step,
+                Some(ast::ClockingSkew::Delay(ref delay)) => hir::ClockingSkew::Delay(
+                    cx.map_ast_with_parent(AstNode::Expr(&delay.expr), node_id),
+                ),
+                None => default,
+            };
+            let input_skew = lower_skew(
+                item.input_skew.as_ref().or(decl.default_input.as_ref()),
+                hir::ClockingSkew::Step,
+            );
+            let output_skew = lower_skew(
+                item.output_skew.as_ref().or(decl.default_output.as_ref()),
+                hir::ClockingSkew::Zero,
+            );
+            if output_skew == hir::ClockingSkew::Step && item.dir.is_output() {
+                cx.emit(
+                    DiagBuilder2::error("`#1step` is not a valid output skew")
+                        .span(item.span)
+                        .add_note("Output skews must be a delay such as `#0` or `#2ns`"),
+                );
+                return Err(());
+            }
+            let hir = hir::ClockVar {
+                id: node_id,
+                name: sig.name,
+                span: sig.span,
+                block: cx.parent_node_id(node_id).unwrap(),
+                dir: item.dir,
+                input_skew,
+                output_skew,
+                expr: cx.map_ast_with_parent(AstNode::Expr(&sig.expr), node_id),
+            };
+            Ok(HirNode::ClockVar(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::Gate(inst, gate) => lower_gate(cx, node_id, inst, gate),
+        AstNode::Coverpoint(cp) => {
+            // Unlabeled coverpoints are named after their expression.
+            let name = match cp.label {
+                Some(label) => label,
+                None => Spanned::new(
+                    get_name_table().intern(&cp.expr.span.extract(), true),
+                    cp.expr.span,
+                ),
+            };
+            let bins = cp
+                .bins
+                .iter()
+                .map(|bins| hir::CoverBins {
+                    name: bins.name,
+                    span: bins.span,
+                    kind: bins.kind,
+                    array: bins.array.as_ref().map(|size| {
+                        size.as_ref()
+                            .map(|size| cx.map_ast_with_parent(AstNode::Expr(size), node_id))
+                    }),
+                    values: bins.values.as_ref().map(|values| {
+                        values
+                            .iter()
+                            .map(|vr| lower_value_range(cx, vr, node_id))
+                            .collect()
+                    }),
+                    iff: bins
+                        .iff
+                        .as_ref()
+                        .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                })
+                .collect();
+            let hir = hir::Coverpoint {
+                id: node_id,
+                name,
+                span: cp.span,
+                expr: cx.map_ast_with_parent(AstNode::Expr(&cp.expr), node_id),
+                iff: cp
+                    .iff
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                bins,
+            };
+            Ok(HirNode::Coverpoint(cx.arena().alloc_hir(hir)))
+        }
         _ => {
             error!("{:#?}", ast);
             cx.unimp_msg("lowering of", &ast)
@@ -505,6 +800,154 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
     }
 }
 
+/// Lower a covergroup to HIR.
+fn lower_covergroup<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    cg: &'gcx ast::Covergroup<'gcx>,
+) -> Result<HirNode<'gcx>> {
+    let event = match cg.event {
+        Some(ast::EventControl {
+            data: ast::EventControlData::Expr(ref expr),
+            ..
+        }) => Some(cx.map_ast_with_parent(AstNode::EventExpr(expr), node_id)),
+        Some(ref ec) => {
+            cx.emit(
+                DiagBuilder2::error("covergroup must be sampled on an explicit event")
+                    .span(ec.span),
+            );
+            return Err(());
+        }
+        None => None,
+    };
+    let points: Vec<_> = cg
+        .points
+        .iter()
+        .map(|cp| cx.map_ast_with_parent(AstNode::Coverpoint(cp), node_id))
+        .collect();
+
+    // Resolve the coverpoints being crossed by name.
+    let mut crosses = vec![];
+    for cross in &cg.crosses {
+        let mut crossed = vec![];
+        for &name in &cross.points {
+            let found = points.iter().cloned().find(|&id| match cx.hir_of(id) {
+                Ok(HirNode::Coverpoint(cp)) => cp.name.value == name.value,
+                _ => false,
+            });
+            match found {
+                Some(id) => crossed.push(id),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a coverpoint of covergroup `{}`",
+                            name, cg.name
+                        ))
+                        .span(name.span),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        if crossed.len() < 2 {
+            cx.emit(
+                DiagBuilder2::error("cross requires at least two coverpoints").span(cross.span),
+            );
+            return Err(());
+        }
+        let name = match cross.label {
+            Some(label) => label,
+            None => {
+                let names: Vec<_> = cross.points.iter().map(|n| n.value.to_string()).collect();
+                Spanned::new(
+                    get_name_table().intern(&names.join("_x_"), true),
+                    cross.span,
+                )
+            }
+        };
+        crosses.push(hir::CoverCross {
+            name,
+            span: cross.span,
+            points: crossed,
+            iff: cross
+                .iff
+                .as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        });
+    }
+
+    let hir = hir::Covergroup {
+        id: node_id,
+        name: cg.name,
+        span: cg.span,
+        event,
+        points,
+        crosses,
+    };
+    Ok(HirNode::Covergroup(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower a gate or switch primitive instance to HIR.
+fn lower_gate<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    inst: &'gcx ast::GateInstName<'gcx>,
+    gate: &'gcx ast::GateInst<'gcx>,
+) -> Result<HirNode<'gcx>> {
+    use ast::GateKind::*;
+    if !inst.dims.is_empty() {
+        cx.emit(DiagBuilder2::error("unsupported: array of primitive instances").span(inst.span));
+        return Err(());
+    }
+
+    // Determine how many of the terminals are outputs and inputs.
+    let num = inst.terminals.len();
+    let (num_outputs, expected) = match gate.kind {
+        And | Nand | Or | Nor | Xor | Xnor => (1, None),
+        Buf | Not => (num.saturating_sub(1), None),
+        Bufif0 | Bufif1 | Notif0 | Notif1 | Nmos | Pmos | Rnmos | Rpmos => (1, Some(3)),
+        Cmos | Rcmos => (1, Some(4)),
+        Tran | Rtran => (2, Some(2)),
+        Tranif0 | Tranif1 | Rtranif0 | Rtranif1 => (2, Some(3)),
+        Pullup | Pulldown => (num, None),
+    };
+    let msg = match expected {
+        Some(n) if num != n => Some(format!(
+            "`{}` requires {} terminals, but {} given",
+            gate.kind, n, num
+        )),
+        None if gate.kind != Pullup && gate.kind != Pulldown && num < 2 => Some(format!(
+            "`{}` requires at least 2 terminals, but {} given",
+            gate.kind, num
+        )),
+        _ => None,
+    };
+    if let Some(msg) = msg {
+        cx.emit(DiagBuilder2::error(msg).span(inst.span));
+        return Err(());
+    }
+
+    let terminals: Vec<_> = inst
+        .terminals
+        .iter()
+        .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
+        .collect();
+    let hir = hir::Gate {
+        id: node_id,
+        name: inst.name,
+        span: inst.span,
+        kind: gate.kind,
+        outputs: terminals[..num_outputs].to_vec(),
+        inputs: terminals[num_outputs..].to_vec(),
+        delay: gate
+            .delays
+            .first()
+            .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        strength: gate.strength,
+    };
+    Ok(HirNode::Gate(cx.arena().alloc_hir(hir)))
+}
+
 /// Lower a module to HIR.
 #[moore_derive::query]
 pub(crate) fn hir_of_module<'a>(
@@ -530,8 +973,16 @@ pub(crate) fn hir_of_module<'a>(
     let ports_new = cx.canonicalize_ports(ast);
     next_rib = ports_new.tail_rib;
 
+    // Programs may not contain always procedures or instances, and checkers
+    // only contain modelling code and other checkers.
+    match ast.kind {
+        ast::ModuleKind::Module => (),
+        ast::ModuleKind::Program => check_program_items(cx, &ast.items),
+        ast::ModuleKind::Checker => check_checker_items(cx, &ast.items),
+    }
+
     // Lower the module body.
-    let block = lower_module_block(cx, next_rib, &ast.items, true, false)?;
+    let block = lower_module_block(cx, next_rib, ast, &ast.items, true, false)?;
 
     // Create the HIR module.
     let hir = hir::Module {
@@ -564,7 +1015,7 @@ pub(crate) fn hir_of_interface<'a>(
     let ports = cx.canonicalize_ports(ast);
 
     // Lower the interface body.
-    let block = lower_module_block(cx, ast.id(), &ast.items, true, true)?;
+    let block = lower_module_block(cx, ast.id(), ast, &ast.items, true, true)?;
 
     // Create the HIR node.
     let hir = hir::Interface { ast, ports, block };
@@ -581,9 +1032,120 @@ pub(crate) fn hir_of_interface<'a>(
     Ok(hir)
 }
 
+/// Check the restrictions on the items of a program.
+///
+/// See IEEE 1800-2017 §24.3. Programs execute in the reactive region and may
+/// not contain always procedures or instances of modules, interfaces, or other
+/// programs.
+fn check_program_items<'gcx>(
+    cx: &impl Context<'gcx>,
+    items: impl IntoIterator<Item = &'gcx ast::Item<'gcx>>,
+) {
+    for item in items {
+        match item.data {
+            ast::ItemData::Procedure(ref prok) => {
+                let kw = match prok.kind {
+                    ast::ProcedureKind::Always => "always",
+                    ast::ProcedureKind::AlwaysComb => "always_comb",
+                    ast::ProcedureKind::AlwaysLatch => "always_latch",
+                    ast::ProcedureKind::AlwaysFf => "always_ff",
+                    ast::ProcedureKind::Initial | ast::ProcedureKind::Final => continue,
+                };
+                cx.emit(
+                    DiagBuilder2::error(format!("`{}` procedure in program", kw))
+                        .span(prok.span)
+                        .add_note("Programs may only contain `initial` and `final` procedures"),
+                );
+            }
+            ast::ItemData::Inst(ref inst) => {
+                cx.emit(
+                    DiagBuilder2::error(format!("instance of `{}` in program", inst.target))
+                        .span(inst.span)
+                        .add_note("Programs cannot instantiate modules, interfaces, or programs"),
+                );
+            }
+            ast::ItemData::GenerateRegion(_, ref items) => check_program_items(cx, items),
+            ast::ItemData::GenerateIf(ref gen) => {
+                check_program_items(cx, &gen.main_block.items);
+                if let Some(ref else_block) = gen.else_block {
+                    check_program_items(cx, &else_block.items);
+                }
+            }
+            ast::ItemData::GenerateFor(ref gen) => check_program_items(cx, &gen.block.items),
+            _ => (),
+        }
+    }
+}
+
+/// Check the restrictions on the items of a checker.
+///
+/// See IEEE 1800-2017 §17.5 and §17.7. Checkers may not contain nets,
+/// continuous assignments, `always` procedures without a specialization, or
+/// instances of anything but other checkers.
+fn check_checker_items<'gcx>(
+    cx: &impl Context<'gcx>,
+    items: impl IntoIterator<Item = &'gcx ast::Item<'gcx>>,
+) {
+    for item in items {
+        match item.data {
+            ast::ItemData::Procedure(ref prok) if prok.kind == ast::ProcedureKind::Always => {
+                cx.emit(
+                    DiagBuilder2::error("`always` procedure in checker")
+                        .span(prok.span)
+                        .add_note(
+                            "Checkers may only contain `initial`, `always_comb`, \
+                             `always_latch`, `always_ff`, and `final` procedures",
+                        ),
+                );
+            }
+            ast::ItemData::ContAssign(ref assign) => {
+                cx.emit(DiagBuilder2::error("continuous assignment in checker").span(assign.span));
+            }
+            ast::ItemData::GateInst(ref gate) => {
+                cx.emit(DiagBuilder2::error("primitive instance in checker").span(gate.span));
+            }
+            ast::ItemData::NetDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::error("net declaration in checker")
+                        .span(decl.span)
+                        .add_note("Checkers may only declare variables"),
+                );
+            }
+            ast::ItemData::Inst(ref inst) => {
+                let is_checker = cx
+                    .gcx()
+                    .find_module(inst.target.value)
+                    .and_then(|id| cx.ast_of(id).ok())
+                    .map(|ast| match ast {
+                        AstNode::Module(m) => m.kind == ast::ModuleKind::Checker,
+                        _ => false,
+                    })
+                    .unwrap_or(false);
+                if !is_checker {
+                    cx.emit(
+                        DiagBuilder2::error(format!("instance of `{}` in checker", inst.target))
+                            .span(inst.span)
+                            .add_note("Checkers can only instantiate other checkers"),
+                    );
+                }
+            }
+            ast::ItemData::GenerateRegion(_, ref items) => check_checker_items(cx, items),
+            ast::ItemData::GenerateIf(ref gen) => {
+                check_checker_items(cx, &gen.main_block.items);
+                if let Some(ref else_block) = gen.else_block {
+                    check_checker_items(cx, &else_block.items);
+                }
+            }
+            ast::ItemData::GenerateFor(ref gen) => check_checker_items(cx, &gen.block.items),
+            _ => (),
+        }
+    }
+}
+
 fn lower_module_block<'gcx>(
     cx: &impl Context<'gcx>,
     parent_rib: NodeId,
+    scope: &'gcx dyn resolver::ScopedNode<'gcx>,
     items: impl IntoIterator<Item = &'gcx ast::Item<'gcx>>,
     allow_ports: bool,
     allow_modports: bool,
@@ -595,10 +1157,20 @@ fn lower_module_block<'gcx>(
     let mut gens = Vec::new();
     let mut params = Vec::new();
     let mut assigns = Vec::new();
+    let mut gates = Vec::new();
+    let mut clockings = Vec::new();
+    let mut typedefs = Vec::new();
+    let mut subroutines = HashMap::new();
+    let mut exports = Vec::new();
+    for &decl in &cx.generated_scope(scope).implicit_nets {
+        next_rib = alloc_net_decl(cx, decl, next_rib, &mut decls);
+    }
     for item in items {
         match item.data {
             ast::ItemData::Dummy => (),
-            ast::ItemData::ModuleDecl(ref decl) => {
+            ast::ItemData::ModuleDecl(ref decl)
+            | ast::ItemData::ProgramDecl(ref decl)
+            | ast::ItemData::CheckerDecl(ref decl) => {
                 let id = cx.map_ast_with_parent(AstNode::Module(decl), next_rib);
                 next_rib = id;
                 procs.push(id);
@@ -613,18 +1185,10 @@ fn lower_module_block<'gcx>(
                 // next_rib = id;
                 // procs.push(id);
                 cx.emit(
-                    DiagBuilder2::warning("unsupported: interface declaration; ignored")
+                    DiagBuilder2::lint(&UNSUPPORTED, "unsupported: interface declaration; ignored")
                         .span(decl.span),
                 );
             }
-            ast::ItemData::ProgramDecl(ref _decl) => {
-                // let id = cx.map_ast_with_parent(AstNode::Program(decl), next_rib);
-                // next_rib = id;
-                // procs.push(id);
-                cx.emit(DiagBuilder2::warning(
-                    "unsupported: program declaration; ignored",
-                ));
-            }
             ast::ItemData::Inst(ref inst) => {
                 let target_id = cx.map_ast_with_parent(AstNode::InstTarget(inst), next_rib);
                 next_rib = target_id;
@@ -668,6 +1232,7 @@ fn lower_module_block<'gcx>(
             ast::ItemData::Typedef(ref def) => {
                 let id = cx.map_ast_with_parent(AstNode::Typedef(def), next_rib);
                 next_rib = id;
+                typedefs.push(id);
             }
             ast::ItemData::ContAssign(ref assign) => {
                 for &(ref lhs, ref rhs) in &assign.assignments {
@@ -677,6 +1242,13 @@ fn lower_module_block<'gcx>(
                     assigns.push(id);
                 }
             }
+            ast::ItemData::GateInst(ref gate) => {
+                for inst in &gate.names {
+                    let id = cx.map_ast_with_parent(AstNode::Gate(inst, gate), next_rib);
+                    next_rib = id;
+                    gates.push(id);
+                }
+            }
             ast::ItemData::ImportDecl(ref decl) => {
                 for item in &decl.items {
                     let id = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
@@ -703,28 +1275,59 @@ fn lower_module_block<'gcx>(
                 );
             }
             ast::ItemData::ClassDecl(ref decl) => {
-                cx.emit(
-                    DiagBuilder2::warning("unsupported: class declaration; ignored")
-                        .span(decl.span),
-                );
+                let id = cx.map_ast_with_parent(AstNode::ClassDecl(decl), next_rib);
+                next_rib = id;
             }
             ast::ItemData::SubroutineDecl(ref decl) => {
                 let id = cx.map_ast_with_parent(AstNode::SubroutineDecl(decl), next_rib);
                 next_rib = id;
+                subroutines.insert(decl.prototype.name.value, decl);
+            }
+            ast::ItemData::DpiDecl(ref decl) => match decl.data {
+                ast::DpiDeclData::Import { .. } => {
+                    let id = cx.map_ast_with_parent(AstNode::DpiDecl(decl), next_rib);
+                    next_rib = id;
+                }
+                ast::DpiDeclData::Export { .. } => exports.push(decl),
+            },
+            ast::ItemData::LetDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::LetDecl(decl), next_rib);
+                next_rib = id;
+            }
+            ast::ItemData::CovergroupDecl(ref cg) => {
+                let id = cx.map_ast_with_parent(AstNode::Covergroup(cg), next_rib);
+                next_rib = id;
+            }
+            ast::ItemData::ClockingDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::ClockingDecl(decl), next_rib);
+                next_rib = id;
+                clockings.push(id);
             }
             ast::ItemData::Assertion(ref assert) => {
-                cx.emit(
-                    DiagBuilder2::warning("unsupported: concurrent assertion; ignored")
-                        .span(assert.span),
-                );
+                let mut d =
+                    DiagBuilder2::lint(&UNSUPPORTED, "unsupported: concurrent assertion; ignored")
+                        .span(assert.span);
+                let in_checker = scope
+                    .as_all()
+                    .get_module()
+                    .map(|m| m.kind == ast::ModuleKind::Checker)
+                    .unwrap_or(false);
+                if in_checker {
+                    d = d.add_note(
+                        "Only the variables and procedures of a checker are elaborated; its \
+                         assertions are not checked",
+                    );
+                }
+                cx.emit(d);
             }
 
             // The remaining items don't need an HIR representation.
-            ast::ItemData::DpiDecl(..)
-            | ast::ItemData::GenvarDecl(..)
-            | ast::ItemData::GenerateRegion(..) => (),
+            ast::ItemData::GenvarDecl(..) | ast::ItemData::GenerateRegion(..) => (),
         }
     }
+    for decl in exports {
+        check_dpi_export(cx, decl, &subroutines);
+    }
     Ok(hir::ModuleBlock {
         insts,
         decls,
@@ -732,10 +1335,67 @@ fn lower_module_block<'gcx>(
         gens,
         params,
         assigns,
+        gates,
+        clockings,
+        typedefs,
         last_rib: next_rib,
     })
 }
 
+/// Check a DPI export against the subroutines declared in the same scope.
+///
+/// The exported subroutine must be a function or task of the matching kind
+/// declared alongside the export (see IEEE 1800-2017 §35.7).
+fn check_dpi_export<'gcx>(
+    cx: &impl Context<'gcx>,
+    decl: &'gcx ast::DpiDecl<'gcx>,
+    subroutines: &HashMap<Name, &'gcx ast::SubroutineDecl<'gcx>>,
+) {
+    let (kind, name) = match decl.data {
+        ast::DpiDeclData::Export { kind, name, .. } => (kind, name),
+        _ => return,
+    };
+    let target = match subroutines.get(&name.value) {
+        Some(x) => x,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!("exported subroutine `{}` not found", name))
+                    .span(name.span)
+                    .add_note(
+                        "DPI exports must name a function or task declared in the same scope",
+                    ),
+            );
+            return;
+        }
+    };
+    if target.prototype.kind != kind {
+        let desc = |kind| match kind {
+            ast::SubroutineKind::Func => "function",
+            ast::SubroutineKind::Task => "task",
+        };
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`{}` is a {}, but exported as a {}",
+                name,
+                desc(target.prototype.kind),
+                desc(kind)
+            ))
+            .span(decl.span)
+            .add_note("Declared here:")
+            .span(target.prototype.name.span),
+        );
+        return;
+    }
+    cx.emit(
+        DiagBuilder2::lint(
+            &UNSUPPORTED,
+            format!("unsupported: DPI export of `{}`; ignored", name),
+        )
+        .span(decl.span)
+        .add_note("Code generation for SystemVerilog subroutines is not supported"),
+    );
+}
+
 fn lower_type<'gcx>(
     cx: &impl Context<'gcx>,
     node_id: NodeId,
@@ -826,8 +1486,11 @@ fn lower_type<'gcx>(
                 }
             }
         }
+        ast::SpecializedType(ref inner, _) => {
+            hir::TypeKind::Specialized(cx.map_ast_with_parent(AstNode::Type(inner), node_id))
+        }
         ast::ChandleType
-        | ast::VirtIntfType(..)
+        | ast::VirtIntfType { .. }
         | ast::EventType
         | ast::MailboxType
         | ast::ImplicitSignedType
@@ -835,7 +1498,6 @@ fn lower_type<'gcx>(
         | ast::ShortRealType
         | ast::RealType
         | ast::RealtimeType
-        | ast::SpecializedType(..)
         | ast::ForwardType { .. }
         | ast::ScopedType { .. } => {
             error!("{:#?}", ty);
@@ -971,7 +1633,7 @@ fn lower_expr_inner<'gcx>(
             };
             if size_needed > size {
                 cx.emit(
-                    DiagBuilder2::warning(format!("`{}` is too large", value,))
+                    DiagBuilder2::lint(&LITERAL_SIZE, format!("`{}` is too large", value,))
                         .span(expr.span)
                         .add_note(format!(
                             "constant is {} bits wide, but the value `{}{}` needs {} bits to not \
@@ -1038,6 +1700,23 @@ fn lower_expr_inner<'gcx>(
         }
 
         ast::IdentExpr(ident) => hir::ExprKind::Ident(ident),
+        ast::ThisExpr => hir::ExprKind::This,
+        ast::SuperExpr => hir::ExprKind::Super,
+        ast::NullExpr => hir::ExprKind::Null,
+        ast::SysIdentExpr(ident) => hir::ExprKind::Builtin(match &*ident.value.as_str() {
+            "time" => hir::BuiltinCall::Time,
+            "realtime" => hir::BuiltinCall::RealTime,
+            "random" => hir::BuiltinCall::Random(None),
+            "urandom" => hir::BuiltinCall::Urandom(None),
+            "finish" => hir::BuiltinCall::Finish(None),
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error(format!("unknown system task `${}`", ident))
+                        .span(expr.human_span()),
+                );
+                hir::BuiltinCall::Unsupported
+            }
+        }),
         ast::UnaryExpr {
             op,
             expr: ref arg,
@@ -1148,6 +1827,46 @@ fn lower_expr_inner<'gcx>(
             // };
             hir::ExprKind::Index(indexee, mode)
         }
+        ast::CallExpr(ref callee, ref args) if crate::constraint::is_std_randomize(callee) => {
+            let mut vars = vec![];
+            for arg in args {
+                match arg.expr {
+                    Some(ref expr) if arg.name.is_none() => {
+                        vars.push(cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
+                    }
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(
+                                "`std::randomize` only accepts variables as positional arguments",
+                            )
+                            .span(arg.span),
+                        );
+                        return Err(());
+                    }
+                }
+            }
+            hir::ExprKind::StdRandomize(vars)
+        }
+        ast::InlineConstraintExpr(ref call, ref items) => {
+            let is_randomize = match call.data {
+                ast::CallExpr(ref callee, _) => match callee.data {
+                    ast::MemberExpr { name, .. } => &*name.value.as_str() == "randomize",
+                    _ => crate::constraint::is_std_randomize(callee),
+                },
+                _ => false,
+            };
+            if !is_randomize {
+                cx.emit(
+                    DiagBuilder2::error("inline constraints must follow a call to `randomize()`")
+                        .span(call.span),
+                );
+                return Err(());
+            }
+            hir::ExprKind::InlineConstraint(
+                cx.map_ast_with_parent(AstNode::Expr(call), node_id),
+                lower_constraint_items(cx, items, node_id),
+            )
+        }
         ast::CallExpr(ref callee, ref args) => match callee.data {
             ast::SysIdentExpr(ident) => {
                 let map_unary = || match args.as_slice() {
@@ -1185,6 +1904,46 @@ fn lower_expr_inner<'gcx>(
                         Err(())
                     }
                 };
+                let map_optional_id = || match args.as_slice() {
+                    [] => Ok(None),
+                    [ast::CallArg {
+                        expr: Some(ref arg),
+                        ..
+                    }] => Ok(Some(cx.map_ast_with_parent(AstNode::Expr(arg), node_id))),
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!("`{}` takes zero or one arguments", ident))
+                                .span(expr.human_span()),
+                        );
+                        Err(())
+                    }
+                };
+                let map_range = || match args.as_slice() {
+                    [ast::CallArg {
+                        expr: Some(ref max),
+                        ..
+                    }] => Ok(hir::BuiltinCall::UrandomRange(
+                        cx.map_ast_with_parent(AstNode::Expr(max), node_id),
+                        None,
+                    )),
+                    [ast::CallArg {
+                        expr: Some(ref max),
+                        ..
+                    }, ast::CallArg {
+                        expr: Some(ref min),
+                        ..
+                    }] => Ok(hir::BuiltinCall::UrandomRange(
+                        cx.map_ast_with_parent(AstNode::Expr(max), node_id),
+                        Some(cx.map_ast_with_parent(AstNode::Expr(min), node_id)),
+                    )),
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!("`{}` takes one or two arguments", ident))
+                                .span(expr.human_span()),
+                        );
+                        Err(())
+                    }
+                };
                 hir::ExprKind::Builtin(match &*ident.value.as_str() {
                     "clog2" => hir::BuiltinCall::Clog2(map_unary_id()?),
                     "signed" => hir::BuiltinCall::Signed(map_unary_id()?),
@@ -1199,12 +1958,18 @@ fn lower_expr_inner<'gcx>(
                     "high" => map_array_dim(hir::ArrayDim::High)?,
                     "increment" => map_array_dim(hir::ArrayDim::Increment)?,
                     "size" => map_array_dim(hir::ArrayDim::Size)?,
+                    "time" => hir::BuiltinCall::Time,
+                    "realtime" => hir::BuiltinCall::RealTime,
+                    "random" => hir::BuiltinCall::Random(map_optional_id()?),
+                    "urandom" => hir::BuiltinCall::Urandom(map_optional_id()?),
+                    "urandom_range" => map_range()?,
+                    "finish" => hir::BuiltinCall::Finish(map_optional_id()?),
                     "display" | "info" | "warning" | "error" | "fatal" => {
                         cx.emit(
-                            DiagBuilder2::warning(format!(
-                                "unsupported: system task `${}`; ignored",
-                                ident
-                            ))
+                            DiagBuilder2::lint(
+                                &UNSUPPORTED,
+                                format!("unsupported: system task `${}`; ignored", ident),
+                            )
                             .span(expr.human_span()),
                         );
                         hir::BuiltinCall::Unsupported
@@ -1218,16 +1983,41 @@ fn lower_expr_inner<'gcx>(
                     }
                 })
             }
-            ast::IdentExpr(name) => {
-                let target =
-                    cx.resolve_upwards_or_error(name, cx.parent_node_id(node_id).unwrap())?;
-                hir::ExprKind::FunctionCall(
-                    target,
-                    args.iter()
-                        .map(|arg| lower_call_arg(cx, arg, node_id))
-                        .collect(),
-                )
+            ast::IdentExpr(..) | ast::ScopeExpr(..) => {
+                let target = match callee.data {
+                    ast::IdentExpr(name) => {
+                        cx.resolve_upwards_or_error(name, cx.parent_node_id(node_id).unwrap())?
+                    }
+                    ast::ScopeExpr(ref scope, name) => {
+                        let scope_id =
+                            cx.map_ast_with_parent(AstNode::Expr(scope.as_ref()), node_id);
+                        let within = cx.resolve_node(scope_id, cx.default_param_env())?;
+                        cx.resolve_downwards_or_error(name, within)?
+                    }
+                    _ => unreachable!(),
+                };
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| lower_call_arg(cx, arg, node_id))
+                    .collect();
+                match cx.hir_of(target)? {
+                    HirNode::LetDecl(decl) => hir::ExprKind::Let(
+                        target,
+                        bind_let_args(cx, decl, &args, expr.human_span())?,
+                    ),
+                    _ => hir::ExprKind::FunctionCall(target, args),
+                }
             }
+            ast::MemberExpr {
+                expr: ref target,
+                name,
+            } => hir::ExprKind::MethodCall(
+                cx.map_ast_with_parent(AstNode::Expr(target), node_id),
+                name,
+                args.iter()
+                    .map(|arg| lower_call_arg(cx, arg, node_id))
+                    .collect(),
+            ),
             _ => {
                 error!("{:#?}", callee);
                 cx.emit(
@@ -1240,6 +2030,16 @@ fn lower_expr_inner<'gcx>(
                 return Err(());
             }
         },
+        ast::ConstructorCallExpr(ref args) => hir::ExprKind::New(
+            args.iter()
+                .map(|arg| lower_call_arg(cx, arg, node_id))
+                .collect(),
+        ),
+        ast::ClassNewExpr(None) => hir::ExprKind::New(vec![]),
+        ast::ClassNewExpr(Some(_)) => {
+            cx.emit(DiagBuilder2::error("unsupported: shallow copy with `new`").span(expr.span()));
+            return Err(());
+        }
         ast::TernaryExpr {
             ref cond,
             ref true_expr,
@@ -1407,21 +2207,7 @@ fn lower_expr_inner<'gcx>(
             cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
             ranges
                 .iter()
-                .map(|vr| match vr {
-                    ast::ValueRange::Single(expr) => Spanned::new(
-                        hir::InsideRange::Single(
-                            cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
-                        ),
-                        expr.span,
-                    ),
-                    ast::ValueRange::Range { lo, hi, span } => Spanned::new(
-                        hir::InsideRange::Range(
-                            cx.map_ast_with_parent(AstNode::Expr(lo), node_id),
-                            cx.map_ast_with_parent(AstNode::Expr(hi), node_id),
-                        ),
-                        *span,
-                    ),
-                })
+                .map(|vr| lower_value_range(cx, vr, node_id))
                 .collect(),
         ),
         ast::BitsExpr { ref arg, .. } => hir::ExprKind::Builtin(hir::BuiltinCall::Bits(arg)),
@@ -1517,6 +2303,31 @@ fn lower_event_expr<'gcx>(
     Ok(())
 }
 
+/// Lower a single value or value range of an `inside` set or bins declaration.
+fn lower_value_range<'gcx>(
+    cx: &impl Context<'gcx>,
+    range: &'gcx ast::ValueRange<'gcx>,
+    parent_id: NodeId,
+) -> Spanned<hir::InsideRange> {
+    match *range {
+        ast::ValueRange::Single(ref expr) => Spanned::new(
+            hir::InsideRange::Single(cx.map_ast_with_parent(AstNode::Expr(expr), parent_id)),
+            expr.span,
+        ),
+        ast::ValueRange::Range {
+            ref lo,
+            ref hi,
+            span,
+        } => Spanned::new(
+            hir::InsideRange::Range(
+                cx.map_ast_with_parent(AstNode::Expr(lo), parent_id),
+                cx.map_ast_with_parent(AstNode::Expr(hi), parent_id),
+            ),
+            span,
+        ),
+    }
+}
+
 /// Lower a list of genvar declarations.
 fn alloc_genvar_init<'gcx>(
     cx: &impl Context<'gcx>,
@@ -1659,6 +2470,29 @@ fn lower_package<'gcx>(
             ast::ItemData::SubroutineDecl(ref decl) => {
                 warn!("ignoring unsupported subroutine `{}`", decl.prototype.name)
             }
+            ast::ItemData::LetDecl(ref decl) => {
+                next_rib = cx.map_ast_with_parent(AstNode::LetDecl(decl), next_rib);
+                names.push((decl.name, next_rib));
+            }
+            ast::ItemData::ClassDecl(ref decl) => {
+                next_rib = cx.map_ast_with_parent(AstNode::ClassDecl(decl), next_rib);
+                names.push((decl.name, next_rib));
+            }
+            ast::ItemData::DpiDecl(ref decl) => match decl.data {
+                ast::DpiDeclData::Import { ref prototype, .. } => {
+                    next_rib = cx.map_ast_with_parent(AstNode::DpiDecl(decl), next_rib);
+                    names.push((prototype.name, next_rib));
+                }
+                ast::DpiDeclData::Export { .. } => {
+                    cx.emit(
+                        DiagBuilder2::lint(
+                            &UNSUPPORTED,
+                            "unsupported: DPI export in package; ignored",
+                        )
+                        .span(decl.span),
+                    );
+                }
+            },
             _ => {
                 cx.emit(
                     DiagBuilder2::error(format!("{:#} cannot appear in a package", item))
@@ -1700,6 +2534,293 @@ pub(crate) fn lower_index_mode<'gcx>(
     }
 }
 
+/// Lower the arguments of a subroutine prototype.
+///
+/// Arguments without an explicit direction inherit the direction of the
+/// preceding argument, or default to `input` (see IEEE 1800-2017 §13.3).
+fn lower_subroutine_args<'gcx>(
+    cx: &impl Context<'gcx>,
+    parent: NodeId,
+    ast: &'gcx [ast::SubroutinePort<'gcx>],
+) -> Vec<hir::SubroutineArg> {
+    let mut dir = ast::SubroutinePortDir::Input;
+    ast.iter()
+        .map(|port| {
+            dir = port.dir.unwrap_or(dir);
+            hir::SubroutineArg {
+                id: cx.map_ast_with_parent(AstNode::SubroutinePort(port), parent),
+                span: port.span,
+                name: port.name.as_ref().map(|n| n.name),
+                dir,
+                ty: cx.map_ast_with_parent(AstNode::Type(&port.ty), parent),
+            }
+        })
+        .collect()
+}
+
+/// Lower the statements in the body of a subroutine.
+///
+/// Each statement is nested within the preceding one, such that declarations
+/// are only visible to the statements that follow them.
+fn lower_subroutine_body<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    decl: &'gcx ast::SubroutineDecl<'gcx>,
+) -> Vec<NodeId> {
+    let mut next_rib = node_id;
+    decl.items
+        .iter()
+        .flat_map(|item| match *item {
+            ast::SubroutineItem::Stmt(ref stmt) => {
+                next_rib = cx.map_ast_with_parent(AstNode::Stmt(stmt), next_rib);
+                Some(next_rib)
+            }
+            ast::SubroutineItem::PortDecl(..) => None,
+        })
+        .collect()
+}
+
+/// Lower a class declaration to HIR.
+fn lower_class<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    decl: &'gcx ast::ClassDecl<'gcx>,
+) -> Result<HirNode<'gcx>> {
+    let mut next_rib = node_id;
+    let mut params = vec![];
+    for param in &decl.params {
+        next_rib = alloc_param_decl(cx, param, next_rib, &mut params);
+    }
+    let extends = decl.extends.as_ref().map(|(ty, args)| {
+        (
+            cx.map_ast_with_parent(AstNode::Type(ty), node_id),
+            args.iter()
+                .map(|arg| lower_call_arg(cx, arg, node_id))
+                .collect(),
+        )
+    });
+    let mut props = vec![];
+    let mut methods = vec![];
+    let mut constraints = vec![];
+    for item in &decl.items {
+        let has = |q| item.qualifiers.iter().any(|&(x, _)| x == q);
+        match item.data {
+            ast::ClassItemData::Property(ref var) => {
+                let mut ids = vec![];
+                next_rib = alloc_var_decl(cx, var, next_rib, &mut ids);
+                for (id, name) in ids.into_iter().zip(var.names.iter()) {
+                    props.push(hir::ClassProperty {
+                        id,
+                        name: Spanned::new(name.name, name.name_span),
+                        statik: has(ast::ClassItemQualifier::Static),
+                        rand: if has(ast::ClassItemQualifier::Randc) {
+                            Some(ast::RandomQualifier::Randc)
+                        } else if has(ast::ClassItemQualifier::Rand) {
+                            Some(ast::RandomQualifier::Rand)
+                        } else {
+                            None
+                        },
+                    });
+                }
+            }
+            ast::ClassItemData::SubroutineDecl(ref sub) => {
+                let id = cx.map_ast_with_parent(AstNode::SubroutineDecl(sub), next_rib);
+                methods.push(hir::ClassMethod {
+                    id,
+                    name: sub.prototype.name,
+                    statik: has(ast::ClassItemQualifier::Static),
+                    virt: has(ast::ClassItemQualifier::Virtual),
+                });
+            }
+            ast::ClassItemData::Typedef(ref def) => {
+                next_rib = cx.map_ast_with_parent(AstNode::Typedef(def), next_rib);
+            }
+            ast::ClassItemData::ParamDecl(ref param) => {
+                next_rib = alloc_param_decl(cx, param, next_rib, &mut params);
+            }
+            ast::ClassItemData::Constraint(ref constraint)
+                if constraint.kind == ast::ConstraintKind::Decl =>
+            {
+                constraints.push(hir::Constraint {
+                    name: Spanned::new(constraint.name, constraint.name_span),
+                    items: lower_constraint_items(cx, &constraint.items, node_id),
+                });
+            }
+            ast::ClassItemData::Null => (),
+            _ => {
+                cx.emit(
+                    DiagBuilder2::lint(
+                        &UNSUPPORTED,
+                        format!(
+                            "unsupported: {} in class; ignored",
+                            match item.data {
+                                ast::ClassItemData::ExternSubroutine(..) => "method prototype",
+                                ast::ClassItemData::Constraint(..) => "constraint prototype",
+                                ast::ClassItemData::ClassDecl => "nested class",
+                                ast::ClassItemData::CovergroupDecl => "covergroup",
+                                _ => unreachable!(),
+                            }
+                        ),
+                    )
+                    .span(item.span),
+                );
+            }
+        }
+    }
+    let hir = hir::Class {
+        id: node_id,
+        ast: decl,
+        params,
+        extends,
+        props,
+        methods,
+        constraints,
+    };
+    Ok(HirNode::Class(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower the items of a constraint block or inline constraint.
+fn lower_constraint_items<'gcx>(
+    cx: &impl Context<'gcx>,
+    items: &'gcx [ast::ConstraintItem<'gcx>],
+    parent_id: NodeId,
+) -> Vec<hir::ConstraintItem> {
+    let map_expr = |expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent_id);
+    items
+        .iter()
+        .map(|item| match item.data {
+            ast::ConstraintItemData::Expr(ref expr) => hir::ConstraintItem::Expr(map_expr(expr)),
+            ast::ConstraintItemData::Dist(ref expr, ref dist) => hir::ConstraintItem::Dist(
+                map_expr(expr),
+                dist.iter()
+                    .map(|d| hir::DistItem {
+                        range: lower_value_range(cx, &d.range, parent_id),
+                        weight: d.weight.as_ref().map(|(kind, w)| (*kind, map_expr(w))),
+                    })
+                    .collect(),
+            ),
+            ast::ConstraintItemData::Implication(ref cond, ref items) => {
+                hir::ConstraintItem::Implication(
+                    map_expr(cond),
+                    lower_constraint_items(cx, items, parent_id),
+                )
+            }
+            ast::ConstraintItemData::If(ref cond, ref main, ref alt) => hir::ConstraintItem::If(
+                map_expr(cond),
+                lower_constraint_items(cx, main, parent_id),
+                lower_constraint_items(cx, alt, parent_id),
+            ),
+            ast::ConstraintItemData::Foreach(ref array, ref indices, ref items) => {
+                hir::ConstraintItem::Foreach(
+                    map_expr(array),
+                    indices
+                        .iter()
+                        .map(|index| {
+                            (
+                                index.index,
+                                cx.map_ast_with_parent(AstNode::ForeachIndex(index), parent_id),
+                            )
+                        })
+                        .collect(),
+                    lower_constraint_items(cx, items, parent_id),
+                )
+            }
+            ast::ConstraintItemData::SolveBefore(ref before, ref after) => {
+                hir::ConstraintItem::SolveBefore(
+                    before.iter().map(map_expr).collect(),
+                    after.iter().map(map_expr).collect(),
+                )
+            }
+        })
+        .collect()
+}
+
+/// Bind the arguments of a reference to a `let` declaration.
+///
+/// Positional arguments bind to the formal arguments in order, named arguments
+/// bind by name. Formal arguments without an actual argument fall back to their
+/// default. Returns the expression bound to each formal argument. See IEEE
+/// 1800-2017 §11.12.
+pub(crate) fn bind_let_args<'gcx>(
+    cx: &impl Context<'gcx>,
+    decl: &'gcx hir::LetDecl,
+    args: &[hir::CallArg],
+    span: Span,
+) -> Result<Vec<(NodeId, NodeId)>> {
+    let formals = decl
+        .args
+        .iter()
+        .map(|&id| match cx.hir_of(id)? {
+            HirNode::LetArg(x) => Ok(x),
+            _ => unreachable!(),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut bound = vec![None; formals.len()];
+    let mut failed = false;
+    for (index, call_arg) in args.iter().enumerate() {
+        let pos = match call_arg.name {
+            Some(name) => formals.iter().position(|arg| arg.name.value == name.value),
+            None if index < formals.len() => Some(index),
+            None => None,
+        };
+        let pos = match pos {
+            Some(pos) => pos,
+            None => {
+                let msg = match call_arg.name {
+                    Some(name) => format!("{} has no argument `{}`", decl.desc_full(), name),
+                    None => format!(
+                        "too many arguments to {}; expected {}",
+                        decl.desc_full(),
+                        formals.len()
+                    ),
+                };
+                cx.emit(
+                    DiagBuilder2::error(msg)
+                        .span(call_arg.span)
+                        .add_note(format!("{} declared here:", decl.desc_full()))
+                        .span(decl.span),
+                );
+                failed = true;
+                continue;
+            }
+        };
+        if bound[pos].is_some() {
+            cx.emit(
+                DiagBuilder2::error(format!("argument {} bound multiple times", pos + 1))
+                    .span(call_arg.span),
+            );
+            failed = true;
+            continue;
+        }
+        bound[pos] = Some(call_arg);
+    }
+
+    let mut bindings = vec![];
+    for (arg, call_arg) in formals.iter().zip(bound) {
+        match call_arg.and_then(|a| a.expr).or(arg.default) {
+            Some(expr) => bindings.push((arg.id, expr)),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "missing argument `{}` in reference to {}",
+                        arg.name,
+                        decl.desc_full()
+                    ))
+                    .span(span)
+                    .add_note(format!("{} declared here:", decl.desc_full()))
+                    .span(decl.span),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(bindings)
+    }
+}
+
 /// Lower a function or method call argument to HIR.
 fn lower_call_arg<'gcx>(
     cx: &impl Context<'gcx>,
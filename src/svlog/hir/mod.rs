@@ -41,6 +41,14 @@ make_arenas!(
         packages: Package,
         enum_variants: EnumVariant,
         subroutines: Subroutine,
+        let_decls: LetDecl,
+        let_args: LetArg,
+        covergroups: Covergroup,
+        coverpoints: Coverpoint,
+        clocking_blocks: ClockingBlock,
+        clock_vars: ClockVar,
+        gates: Gate,
+        classes: Class<'hir>,
     }
 );
 
@@ -59,6 +67,7 @@ pub(crate) fn accessed_nodes<'a>(
             read: Default::default(),
             written: Default::default(),
         },
+        inlined: vec![],
     };
     k.visit_node_with_id(node_id, false);
     Ok(Arc::new(k.table))
@@ -86,13 +95,15 @@ pub enum AccessedNode {
     Regular(NodeId),
     /// An interface signal.
     Intf(NodeId, NodeId),
+    /// A signal inside another instance, referred to hierarchically.
+    Hier(HierPath, NodeId),
 }
 
 impl AccessedNode {
     /// Get the ID of the accessed node, dropping any context information.
     pub fn id(&self) -> NodeId {
         match *self {
-            Self::Regular(id) | Self::Intf(_, id) => id,
+            Self::Regular(id) | Self::Intf(_, id) | Self::Hier(_, id) => id,
         }
     }
 }
@@ -103,11 +114,22 @@ impl From<NodeId> for AccessedNode {
     }
 }
 
+/// A path of instances through the design hierarchy.
+///
+/// In a hierarchical reference such as `a.b.c.x`, this represents the `a.b.c`
+/// part, where each instance is declared within the module of the preceding
+/// one. This is merely a handle that is cheap to copy and pass around. Use the
+/// [`Context`] to resolve this to the actual instance nodes.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct HierPath(pub(crate) u32);
+
 /// A visitor for the HIR that populates an access table.
 struct AccessTableCollector<'a, C> {
     cx: &'a C,
     env: ParamEnv,
     table: AccessTable,
+    /// The tasks being inlined at the current point of the analysis.
+    inlined: Vec<NodeId>,
 }
 
 impl<'a, 'gcx: 'a, C> Visitor<'gcx> for AccessTableCollector<'a, C>
@@ -148,6 +170,10 @@ where
                 }
                 true
             }
+            mir::LvalueKind::HierSignal(path, sig) => {
+                self.table.written.insert(AccessedNode::Hier(path, sig));
+                false
+            }
             _ => true,
         }
     }
@@ -168,6 +194,24 @@ where
                 }
                 true
             }
+            mir::RvalueKind::HierSignal(path, sig) => {
+                self.table.read.insert(AccessedNode::Hier(path, sig));
+                false
+            }
+            mir::RvalueKind::Call { target, .. } => {
+                // Tasks are inlined into the calling process, which therefore
+                // accesses whatever the task body accesses.
+                if let Ok(HirNode::Subroutine(sub)) = self.cx.hir_of(target) {
+                    if sub.dpi.is_none() && !self.inlined.contains(&sub.id) {
+                        self.inlined.push(sub.id);
+                        for &stmt in &sub.body {
+                            Visitor::visit_node_with_id(self, stmt, false);
+                        }
+                        self.inlined.pop();
+                    }
+                }
+                true
+            }
             _ => true,
         }
     }
@@ -179,5 +223,9 @@ where
 {
     fn is_binding_interesting(&self, binding: NodeId) -> bool {
         !self.cx.is_parent_of(self.table.node_id, binding)
+            && !self
+                .inlined
+                .iter()
+                .any(|&sub| self.cx.is_parent_of(sub, binding))
     }
 }
@@ -34,6 +34,14 @@ pub enum HirNode<'a> {
     Package(&'a Package),
     EnumVariant(&'a EnumVariant),
     Subroutine(&'a Subroutine),
+    LetDecl(&'a LetDecl),
+    LetArg(&'a LetArg),
+    Covergroup(&'a Covergroup),
+    Coverpoint(&'a Coverpoint),
+    ClockingBlock(&'a ClockingBlock),
+    ClockVar(&'a ClockVar),
+    Gate(&'a Gate),
+    Class(&'a Class<'a>),
 }
 
 impl<'hir> HasSpan for HirNode<'hir> {
@@ -60,6 +68,14 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Package(x) => x.span(),
             HirNode::EnumVariant(x) => x.span(),
             HirNode::Subroutine(x) => x.span(),
+            HirNode::LetDecl(x) => x.span(),
+            HirNode::LetArg(x) => x.span(),
+            HirNode::Covergroup(x) => x.span(),
+            HirNode::Coverpoint(x) => x.span(),
+            HirNode::ClockingBlock(x) => x.span(),
+            HirNode::ClockVar(x) => x.span(),
+            HirNode::Gate(x) => x.span(),
+            HirNode::Class(x) => x.span(),
         }
     }
 
@@ -86,6 +102,14 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Package(x) => x.human_span(),
             HirNode::EnumVariant(x) => x.human_span(),
             HirNode::Subroutine(x) => x.human_span(),
+            HirNode::LetDecl(x) => x.human_span(),
+            HirNode::LetArg(x) => x.human_span(),
+            HirNode::Covergroup(x) => x.human_span(),
+            HirNode::Coverpoint(x) => x.human_span(),
+            HirNode::ClockingBlock(x) => x.human_span(),
+            HirNode::ClockVar(x) => x.human_span(),
+            HirNode::Gate(x) => x.human_span(),
+            HirNode::Class(x) => x.human_span(),
         }
     }
 }
@@ -114,6 +138,14 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Package(x) => x.desc(),
             HirNode::EnumVariant(x) => x.desc(),
             HirNode::Subroutine(x) => x.desc(),
+            HirNode::LetDecl(x) => x.desc(),
+            HirNode::LetArg(x) => x.desc(),
+            HirNode::Covergroup(x) => x.desc(),
+            HirNode::Coverpoint(x) => x.desc(),
+            HirNode::ClockingBlock(x) => x.desc(),
+            HirNode::ClockVar(x) => x.desc(),
+            HirNode::Gate(x) => x.desc(),
+            HirNode::Class(x) => x.desc(),
         }
     }
 
@@ -140,6 +172,14 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Package(x) => x.desc_full(),
             HirNode::EnumVariant(x) => x.desc_full(),
             HirNode::Subroutine(x) => x.desc_full(),
+            HirNode::LetDecl(x) => x.desc_full(),
+            HirNode::LetArg(x) => x.desc_full(),
+            HirNode::Covergroup(x) => x.desc_full(),
+            HirNode::Coverpoint(x) => x.desc_full(),
+            HirNode::ClockingBlock(x) => x.desc_full(),
+            HirNode::ClockVar(x) => x.desc_full(),
+            HirNode::Gate(x) => x.desc_full(),
+            HirNode::Class(x) => x.desc_full(),
         }
     }
 }
@@ -179,11 +219,15 @@ impl HasSpan for Module<'_> {
 
 impl HasDesc for Module<'_> {
     fn desc(&self) -> &'static str {
-        "module"
+        match self.ast.kind {
+            ast::ModuleKind::Module => "module",
+            ast::ModuleKind::Program => "program",
+            ast::ModuleKind::Checker => "checker",
+        }
     }
 
     fn desc_full(&self) -> String {
-        format!("module `{}`", self.ast.name)
+        format!("{} `{}`", self.desc(), self.ast.name)
     }
 }
 
@@ -202,6 +246,12 @@ pub struct ModuleBlock {
     pub params: Vec<NodeId>,
     /// The continuous assignments in the module.
     pub assigns: Vec<NodeId>,
+    /// The gate and switch primitive instances in the module.
+    pub gates: Vec<NodeId>,
+    /// The clocking blocks in the module.
+    pub clockings: Vec<NodeId>,
+    /// The type definitions in the module.
+    pub typedefs: Vec<NodeId>,
     /// The bottom of the name scope tree.
     pub last_rib: NodeId,
 }
@@ -525,6 +575,10 @@ pub enum TypeKind {
     RefExpr(NodeId),
     /// A type reference on a type, such as `type(int)`.
     RefType(NodeId),
+    /// A specialized class type, such as `C #(8)`.
+    ///
+    /// Refers to the type being specialized.
+    Specialized(NodeId),
 }
 
 impl HasDesc for TypeKind {
@@ -675,6 +729,25 @@ pub enum ExprKind<'a> {
     Inside(NodeId, Vec<Spanned<InsideRange>>),
     /// A function call such as `foo(a, b, c)`.
     FunctionCall(NodeId, Vec<CallArg>),
+    /// A reference to a `let` declaration such as `foo(a, b)`, with the
+    /// expression bound to each of its formal arguments.
+    Let(NodeId, Vec<(NodeId, NodeId)>),
+    /// A method call such as `foo.sample()`.
+    MethodCall(NodeId, Spanned<Name>, Vec<CallArg>),
+    /// A constructor call such as `new` or `new(a, b)`.
+    New(Vec<CallArg>),
+    /// A call to the `std::randomize(a, b)` function.
+    StdRandomize(Vec<NodeId>),
+    /// A call to `randomize()` with inline constraints, such as
+    /// `foo.randomize() with { a < b; }`.
+    InlineConstraint(NodeId, Vec<ConstraintItem>),
+    /// The handle to the current object, `this`.
+    This,
+    /// The handle to the current object as an instance of its base class,
+    /// `super`.
+    Super,
+    /// The null class handle, `null`.
+    Null,
     /// An assignment.
     Assign {
         op: ast::AssignOp,
@@ -853,6 +926,18 @@ pub enum BuiltinCall<'a> {
     IsUnknown(&'a ast::Expr<'a>),
     /// A call to one of the array dimension functions.
     ArrayDim(ArrayDim, &'a ast::Expr<'a>, Option<&'a ast::Expr<'a>>),
+    /// A call to the `$time` function.
+    Time,
+    /// A call to the `$realtime` function.
+    RealTime,
+    /// A call to the `$random` function, with an optional seed variable.
+    Random(Option<NodeId>),
+    /// A call to the `$urandom` function, with an optional seed.
+    Urandom(Option<NodeId>),
+    /// A call to the `$urandom_range(max, min)` function.
+    UrandomRange(NodeId, Option<NodeId>),
+    /// A call to the `$finish` task, with an optional diagnostic level.
+    Finish(Option<NodeId>),
 }
 
 /// The different builtin array dimension function calls that are supported.
@@ -971,6 +1056,37 @@ impl HasDesc for Stmt {
     }
 }
 
+/// The synthesis attributes of an `if` or `case` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaseSynthesis {
+    /// At least one branch matches, either because the statement has an
+    /// `else` or `default` branch, or because it is `unique` or `priority`.
+    pub full_case: bool,
+    /// At most one branch matches, because the statement is `unique` or
+    /// `unique0`.
+    pub parallel_case: bool,
+}
+
+impl StmtKind {
+    /// Determine the `full_case` and `parallel_case` synthesis attributes of
+    /// an `if` or `case` statement.
+    ///
+    /// Returns `None` for all other statements. For an `if` statement, this
+    /// only considers the qualifier and `else` branch of this statement, not
+    /// of the `else if` chain it starts.
+    pub fn case_synthesis(&self) -> Option<CaseSynthesis> {
+        let (up, has_default) = match *self {
+            StmtKind::If { up, else_stmt, .. } => (up, else_stmt.is_some()),
+            StmtKind::Case { up, default, .. } => (up, default.is_some()),
+            _ => return None,
+        };
+        Some(CaseSynthesis {
+            full_case: has_default || up.map(|up| up.is_full()).unwrap_or(false),
+            parallel_case: up.map(|up| up.is_parallel()).unwrap_or(false),
+        })
+    }
+}
+
 /// The different forms a statement can take.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StmtKind {
@@ -994,9 +1110,13 @@ pub enum StmtKind {
     /// An if statement.
     ///
     /// ```text
-    /// if (<cond>) <main_stmt> [else <else_stmt>]
+    /// [unique|unique0|priority] if (<cond>) <main_stmt> [else <else_stmt>]
     /// ```
+    ///
+    /// The qualifier applies to the entire `else if` chain, and is only
+    /// recorded on its first statement.
     If {
+        up: Option<ast::UniquePriority>,
         cond: NodeId,
         main_stmt: NodeId,
         else_stmt: Option<NodeId>,
@@ -1012,11 +1132,30 @@ pub enum StmtKind {
     InlineGroup { stmts: Vec<NodeId>, rib: NodeId },
     /// A case statement.
     Case {
+        up: Option<ast::UniquePriority>,
         expr: NodeId,
         ways: Vec<(Vec<NodeId>, NodeId)>,
         default: Option<NodeId>,
         kind: ast::CaseKind,
     },
+    /// A return statement with an optional return value.
+    Return(Option<NodeId>),
+    /// An event trigger.
+    ///
+    /// ```text
+    /// -> <target>;
+    /// ->> [#<delay>] <target>;
+    /// ```
+    ///
+    /// The kind is a blocking identity assignment for `->`, and a nonblocking
+    /// assignment for `->>`.
+    Trigger { target: NodeId, kind: AssignKind },
+    /// A wait statement.
+    ///
+    /// ```text
+    /// wait (<cond>) <stmt>
+    /// ```
+    Wait { cond: NodeId, stmt: NodeId },
 }
 
 /// The different forms an assignment can take.
@@ -1304,7 +1443,7 @@ pub struct CallArg {
 }
 
 /// A subroutine declaration.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Subroutine {
     pub id: NodeId,
     pub name: Spanned<Name>,
@@ -1313,6 +1452,28 @@ pub struct Subroutine {
     pub kind: ast::SubroutineKind,
     /// Optional return type in case of a function.
     pub retty: Option<NodeId>,
+    /// The arguments of the subroutine.
+    pub args: Vec<SubroutineArg>,
+    /// The statements in the body of the subroutine.
+    pub body: Vec<NodeId>,
+    /// The DPI import details, if the subroutine is implemented in C.
+    pub dpi: Option<DpiImport>,
+}
+
+impl Subroutine {
+    /// Find the argument a call argument binds to.
+    ///
+    /// Positional call arguments bind to the subroutine arguments in order,
+    /// named call arguments bind to the argument with the same name.
+    pub fn find_arg(&self, call_arg: &CallArg, index: usize) -> Option<&SubroutineArg> {
+        match call_arg.name {
+            Some(name) => self
+                .args
+                .iter()
+                .find(|arg| arg.name.map(|n| n.value) == Some(name.value)),
+            None => self.args.get(index),
+        }
+    }
 }
 
 impl HasSpan for Subroutine {
@@ -1340,3 +1501,430 @@ impl HasDesc for Subroutine {
         }
     }
 }
+
+/// A subroutine argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubroutineArg {
+    /// The node which declares the argument within the subroutine body.
+    pub id: NodeId,
+    /// Span of the entire argument.
+    pub span: Span,
+    /// Optional argument name.
+    pub name: Option<Spanned<Name>>,
+    /// The direction of the argument.
+    pub dir: ast::SubroutinePortDir,
+    /// The type of the argument.
+    pub ty: NodeId,
+}
+
+/// The details of a DPI import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DpiImport {
+    /// The name of the C function implementing the subroutine.
+    pub cname: Spanned<Name>,
+    /// Whether the import is `pure` or `context`.
+    pub property: Option<ast::DpiProperty>,
+}
+
+/// A `let` declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetDecl {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The formal arguments.
+    pub args: Vec<NodeId>,
+    /// The expression the declaration expands to.
+    pub expr: NodeId,
+}
+
+impl HasSpan for LetDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for LetDecl {
+    fn desc(&self) -> &'static str {
+        "let declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("let declaration `{}`", self.name)
+    }
+}
+
+/// A formal argument of a `let` declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetArg {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The type of the argument, or `None` if it is untyped.
+    pub ty: Option<NodeId>,
+    /// The default value of the argument.
+    pub default: Option<NodeId>,
+}
+
+impl HasSpan for LetArg {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for LetArg {
+    fn desc(&self) -> &'static str {
+        "let argument"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("let argument `{}`", self.name)
+    }
+}
+
+/// A class declaration.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Class<'a> {
+    pub id: NodeId,
+    /// The AST node.
+    pub ast: &'a ast::ClassDecl<'a>,
+    /// The parameters of the class.
+    pub params: Vec<NodeId>,
+    /// The base class and the arguments passed to its constructor.
+    pub extends: Option<(NodeId, Vec<CallArg>)>,
+    /// The properties declared in the class.
+    pub props: Vec<ClassProperty>,
+    /// The methods declared in the class.
+    pub methods: Vec<ClassMethod>,
+    /// The constraint blocks declared in the class.
+    pub constraints: Vec<Constraint>,
+}
+
+impl<'a> Deref for Class<'a> {
+    type Target = &'a ast::ClassDecl<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ast
+    }
+}
+
+impl HasSpan for Class<'_> {
+    fn span(&self) -> Span {
+        self.ast.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.ast.name.span
+    }
+}
+
+impl HasDesc for Class<'_> {
+    fn desc(&self) -> &'static str {
+        "class"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("class `{}`", self.ast.name)
+    }
+}
+
+/// A property of a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassProperty {
+    /// The variable declaration of the property.
+    pub id: NodeId,
+    /// The name of the property.
+    pub name: Spanned<Name>,
+    /// Whether the property is shared among all instances of the class.
+    pub statik: bool,
+    /// Whether the property is declared `rand` or `randc`.
+    pub rand: Option<ast::RandomQualifier>,
+}
+
+/// A method of a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassMethod {
+    /// The subroutine declaration of the method.
+    pub id: NodeId,
+    /// The name of the method.
+    pub name: Spanned<Name>,
+    /// Whether the method is called without an object.
+    pub statik: bool,
+    /// Whether the method is declared `virtual`.
+    pub virt: bool,
+}
+
+/// A constraint block of a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    /// The name of the constraint block.
+    pub name: Spanned<Name>,
+    /// The constraints in the block.
+    pub items: Vec<ConstraintItem>,
+}
+
+/// A constraint on the random variables of a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintItem {
+    /// An expression that must hold.
+    Expr(NodeId),
+    /// A distribution of the values of an expression.
+    Dist(NodeId, Vec<DistItem>),
+    /// Constraints that must hold if a condition holds.
+    Implication(NodeId, Vec<ConstraintItem>),
+    /// Constraints that must hold if a condition holds or does not hold.
+    If(NodeId, Vec<ConstraintItem>, Vec<ConstraintItem>),
+    /// Constraints that must hold for every element of an array, together
+    /// with the dimension each loop variable iterates over.
+    Foreach(NodeId, Vec<(usize, NodeId)>, Vec<ConstraintItem>),
+    /// Random variables that must be solved before others.
+    SolveBefore(Vec<NodeId>, Vec<NodeId>),
+}
+
+/// An item of a `dist` constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistItem {
+    /// The values the weight applies to.
+    pub range: Spanned<InsideRange>,
+    /// The weight and how it applies to the values.
+    pub weight: Option<(ast::DistWeight, NodeId)>,
+}
+
+/// A covergroup declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Covergroup {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The event upon which the covergroup is sampled automatically.
+    pub event: Option<NodeId>,
+    /// The coverpoints.
+    pub points: Vec<NodeId>,
+    /// The cross coverage declarations.
+    pub crosses: Vec<CoverCross>,
+}
+
+impl HasSpan for Covergroup {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Covergroup {
+    fn desc(&self) -> &'static str {
+        "covergroup"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("covergroup `{}`", self.name)
+    }
+}
+
+/// A coverpoint within a covergroup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverpoint {
+    pub id: NodeId,
+    /// The label of the coverpoint, or the sampled expression if unlabeled.
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The sampled expression.
+    pub expr: NodeId,
+    /// The optional `iff` guard.
+    pub iff: Option<NodeId>,
+    /// The explicitly declared bins. Automatic bins are created if none of
+    /// these are regular `bins`.
+    pub bins: Vec<CoverBins>,
+}
+
+impl HasSpan for Coverpoint {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Coverpoint {
+    fn desc(&self) -> &'static str {
+        "coverpoint"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("coverpoint `{}`", self.name)
+    }
+}
+
+/// A bins declaration within a coverpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverBins {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub kind: ast::CoverBinsKind,
+    /// Whether this is an array of bins, with an optional explicit size.
+    pub array: Option<Option<NodeId>>,
+    /// The covered values, or `None` for `default`.
+    pub values: Option<Vec<Spanned<InsideRange>>>,
+    /// The optional `iff` guard.
+    pub iff: Option<NodeId>,
+}
+
+/// A cross coverage declaration within a covergroup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverCross {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The crossed coverpoints.
+    pub points: Vec<NodeId>,
+    /// The optional `iff` guard.
+    pub iff: Option<NodeId>,
+}
+
+/// A clocking block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockingBlock {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// Whether this is the default clocking block of the enclosing scope.
+    pub default: bool,
+    /// The clocking event.
+    pub event: NodeId,
+    /// The signals declared in the block.
+    pub signals: Vec<NodeId>,
+}
+
+impl HasSpan for ClockingBlock {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for ClockingBlock {
+    fn desc(&self) -> &'static str {
+        "clocking block"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("clocking block `{}`", self.name)
+    }
+}
+
+/// A signal declared in a clocking block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockVar {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The clocking block this signal is declared in.
+    pub block: NodeId,
+    pub dir: ast::ClockingDir,
+    /// The input skew, with the block's default applied.
+    pub input_skew: ClockingSkew,
+    /// The output skew, with the block's default applied.
+    pub output_skew: ClockingSkew,
+    /// The expression the signal refers to.
+    pub expr: NodeId,
+}
+
+impl ClockVar {
+    /// Get the explicit input skew of the signal, if it is an input and has
+    /// one.
+    pub fn input_delay(&self) -> Option<NodeId> {
+        match self.input_skew {
+            ClockingSkew::Delay(skew) if self.dir.is_input() => Some(skew),
+            _ => None,
+        }
+    }
+}
+
+impl HasSpan for ClockVar {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for ClockVar {
+    fn desc(&self) -> &'static str {
+        "clocking signal"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("clocking signal `{}`", self.name)
+    }
+}
+
+/// The skew of a clocking block signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockingSkew {
+    /// `#1step`, the default for inputs. The value right before the clocking
+    /// event is sampled.
+    Step,
+    /// No skew, the default for outputs.
+    Zero,
+    /// An explicit delay.
+    Delay(NodeId),
+}
+
+/// A gate or switch primitive instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate {
+    pub id: NodeId,
+    pub name: Option<Spanned<Name>>,
+    pub span: Span,
+    pub kind: ast::GateKind,
+    /// The output terminals. For bidirectional switches, these are the two
+    /// inout terminals.
+    pub outputs: Vec<NodeId>,
+    /// The input terminals, with the data inputs before the control inputs.
+    pub inputs: Vec<NodeId>,
+    /// The propagation delay.
+    pub delay: Option<NodeId>,
+    /// The drive strength, or the single pull strength of a `pullup` or
+    /// `pulldown`.
+    pub strength: Option<(ast::DriveStrength, Option<ast::DriveStrength>)>,
+}
+
+impl HasSpan for Gate {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.map(|n| n.span).unwrap_or(self.span)
+    }
+}
+
+impl HasDesc for Gate {
+    fn desc(&self) -> &'static str {
+        "primitive instance"
+    }
+
+    fn desc_full(&self) -> String {
+        match self.name {
+            Some(name) => format!("`{}` instance `{}`", self.kind, name),
+            None => format!("`{}` instance", self.kind),
+        }
+    }
+}
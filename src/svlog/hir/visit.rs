@@ -41,6 +41,11 @@ pub trait Visitor<'a>: Sized {
             HirNode::ExtPort(x) => self.visit_ext_port(x),
             HirNode::Inst(x) => self.visit_inst(x),
             HirNode::InstTarget(x) => self.visit_inst_target(x),
+            HirNode::Covergroup(x) => self.visit_covergroup(x),
+            HirNode::Coverpoint(x) => self.visit_coverpoint(x),
+            HirNode::ClockingBlock(x) => self.visit_clocking_block(x),
+            HirNode::ClockVar(x) => self.visit_clock_var(x),
+            HirNode::Gate(x) => self.visit_gate(x),
             _ => (),
         }
     }
@@ -104,6 +109,26 @@ pub trait Visitor<'a>: Sized {
     fn visit_inst_target(&mut self, hir: &'a InstTarget<'a>) {
         walk_inst_target(self, hir);
     }
+
+    fn visit_covergroup(&mut self, hir: &'a Covergroup) {
+        walk_covergroup(self, hir);
+    }
+
+    fn visit_coverpoint(&mut self, hir: &'a Coverpoint) {
+        walk_coverpoint(self, hir);
+    }
+
+    fn visit_gate(&mut self, hir: &'a Gate) {
+        walk_gate(self, hir);
+    }
+
+    fn visit_clocking_block(&mut self, hir: &'a ClockingBlock) {
+        walk_clocking_block(self, hir);
+    }
+
+    fn visit_clock_var(&mut self, hir: &'a ClockVar) {
+        walk_clock_var(self, hir);
+    }
 }
 
 /// Walk the contents of a module.
@@ -140,6 +165,9 @@ pub fn walk_module_block<'a>(visitor: &mut impl Visitor<'a>, blk: &'a ModuleBloc
     for &id in &blk.assigns {
         visitor.visit_node_with_id(id, false);
     }
+    for &id in &blk.gates {
+        visitor.visit_node_with_id(id, false);
+    }
 }
 
 /// Walk the contents of a procedure.
@@ -170,6 +198,7 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
             cond,
             main_stmt,
             else_stmt,
+            ..
         } => {
             visitor.visit_node_with_id(cond, false);
             visitor.visit_node_with_id(main_stmt, false);
@@ -213,6 +242,19 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
                 visitor.visit_node_with_id(default, false);
             }
         }
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                visitor.visit_node_with_id(expr, false);
+            }
+        }
+        StmtKind::Trigger { target, .. } => {
+            visitor.visit_node_with_id(target, true);
+            visitor.visit_node_with_id(target, false);
+        }
+        StmtKind::Wait { cond, stmt } => {
+            visitor.visit_node_with_id(cond, false);
+            visitor.visit_node_with_id(stmt, false);
+        }
     }
 }
 
@@ -220,6 +262,11 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
 pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: bool) {
     match expr.kind {
         ExprKind::Builtin(BuiltinCall::Unsupported)
+        | ExprKind::This
+        | ExprKind::Super
+        | ExprKind::Null
+        | ExprKind::Builtin(BuiltinCall::Time)
+        | ExprKind::Builtin(BuiltinCall::RealTime)
         | ExprKind::IntConst { .. }
         | ExprKind::UnsizedConst(_)
         | ExprKind::TimeConst(_)
@@ -266,6 +313,27 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
                 visitor.visit_node_with_id(dim.id(), false);
             }
         }
+        ExprKind::Builtin(BuiltinCall::Random(seed)) => {
+            if let Some(seed) = seed {
+                visitor.visit_node_with_id(seed, true);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::Urandom(seed)) => {
+            if let Some(seed) = seed {
+                visitor.visit_node_with_id(seed, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::UrandomRange(max, min)) => {
+            visitor.visit_node_with_id(max, false);
+            if let Some(min) = min {
+                visitor.visit_node_with_id(min, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::Finish(level)) => {
+            if let Some(level) = level {
+                visitor.visit_node_with_id(level, false);
+            }
+        }
         ExprKind::Builtin(BuiltinCall::Bits(arg)) => {
             visitor.visit_node_with_id(arg.id(), false);
         }
@@ -336,10 +404,81 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
                 }
             }
         }
+        ExprKind::Let(_, ref bindings) => {
+            for &(_, expr) in bindings {
+                visitor.visit_node_with_id(expr, false);
+            }
+        }
+        ExprKind::MethodCall(target, _, ref args) => {
+            visitor.visit_node_with_id(target, false);
+            for &arg in args {
+                if let Some(expr) = arg.expr {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
+        ExprKind::New(ref args) => {
+            for &arg in args {
+                if let Some(expr) = arg.expr {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
         ExprKind::Assign { lhs, rhs, .. } => {
             visitor.visit_node_with_id(lhs.id, true);
             visitor.visit_node_with_id(rhs.id, false);
         }
+        ExprKind::StdRandomize(ref args) => {
+            for &arg in args {
+                visitor.visit_node_with_id(arg, true);
+            }
+        }
+        ExprKind::InlineConstraint(call, ref items) => {
+            visitor.visit_node_with_id(call, lvalue);
+            walk_constraint_items(visitor, items);
+        }
+    }
+}
+
+/// Walk the contents of a list of constraint items.
+pub fn walk_constraint_items<'a>(visitor: &mut impl Visitor<'a>, items: &'a [ConstraintItem]) {
+    for item in items {
+        match *item {
+            ConstraintItem::Expr(expr) => visitor.visit_node_with_id(expr, false),
+            ConstraintItem::Dist(expr, ref dist) => {
+                visitor.visit_node_with_id(expr, false);
+                for d in dist {
+                    match d.range.value {
+                        InsideRange::Single(expr) => visitor.visit_node_with_id(expr, false),
+                        InsideRange::Range(lo, hi) => {
+                            visitor.visit_node_with_id(lo, false);
+                            visitor.visit_node_with_id(hi, false);
+                        }
+                    }
+                    if let Some((_, weight)) = d.weight {
+                        visitor.visit_node_with_id(weight, false);
+                    }
+                }
+            }
+            ConstraintItem::Implication(cond, ref items) => {
+                visitor.visit_node_with_id(cond, false);
+                walk_constraint_items(visitor, items);
+            }
+            ConstraintItem::If(cond, ref main, ref alt) => {
+                visitor.visit_node_with_id(cond, false);
+                walk_constraint_items(visitor, main);
+                walk_constraint_items(visitor, alt);
+            }
+            ConstraintItem::Foreach(array, _, ref items) => {
+                visitor.visit_node_with_id(array, false);
+                walk_constraint_items(visitor, items);
+            }
+            ConstraintItem::SolveBefore(ref before, ref after) => {
+                for &expr in before.iter().chain(after.iter()) {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
     }
 }
 
@@ -417,3 +556,68 @@ pub fn walk_inst_target<'a>(visitor: &mut impl Visitor<'a>, hir: &'a InstTarget)
         visitor.visit_node_with_id(p, false);
     }
 }
+
+/// Walk the contents of a covergroup.
+///
+/// This visits the sampling event and the expressions that are sampled, but
+/// not the constant bin values.
+pub fn walk_covergroup<'a>(visitor: &mut impl Visitor<'a>, hir: &'a Covergroup) {
+    if let Some(event) = hir.event {
+        visitor.visit_node_with_id(event, false);
+    }
+    for &point in &hir.points {
+        visitor.visit_node_with_id(point, false);
+    }
+    for cross in &hir.crosses {
+        if let Some(iff) = cross.iff {
+            visitor.visit_node_with_id(iff, false);
+        }
+    }
+}
+
+/// Walk the contents of a coverpoint.
+pub fn walk_coverpoint<'a>(visitor: &mut impl Visitor<'a>, hir: &'a Coverpoint) {
+    visitor.visit_node_with_id(hir.expr, false);
+    if let Some(iff) = hir.iff {
+        visitor.visit_node_with_id(iff, false);
+    }
+}
+
+/// Walk the contents of a clocking block.
+pub fn walk_clocking_block<'a>(visitor: &mut impl Visitor<'a>, hir: &'a ClockingBlock) {
+    visitor.visit_node_with_id(hir.event, false);
+    for &signal in &hir.signals {
+        visitor.visit_node_with_id(signal, false);
+    }
+}
+
+/// Walk the contents of a clocking block signal.
+///
+/// The referenced expression is visited as an rvalue if the signal is sampled,
+/// and as an lvalue if it is driven.
+pub fn walk_clock_var<'a>(visitor: &mut impl Visitor<'a>, hir: &'a ClockVar) {
+    if hir.dir.is_input() {
+        visitor.visit_node_with_id(hir.expr, false);
+    }
+    if hir.dir.is_output() {
+        visitor.visit_node_with_id(hir.expr, true);
+    }
+    for &skew in &[hir.input_skew, hir.output_skew] {
+        if let ClockingSkew::Delay(delay) = skew {
+            visitor.visit_node_with_id(delay, false);
+        }
+    }
+}
+
+/// Walk the contents of a gate or switch primitive instance.
+pub fn walk_gate<'a>(visitor: &mut impl Visitor<'a>, hir: &'a Gate) {
+    for &output in &hir.outputs {
+        visitor.visit_node_with_id(output, true);
+    }
+    for &input in &hir.inputs {
+        visitor.visit_node_with_id(input, false);
+    }
+    if let Some(delay) = hir.delay {
+        visitor.visit_node_with_id(delay, false);
+    }
+}
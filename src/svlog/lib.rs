@@ -112,22 +112,34 @@ macro_rules! assert_type {
 }
 
 mod ast_map;
+pub mod case;
+mod cdc;
+pub mod class;
+pub mod clocking;
 mod codegen;
+pub mod constraint;
 mod context;
+pub mod coverage;
+mod drivers;
 pub mod hir;
 mod inst_details;
+pub mod lint;
 pub mod mir;
+mod naming;
 mod param_env;
 #[warn(missing_docs)]
 pub mod pattern_mapping;
 pub mod port_list;
 mod port_mapping;
+pub mod procedure;
 pub mod resolver;
 pub mod rst;
+pub mod timescale;
 #[warn(missing_docs)]
 pub mod ty;
 pub mod typeck;
 pub mod value;
+pub mod virtual_intf;
 
 pub use moore_common::{
     name::Name,
@@ -264,18 +276,24 @@ mod queries {
     use crate::crate_prelude::*;
     #[allow(deprecated)]
     use crate::{
+        case::*,
+        coverage::*,
         hir::lowering::*,
         hir::{accessed_nodes, AccessTable},
         inst_details::*,
+        lint::*,
         param_env::*,
         pattern_mapping::*,
         port_list::{self, *},
         port_mapping::*,
+        procedure::*,
         resolver::*,
         rst::*,
+        timescale::*,
         ty::UnpackedType,
         typeck::*,
         value::*,
+        virtual_intf::*,
     };
     use std::{
         cell::RefCell,
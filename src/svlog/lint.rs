@@ -0,0 +1,181 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Lint checks.
+//!
+//! Optional warnings about constructs that are legal, but likely to be a
+//! mistake. Each category is a lint that is enabled with a `-W` option on the
+//! command line.
+
+use crate::crate_prelude::*;
+use crate::{
+    hir::HirNode,
+    syntax::token::Lit,
+    typeck::{describe_cast_op, CastType},
+    value::ValueKind,
+};
+use moore_common::lint::{EXTENSION, LITERAL_OVERFLOW, SIGN, TRUNCATION};
+use num::{BigInt, One, Signed, Zero};
+
+/// Check the implicit conversion of a value assigned to a variable, net, or
+/// port.
+///
+/// Warns about truncation that loses bits, extension of non-constant values,
+/// conversion between signed and unsigned values, and unsized literals that
+/// do not fit into the assigned type, if the corresponding warning is enabled.
+/// Each warning lists the casts applied to the value.
+#[moore_derive::query]
+pub(crate) fn check_implicit_conversion<'a>(
+    cx: &impl Context<'a>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Result<()> {
+    let sess = cx.sess();
+    if ![&TRUNCATION, &EXTENSION, &SIGN, &LITERAL_OVERFLOW]
+        .iter()
+        .any(|lint| sess.has_lint(lint))
+    {
+        return Ok(());
+    }
+    let cast = match cx.cast_type(expr_id, env) {
+        Some(x) if x.is_error() => return Err(()),
+        Some(x) if !x.casts.is_empty() => x,
+        _ => return Ok(()),
+    };
+    let (from, to) = match (
+        cast.init.get_simple_bit_vector(),
+        cast.ty.get_simple_bit_vector(),
+    ) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return Ok(()),
+    };
+    let value = constant_before_cast(cx, expr_id, env);
+    let span = cx.span(expr_id);
+
+    if from.size > to.size {
+        match value {
+            Some(ref v) if fits(v, to.size) => (),
+            Some(_) if is_unsized_literal(cx, expr_id) => {
+                if sess.has_lint(&LITERAL_OVERFLOW) {
+                    let d = DiagBuilder2::lint(
+                        &LITERAL_OVERFLOW,
+                        format!(
+                            "unsized literal `{}` does not fit into {} bits of `{}`",
+                            span.extract(),
+                            to.size,
+                            cast.ty
+                        ),
+                    );
+                    cx.emit(with_casts(d.span(span), &cast));
+                }
+            }
+            _ => {
+                if sess.has_lint(&TRUNCATION) {
+                    let d = DiagBuilder2::lint(
+                        &TRUNCATION,
+                        format!(
+                            "`{}` implicitly truncated from {} to {} bits",
+                            span.extract(),
+                            from.size,
+                            to.size
+                        ),
+                    );
+                    cx.emit(with_casts(d.span(span), &cast));
+                }
+            }
+        }
+    } else if from.size < to.size && value.is_none() && sess.has_lint(&EXTENSION) {
+        let d = DiagBuilder2::lint(
+            &EXTENSION,
+            format!(
+                "`{}` implicitly {}-extended from {} to {} bits",
+                span.extract(),
+                if from.is_signed() { "sign" } else { "zero" },
+                from.size,
+                to.size
+            ),
+        );
+        cx.emit(with_casts(d.span(span), &cast));
+    }
+
+    if from.sign != to.sign && value.is_none() && sess.has_lint(&SIGN) {
+        let d = DiagBuilder2::lint(
+            &SIGN,
+            format!(
+                "`{}` implicitly converted from {} to {}",
+                span.extract(),
+                from.sign,
+                to.sign
+            ),
+        );
+        cx.emit(with_casts(d.span(span), &cast));
+    }
+    Ok(())
+}
+
+/// Add the cast chain of a value to a diagnostic.
+fn with_casts(mut d: DiagBuilder2, cast: &CastType) -> DiagBuilder2 {
+    d = d.add_note(format!("cast from `{}` to `{}`:", cast.init, cast.ty));
+    for &(op, ty) in &cast.casts {
+        d = d.add_note(format!("- {}", describe_cast_op(op, ty)));
+    }
+    d
+}
+
+/// Determine the value of an expression before any implicit casts, if it is
+/// a constant.
+fn constant_before_cast<'a>(
+    cx: &impl Context<'a>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Option<BigInt> {
+    let mut mir = cx.mir_rvalue(expr_id, env);
+    if !mir.is_const() {
+        return None;
+    }
+    loop {
+        mir = match mir.kind {
+            mir::RvalueKind::Truncate(_, value)
+            | mir::RvalueKind::ZeroExtend(_, value)
+            | mir::RvalueKind::SignExtend(_, value)
+            | mir::RvalueKind::CastSign(_, value)
+            | mir::RvalueKind::CastValueDomain { value, .. }
+            | mir::RvalueKind::Transmute(value) => value,
+            _ => break,
+        };
+    }
+    match cx.const_mir_rvalue(Ref(mir)).kind {
+        ValueKind::Int(ref v, ..) => Some(v.clone()),
+        _ => None,
+    }
+}
+
+/// Check whether a value can be represented in a number of bits, as either a
+/// signed or an unsigned integer.
+fn fits(value: &BigInt, width: usize) -> bool {
+    if width == 0 {
+        return value.is_zero();
+    }
+    let max = BigInt::one() << width;
+    let min = -(BigInt::one() << (width - 1));
+    value.is_negative() && *value >= min || !value.is_negative() && *value < max
+}
+
+/// Check whether an expression is a literal without an explicit size, such as
+/// `42` or `'hff`.
+fn is_unsized_literal<'a>(cx: &impl Context<'a>, expr_id: NodeId) -> bool {
+    match cx.hir_of(expr_id) {
+        Ok(HirNode::Expr(_)) => (),
+        _ => return false,
+    }
+    match cx.ast_for_id(expr_id).as_all().get_expr() {
+        Some(ast::Expr {
+            data: ast::LiteralExpr(Lit::Number(_, None)),
+            ..
+        })
+        | Some(ast::Expr {
+            data: ast::LiteralExpr(Lit::BasedInteger(None, ..)),
+            ..
+        }) => true,
+        _ => false,
+    }
+}
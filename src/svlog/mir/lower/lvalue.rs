@@ -98,10 +98,31 @@ fn try_lower_expr<'gcx>(
     match hir.kind {
         // Identifiers and scoped identifiers we simply resolve and try to lower
         // the resolved node to an MIR node.
+        hir::ExprKind::Ident(name)
+            if crate::class::is_property(cx, cx.resolve_node(expr_id, env)?) =>
+        {
+            let (object, class, prop) =
+                super::rvalue::lower_class_property(cx, expr_id, env, None, name)?;
+            return Ok(builder.build(
+                ty,
+                LvalueKind::ClassProperty {
+                    object,
+                    class,
+                    prop,
+                },
+            ));
+        }
         hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
             let binding = cx.resolve_node(expr_id, env)?;
             return match cx.hir_of(binding)? {
                 HirNode::GenvarDecl(decl) => Ok(builder.build(ty, LvalueKind::Genvar(decl.id))),
+                HirNode::VarDecl(decl) if ty.get_virtual_interface().is_some() => {
+                    // Refer to the interface instance the variable is bound
+                    // to during elaboration.
+                    let binding = cx.virtual_intf_binding(decl.id, env)?;
+                    let value = cx.mir_lvalue(binding, env);
+                    Ok(builder.build(ty, value.kind.clone()))
+                }
                 HirNode::VarDecl(decl) => Ok(builder.build(ty, LvalueKind::Var(decl.id))),
                 HirNode::IntPort(port) if ty.resolve_full().core.get_interface().is_some() => {
                     Ok(builder.build(ty, LvalueKind::Intf(port.id)))
@@ -150,8 +171,28 @@ fn try_lower_expr<'gcx>(
 
         hir::ExprKind::Field(target, name) => {
             let target_ty = cx.self_determined_type(target, env);
-            let value = cx.mir_lvalue(target, env);
-            if let Some(intf) = target_ty.and_then(|ty| ty.get_interface()) {
+            if let Some(block) = crate::clocking::clocking_block_of_expr(cx, target, env) {
+                // Synchronous drives assign to the signal the clocking block
+                // output refers to. The output skew is applied by codegen.
+                let var = crate::clocking::resolve_clock_var(cx, block, name)?;
+                if !var.dir.is_output() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "input `{}` of clocking block `{}` cannot be driven",
+                            var.name, block.name
+                        ))
+                        .span(span)
+                        .add_note(format!("`{}` declared as input here:", var.name))
+                        .span(var.span),
+                    );
+                    return Err(());
+                }
+                let value = cx.mir_lvalue(var.expr, env);
+                return Ok(builder.build(ty, value.kind.clone()));
+            } else if let Some(intf) =
+                target_ty.and_then(|ty| ty.get_interface().or_else(|| ty.get_virtual_interface()))
+            {
+                let value = cx.mir_lvalue(target, env);
                 let def = cx.resolve_hierarchical_or_error(name, intf.ast)?;
                 // Distinguish `intf.modport` and `intf.signal`.
                 if def.node.as_all().is_modport_name() {
@@ -159,7 +200,33 @@ fn try_lower_expr<'gcx>(
                 } else {
                     return Ok(builder.build(ty, LvalueKind::IntfSignal(value, def.node.id())));
                 }
+            } else if target_ty.and_then(|ty| ty.get_class()).is_some() {
+                let (object, class, prop) =
+                    super::rvalue::lower_class_property(cx, expr_id, env, Some(target), name)?;
+                return Ok(builder.build(
+                    ty,
+                    LvalueKind::ClassProperty {
+                        object,
+                        class,
+                        prop,
+                    },
+                ));
+            } else if let Some(module) = target_ty.and_then(|ty| ty.get_module()) {
+                let inst = cx.mir_rvalue(target, env);
+                match super::rvalue::lower_hier_member(cx, inst, module, name)? {
+                    (path, Some(decl)) if cx.hier_path_insts(path).is_empty() => {
+                        return match cx.hir_of(decl)? {
+                            HirNode::IntPort(..) => Ok(builder.build(ty, LvalueKind::Port(decl))),
+                            _ => Ok(builder.build(ty, LvalueKind::Var(decl))),
+                        };
+                    }
+                    (path, Some(decl)) => {
+                        return Ok(builder.build(ty, LvalueKind::HierSignal(path, decl)))
+                    }
+                    (_, None) => (),
+                }
             } else {
+                let value = cx.mir_lvalue(target, env);
                 let (field, _) = cx.resolve_field_access(expr_id, env)?;
                 return Ok(builder.build(ty, LvalueKind::Member { value, field }));
             }
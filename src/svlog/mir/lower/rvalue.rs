@@ -7,11 +7,12 @@ use crate::{
     hir::HirNode,
     mir::rvalue::*,
     syntax::ast::BasicNode,
-    ty::{SbvType, UnpackedType},
+    ty::{PackedType, SbvType, UnpackedType},
     typeck::{CastOp, CastType},
     value::{self, ValueData, ValueKind},
     ParamEnv,
 };
+use moore_common::name::get_name_table;
 use num::{BigInt, One, Signed, ToPrimitive, Zero};
 use std::{cmp::max, collections::HashMap};
 
@@ -124,6 +125,16 @@ fn lower_expr_inner<'gcx>(
         return Err(());
     }
 
+    // References to `let` declarations are expanded in place.
+    if let Some(expansion) = crate::typeck::let_expansion(cx, hir, env) {
+        let (body, env) = expansion?;
+        let builder = Builder {
+            env,
+            ..builder.with(body.id)
+        };
+        return lower_expr_inner(&builder, body, ty);
+    }
+
     // Determine the expression type and match on the various forms.
     match hir.kind {
         // Literals
@@ -171,7 +182,7 @@ fn lower_expr_inner<'gcx>(
 
         // Built-in function calls
         hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported) => {
-            Ok(builder.constant(value::make_int(ty, num::zero())))
+            Ok(builder.constant(value::make_null(ty)))
         }
         hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(arg)) => {
             let arg_val = cx.constant_value_of(arg, env);
@@ -211,9 +222,51 @@ fn lower_expr_inner<'gcx>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_)) => {
             bug_span!(span, cx, "unsupported system function {:?}", hir.kind)
         }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Time)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealTime) => {
+            // `$time` reports the time rounded to the time unit of the
+            // enclosing module, `$realtime` rounded to its precision.
+            let scale = crate::timescale::time_scale_at(cx, cx.ast_for_id(expr_id));
+            let granularity = match hir.kind {
+                hir::ExprKind::Builtin(hir::BuiltinCall::Time) => scale.unit,
+                _ => scale.prec,
+            };
+            Ok(builder.build(ty, RvalueKind::SimTime(granularity)))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Random(None)) => {
+            Ok(builder.build(ty, RvalueKind::Random))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Random(Some(seed))) => {
+            Ok(lower_random_seeded(builder, ty, seed))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Urandom(seed)) => Ok(builder.build(
+            ty,
+            RvalueKind::Urandom(seed.map(|seed| cx.mir_rvalue(seed, env))),
+        )),
+        hir::ExprKind::Builtin(hir::BuiltinCall::UrandomRange(max, min)) => Ok(builder.build(
+            ty,
+            RvalueKind::UrandomRange {
+                max: cx.mir_rvalue(max, env),
+                min: match min {
+                    Some(min) => cx.mir_rvalue(min, env),
+                    None => builder.constant(value::make_int(ty, Zero::zero())),
+                },
+            },
+        )),
+        hir::ExprKind::Builtin(hir::BuiltinCall::Finish(level)) => {
+            // The diagnostic level defaults to 1. See IEEE 1800-2017 §20.2.
+            let level = match level {
+                Some(level) => cx.mir_rvalue(level, env),
+                None => {
+                    let int_ty = PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx);
+                    builder.constant(value::make_int(int_ty, One::one()))
+                }
+            };
+            Ok(builder.build(ty, RvalueKind::Finish(level)))
+        }
         hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_)) => {
             // Since we currently don't emit logic types, this is always zero.
-            Ok(builder.constant(value::make_int(ty, num::zero())))
+            Ok(builder.constant(value::make_null(ty)))
         }
         hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(func, arg, dim)) => {
             // Decide which dimension to inspect.
@@ -274,10 +327,31 @@ fn lower_expr_inner<'gcx>(
             Ok(builder.constant(value::make_int(ty, value.into())))
         }
 
+        hir::ExprKind::Ident(name)
+            if crate::class::is_property(cx, cx.resolve_node(expr_id, env)?) =>
+        {
+            let (object, class, prop) = lower_class_property(cx, expr_id, env, None, name)?;
+            Ok(builder.build(
+                ty,
+                RvalueKind::ClassProperty {
+                    object,
+                    class,
+                    prop,
+                },
+            ))
+        }
         hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
             let binding = builder.cx.resolve_node(expr_id, env)?;
             match builder.cx.hir_of(binding)? {
+                HirNode::VarDecl(decl) if ty.get_virtual_interface().is_some() => {
+                    // Refer to the interface instance the variable is bound
+                    // to during elaboration.
+                    let binding = builder.cx.virtual_intf_binding(decl.id, env)?;
+                    let value = builder.cx.mir_rvalue(binding, env);
+                    Ok(builder.build(ty, value.kind.clone()))
+                }
                 HirNode::VarDecl(decl) => Ok(builder.build(ty, RvalueKind::Var(decl.id))),
+                HirNode::ClockingBlock(block) => Ok(builder.build(ty, RvalueKind::Var(block.id))),
                 HirNode::IntPort(port) if ty.resolve_full().core.get_interface().is_some() => {
                     Ok(builder.build(ty, RvalueKind::Intf(port.id)))
                 }
@@ -285,10 +359,24 @@ fn lower_expr_inner<'gcx>(
                 HirNode::Inst(inst) if ty.resolve_full().core.get_interface().is_some() => {
                     Ok(builder.build(ty, RvalueKind::Intf(inst.id)))
                 }
+                HirNode::Inst(inst) if ty.resolve_full().core.get_module().is_some() => {
+                    let path = builder.cx.intern_hier_path(&[inst.id]);
+                    Ok(builder.build(ty, RvalueKind::Inst(path)))
+                }
+                // The root of a hierarchical reference such as `tb.c.x` within
+                // `tb` refers to the current instance.
+                HirNode::Module(..) if ty.resolve_full().core.get_module().is_some() => {
+                    let path = builder.cx.intern_hier_path(&[]);
+                    Ok(builder.build(ty, RvalueKind::Inst(path)))
+                }
                 HirNode::EnumVariant(..) | HirNode::ValueParam(..) | HirNode::GenvarDecl(..) => {
                     let k = builder.cx.constant_value_of(binding, env);
                     Ok(builder.build(ty, RvalueKind::Const(k)))
                 }
+                HirNode::LetArg(arg) => match builder.cx.param_env_data(env).find_let_arg(arg.id) {
+                    Some(actual) => Ok(builder.cx.mir_rvalue(actual.id(), actual.env())),
+                    None => bug_span!(span, builder.cx, "{} not bound", arg.desc_full()),
+                },
                 x => {
                     builder.cx.emit(
                         DiagBuilder2::error(format!(
@@ -396,7 +484,26 @@ fn lower_expr_inner<'gcx>(
         hir::ExprKind::Field(target, name) => {
             let target_ty = cx.self_determined_type(target, env);
             let value = cx.mir_rvalue(target, env);
-            if let Some(intf) = target_ty.and_then(|ty| ty.get_interface()) {
+            if let Some(block) = crate::clocking::clocking_block_of_expr(cx, target, env) {
+                // Clocking block inputs evaluate to the value sampled at the
+                // last clocking event.
+                let var = crate::clocking::resolve_clock_var(cx, block, name)?;
+                if !var.dir.is_input() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "output `{}` of clocking block `{}` cannot be read",
+                            var.name, block.name
+                        ))
+                        .span(span)
+                        .add_note(format!("`{}` declared as output here:", var.name))
+                        .span(var.span),
+                    );
+                    return Err(());
+                }
+                Ok(builder.build(ty, RvalueKind::Var(var.id)))
+            } else if let Some(intf) =
+                target_ty.and_then(|ty| ty.get_interface().or_else(|| ty.get_virtual_interface()))
+            {
                 let def = cx.resolve_hierarchical_or_error(name, intf.ast)?;
                 // Distinguish `intf.modport` and `intf.signal`.
                 if def.node.as_all().is_modport_name() {
@@ -404,12 +511,45 @@ fn lower_expr_inner<'gcx>(
                 } else {
                     Ok(builder.build(ty, RvalueKind::IntfSignal(value, def.node.id())))
                 }
+            } else if target_ty.and_then(|ty| ty.get_class()).is_some() {
+                let (object, class, prop) =
+                    lower_class_property(cx, expr_id, env, Some(target), name)?;
+                Ok(builder.build(
+                    ty,
+                    RvalueKind::ClassProperty {
+                        object,
+                        class,
+                        prop,
+                    },
+                ))
+            } else if target_ty.map(|ty| ty.is_event()).unwrap_or(false) {
+                // Typeck ensures that `triggered` is the only member.
+                Ok(builder.build(ty, RvalueKind::EventTriggered(value)))
+            } else if let Some(module) = target_ty.and_then(|ty| ty.get_module()) {
+                match lower_hier_member(cx, value, module, name)? {
+                    // Signals of the current instance, as in `tb.x` within
+                    // `tb`, are accessed directly.
+                    (path, Some(decl)) if cx.hier_path_insts(path).is_empty() => {
+                        match cx.hir_of(decl)? {
+                            HirNode::IntPort(..) => Ok(builder.build(ty, RvalueKind::Port(decl))),
+                            _ => Ok(builder.build(ty, RvalueKind::Var(decl))),
+                        }
+                    }
+                    (path, Some(decl)) => Ok(builder.build(ty, RvalueKind::HierSignal(path, decl))),
+                    (path, None) => Ok(builder.build(ty, RvalueKind::Inst(path))),
+                }
             } else {
                 let (field, _) = cx.resolve_field_access(expr_id, env)?;
                 Ok(builder.build(ty, RvalueKind::Member { value, field }))
             }
         }
 
+        // Casts to `void` evaluate the argument and discard its value.
+        hir::ExprKind::Cast(_, expr) if ty.is_void() => {
+            let value = cx.mir_rvalue(expr, env);
+            Ok(builder.build(ty, value.kind.clone()))
+        }
+
         // Casts are handled by the `cast_type` query, and the cast handling
         // that happens after the lowering to an MIR rvalue.
         hir::ExprKind::Cast(_, expr)
@@ -483,19 +623,651 @@ fn lower_expr_inner<'gcx>(
             Ok(check)
         }
 
-        hir::ExprKind::FunctionCall(..) => {
-            bug_span!(
-                span,
-                cx,
-                "lowering of {} to mir not yet supported",
-                hir.desc_full()
-            );
+        hir::ExprKind::Let(..) => unreachable!("let references are expanded above"),
+
+        hir::ExprKind::FunctionCall(target, ref args) => {
+            let sub = match cx.hir_of(target)? {
+                HirNode::Subroutine(x) => x,
+                _ => unreachable!(),
+            };
+            // Methods called by name from within a class operate on `this`.
+            // Static methods may also be called from outside, as `C::m()`.
+            if crate::class::is_method(cx, target) {
+                let decl = crate::class::enclosing_class(cx, target).unwrap();
+                let this = match crate::class::this_type(cx, expr_id, env) {
+                    Some(this) => {
+                        crate::class::find_ancestor(cx, this.get_class().unwrap(), decl.id())?
+                            .map(|_| this)
+                    }
+                    None => None,
+                };
+                let this = match this {
+                    Some(this) => this,
+                    None if crate::class::is_static_method(cx, target)? => {
+                        crate::typeck::class_type(cx, decl, env, None)
+                    }
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "method `{}` of class `{}` called without an object",
+                                sub.name, decl.name
+                            ))
+                            .span(span)
+                            .add_note("Only static methods can be called through the class name"),
+                        );
+                        return Err(());
+                    }
+                };
+                let object = builder.build(this, RvalueKind::This);
+                return lower_class_method_call(builder, ty, object, sub.name, args, true);
+            }
+            if sub.dpi.is_none() && sub.kind == ast::SubroutineKind::Task {
+                let args = lower_call_args(builder, sub, args)?;
+                return Ok(builder.build(
+                    ty,
+                    RvalueKind::Call {
+                        target: sub.id,
+                        args,
+                    },
+                ));
+            }
+            if sub.dpi.is_none() {
+                bug_span!(
+                    span,
+                    cx,
+                    "lowering of {} to mir not yet supported",
+                    hir.desc_full()
+                );
+            }
+            lower_dpi_call(builder, ty, sub, args)
         }
 
         hir::ExprKind::Assign { op, lhs, rhs } => Ok(lower_assign(&builder, ty, op, lhs, rhs)),
+
+        hir::ExprKind::MethodCall(target, name, ref args) => {
+            lower_method_call(builder, ty, target, name, args)
+        }
+
+        hir::ExprKind::StdRandomize(_) => lower_randomize(builder, ty, hir, None),
+
+        hir::ExprKind::InlineConstraint(call, _) => match cx.hir_of(call)? {
+            HirNode::Expr(call) => lower_randomize(builder, ty, call, Some(expr_id)),
+            _ => unreachable!(),
+        },
+
+        hir::ExprKind::New(ref args) => {
+            if ty.get_covergroup().is_some() {
+                // Covergroup instances start out with all counters cleared.
+                Ok(builder.build(ty, RvalueKind::Const(cx.type_default_value(ty))))
+            } else if let Some(class) = ty.get_class() {
+                let args = match crate::class::find_constructor(cx, class)? {
+                    Some(ctor) => match cx.hir_of(ctor.member.id)? {
+                        HirNode::Subroutine(sub) => lower_call_args(builder, sub, args)?,
+                        _ => unreachable!(),
+                    },
+                    None => lower_implicit_constructor_args(builder, class, args)?,
+                };
+                Ok(builder.build(ty, RvalueKind::ClassNew(args)))
+            } else {
+                cx.emit(
+                    DiagBuilder2::error(format!("cannot construct a value of type `{}`", ty))
+                        .span(span),
+                );
+                Err(())
+            }
+        }
+
+        // The current object, viewed as an instance of its class or base class.
+        hir::ExprKind::This | hir::ExprKind::Super => Ok(builder.build(ty, RvalueKind::This)),
+
+        hir::ExprKind::Null => {
+            if ty.get_class().is_none() {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`null` cannot be used as a value of type `{}`",
+                        ty
+                    ))
+                    .span(span)
+                    .add_note("`null` can only be assigned to class handles"),
+                );
+                return Err(());
+            }
+            Ok(builder.constant(value::make_null(ty)))
+        }
     }
 }
 
+/// Resolve an access to a class property.
+///
+/// The property is accessed through the object `target` evaluates to, or
+/// through `this` if no target is given. Returns the object, or `None` for
+/// static properties, together with the class declaring the property and the
+/// property itself.
+pub(crate) fn lower_class_property<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr_id: NodeId,
+    env: ParamEnv,
+    target: Option<NodeId>,
+    name: Spanned<Name>,
+) -> Result<(
+    Option<&'gcx Rvalue<'gcx>>,
+    crate::ty::ClassType<'gcx>,
+    NodeId,
+)> {
+    let builder = Builder {
+        cx,
+        span: cx.span(expr_id),
+        expr: expr_id,
+        env,
+    };
+    let object = match target {
+        Some(target) => cx.mir_rvalue(target, env),
+        None if crate::constraint::inline_constraint_object(cx, expr_id).is_some() => {
+            // Names in inline constraints refer to the properties of the
+            // randomized object, which acts as `this` during randomization.
+            match crate::constraint::inline_constraint_class(cx, expr_id, env) {
+                Some(class) if crate::class::find_property(cx, &class, name.value)?.is_some() => {
+                    let ty = UnpackedType::make(cx, crate::ty::UnpackedCore::Class(class));
+                    builder.build(ty, RvalueKind::This)
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "unsupported: property `{}` of the calling object used in an \
+                             inline constraint",
+                            name
+                        ))
+                        .span(name.span)
+                        .add_note("Only properties of the randomized object can be used"),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        None => match crate::class::this_type(cx, expr_id, env) {
+            Some(ty) => builder.build(ty, RvalueKind::This),
+            None => bug_span!(builder.span, cx, "property accessed outside of a class"),
+        },
+    };
+    if object.is_error() {
+        return Err(());
+    }
+    let class = object.ty.get_class().unwrap();
+    let prop = match crate::class::find_property(cx, class, name.value)? {
+        Some(x) => x,
+        None => return Err(()),
+    };
+    if prop.member.statik {
+        return Ok((None, prop.class, prop.member.id));
+    }
+    if target.is_none() && in_static_method(cx, expr_id)? {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "non-static property `{}` used in a static method",
+                name
+            ))
+            .span(name.span)
+            .add_note("Static methods have no `this` object"),
+        );
+        return Err(());
+    }
+    Ok((Some(object), prop.class, prop.member.id))
+}
+
+/// Check whether a node is nested in a static method.
+fn in_static_method<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<bool> {
+    let sub = match crate::class::enclosing_subroutine(cx, node_id) {
+        Some(x) => x,
+        None => return Ok(false),
+    };
+    let class = match crate::class::enclosing_class(cx, sub.id()) {
+        Some(x) => x,
+        None => return Ok(false),
+    };
+    Ok(crate::class::class_hir(cx, class)?
+        .methods
+        .iter()
+        .any(|m| m.id == sub.id() && m.statik))
+}
+
+/// Lower a method call.
+///
+/// Besides class methods, only the built-in methods of covergroups are
+/// supported, as checked by `type_of_method_call`.
+fn lower_method_call<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: &'gcx UnpackedType<'gcx>,
+    target: NodeId,
+    name: Spanned<Name>,
+    args: &'gcx [hir::CallArg],
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let state = cx.mir_rvalue(target, builder.env);
+    if state.is_error() {
+        return Err(());
+    }
+    if state.ty.get_class().is_some() {
+        if crate::constraint::is_randomize(name.value) {
+            let hir = match cx.hir_of(builder.expr)? {
+                HirNode::Expr(x) => x,
+                _ => unreachable!(),
+            };
+            return lower_randomize(builder, ty, hir, None);
+        }
+        // Calls through `super` always call the base class implementation.
+        let is_super = match cx.hir_of(target)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Super,
+                ..
+            }) => true,
+            _ => false,
+        };
+        return lower_class_method_call(builder, ty, state, name, args, !is_super);
+    }
+    let cg = state.ty.get_covergroup().unwrap();
+    match &*name.value.as_str() {
+        "sample" => {
+            let hir = match cx.hir_of(cg.ast.id())? {
+                HirNode::Covergroup(x) => x,
+                _ => unreachable!(),
+            };
+            let guard = |iff: Option<NodeId>| match iff {
+                Some(iff) => cx.mir_rvalue(iff, cg.env),
+                None => builder.constant(value::make_int(UnpackedType::make_logic(), One::one())),
+            };
+            let mut points = vec![];
+            let mut point_guards = vec![];
+            for &id in &hir.points {
+                let cp = match cx.hir_of(id)? {
+                    HirNode::Coverpoint(x) => x,
+                    _ => unreachable!(),
+                };
+                points.push(cx.mir_rvalue(cp.expr, cg.env));
+                point_guards.push(guard(cp.iff));
+            }
+            let cross_guards = hir.crosses.iter().map(|c| guard(c.iff)).collect();
+            Ok(builder.build(
+                ty,
+                RvalueKind::CoverSample {
+                    group: cx.mir_lvalue(target, builder.env),
+                    state,
+                    points,
+                    point_guards,
+                    cross_guards,
+                },
+            ))
+        }
+        _ => Ok(builder.build(ty, RvalueKind::CoverCoverage(state))),
+    }
+}
+
+/// Lower a call to `obj.randomize()` or `std::randomize(...)`.
+///
+/// The `constraints` are the inline constraint expression wrapping the call,
+/// if any.
+fn lower_randomize<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: &'gcx UnpackedType<'gcx>,
+    call: &'gcx hir::Expr<'gcx>,
+    constraints: Option<NodeId>,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let env = builder.env;
+    let mut object = None;
+    let mut vars = vec![];
+    match call.kind {
+        hir::ExprKind::MethodCall(target, ..) => object = Some(cx.mir_rvalue(target, env)),
+        hir::ExprKind::StdRandomize(ref args) => {
+            for &arg in args {
+                let is_var = match cx.hir_of(cx.resolve_node(arg, env)?)? {
+                    HirNode::VarDecl(decl) => !crate::class::is_property(cx, decl.id),
+                    _ => false,
+                };
+                if !is_var {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "unsupported: `{}` passed to `std::randomize`",
+                            cx.span(arg).extract()
+                        ))
+                        .span(cx.span(arg))
+                        .add_note("Only local and module variables can be randomized"),
+                    );
+                    return Err(());
+                }
+                vars.push(cx.mir_lvalue(arg, env));
+            }
+        }
+        _ => unreachable!(),
+    }
+    if object.map(|o| o.is_error()).unwrap_or(false) || vars.iter().any(|v| v.is_error()) {
+        return Err(());
+    }
+
+    // Call the `pre_randomize()` and `post_randomize()` methods if the class
+    // declares them.
+    let class = object.map(|o| o.ty.get_class().unwrap().clone());
+    let mut hooks = vec![];
+    for name in &["pre_randomize", "post_randomize"] {
+        let name = get_name_table().intern(name, true);
+        let hook = match (object, &class) {
+            (Some(object), Some(class))
+                if crate::class::find_method(cx, class, name)?.is_some() =>
+            {
+                let name = Spanned::new(name, builder.span);
+                Some(lower_class_method_call(
+                    builder,
+                    UnpackedType::make_void(),
+                    object,
+                    name,
+                    &[],
+                    true,
+                )?)
+            }
+            _ => None,
+        };
+        hooks.push(hook);
+    }
+
+    Ok(builder.build(
+        ty,
+        RvalueKind::Randomize {
+            object,
+            class,
+            vars,
+            constraints,
+            pre: hooks[0],
+            post: hooks[1],
+        },
+    ))
+}
+
+/// Lower a call to a method of a class.
+///
+/// Calls to virtual methods are dispatched based on the class of the object at
+/// runtime, unless `dispatch` is false. Calls to `new` invoke the constructor
+/// on an existing object, as done by `super.new(...)`.
+fn lower_class_method_call<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: &'gcx UnpackedType<'gcx>,
+    object: &'gcx Rvalue<'gcx>,
+    name: Spanned<Name>,
+    args: &'gcx [hir::CallArg],
+    dispatch: bool,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let class = object.ty.get_class().unwrap();
+    let (decl, method, args) = match crate::typeck::find_class_method(cx, class, name)? {
+        Some(method) => {
+            let sub = match cx.hir_of(method.member.id)? {
+                HirNode::Subroutine(x) => x,
+                _ => unreachable!(),
+            };
+            let args = lower_call_args(builder, sub, args)?;
+            (method.class, Some(method.member), args)
+        }
+        None => (
+            class.clone(),
+            None,
+            lower_implicit_constructor_args(builder, class, args)?,
+        ),
+    };
+    let statik = method.map(|m| m.statik).unwrap_or(false);
+    let dispatch = dispatch
+        && !statik
+        && method.is_some()
+        && crate::class::is_virtual_method(cx, &decl, name.value)?;
+    let is_ctor = &*name.value.as_str() == "new";
+    Ok(builder.build(
+        ty,
+        RvalueKind::MethodCall {
+            object: if statik { None } else { Some(object) },
+            class: decl,
+            method: method.filter(|_| !is_ctor).map(|m| m.id),
+            args,
+            dispatch,
+        },
+    ))
+}
+
+/// Lower the call to the base class constructor implied by an `extends` clause.
+///
+/// The arguments of the clause are passed to the constructor of the base
+/// class. Returns `None` if the class does not extend another class.
+pub(crate) fn lower_base_constructor<'gcx>(
+    cx: &impl Context<'gcx>,
+    class: &crate::ty::ClassType<'gcx>,
+) -> Result<Option<&'gcx Rvalue<'gcx>>> {
+    let hir = crate::class::class_hir(cx, class.ast)?;
+    let (ty_id, args) = match hir.extends {
+        Some((ty_id, ref args)) => (ty_id, args),
+        None => return Ok(None),
+    };
+    let base = match crate::class::base_class(cx, class)? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let builder = Builder {
+        cx,
+        span: cx.span(ty_id),
+        expr: ty_id,
+        env: class.env,
+    };
+    let object = builder.build(
+        UnpackedType::make(cx, crate::ty::UnpackedCore::Class(base)),
+        RvalueKind::This,
+    );
+    let name = Spanned::new(get_name_table().intern("new", true), builder.span);
+    lower_class_method_call(
+        &builder,
+        UnpackedType::make_void(),
+        object,
+        name,
+        args,
+        false,
+    )
+    .map(Some)
+}
+
+/// Check the arguments passed to the implicit constructor of a class.
+///
+/// Classes without a `new` method have a constructor without arguments.
+fn lower_implicit_constructor_args<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    class: &crate::ty::ClassType<'gcx>,
+    args: &'gcx [hir::CallArg],
+) -> Result<Vec<&'gcx Rvalue<'gcx>>> {
+    if let Some(arg) = args.first() {
+        builder.cx.emit(
+            DiagBuilder2::error(format!(
+                "constructor of class `{}` takes no arguments",
+                class.ast.name
+            ))
+            .span(arg.span)
+            .add_note(format!(
+                "`{}` does not declare a `new` method",
+                class.ast.name
+            ))
+            .span(class.ast.name.span),
+        );
+        return Err(());
+    }
+    Ok(vec![])
+}
+
+/// Resolve a member access into a module instance.
+///
+/// Returns the extended instance path if `name` refers to another instance
+/// within `target`, or the path and the declaration if `name` refers to a
+/// signal.
+pub(crate) fn lower_hier_member<'gcx>(
+    cx: &impl Context<'gcx>,
+    target: &'gcx Rvalue<'gcx>,
+    module: &ty::ModuleType<'gcx>,
+    name: Spanned<Name>,
+) -> Result<(hir::HierPath, Option<NodeId>)> {
+    let path = match target.kind {
+        RvalueKind::Inst(path) => path,
+        RvalueKind::Error => return Err(()),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "hierarchical reference through `{}` not supported",
+                    target.span.extract()
+                ))
+                .span(target.span),
+            );
+            return Err(());
+        }
+    };
+    let def = cx.resolve_hierarchical_or_error(name, module.ast)?;
+    match cx.hir_of(def.node.id())? {
+        HirNode::Inst(inst) => {
+            if cx.type_of(inst.id, module.env)?.get_module().is_none() {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "hierarchical reference into {} not supported",
+                        inst.desc_full()
+                    ))
+                    .span(name.span)
+                    .add_note("Only module instances can be referenced hierarchically"),
+                );
+                return Err(());
+            }
+            let mut insts = cx.hier_path_insts(path).to_vec();
+            insts.push(inst.id);
+            Ok((cx.intern_hier_path(&insts), None))
+        }
+        HirNode::VarDecl(decl) => Ok((path, Some(decl.id))),
+        HirNode::IntPort(port) => Ok((path, Some(port.id))),
+        x => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be referenced hierarchically",
+                    x.desc_full()
+                ))
+                .span(name.span)
+                .add_note(format!("{} declared here:", x.desc_full()))
+                .span(x.human_span()),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Lower a call to a DPI-imported subroutine.
+fn lower_dpi_call<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: &'gcx UnpackedType<'gcx>,
+    sub: &'gcx hir::Subroutine,
+    args: &'gcx [hir::CallArg],
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let args = lower_call_args(builder, sub, args)?;
+    Ok(builder.build(
+        ty,
+        RvalueKind::Call {
+            target: sub.id,
+            args,
+        },
+    ))
+}
+
+/// Lower the arguments of a subroutine call.
+///
+/// Binds the call arguments to the subroutine's arguments, either by position
+/// or by name, and lowers them in the order of the subroutine's arguments.
+fn lower_call_args<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    sub: &'gcx hir::Subroutine,
+    args: &'gcx [hir::CallArg],
+) -> Result<Vec<&'gcx Rvalue<'gcx>>> {
+    let cx = builder.cx;
+    let mut bound = vec![None; sub.args.len()];
+    let mut failed = false;
+    for (index, call_arg) in args.iter().enumerate() {
+        let pos = match call_arg.name {
+            Some(name) => sub
+                .args
+                .iter()
+                .position(|arg| arg.name.map(|n| n.value) == Some(name.value)),
+            None if index < sub.args.len() => Some(index),
+            None => None,
+        };
+        let pos = match pos {
+            Some(pos) => pos,
+            None => {
+                let msg = match call_arg.name {
+                    Some(name) => format!("{} has no argument `{}`", sub.desc_full(), name),
+                    None => format!(
+                        "too many arguments to {}; expected {}",
+                        sub.desc_full(),
+                        sub.args.len()
+                    ),
+                };
+                cx.emit(
+                    DiagBuilder2::error(msg)
+                        .span(call_arg.span)
+                        .add_note(format!("{} declared here:", sub.desc_full()))
+                        .span(sub.span),
+                );
+                failed = true;
+                continue;
+            }
+        };
+        if bound[pos].is_some() {
+            cx.emit(
+                DiagBuilder2::error(format!("argument {} bound multiple times", pos + 1))
+                    .span(call_arg.span),
+            );
+            failed = true;
+            continue;
+        }
+        bound[pos] = Some(call_arg);
+    }
+
+    let mut lowered = vec![];
+    for (arg, call_arg) in sub.args.iter().zip(bound) {
+        let desc = match arg.name {
+            Some(name) => format!("argument `{}`", name),
+            None => format!("argument {}", lowered.len() + 1),
+        };
+        if arg.dir != ast::SubroutinePortDir::Input {
+            let what = if sub.dpi.is_some() {
+                "DPI import"
+            } else if crate::class::is_method(cx, sub.id) {
+                "method"
+            } else {
+                "task"
+            };
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} of {} `{}` is not an input",
+                    desc, what, sub.name
+                ))
+                .span(arg.span)
+                .add_note(format!("Only `input` arguments of {}s are supported", what)),
+            );
+            failed = true;
+            continue;
+        }
+        match call_arg.and_then(|a| a.expr) {
+            Some(expr) => lowered.push(cx.mir_rvalue(expr, builder.env)),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!("missing {} in call to {}", desc, sub.desc_full()))
+                        .span(builder.span)
+                        .add_note(format!("{} declared here:", sub.desc_full()))
+                        .span(sub.span),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed || lowered.iter().any(|arg| arg.is_error()) {
+        return Err(());
+    }
+    Ok(lowered)
+}
+
 /// Compute the base and length of an indexing operation.
 ///
 /// Determine the index of the LSB and the width of the selection. Note that
@@ -634,7 +1406,7 @@ fn lower_cast<'gcx>(
                 assert_span!(value.ty.is_simple_bit_vector(), value.span, builder.cx);
                 value = unpack_simple_bit_vector(builder, value, to);
             }
-            CastOp::PickModport => {
+            CastOp::PickModport | CastOp::Upcast | CastOp::Virtual => {
                 value = builder.build(to, value.kind.clone());
             }
             CastOp::PackString => {
@@ -985,6 +1757,8 @@ fn lower_binary<'gcx>(
             let op_ty = builder.cx.need_operation_type(builder.expr, builder.env);
             if op_ty.is_string() {
                 lower_string_comparison(builder, ty, op_ty, op, lhs, rhs)
+            } else if op_ty.get_class().is_some() {
+                lower_handle_comparison(builder, ty, op_ty, op, lhs, rhs)
             } else {
                 lower_int_comparison(builder, ty, op_ty, op, lhs, rhs)
             }
@@ -1160,6 +1934,50 @@ fn make_int_comparison<'a>(
     )
 }
 
+/// Map a comparison of class handles to MIR.
+///
+/// Handles compare equal if they refer to the same object.
+fn lower_handle_comparison<'a>(
+    builder: &Builder<'_, impl Context<'a>>,
+    result_ty: &'a UnpackedType<'a>,
+    op_ty: &'a UnpackedType<'a>,
+    op: hir::BinaryOp,
+    lhs: NodeId,
+    rhs: NodeId,
+) -> &'a Rvalue<'a> {
+    let lhs = builder.cx.mir_rvalue(lhs, builder.env);
+    let rhs = builder.cx.mir_rvalue(rhs, builder.env);
+    if lhs.is_error() || rhs.is_error() {
+        return builder.error();
+    }
+    let op = match op {
+        hir::BinaryOp::Eq => IntCompOp::Eq,
+        hir::BinaryOp::Neq => IntCompOp::Neq,
+        _ => {
+            builder.cx.emit(
+                DiagBuilder2::error(format!(
+                    "class handles of type `{}` can only be compared for equality",
+                    op_ty
+                ))
+                .span(builder.span),
+            );
+            return builder.error();
+        }
+    };
+    assert_type!(lhs.ty, op_ty, builder.span, builder.cx);
+    assert_type!(rhs.ty, op_ty, builder.span, builder.cx);
+    builder.build(
+        result_ty,
+        RvalueKind::IntComp {
+            op,
+            sign: ty::Sign::Unsigned,
+            domain: ty::Domain::TwoValued,
+            lhs,
+            rhs,
+        },
+    )
+}
+
 /// Map a string comparison operator to MIR.
 fn lower_string_comparison<'a>(
     builder: &Builder<'_, impl Context<'a>>,
@@ -1528,6 +2346,130 @@ fn lower_int_incdec<'gcx>(
     )
 }
 
+/// Map a `$random(seed)` call to MIR.
+///
+/// The seed variable is advanced and the result is derived from its new value
+/// as by `rtl_dist_uniform(seed, INT_MIN, INT_MAX)` of IEEE 1800-2017 Annex N,
+/// which other simulators use to implement `$random`. For this range the
+/// floating-point arithmetic of the reference implementation reduces to the
+/// following integer operations:
+///
+/// ```text
+/// seed = (seed == 0 ? 259341593 : seed) * 69069 + 1;
+/// result = (seed & ~'h1ff) + 'h200 + (seed >> 23) + 'h80000000
+///     - ((seed & 'h807ffe00) == 0);
+/// ```
+fn lower_random_seeded<'a>(
+    builder: &Builder<'_, impl Context<'a>>,
+    ty: &'a UnpackedType<'a>,
+    seed: NodeId,
+) -> &'a Rvalue<'a> {
+    let cx = builder.cx;
+    let lv = cx.mir_lvalue(seed, builder.env);
+    let rv = cx.mir_rvalue(seed, builder.env);
+    if lv.is_error() || rv.is_error() {
+        return builder.error();
+    }
+    match lv.ty.get_simple_bit_vector() {
+        Some(sbvt) if sbvt.size == 32 => (),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "seed of `$random` must be a 32 bit integral variable, but `{}` is of type \
+                     `{}`",
+                    rv.span.extract(),
+                    lv.ty
+                ))
+                .span(rv.span),
+            );
+            return builder.error();
+        }
+    }
+
+    // Helpers to assemble the arithmetic on `int` values.
+    let bit_ty = PackedType::make(cx, ty::IntVecType::Bit).to_unpacked(cx);
+    let konst = |value: i64| builder.constant(value::make_int(ty, value.into()));
+    let arith = |op, lhs, rhs| {
+        builder.build(
+            ty,
+            RvalueKind::IntBinaryArith {
+                op,
+                sign: ty::Sign::Signed,
+                domain: ty::Domain::TwoValued,
+                lhs,
+                rhs,
+            },
+        )
+    };
+    let and = |lhs, rhs| {
+        builder.build(
+            ty,
+            RvalueKind::BinaryBitwise {
+                op: BinaryBitwiseOp::And,
+                lhs,
+                rhs,
+            },
+        )
+    };
+    let is_zero = |value| make_int_comparison(builder, bit_ty, ty, IntCompOp::Eq, value, konst(0));
+
+    // Advance the seed.
+    let transmute = |to: &'a UnpackedType<'a>, value: &'a Rvalue<'a>| {
+        if value.ty.is_identical(to) {
+            value
+        } else {
+            builder.build(to, RvalueKind::Transmute(value))
+        }
+    };
+    let old = transmute(ty, rv);
+    let old = builder.build(
+        ty,
+        RvalueKind::Ternary {
+            cond: is_zero(old),
+            true_value: konst(259341593),
+            false_value: old,
+        },
+    );
+    let new = arith(
+        IntBinaryArithOp::Add,
+        arith(IntBinaryArithOp::Mul, old, konst(69069)),
+        konst(1),
+    );
+
+    // Derive the result from the new seed.
+    let top = builder.build(
+        ty,
+        RvalueKind::Shift {
+            op: ShiftOp::Right,
+            arith: false,
+            value: new,
+            amount: konst(23),
+        },
+    );
+    let correction = builder.build(
+        ty,
+        RvalueKind::Ternary {
+            cond: is_zero(and(new, konst(-0x7f800200))),
+            true_value: konst(1),
+            false_value: konst(0),
+        },
+    );
+    let result = and(new, konst(-0x200));
+    let result = arith(IntBinaryArithOp::Add, result, konst(0x200));
+    let result = arith(IntBinaryArithOp::Add, result, top);
+    let result = arith(IntBinaryArithOp::Add, result, konst(-0x80000000));
+    let result = arith(IntBinaryArithOp::Sub, result, correction);
+
+    builder.build(
+        ty,
+        RvalueKind::Assignment {
+            lvalue: lv,
+            rvalue: transmute(lv.ty, new),
+            result,
+        },
+    )
+}
+
 /// Map an assignment operator to MIR.
 fn lower_assign<'a>(
     builder: &Builder<'_, impl Context<'a>>,
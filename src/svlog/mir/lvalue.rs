@@ -69,6 +69,8 @@ pub enum LvalueKind<'a> {
     Intf(NodeId),
     /// A reference to an interface's signal.
     IntfSignal(&'a Lvalue<'a>, NodeId),
+    /// A hierarchical reference to a signal inside another instance.
+    HierSignal(hir::HierPath, NodeId),
     /// A bit- or part-select.
     Index {
         value: &'a Lvalue<'a>,
@@ -77,6 +79,12 @@ pub enum LvalueKind<'a> {
     },
     /// A struct field access.
     Member { value: &'a Lvalue<'a>, field: usize },
+    /// A property of an object. Static properties have no object.
+    ClassProperty {
+        object: Option<&'a Rvalue<'a>>,
+        class: ty::ClassType<'a>,
+        prop: NodeId,
+    },
     /// An error occurred during lowering.
     Error,
 }
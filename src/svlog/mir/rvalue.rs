@@ -169,6 +169,11 @@ pub enum RvalueKind<'a> {
     Intf(NodeId),
     /// A reference to a locally instantiated interface signal.
     IntfSignal(&'a Rvalue<'a>, NodeId),
+    /// A reference to a module instance, given as the path of instances
+    /// leading up to it.
+    Inst(hir::HierPath),
+    /// A hierarchical reference to a signal inside another instance.
+    HierSignal(hir::HierPath, NodeId),
     /// A bit- or part-select.
     Index {
         value: &'a Rvalue<'a>,
@@ -215,6 +220,82 @@ pub enum RvalueKind<'a> {
         lhs: &'a Rvalue<'a>,
         rhs: &'a Rvalue<'a>,
     },
+    /// A call to a subroutine imported via DPI, or to a task declared in a
+    /// module.
+    Call {
+        target: NodeId,
+        args: Vec<&'a Rvalue<'a>>,
+    },
+    /// Sample a covergroup, updating its hit counters.
+    CoverSample {
+        /// The covergroup instance to be updated.
+        group: &'a Lvalue<'a>,
+        /// The current hit counters of the instance.
+        state: &'a Rvalue<'a>,
+        /// The sampled value of each coverpoint.
+        points: Vec<&'a Rvalue<'a>>,
+        /// The `iff` guard of each coverpoint.
+        point_guards: Vec<&'a Rvalue<'a>>,
+        /// The `iff` guard of each cross.
+        cross_guards: Vec<&'a Rvalue<'a>>,
+    },
+    /// The coverage of a covergroup instance as an integer percentage.
+    CoverCoverage(&'a Rvalue<'a>),
+    /// The current simulation time, rounded to the given granularity in
+    /// seconds.
+    SimTime(num::BigRational),
+    /// The next value of the global `$random` generator.
+    Random,
+    /// The next value of the `$urandom` generator of the calling process,
+    /// which is reseeded first if a seed is given.
+    Urandom(Option<&'a Rvalue<'a>>),
+    /// The next value of the `$urandom` generator of the calling process,
+    /// within the range between `max` and `min`.
+    UrandomRange {
+        max: &'a Rvalue<'a>,
+        min: &'a Rvalue<'a>,
+    },
+    /// Whether an event has been triggered in the current time step.
+    EventTriggered(&'a Rvalue<'a>),
+    /// End the simulation with the given diagnostic level, as by `$finish`.
+    Finish(&'a Rvalue<'a>),
+    /// The handle of the object whose method is being executed.
+    This,
+    /// Construct a new object, passing the arguments to its constructor.
+    ClassNew(Vec<&'a Rvalue<'a>>),
+    /// A property of an object. Static properties have no object.
+    ClassProperty {
+        object: Option<&'a Rvalue<'a>>,
+        class: ty::ClassType<'a>,
+        prop: NodeId,
+    },
+    /// A call to a method of an object. Static methods have no object.
+    MethodCall {
+        object: Option<&'a Rvalue<'a>>,
+        class: ty::ClassType<'a>,
+        /// The method called, or `None` for the constructor.
+        method: Option<NodeId>,
+        args: Vec<&'a Rvalue<'a>>,
+        /// Whether the method is looked up based on the object's class at
+        /// runtime.
+        dispatch: bool,
+    },
+    /// Randomize the random variables of an object, or the given variables,
+    /// yielding whether the constraints could be satisfied.
+    Randomize {
+        /// The object whose `rand` and `randc` properties are randomized.
+        object: Option<&'a Rvalue<'a>>,
+        /// The class of the object.
+        class: Option<ty::ClassType<'a>>,
+        /// The variables passed to `std::randomize`.
+        vars: Vec<&'a Lvalue<'a>>,
+        /// The inline constraint expression, if any.
+        constraints: Option<NodeId>,
+        /// The call to the object's `pre_randomize()` method, if any.
+        pre: Option<&'a Rvalue<'a>>,
+        /// The call to the object's `post_randomize()` method, if any.
+        post: Option<&'a Rvalue<'a>>,
+    },
     /// An error occurred during lowering.
     Error,
 }
@@ -257,6 +338,8 @@ impl<'a> RvalueKind<'a> {
             RvalueKind::Port(_) => false,
             RvalueKind::Intf(_) => false,
             RvalueKind::IntfSignal(..) => false,
+            RvalueKind::Inst(..) => false,
+            RvalueKind::HierSignal(..) => false,
             RvalueKind::Index { .. } => false, // TODO(fschuiki): reactivate once impl
             // RvalueKind::Index { value, base, .. } => value.is_const() && base.is_const(),
             RvalueKind::Ternary {
@@ -266,6 +349,20 @@ impl<'a> RvalueKind<'a> {
             } => cond.is_const() && true_value.is_const() && false_value.is_const(),
             RvalueKind::Shift { value, amount, .. } => value.is_const() && amount.is_const(),
             RvalueKind::Assignment { .. } => false,
+            RvalueKind::Call { .. } => false,
+            RvalueKind::CoverSample { .. } => false,
+            RvalueKind::CoverCoverage(..) => false,
+            RvalueKind::SimTime(..) => false,
+            RvalueKind::Random => false,
+            RvalueKind::Urandom(..) => false,
+            RvalueKind::UrandomRange { .. } => false,
+            RvalueKind::EventTriggered(..) => false,
+            RvalueKind::Finish(..) => false,
+            RvalueKind::This => false,
+            RvalueKind::ClassNew(..) => false,
+            RvalueKind::ClassProperty { .. } => false,
+            RvalueKind::MethodCall { .. } => false,
+            RvalueKind::Randomize { .. } => false,
             RvalueKind::Error => true,
         }
     }
@@ -26,11 +26,14 @@ impl<'a> WalkVisitor<'a> for bool {}
 impl<'a> WalkVisitor<'a> for usize {}
 impl<'a> WalkVisitor<'a> for NodeId {}
 impl<'a> WalkVisitor<'a> for ParamEnv {}
+impl<'a> WalkVisitor<'a> for crate::hir::HierPath {}
 impl<'a> WalkVisitor<'a> for Span {}
 impl<'a> WalkVisitor<'a> for ty::UnpackedType<'a> {}
+impl<'a> WalkVisitor<'a> for ty::ClassType<'a> {}
 impl<'a> WalkVisitor<'a> for ty::Sign {}
 impl<'a> WalkVisitor<'a> for ty::Domain {}
 impl<'a> WalkVisitor<'a> for value::Value<'_> {}
+impl<'a> WalkVisitor<'a> for num::BigRational {}
 
 impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for &'_ T {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
@@ -38,6 +41,14 @@ impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for &'_ T {
     }
 }
 
+impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for Option<T> {
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        if let Some(x) = self {
+            x.walk(visitor);
+        }
+    }
+}
+
 impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for Vec<T> {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
         for x in self {
@@ -0,0 +1,174 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Naming convention checks.
+//!
+//! The `naming` lint checks the names of modules, ports, parameters, type
+//! definitions, and registers against a regular expression per kind of
+//! declaration. The expressions are options of the lint, such that a team can
+//! configure its own conventions in a lint configuration file.
+
+use crate::crate_prelude::*;
+use crate::hir::HirNode;
+use moore_common::lint::NAMING;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+
+/// The kinds of declarations checked, as pairs of lint option and a
+/// description of the declarations.
+const KINDS: &[(&str, &str)] = &[
+    ("module", "modules"),
+    ("input", "input ports"),
+    ("output", "output ports"),
+    ("inout", "inout ports"),
+    ("parameter", "parameters"),
+    ("localparam", "local parameters"),
+    ("type", "type definitions"),
+    ("register", "registers"),
+];
+
+/// The naming conventions configured for the `naming` lint.
+pub(crate) struct NamingRules {
+    patterns: HashMap<&'static str, Regex>,
+}
+
+impl NamingRules {
+    /// Compile the naming conventions configured in the session.
+    ///
+    /// Each pattern must match the entire name. Reports an error for each
+    /// pattern that is not a valid regular expression.
+    pub fn new<'a>(cx: &impl BaseContext<'a>) -> Result<NamingRules> {
+        let lints = &cx.sess().opts.lints;
+        let mut patterns = HashMap::new();
+        let mut failed = false;
+        for &(key, _) in KINDS {
+            let pattern = lints.option(&NAMING, key);
+            match Regex::new(&format!("^(?:{})$", pattern)) {
+                Ok(re) => {
+                    patterns.insert(key, re);
+                }
+                Err(err) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "invalid pattern `{}` for lint option `naming.{}`",
+                            pattern, key
+                        ))
+                        .add_note(err.to_string()),
+                    );
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            return Err(());
+        }
+        Ok(NamingRules { patterns })
+    }
+
+    /// Check the names declared in a module.
+    ///
+    /// Checks the name of the module, its ports and parameters, the parameters
+    /// and type definitions in its body and generate blocks, and the variables
+    /// in `registers`.
+    pub fn check_module<'a>(
+        &self,
+        cx: &impl Context<'a>,
+        hir: &'a hir::Module<'a>,
+        registers: impl IntoIterator<Item = NodeId>,
+    ) {
+        self.check(cx, "module", hir.desc_full(), hir.name);
+        for port in &hir.ports_new.int {
+            let key = match port.dir {
+                ast::PortDir::Input => "input",
+                ast::PortDir::Output => "output",
+                ast::PortDir::Inout => "inout",
+                ast::PortDir::Ref => continue,
+            };
+            self.check(
+                cx,
+                key,
+                format!("{} `{}`", port.dir, port.name.value),
+                port.name,
+            );
+        }
+        for &id in hir.params {
+            self.check_node(cx, id);
+        }
+        self.check_block(cx, &hir.block);
+        let registers: BTreeSet<_> = registers.into_iter().collect();
+        for id in registers {
+            if let Ok(HirNode::VarDecl(decl)) = cx.hir_of(id) {
+                self.check(
+                    cx,
+                    "register",
+                    format!("register `{}`", decl.name.value),
+                    decl.name,
+                );
+            }
+        }
+    }
+
+    /// Check the parameters and type definitions in a module block.
+    fn check_block<'a>(&self, cx: &impl Context<'a>, block: &'a hir::ModuleBlock) {
+        for &id in block.params.iter().chain(block.typedefs.iter()) {
+            self.check_node(cx, id);
+        }
+        for &id in &block.gens {
+            let gen = match cx.hir_of(id) {
+                Ok(HirNode::Gen(gen)) => gen,
+                _ => continue,
+            };
+            match gen.kind {
+                hir::GenKind::If {
+                    ref main_body,
+                    ref else_body,
+                    ..
+                } => {
+                    self.check_block(cx, main_body);
+                    if let Some(else_body) = else_body {
+                        self.check_block(cx, else_body);
+                    }
+                }
+                hir::GenKind::For { ref body, .. } => self.check_block(cx, body),
+            }
+        }
+    }
+
+    /// Check the name of a parameter or type definition.
+    fn check_node<'a>(&self, cx: &impl Context<'a>, id: NodeId) {
+        match cx.hir_of(id) {
+            Ok(HirNode::ValueParam(param)) if param.local => self.check(
+                cx,
+                "localparam",
+                format!("local parameter `{}`", param.name.value),
+                param.name,
+            ),
+            Ok(HirNode::ValueParam(param)) => {
+                self.check(cx, "parameter", param.desc_full(), param.name)
+            }
+            Ok(HirNode::Typedef(def)) => {
+                self.check(cx, "type", format!("type `{}`", def.name.value), def.name)
+            }
+            _ => (),
+        }
+    }
+
+    /// Check a name against the convention for a kind of declaration.
+    fn check<'a>(&self, cx: &impl Context<'a>, key: &str, desc: String, name: Spanned<Name>) {
+        if self.patterns[key].is_match(&name.value.as_str()) {
+            return;
+        }
+        let what = KINDS.iter().find(|&&(k, _)| k == key).unwrap().1;
+        cx.emit(
+            DiagBuilder2::lint(
+                &NAMING,
+                format!("{} does not follow the naming convention", desc),
+            )
+            .span(name.span)
+            .add_note(format!(
+                "Names of {} must match `{}`",
+                what,
+                cx.sess().opts.lints.option(&NAMING, key)
+            )),
+        );
+    }
+}
@@ -5,10 +5,11 @@
 use crate::{
     ast_map::AstNode,
     crate_prelude::*,
-    hir::{NamedParam, PosParam},
+    hir::{HirNode, NamedParam, PosParam},
     ty::UnpackedType,
     value::Value,
 };
+use std::borrow::Cow;
 
 /// A parameter environment.
 ///
@@ -81,6 +82,7 @@ pub struct ParamEnvData<'t> {
     values: Vec<(NodeId, ParamEnvBinding<Value<'t>>)>,
     types: Vec<(NodeId, ParamEnvBinding<&'t UnpackedType<'t>>)>,
     intfs: Vec<(NodeId, NodeEnvId)>,
+    lets: Vec<(NodeId, NodeEnvId)>,
 }
 
 impl<'t> ParamEnvData<'t> {
@@ -108,6 +110,14 @@ impl<'t> ParamEnvData<'t> {
             .map(|&(_, id)| id)
     }
 
+    /// Find the expression bound to a `let` argument.
+    pub fn find_let_arg(&self, node_id: NodeId) -> Option<NodeEnvId> {
+        self.lets
+            .iter()
+            .find(|&&(id, _)| id == node_id)
+            .map(|&(_, id)| id)
+    }
+
     /// Find the node assigned to a value parameter.
     pub fn reverse_find_value(&self, node_id: NodeId) -> Option<NodeId> {
         self.values
@@ -129,6 +139,14 @@ impl<'t> ParamEnvData<'t> {
     pub fn add_interfaces(&mut self, iter: impl IntoIterator<Item = (NodeId, NodeEnvId)>) {
         self.intfs.extend(iter);
     }
+
+    /// Bind expressions to the arguments of a `let` declaration.
+    pub fn add_let_args(&mut self, iter: impl IntoIterator<Item = (NodeId, NodeEnvId)>) {
+        for (arg, actual) in iter {
+            self.lets.retain(|&(n, _)| n != arg);
+            self.lets.push((arg, actual));
+        }
+    }
 }
 
 /// A binding in a parameter environment.
@@ -155,6 +173,12 @@ pub enum ParamEnvSource<'hir> {
         pos: &'hir [PosParam],
         named: &'hir [NamedParam],
     },
+    ClassInst {
+        class: Ref<'hir, hir::Class<'hir>>,
+        env: ParamEnv,
+        /// The `C #(...)` type that specializes the class, if any.
+        spec: Option<Ref<'hir, ast::Type<'hir>>>,
+    },
 }
 
 pub(crate) fn compute<'gcx>(
@@ -205,6 +229,59 @@ pub(crate) fn compute<'gcx>(
             pos,
             named,
         ),
+        ParamEnvSource::ClassInst { class, env, spec } => {
+            let mut pos = vec![];
+            let mut named = vec![];
+            if let Some(Ref(spec)) = spec {
+                if let ast::SpecializedType(_, ref params) = spec.kind.data {
+                    for param in params {
+                        let value_id =
+                            cx.map_ast_with_parent(AstNode::TypeOrExpr(&param.expr), spec.id());
+                        match param.name {
+                            Some(name) => named.push((
+                                param.span,
+                                Spanned::new(name.name, name.span),
+                                Some(value_id),
+                            )),
+                            None => pos.push((param.span, Some(value_id))),
+                        }
+                    }
+                }
+            }
+
+            // Resolve every parameter to a direct binding, such that the same
+            // specialization of a class always maps to the same environment,
+            // regardless of where it is spelled out.
+            let inst_env =
+                param_env_from_instance(cx, class.ast, class.params.clone(), env, &pos, &named)?;
+            let mut types = vec![];
+            let mut values = vec![];
+            for &param_id in &class.params {
+                match cx.ast_of(param_id)? {
+                    AstNode::TypeParam(_, p) => {
+                        let ty = cx.map_to_type_or_error(Ref(p), inst_env);
+                        types.push((param_id, ParamEnvBinding::Direct(ty)));
+                    }
+                    AstNode::ValueParam(..) => {
+                        let value = cx.constant_value_of(param_id, inst_env);
+                        if value.is_error() {
+                            return Err(());
+                        }
+                        values.push((param_id, ParamEnvBinding::Direct(value)));
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            let env = cx.intern_param_env(ParamEnvData {
+                module: Some(class.id),
+                types,
+                values,
+                intfs: Default::default(),
+                lets: Default::default(),
+            });
+            cx.add_param_env_context(env, class.id);
+            Ok(env)
+        }
     }
 }
 
@@ -295,7 +372,52 @@ fn param_env_from_instance<'a>(
         types,
         values,
         intfs: Default::default(),
+        lets: Default::default(),
     });
     cx.add_param_env_context(env, node.id());
     Ok(env)
 }
+
+/// Compute the parameter environment of the expansion of a `let` reference.
+///
+/// The environment of the reference is extended with bindings from each of the
+/// declaration's formal arguments to the corresponding actual expression. The
+/// declaration's expression is then evaluated in this environment.
+#[moore_derive::query]
+pub(crate) fn let_env<'a>(
+    cx: &impl Context<'a>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Result<(NodeId, ParamEnv)> {
+    let hir = match cx.hir_of(expr_id)? {
+        HirNode::Expr(x) => x,
+        x => bug_span!(x.span(), cx, "no let expansion for {:?}", x),
+    };
+    let (decl_id, bindings) = match hir.kind {
+        hir::ExprKind::Let(decl_id, ref bindings) => (decl_id, Cow::Borrowed(bindings)),
+        _ => {
+            // A plain identifier referring to the declaration binds the
+            // default values of all arguments.
+            let decl_id = cx.resolve_node(expr_id, env)?;
+            let decl = match cx.hir_of(decl_id)? {
+                HirNode::LetDecl(x) => x,
+                x => bug_span!(hir.span, cx, "{} is not a let declaration", x.desc_full()),
+            };
+            let bindings = hir::lowering::bind_let_args(cx, decl, &[], hir.span)?;
+            (decl_id, Cow::Owned(bindings))
+        }
+    };
+    let decl = match cx.hir_of(decl_id)? {
+        HirNode::LetDecl(x) => x,
+        _ => unreachable!(),
+    };
+    // Besides the arguments, the declaration itself is bound to the reference
+    // such that the expansion can find the type context of its call site.
+    let mut data = cx.param_env_data(env).clone();
+    data.add_let_args(
+        std::iter::once((decl_id, expr_id.env(env)))
+            .chain(bindings.iter().map(|&(arg, actual)| (arg, actual.env(env)))),
+    );
+    let let_env = cx.intern_param_env(data);
+    Ok((decl.expr, let_env))
+}
@@ -5,6 +5,7 @@
 
 use crate::crate_prelude::*;
 use crate::{ast_map::AstNode, common::arenas::Alloc, hir::HirNode, value::ValueKind};
+use moore_common::lint::PATTERN_OVERWRITE;
 use num::cast::ToPrimitive;
 use std::{collections::HashMap, sync::Arc};
 
@@ -204,12 +205,15 @@ fn map_named_array_pattern<'a>(
                 let entry = (PatternField::Array(elem_ty), to);
                 if let Some((_, prev)) = values.insert(index, entry) {
                     cx.emit(
-                        DiagBuilder2::warning(format!(
-                            "`{}` overwrites previous value `{}` at index {}",
-                            to.span.extract(),
-                            prev.span.extract(),
-                            index
-                        ))
+                        DiagBuilder2::lint(
+                            &PATTERN_OVERWRITE,
+                            format!(
+                                "`{}` overwrites previous value `{}` at index {}",
+                                to.span.extract(),
+                                prev.span.extract(),
+                                index
+                            ),
+                        )
                         .span(to.span)
                         .add_note("Previous value was here:")
                         .span(prev.span),
@@ -334,12 +338,15 @@ fn map_named_struct_pattern<'a>(
                     let entry = (PatternField::Struct(&strukt.members[index]), to);
                     if let Some((_, prev)) = values.insert(index, entry) {
                         cx.emit(
-                            DiagBuilder2::warning(format!(
-                                "`{}` overwrites previous value `{}` for member `{}`",
-                                to.span.extract(),
-                                prev.span.extract(),
-                                name
-                            ))
+                            DiagBuilder2::lint(
+                                &PATTERN_OVERWRITE,
+                                format!(
+                                    "`{}` overwrites previous value `{}` for member `{}`",
+                                    to.span.extract(),
+                                    prev.span.extract(),
+                                    name
+                                ),
+                            )
                             .span(to.span)
                             .add_note("Previous value was here:")
                             .span(prev.span),
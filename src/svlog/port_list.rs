@@ -170,6 +170,9 @@ pub trait PortedNode<'a>: ast::AnyNode<'a> + resolver::ScopedNode<'a> {
 
     /// Get the items of the node.
     fn items(&self) -> &[ast::Item<'a>];
+
+    /// Get the net type of implicit nets, or `None` if they are disabled.
+    fn default_nettype(&self) -> Option<ast::NetType>;
 }
 
 impl<'a> PortedNode<'a> for ast::Module<'a> {
@@ -179,6 +182,9 @@ impl<'a> PortedNode<'a> for ast::Module<'a> {
     fn items(&self) -> &[ast::Item<'a>] {
         &self.items
     }
+    fn default_nettype(&self) -> Option<ast::NetType> {
+        self.default_nettype
+    }
 }
 
 impl<'a> PortedNode<'a> for ast::Interface<'a> {
@@ -188,6 +194,9 @@ impl<'a> PortedNode<'a> for ast::Interface<'a> {
     fn items(&self) -> &[ast::Item<'a>] {
         &self.items
     }
+    fn default_nettype(&self) -> Option<ast::NetType> {
+        self.default_nettype
+    }
 }
 
 // Compare and hash `PortedNode` by reference for use in the query system.
@@ -242,7 +251,12 @@ pub(crate) fn canonicalize_ports<'a>(
 
     // First determined if the node uses ANSI or non-ANSI style. We do this by
     // Determining whether the first port has type, sign, and direction omitted.
-    // If it has, the ports are declared in non-ANSI style.
+    // If it has, the ports are declared in non-ANSI style. Checkers have no
+    // non-ANSI style and always declare their ports in the header.
+    let checker = match node.as_all() {
+        ast::AllNode::Module(m) => m.kind == ast::ModuleKind::Checker,
+        _ => false,
+    };
     let (nonansi, first_span) = {
         let first = match ast_ports.first() {
             Some(p) => p,
@@ -275,7 +289,7 @@ pub(crate) fn canonicalize_ports<'a>(
             ast::PortData::Implicit(_) => true,
             _ => false,
         };
-        (nonansi, first.span())
+        (nonansi && !checker, first.span())
     };
     debug!(
         "Module uses {} style",
@@ -292,29 +306,48 @@ pub(crate) fn canonicalize_ports<'a>(
     // Extend the internal port with default sign, port kind, and data type
     // where necessary in order to arrive at a final internal port list.
     let mut ports = vec![];
-    let default_net_type = ast::NetType::Wire; // TODO: Make changeable by directive
+    let default_net_type = node.default_nettype();
 
     for port in partial_ports.int {
         let port_id = port.ast.id();
 
-        // Determine the port kind.
-        let kind = port.kind.unwrap_or_else(|| match port.dir {
-            ast::PortDir::Input | ast::PortDir::Inout => ast::VarKind::Net {
-                ty: default_net_type,
-                kind: ast::NetKind::None,
-            },
-            ast::PortDir::Output => {
-                if port.ty.data == ast::ImplicitType {
-                    ast::VarKind::Net {
-                        ty: default_net_type,
+        // Determine the port kind. Ports that would become a net of the
+        // default net type are an error if `default_nettype none is in
+        // effect, unless they carry an explicit data type, in which case they
+        // become variables.
+        let implicit_ty = port.ty_span.is_none() || port.ty.data == ast::ImplicitType;
+        let kind = match port.kind {
+            Some(kind) => kind,
+            None => {
+                let implicit_net = match port.dir {
+                    ast::PortDir::Input | ast::PortDir::Inout => true,
+                    ast::PortDir::Output => implicit_ty,
+                    ast::PortDir::Ref => false,
+                };
+                match (implicit_net, default_net_type) {
+                    (true, Some(ty)) => ast::VarKind::Net {
+                        ty,
                         kind: ast::NetKind::None,
+                    },
+                    (true, None) if implicit_ty => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "port `{}` has no net type, but `default_nettype none is in \
+                                 effect",
+                                port.name
+                            ))
+                            .span(port.name.span)
+                            .add_note("Declare the port with an explicit net type such as `wire`"),
+                        );
+                        ast::VarKind::Net {
+                            ty: ast::NetType::Wire,
+                            kind: ast::NetKind::None,
+                        }
                     }
-                } else {
-                    ast::VarKind::Var
+                    _ => ast::VarKind::Var,
                 }
             }
-            ast::PortDir::Ref => ast::VarKind::Var,
-        });
+        };
 
         // Verify that `inout` ports are of net kind, and `ref` ports are of var
         // kind.
@@ -434,13 +467,18 @@ fn lower_node_ports_ansi<'a>(
     // Ports have a rather sticky way of tracking types, signs, dimensions, etc.
     // Keep a list of "carry" variables that carry over the previous port's
     // details over to the next port. Initialize with the default mandated by
-    // the standard.
-    let mut carry_dir = ast::PortDir::Inout;
+    // the standard. Checker ports are inputs unless stated otherwise (see IEEE
+    // 1800-2017 §17.2).
+    let mut carry_dir = match node.as_all() {
+        ast::AllNode::Module(m) if m.kind == ast::ModuleKind::Checker => ast::PortDir::Input,
+        _ => ast::PortDir::Inout,
+    };
     let mut carry_kind: Option<ast::VarKind> = None;
     let mut carry_ty = Cow::Owned(ast::TypeKind::new(
         first_span.begin().into(),
         ast::LogicType,
     ));
+    let mut carry_ty_span = None;
     let mut carry_sign = ast::TypeSign::None;
     let mut carry_packed_dims: &[ast::TypeDim] = &[];
 
@@ -461,6 +499,10 @@ fn lower_node_ports_ansi<'a>(
                 dims: unpacked_dims,
                 expr,
             } => {
+                // Whether the port inherits everything from the previous port,
+                // in which case an explicit data type carries over as well.
+                let inherits = dir.is_none() && kind.is_none();
+
                 // If no direction has been provided, use the one carried over
                 // from the previous port.
                 let dir = dir.unwrap_or(carry_dir);
@@ -475,7 +517,8 @@ fn lower_node_ports_ansi<'a>(
                     && ty.sign == ast::TypeSign::None
                     && ty.dims.is_empty()
                 {
-                    (carry_ty, carry_sign, carry_packed_dims, None)
+                    let ty_span = if inherits { carry_ty_span } else { None };
+                    (carry_ty, carry_sign, carry_packed_dims, ty_span)
                 } else {
                     (
                         Cow::Borrowed(&ty.kind),
@@ -490,6 +533,7 @@ fn lower_node_ports_ansi<'a>(
                 carry_dir = dir;
                 carry_kind = kind;
                 carry_ty = ty.clone();
+                carry_ty_span = ty_span;
                 carry_sign = sign;
                 carry_packed_dims = packed_dims;
 
@@ -0,0 +1,389 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Semantic checks on procedures.
+//!
+//! The `always_comb`, `always_latch`, and `always_ff` procedures state the
+//! intent to model combinational logic, latches, and flip-flops, respectively
+//! (see IEEE 1800-2017 §9.2.2). The checks in this module report procedures
+//! whose contents contradict that intent.
+
+use crate::crate_prelude::*;
+use crate::hir::{AccessedNode, HirNode, Visitor};
+use moore_common::lint::{ALWAYS_FF_CLOCK, BLOCKING_IN_FF, INFERRED_LATCH, NONBLOCKING_IN_COMB};
+use num::BigInt;
+use std::collections::{BTreeSet, HashMap};
+
+/// Check the contents of an `always_comb` or `always_ff` procedure.
+///
+/// Warns about variables that are not assigned on all paths through an
+/// `always_comb` procedure and therefore infer a latch, about `always_ff`
+/// procedures that are not triggered by a clock edge, and about blocking
+/// assignments in `always_ff` and nonblocking assignments in `always_comb`.
+#[moore_derive::query]
+pub(crate) fn check_procedure<'a>(
+    cx: &impl Context<'a>,
+    proc_id: NodeId,
+    env: ParamEnv,
+) -> Result<()> {
+    let hir = proc_hir(cx, proc_id)?;
+    match hir.kind {
+        ast::ProcedureKind::AlwaysComb => {
+            check_inferred_latches(cx, hir, env)?;
+            check_assign_kinds(cx, hir, env);
+        }
+        ast::ProcedureKind::AlwaysFf => {
+            check_clock_edge(cx, hir, env)?;
+            check_assign_kinds(cx, hir, env);
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Get the HIR of a procedure.
+fn proc_hir<'a>(cx: &impl Context<'a>, id: NodeId) -> Result<&'a hir::Proc> {
+    match cx.hir_of(id)? {
+        HirNode::Proc(x) => Ok(x),
+        _ => unreachable!(),
+    }
+}
+
+/// Report variables that are not assigned on all paths through an
+/// `always_comb` procedure.
+fn check_inferred_latches<'a>(
+    cx: &impl Context<'a>,
+    hir: &'a hir::Proc,
+    env: ParamEnv,
+) -> Result<()> {
+    let acc = cx.accessed_nodes(hir.stmt, env)?;
+    let mut paths = PathAnalysis {
+        cx,
+        env,
+        assigns: HashMap::new(),
+    };
+    let assigned = paths.stmt(hir.stmt, false);
+    for node in &acc.written {
+        let id = match *node {
+            AccessedNode::Regular(id) => id,
+            _ => continue,
+        };
+        if assigned.contains(&id) {
+            continue;
+        }
+        let desc = cx.hir_of(id)?.desc_full();
+        let span = paths.assigns.get(&id).cloned().unwrap_or(hir.span);
+        cx.emit(
+            DiagBuilder2::lint(
+                &INFERRED_LATCH,
+                format!(
+                    "{} is not assigned on all paths through `always_comb` procedure; latch \
+                     inferred",
+                    desc
+                ),
+            )
+            .span(span)
+            .add_note(format!(
+                "Assign {} on every path, e.g. at the start of the procedure, or use \
+                 `always_latch`",
+                desc
+            )),
+        );
+    }
+    Ok(())
+}
+
+/// Report an `always_ff` procedure that is not triggered by a clock edge.
+fn check_clock_edge<'a>(cx: &impl Context<'a>, hir: &'a hir::Proc, env: ParamEnv) -> Result<()> {
+    let event_expr = match cx.hir_of(hir.stmt)? {
+        HirNode::Stmt(hir::Stmt {
+            kind:
+                hir::StmtKind::Timed {
+                    control: hir::TimingControl::ExplicitEvent(expr_id),
+                    ..
+                },
+            ..
+        }) => Some(*expr_id),
+        _ => None,
+    };
+    let has_edge = match event_expr {
+        Some(expr_id) => match cx.hir_of(expr_id)? {
+            HirNode::EventExpr(x) => x.events.iter().any(|event| {
+                event.edge != ast::EdgeIdent::Implicit
+                    || crate::clocking::clocking_block_of_expr(cx, event.expr, env).is_some()
+            }),
+            _ => false,
+        },
+        None => false,
+    };
+    if !has_edge {
+        cx.emit(
+            DiagBuilder2::lint(
+                &ALWAYS_FF_CLOCK,
+                "`always_ff` procedure is not triggered by a clock edge",
+            )
+            .span(hir.span)
+            .add_note(
+                "Start the procedure with an event control such as `@(posedge clk)`, or use \
+                 `always_comb` or `always_latch`",
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Report blocking assignments in `always_ff` procedures, and nonblocking
+/// assignments in `always_comb` procedures.
+fn check_assign_kinds<'a>(cx: &impl Context<'a>, hir: &'a hir::Proc, env: ParamEnv) {
+    let mut checker = AssignKindChecker { cx, env, hir };
+    checker.visit_node_with_id(hir.stmt, false);
+}
+
+/// Determine the variables assigned by an lvalue.
+fn assigned_vars(lv: &mir::Lvalue, into: &mut Vec<NodeId>) {
+    match lv.kind {
+        mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => into.push(id),
+        mir::LvalueKind::Index { value, .. } | mir::LvalueKind::Member { value, .. } => {
+            assigned_vars(value, into)
+        }
+        mir::LvalueKind::DestructArray(ref values) => {
+            for value in values.values() {
+                assigned_vars(value, into);
+            }
+        }
+        mir::LvalueKind::DestructStruct(ref values) => {
+            for value in values {
+                assigned_vars(value, into);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// An analysis of the variables that are assigned on all paths through a
+/// statement.
+///
+/// An assignment to a part of a variable, such as `x[0] = 1`, counts as an
+/// assignment to the entire variable.
+struct PathAnalysis<'a, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    /// The first assignment to each variable.
+    assigns: HashMap<NodeId, Span>,
+}
+
+impl<'a, 'gcx: 'a, C> PathAnalysis<'a, C>
+where
+    C: Context<'gcx>,
+{
+    /// Determine the variables assigned on all paths through a statement.
+    ///
+    /// If `full` is set, the statement is part of a `unique` or `priority`
+    /// `else if` chain, such that a missing `else` is not a path.
+    fn stmt(&mut self, stmt_id: NodeId, full: bool) -> BTreeSet<NodeId> {
+        let stmt = match self.cx.hir_of(stmt_id) {
+            Ok(HirNode::Stmt(x)) => x,
+            _ => return BTreeSet::new(),
+        };
+        match stmt.kind {
+            hir::StmtKind::Block(ref stmts) | hir::StmtKind::InlineGroup { ref stmts, .. } => {
+                let mut assigned = BTreeSet::new();
+                for &stmt in stmts {
+                    assigned.extend(self.stmt(stmt, false));
+                }
+                assigned
+            }
+            hir::StmtKind::Assign { lhs, .. } => {
+                let mut vars = vec![];
+                assigned_vars(self.cx.mir_lvalue(lhs, self.env), &mut vars);
+                for &var in &vars {
+                    self.assigns.entry(var).or_insert(stmt.span);
+                }
+                vars.into_iter().collect()
+            }
+            hir::StmtKind::Timed { stmt, .. } | hir::StmtKind::Wait { stmt, .. } => {
+                self.stmt(stmt, false)
+            }
+            hir::StmtKind::If {
+                up,
+                main_stmt,
+                else_stmt,
+                ..
+            } => {
+                let full = full || up.map(|up| up.is_full()).unwrap_or(false);
+                let main = self.stmt(main_stmt, false);
+                match else_stmt {
+                    Some(else_stmt) => {
+                        let is_else_if = match self.cx.hir_of(else_stmt) {
+                            Ok(HirNode::Stmt(hir::Stmt {
+                                kind: hir::StmtKind::If { up: None, .. },
+                                ..
+                            })) => true,
+                            _ => false,
+                        };
+                        let other = self.stmt(else_stmt, full && is_else_if);
+                        main.intersection(&other).cloned().collect()
+                    }
+                    None if full => main,
+                    None => BTreeSet::new(),
+                }
+            }
+            hir::StmtKind::Case {
+                ref ways, default, ..
+            } => {
+                let full = stmt.kind.case_synthesis().unwrap().full_case
+                    || crate::case::handles_all_values(self.cx, stmt_id, self.env);
+                let mut branches = vec![];
+                for &(_, stmt) in ways {
+                    branches.push(self.stmt(stmt, false));
+                }
+                if let Some(default) = default {
+                    branches.push(self.stmt(default, false));
+                }
+                let mut branches = branches.into_iter();
+                match branches.next() {
+                    Some(first) if full => branches.fold(first, |acc, branch| {
+                        acc.intersection(&branch).cloned().collect()
+                    }),
+                    _ => BTreeSet::new(),
+                }
+            }
+            hir::StmtKind::Loop { kind, body } => {
+                let assigned = self.stmt(body, false);
+                match kind {
+                    hir::LoopKind::Forever | hir::LoopKind::Do(_) => assigned,
+                    hir::LoopKind::For(init, cond, _) if self.runs_once(init, cond) => assigned,
+                    _ => BTreeSet::new(),
+                }
+            }
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Check whether a `for` loop runs at least once.
+    ///
+    /// This is the case if the loop variable starts out at a constant, and
+    /// the condition compares it against a constant such that it holds for
+    /// the first iteration, as in `for (int i = 0; i < N; i++)`.
+    fn runs_once(&self, init: NodeId, cond: NodeId) -> bool {
+        let (var, start) = match self.loop_var(init) {
+            Some(x) => x,
+            None => return false,
+        };
+        let (op, lhs, rhs) = match self.cx.hir_of(cond) {
+            Ok(HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Binary(op, lhs, rhs),
+                ..
+            })) => (*op, *lhs, *rhs),
+            _ => return false,
+        };
+        let is_var = |expr_id| self.cx.resolve_node(expr_id, self.env) == Ok(var);
+        let (start, bound) = if is_var(lhs) {
+            match self.const_int(rhs) {
+                Some(bound) => (start, bound),
+                None => return false,
+            }
+        } else if is_var(rhs) {
+            match self.const_int(lhs) {
+                Some(bound) => (bound, start),
+                None => return false,
+            }
+        } else {
+            return false;
+        };
+        match op {
+            hir::BinaryOp::Lt => start < bound,
+            hir::BinaryOp::Leq => start <= bound,
+            hir::BinaryOp::Gt => start > bound,
+            hir::BinaryOp::Geq => start >= bound,
+            hir::BinaryOp::Neq => start != bound,
+            _ => false,
+        }
+    }
+
+    /// Determine the variable a `for` loop initializes, and its constant
+    /// initial value.
+    fn loop_var(&self, init: NodeId) -> Option<(NodeId, BigInt)> {
+        match self.cx.hir_of(init).ok()? {
+            HirNode::Stmt(hir::Stmt {
+                kind: hir::StmtKind::InlineGroup { stmts, .. },
+                ..
+            }) if stmts.len() == 1 => match self.cx.hir_of(stmts[0]).ok()? {
+                HirNode::VarDecl(decl) => Some((decl.id, self.const_int(decl.init?)?)),
+                _ => None,
+            },
+            HirNode::Stmt(hir::Stmt {
+                kind: hir::StmtKind::Assign { lhs, rhs, .. },
+                ..
+            }) => Some((
+                self.cx.resolve_node(*lhs, self.env).ok()?,
+                self.const_int(*rhs)?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Evaluate an expression to a constant integer, if it is constant.
+    fn const_int(&self, expr_id: NodeId) -> Option<BigInt> {
+        let mir = self.cx.mir_rvalue(expr_id, self.env);
+        if mir.is_error() || !mir.is_const() {
+            return None;
+        }
+        self.cx.const_mir_rvalue(mir.into()).get_int().cloned()
+    }
+}
+
+/// A visitor that reports assignments of the wrong kind in a procedure.
+struct AssignKindChecker<'a, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    hir: &'a hir::Proc,
+}
+
+impl<'a, 'gcx: 'a, C> Visitor<'gcx> for AssignKindChecker<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        if let hir::StmtKind::Assign { lhs, kind, .. } = stmt.kind {
+            match (self.hir.kind, kind) {
+                (ast::ProcedureKind::AlwaysFf, hir::AssignKind::Block(_)) => {
+                    // Blocking assignments to variables declared within the
+                    // procedure are fine, since they do not model flip-flops.
+                    let mut vars = vec![];
+                    assigned_vars(self.cx.mir_lvalue(lhs, self.env), &mut vars);
+                    if vars
+                        .iter()
+                        .any(|&var| !self.cx.is_parent_of(self.hir.id, var))
+                    {
+                        self.cx.emit(
+                            DiagBuilder2::lint(
+                                &BLOCKING_IN_FF,
+                                "blocking assignment in `always_ff` procedure",
+                            )
+                            .span(stmt.span)
+                            .add_note("Use a nonblocking assignment `<=` to model a flip-flop"),
+                        );
+                    }
+                }
+                (ast::ProcedureKind::AlwaysComb, hir::AssignKind::Nonblock)
+                | (ast::ProcedureKind::AlwaysComb, hir::AssignKind::NonblockDelay(_)) => {
+                    self.cx.emit(
+                        DiagBuilder2::lint(
+                            &NONBLOCKING_IN_COMB,
+                            "nonblocking assignment in `always_comb` procedure",
+                        )
+                        .span(stmt.span)
+                        .add_note("Use a blocking assignment `=` to model combinational logic"),
+                    );
+                }
+                _ => (),
+            }
+        }
+        hir::walk_stmt(self, stmt);
+    }
+}
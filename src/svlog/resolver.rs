@@ -9,7 +9,7 @@ use crate::crate_prelude::*;
 use crate::{
     ast::AnyNode,
     ast_map::AstNode,
-    common::{SessionContext, Verbosity},
+    common::{arenas::Alloc, SessionContext, Verbosity},
     hir::HirNode,
     port_list::{self, AsPortedNode},
     ParamEnv,
@@ -147,6 +147,15 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
             }
         }
         AstNode::SubroutineDecl(decl) => Some(RibKind::Normal(decl.prototype.name, node_id)),
+        AstNode::DpiDecl(decl) => match decl.data {
+            ast::DpiDeclData::Import { ref prototype, .. } => {
+                Some(RibKind::Normal(prototype.name, node_id))
+            }
+            ast::DpiDeclData::Export { .. } => None,
+        },
+        AstNode::LetDecl(decl) => Some(RibKind::Normal(decl.name, node_id)),
+        AstNode::LetArg(arg) => Some(RibKind::Normal(arg.name, node_id)),
+        AstNode::Covergroup(cg) => Some(RibKind::Normal(cg.name, node_id)),
         _ => None,
     };
     if kind.is_none() {
@@ -302,7 +311,16 @@ pub(crate) fn resolve_node<'gcx>(
     let hir = cx.hir_of(node_id)?;
     match hir {
         HirNode::Expr(expr) => match expr.kind {
-            hir::ExprKind::Ident(ident) => return cx.resolve_upwards_or_error(ident, node_id),
+            hir::ExprKind::Ident(ident) => {
+                // Names in inline constraints refer to the properties of the
+                // randomized object first.
+                if let Some(prop) =
+                    crate::constraint::resolve_inline_property(cx, node_id, ident.value, env)
+                {
+                    return Ok(prop);
+                }
+                return cx.resolve_upwards_or_error(ident, node_id);
+            }
             hir::ExprKind::Scope(scope_id, name) => {
                 let within = cx.resolve_node(scope_id, env)?;
                 return cx.resolve_downwards_or_error(name, within);
@@ -398,6 +416,9 @@ pub(crate) fn resolve_field_access<'a>(
     };
 
     let ty = cx.type_of(target_id, env)?;
+    if ty.is_error() {
+        return Err(());
+    }
     let strukt = if let Some(strukt) = ty.get_struct() {
         strukt
     } else {
@@ -452,10 +473,12 @@ impl<'a> ScopedNode<'a> for ast::Stmt<'a> {}
 impl<'a> ScopedNode<'a> for ast::Procedure<'a> {}
 impl<'a> ScopedNode<'a> for ast::ClassDecl<'a> {}
 impl<'a> ScopedNode<'a> for ast::SubroutineDecl<'a> {}
+impl<'a> ScopedNode<'a> for ast::LetDecl<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateFor<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateIf<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateCase<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateBlock<'a> {}
+impl<'a> ScopedNode<'a> for ast::ConstraintItem<'a> {}
 
 // Compare and hash scoped nodes by reference for use in the query system.
 impl<'a> Eq for &'a dyn ScopedNode<'a> {}
@@ -506,10 +529,15 @@ impl<'a> AsScopedNode<'a> for ast::AllNode<'a> {
             ast::AllNode::Procedure(x) => Some(x),
             ast::AllNode::ClassDecl(x) => Some(x),
             ast::AllNode::SubroutineDecl(x) => Some(x),
+            ast::AllNode::LetDecl(x) => Some(x),
             ast::AllNode::GenerateFor(x) => Some(x),
             ast::AllNode::GenerateIf(x) => Some(x),
             ast::AllNode::GenerateCase(x) => Some(x),
             ast::AllNode::GenerateBlock(x) => Some(x),
+            ast::AllNode::ConstraintItem(x) => match x.data {
+                ast::ConstraintItemData::Foreach(..) => Some(x),
+                _ => None,
+            },
             _ => None,
         }
     }
@@ -535,6 +563,7 @@ pub(crate) fn generated_scope<'a>(
             defs: Default::default(),
             wildcard_imports: Default::default(),
             subscopes: Default::default(),
+            implicit_nets: Default::default(),
         },
     );
     debug!("Generating scope {:?}", node);
@@ -559,6 +588,10 @@ pub(crate) fn generated_scope<'a>(
     // Gather the definitions.
     node.accept(&mut gen);
 
+    // Declare implicit nets for undeclared identifiers on port connections
+    // and the left-hand side of continuous assignments.
+    declare_implicit_nets(cx, &mut gen);
+
     // If this is the AST root, pull up `GLOBAL` definitions from the subscopes.
     if node.as_all().is_root() {
         trace!("Pulling up global defs from subscopes");
@@ -588,6 +621,8 @@ pub struct Scope<'a> {
     pub wildcard_imports: Vec<&'a ast::ImportItem<'a>>,
     /// The subscopes.
     pub subscopes: Vec<&'a dyn ScopedNode<'a>>,
+    /// The implicit nets declared in this scope.
+    pub implicit_nets: Vec<&'a ast::NetDecl<'a>>,
 }
 
 /// A definition in a scope.
@@ -716,12 +751,26 @@ struct ScopeGenerator<'a, 'c, C> {
     cx: &'c C,
     /// The scope being assembled.
     scope: Scope<'a>,
+    /// Identifiers which implicitly declare a net if undeclared.
+    implicit_uses: Vec<&'a ast::Expr<'a>>,
 }
 
 impl<'a, 'c, C: Context<'a>> ScopeGenerator<'a, 'c, C> {
     /// Create a new scope generator.
     pub fn new(cx: &'c C, scope: Scope<'a>) -> Self {
-        ScopeGenerator { cx, scope }
+        ScopeGenerator {
+            cx,
+            scope,
+            implicit_uses: Default::default(),
+        }
+    }
+
+    /// Register an expression which may implicitly declare a net.
+    pub fn add_implicit_use(&mut self, expr: &'a ast::Expr<'a>) {
+        if let ast::IdentExpr(..) = expr.data {
+            trace!(" - Adding potential implicit net {:?}", expr);
+            self.implicit_uses.push(expr);
+        }
     }
 
     /// Register a subscope.
@@ -867,12 +916,18 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         {
             return true;
         }
+        // Class properties are visible throughout the class body.
+        let is_property = node
+            .get_parent()
+            .and_then(|p| p.get_parent())
+            .map(|p| p.as_all().is_class_decl())
+            .unwrap_or(false);
         self.add_def(Def {
             node: DefNode::Ast(node),
             name: Spanned::new(node.name, node.name_span),
             vis: DefVis::LOCAL | DefVis::HIERARCHICAL,
             may_override: false,
-            ordered: true,
+            ordered: !is_property,
         });
         true
     }
@@ -932,6 +987,28 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         true
     }
 
+    fn pre_visit_covergroup(&mut self, node: &'a ast::Covergroup<'a>) -> bool {
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::NAMESPACE | DefVis::HIERARCHICAL,
+            may_override: false,
+            ordered: true,
+        });
+        false
+    }
+
+    fn pre_visit_clocking_decl(&mut self, node: &'a ast::ClockingDecl<'a>) -> bool {
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::NAMESPACE | DefVis::HIERARCHICAL,
+            may_override: false,
+            ordered: true,
+        });
+        false
+    }
+
     fn pre_visit_procedure(&mut self, node: &'a ast::Procedure<'a>) -> bool {
         self.add_subscope(node);
         false
@@ -939,13 +1016,13 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
 
     fn pre_visit_class_decl(&mut self, node: &'a ast::ClassDecl<'a>) -> bool {
         self.add_subscope(node);
-        // self.add_def(Def {
-        //     node: DefNode::Ast(node),
-        //     name: node.name,
-        //     vis: DefVis::LOCAL | DefVis::NAMESPACE,
-        //     may_override: false,
-        //     ordered: true,
-        // });
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::NAMESPACE,
+            may_override: false,
+            ordered: true,
+        });
         false
     }
 
@@ -985,6 +1062,29 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         true
     }
 
+    fn pre_visit_port_conn(&mut self, node: &'a ast::PortConn<'a>) -> bool {
+        match node.data {
+            ast::PortConnData::Named(_, ast::PortConnMode::Connected(ref expr))
+            | ast::PortConnData::Positional(ref expr) => self.add_implicit_use(expr),
+            _ => (),
+        }
+        true
+    }
+
+    fn pre_visit_cont_assign(&mut self, node: &'a ast::ContAssign<'a>) -> bool {
+        for (lhs, _) in &node.assignments {
+            self.add_implicit_use(lhs);
+        }
+        true
+    }
+
+    fn pre_visit_gate_inst_name(&mut self, node: &'a ast::GateInstName<'a>) -> bool {
+        for terminal in &node.terminals {
+            self.add_implicit_use(terminal);
+        }
+        true
+    }
+
     fn pre_visit_generate_for(&mut self, node: &'a ast::GenerateFor<'a>) -> bool {
         self.add_subscope(node);
         false
@@ -1034,6 +1134,17 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         }
     }
 
+    fn pre_visit_constraint_item(&mut self, node: &'a ast::ConstraintItem<'a>) -> bool {
+        // Do not traverse into constraints that generate their own scope.
+        match node.data {
+            ast::ConstraintItemData::Foreach(..) => {
+                self.add_subscope(node);
+                false
+            }
+            _ => true,
+        }
+    }
+
     fn pre_visit_foreach_index(&mut self, node: &'a ast::ForeachIndex<'a>) -> bool {
         self.add_def(Def {
             node: DefNode::Ast(node),
@@ -1045,6 +1156,29 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         true
     }
 
+    fn pre_visit_let_decl(&mut self, node: &'a ast::LetDecl<'a>) -> bool {
+        self.add_subscope(node);
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::NAMESPACE | DefVis::HIERARCHICAL,
+            may_override: false,
+            ordered: true,
+        });
+        false
+    }
+
+    fn pre_visit_let_arg(&mut self, node: &'a ast::LetArg<'a>) -> bool {
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL,
+            may_override: false,
+            ordered: false,
+        });
+        true
+    }
+
     fn pre_visit_dpi_decl(&mut self, node: &'a ast::DpiDecl<'a>) -> bool {
         match node.data {
             ast::DpiDeclData::Import { ref prototype, .. } => {
@@ -1062,6 +1196,147 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
     }
 }
 
+/// Declare implicit nets for the undeclared identifiers gathered by a scope
+/// generator.
+///
+/// An identifier on a port connection, a primitive terminal, or the left-hand
+/// side of a continuous assignment that does not resolve to anything implicitly
+/// declares a net of the type set by `` `default_nettype``. If implicit nets
+/// have been disabled with `` `default_nettype none``, this is an error.
+fn declare_implicit_nets<'a, C: Context<'a>>(cx: &C, gen: &mut ScopeGenerator<'a, '_, C>) {
+    let uses = std::mem::replace(&mut gen.implicit_uses, Vec::new());
+    if uses.is_empty() {
+        return;
+    }
+
+    // Find the design element that determines the default net type.
+    let mut next = Some(gen.scope.node.as_any());
+    let mut default_nettype = Some(ast::NetType::Wire);
+    while let Some(node) = next {
+        match node.as_all() {
+            ast::AllNode::Module(x) => {
+                default_nettype = x.default_nettype;
+                break;
+            }
+            ast::AllNode::Interface(x) => {
+                default_nettype = x.default_nettype;
+                break;
+            }
+            _ => next = node.get_parent(),
+        }
+    }
+
+    // The location of this scope in its parent, used to look up names that are
+    // not defined locally. We cannot resolve in the scope itself, since that
+    // would require the scope we are currently generating.
+    let parent_at = gen
+        .scope
+        .parent
+        .map(|_| cx.scope_location(gen.scope.node.as_any()));
+
+    for expr in uses {
+        let name = match expr.data {
+            ast::IdentExpr(name) => name,
+            _ => continue,
+        };
+
+        // Check the local definitions, including previous implicit nets.
+        if let Some(def) = gen.scope.defs.get(&name.value) {
+            if def.vis.contains(DefVis::LOCAL) && (!def.ordered || def.node.order() < expr.order())
+            {
+                continue;
+            }
+        }
+
+        // Check the local wildcard imports. The imported package is looked up
+        // from the parent scope, which is where packages live.
+        let mut found = false;
+        if let Some(at) = parent_at {
+            for &import in &gen.scope.wildcard_imports {
+                if import.order() > expr.order() {
+                    continue;
+                }
+                let inside = match cx.resolve_local(import.pkg.value, at, true) {
+                    Ok(Some(def)) => match def.node {
+                        DefNode::Ast(node) => node.as_all().get_scoped_node(),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(inside) = inside {
+                    if cx.resolve_namespace(name.value, inside).is_some() {
+                        found = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if found {
+            continue;
+        }
+
+        // Check the parent scopes.
+        if let Some(at) = parent_at {
+            if let Ok(Some(_)) = cx.resolve_local(name.value, at, false) {
+                continue;
+            }
+        }
+
+        // Declare the implicit net, or complain if they are disabled. In the
+        // latter case we still declare a `wire` to avoid follow-up errors.
+        let net_type = match default_nettype {
+            Some(x) => x,
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` not declared, and `default_nettype none forbids implicit nets",
+                        name
+                    ))
+                    .span(name.span),
+                );
+                ast::NetType::Wire
+            }
+        };
+        debug!("Declaring implicit {} `{}`", net_type, name);
+        let decl = cx.arena().alloc(ast::NetDecl::new(
+            name.span,
+            ast::NetDeclData {
+                net_type,
+                strength: None,
+                kind: ast::NetKind::None,
+                ty: ast::Type::new(
+                    name.span,
+                    ast::TypeData {
+                        kind: ast::TypeKind::new(name.span, ast::ImplicitType),
+                        sign: ast::TypeSign::None,
+                        dims: vec![],
+                    },
+                ),
+                delay: None,
+                names: vec![ast::VarDeclName::new(
+                    name.span,
+                    ast::VarDeclNameData {
+                        name: name.value,
+                        name_span: name.span,
+                        dims: vec![],
+                        init: None,
+                    },
+                )],
+            },
+        ));
+        decl.link_attach(expr.get_parent().unwrap_or(expr), expr.order());
+        cx.register_ast(decl);
+        gen.add_def(Def {
+            node: DefNode::Ast(&decl.names[0]),
+            name,
+            vis: DefVis::LOCAL | DefVis::HIERARCHICAL,
+            may_override: false,
+            ordered: false,
+        });
+        gen.scope.implicit_nets.push(decl);
+    }
+}
+
 /// Determine the location of a node within its enclosing scope.
 #[moore_derive::query]
 pub(crate) fn scope_location<'a>(
@@ -1158,6 +1433,14 @@ pub(crate) fn resolve_local<'a>(
             }
         }
 
+        // Classes inherit the members of their base class.
+        if let Some(class) = scope.node.as_all().get_class_decl() {
+            if let Some(def) = resolve_inherited(cx, name, class)? {
+                debug!(" - Found inherited {:?}", def);
+                return Ok(Some(def));
+            }
+        }
+
         // Check the wildcard imports for any luck.
         if skip_imports {
             continue;
@@ -1176,6 +1459,89 @@ pub(crate) fn resolve_local<'a>(
     Ok(None)
 }
 
+/// Resolve a name among the members inherited by a class.
+///
+/// Walks up the chain of base classes named in `extends` clauses and returns
+/// the first member with the given name. Returns `None` if no base class
+/// declares such a member.
+fn resolve_inherited<'a>(
+    cx: &impl Context<'a>,
+    name: Name,
+    class: &'a ast::ClassDecl<'a>,
+) -> Result<Option<&'a Def<'a>>> {
+    let mut seen = vec![class.id()];
+    let mut next = resolve_base_class(cx, class)?;
+    while let Some(base) = next {
+        if seen.contains(&base.id()) {
+            break;
+        }
+        seen.push(base.id());
+        let scope = cx.generated_scope(base);
+        match scope.defs.get(&name) {
+            Some(def) if def.vis.contains(DefVis::LOCAL) => return Ok(Some(def)),
+            _ => (),
+        }
+        next = resolve_base_class(cx, base)?;
+    }
+    Ok(None)
+}
+
+/// Resolve the base class named in the `extends` clause of a class.
+///
+/// Returns `None` if the class does not extend another class.
+pub(crate) fn resolve_base_class<'a>(
+    cx: &impl Context<'a>,
+    class: &'a ast::ClassDecl<'a>,
+) -> Result<Option<&'a ast::ClassDecl<'a>>> {
+    let ty = match class.extends {
+        Some((ref ty, _)) => ty,
+        None => return Ok(None),
+    };
+    let kind = match ty.kind.data {
+        ast::SpecializedType(ref inner, _) => &inner.kind.data,
+        ref kind => kind,
+    };
+    let def = match *kind {
+        ast::NamedType(name) => cx.resolve_local_or_error(name, cx.scope_location(class), false)?,
+        ast::ScopedType {
+            ty: ref scope,
+            member: false,
+            name,
+        } => {
+            let inside = match scope.kind.data {
+                ast::NamedType(pkg) => {
+                    cx.resolve_local_or_error(pkg, cx.scope_location(class), false)?
+                }
+                _ => return Ok(None),
+            };
+            let inside = match inside.node {
+                DefNode::Ast(node) => node.as_all().get_scoped_node(),
+                _ => None,
+            };
+            match inside {
+                Some(inside) => cx.resolve_namespace_or_error(name, inside)?,
+                None => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+    match def.node {
+        DefNode::Ast(node) => match node.as_all().get_class_decl() {
+            Some(base) => Ok(Some(base)),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!("`{}` is not a class", ty.span.extract()))
+                        .span(ty.span)
+                        .add_note(format!("`{}` refers to {}", ty.span.extract(), node))
+                        .span(node.human_span()),
+                );
+                Err(())
+            }
+        },
+        _ => Ok(None),
+    }
+}
+
 /// Resolve a local name in a scope or emit an error.
 ///
 /// Calls `resolve_local`. Either returns `Ok` if a node was found, or `Err`
@@ -1415,6 +1781,13 @@ where
             }
         }
 
+        // Don't resolve names in inline constraints, since these may refer
+        // to the properties of the randomized object. These are resolved
+        // during type checking.
+        if crate::constraint::inline_constraint_object(self.cx, node.id()).is_some() {
+            return false;
+        }
+
         match node.data {
             ast::IdentExpr(ident) => {
                 self.failed |= self
@@ -1423,6 +1796,7 @@ where
                     .is_err();
                 false
             }
+            ast::ScopeExpr(..) if crate::constraint::is_std_randomize(node) => false,
             _ => true,
         }
     }
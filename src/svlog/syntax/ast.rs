@@ -636,9 +636,13 @@ pub enum Item<'a> {
     InterfaceDecl(#[forward] Interface<'a>),
     PackageDecl(#[forward] Package<'a>),
     ClassDecl(#[forward] ClassDecl<'a>),
-    ProgramDecl(()),
+    ProgramDecl(#[forward] Module<'a>),
+    CheckerDecl(#[forward] Module<'a>),
     ImportDecl(#[forward] ImportDecl<'a>),
     DpiDecl(#[forward] DpiDecl<'a>),
+    LetDecl(#[forward] LetDecl<'a>),
+    CovergroupDecl(#[forward] Covergroup<'a>),
+    ClockingDecl(#[forward] ClockingDecl<'a>),
     ParamDecl(#[forward] ParamDecl<'a>),
     ModportDecl(#[forward] Modport<'a>),
     Typedef(#[forward] Typedef<'a>),
@@ -655,6 +659,7 @@ pub enum Item<'a> {
     NetDecl(NetDecl<'a>),
     VarDecl(#[forward] VarDecl<'a>),
     Inst(Inst<'a>),
+    GateInst(#[forward] GateInst<'a>),
 }
 
 /// A module.
@@ -663,6 +668,7 @@ pub enum Item<'a> {
 #[definite("module `{}`", name)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Module<'a> {
+    pub kind: ModuleKind,
     pub lifetime: Lifetime, // default static
     #[name]
     pub name: Spanned<Name>,
@@ -670,6 +676,23 @@ pub struct Module<'a> {
     pub params: Vec<ParamDecl<'a>>,
     pub ports: Vec<Port<'a>>,
     pub items: Vec<Item<'a>>,
+    /// The net type for implicit nets, as set by `` `default_nettype``. `None`
+    /// if implicit nets are disabled.
+    pub default_nettype: Option<NetType>,
+    /// The `timeunit` and `timeprecision` declarations in the body.
+    pub timeunits: Timeunit,
+    /// The time unit and precision set by `` `timescale``, if any.
+    pub timescale: Timeunit,
+}
+
+/// Whether a module-like construct was declared as a module, a program, or a
+/// checker.
+#[moore_derive::visit]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModuleKind {
+    Module,
+    Program,
+    Checker,
 }
 
 /// An interface.
@@ -684,6 +707,13 @@ pub struct Interface<'a> {
     pub params: Vec<ParamDecl<'a>>,
     pub ports: Vec<Port<'a>>,
     pub items: Vec<Item<'a>>,
+    /// The net type for implicit nets, as set by `` `default_nettype``. `None`
+    /// if implicit nets are disabled.
+    pub default_nettype: Option<NetType>,
+    /// The `timeunit` and `timeprecision` declarations in the body.
+    pub timeunits: Timeunit,
+    /// The time unit and precision set by `` `timescale``, if any.
+    pub timescale: Timeunit,
 }
 
 /// A package.
@@ -696,6 +726,8 @@ pub struct Package<'a> {
     #[name]
     pub name: Spanned<Name>,
     pub timeunits: Timeunit,
+    /// The time unit and precision set by `` `timescale``, if any.
+    pub timescale: Timeunit,
     pub items: Vec<Item<'a>>,
 }
 
@@ -747,7 +779,10 @@ pub enum TypeKind<'a> {
     NamedType(Spanned<Name>),
     StringType,
     ChandleType,
-    VirtIntfType(Name),
+    VirtIntfType {
+        name: Spanned<Name>,
+        modport: Option<Spanned<Name>>,
+    },
     EventType,
     MailboxType,
     ImplicitSignedType,
@@ -1158,6 +1193,11 @@ pub enum StmtKind<'a> {
     AssertionStmt(Box<Assertion<'a>>),
     WaitExprStmt(Expr<'a>, Box<Stmt<'a>>),
     WaitForkStmt,
+    TriggerStmt {
+        target: Expr<'a>,
+        nonblocking: bool,
+        delay: Option<DelayControl<'a>>,
+    },
     DisableForkStmt,
     DisableStmt(Name),
 }
@@ -1190,6 +1230,20 @@ pub enum UniquePriority {
     Priority,
 }
 
+impl UniquePriority {
+    /// Check whether the qualifier asserts that at least one branch matches,
+    /// which corresponds to the `full_case` synthesis attribute.
+    pub fn is_full(self) -> bool {
+        self != UniquePriority::Unique0
+    }
+
+    /// Check whether the qualifier asserts that at most one branch matches,
+    /// which corresponds to the `parallel_case` synthesis attribute.
+    pub fn is_parallel(self) -> bool {
+        self != UniquePriority::Priority
+    }
+}
+
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum CaseKind {
@@ -1325,6 +1379,7 @@ pub enum Expr<'a> {
     /// A system identifier, like `$foo`.
     SysIdentExpr(Spanned<Name>),
     ThisExpr,
+    SuperExpr,
     DollarExpr,
     NullExpr,
     ScopeExpr(Box<Expr<'a>>, Spanned<Name>),
@@ -1386,6 +1441,9 @@ pub enum Expr<'a> {
     CastExpr(Type<'a>, Box<Expr<'a>>),
     CastSizeExpr(Box<Expr<'a>>, Box<Expr<'a>>),
     CastSignExpr(Spanned<TypeSign>, Box<Expr<'a>>),
+    /// A call to `randomize()` with inline constraints, like
+    /// `obj.randomize() with { x < 10; }`.
+    InlineConstraintExpr(Box<Expr<'a>>, Vec<ConstraintItem<'a>>),
     /// A `$bits` call.
     BitsExpr {
         name: Spanned<Name>,
@@ -1621,7 +1679,7 @@ pub enum ClassItemQualifier {
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClassItemData<'a> {
-    Property,
+    Property(VarDecl<'a>),
     Typedef(Typedef<'a>),
     SubroutineDecl(SubroutineDecl<'a>),
     ExternSubroutine(SubroutinePrototype<'a>),
@@ -1672,19 +1730,42 @@ pub enum ConstraintKind {
     PureProto,
 }
 
+/// A constraint item.
+#[moore_derive::node]
+#[indefinite("constraint")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintItem<'a> {
+    /// An expression that must hold, like `x < 10`.
+    Expr(Expr<'a>),
+    /// A distribution, like `x dist { 0 := 1, [1:9] :/ 4 }`.
+    Dist(Expr<'a>, Vec<DistItem<'a>>),
+    /// An implication, like `mode == 0 -> x < 10`.
+    Implication(Expr<'a>, Vec<ConstraintItem<'a>>),
+    /// A conditional, like `if (mode == 0) x < 10; else x > 20;`.
+    If(Expr<'a>, Vec<ConstraintItem<'a>>, Vec<ConstraintItem<'a>>),
+    /// An iteration over an array, like `foreach (a[i]) a[i] < 10;`.
+    Foreach(Expr<'a>, Vec<ForeachIndex<'a>>, Vec<ConstraintItem<'a>>),
+    /// An ordering of random variables, like `solve x before y;`.
+    SolveBefore(Vec<Expr<'a>>, Vec<Expr<'a>>),
+}
+
+/// An item of a `dist` constraint, like `[1:9] :/ 4`.
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ConstraintItem<'a> {
+pub struct DistItem<'a> {
     pub span: Span,
-    pub data: ConstraintItemData<'a>,
+    pub range: ValueRange<'a>,
+    pub weight: Option<(DistWeight, Expr<'a>)>,
 }
 
+/// How the weight of a `dist` item is applied.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ConstraintItemData<'a> {
-    If,
-    Foreach,
-    Expr(Expr<'a>),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistWeight {
+    /// The weight applies to each value in the range, as in `:=`.
+    PerValue,
+    /// The weight is divided among the values in the range, as in `:/`.
+    PerRange,
 }
 
 /// A function or task declaration.
@@ -1807,6 +1888,24 @@ pub enum DriveStrength {
     HighZ1,
 }
 
+impl std::fmt::Display for DriveStrength {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            DriveStrength::Supply0 => "supply0",
+            DriveStrength::Strong0 => "strong0",
+            DriveStrength::Pull0 => "pull0",
+            DriveStrength::Weak0 => "weak0",
+            DriveStrength::HighZ0 => "highz0",
+            DriveStrength::Supply1 => "supply1",
+            DriveStrength::Strong1 => "strong1",
+            DriveStrength::Pull1 => "pull1",
+            DriveStrength::Weak1 => "weak1",
+            DriveStrength::HighZ1 => "highz1",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[moore_derive::visit]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChargeStrength {
@@ -2036,6 +2135,130 @@ impl<'a> InstName<'a> {
     }
 }
 
+/// An instantiation of a gate or switch primitive.
+///
+/// For example `and #1 g1 (y, a, b), g2 (z, c, d);`.
+#[moore_derive::node]
+#[indefinite("primitive instantiation")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateInst<'a> {
+    /// The primitive to instantiate.
+    pub kind: GateKind,
+    /// The drive strength, or the single pull strength of a `pullup` or
+    /// `pulldown`.
+    pub strength: Option<(DriveStrength, Option<DriveStrength>)>,
+    /// The rise, fall, and turn-off delays.
+    pub delays: Vec<Expr<'a>>,
+    /// The names and terminals of the primitive instances.
+    pub names: Vec<GateInstName<'a>>,
+}
+
+/// A single primitive instance.
+///
+/// For example the `g1 (y, a, b)` in `and g1 (y, a, b), g2 (z, c, d);`.
+#[moore_derive::node]
+#[indefinite("primitive instance")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateInstName<'a> {
+    /// The optional name of the instance.
+    pub name: Option<Spanned<Name>>,
+    /// The unpacked dimensions.
+    pub dims: Vec<TypeDim<'a>>,
+    /// The terminal connections.
+    pub terminals: Vec<Expr<'a>>,
+}
+
+/// A gate or switch primitive.
+///
+/// See IEEE 1800-2017 §28 "Gate-level and switch-level modeling".
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateKind {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Xnor,
+    Buf,
+    Not,
+    Bufif0,
+    Bufif1,
+    Notif0,
+    Notif1,
+    Nmos,
+    Pmos,
+    Rnmos,
+    Rpmos,
+    Cmos,
+    Rcmos,
+    Tran,
+    Rtran,
+    Tranif0,
+    Tranif1,
+    Rtranif0,
+    Rtranif1,
+    Pullup,
+    Pulldown,
+}
+
+impl GateKind {
+    /// Check whether this is a bidirectional pass switch.
+    pub fn is_bidirectional(self) -> bool {
+        match self {
+            GateKind::Tran
+            | GateKind::Rtran
+            | GateKind::Tranif0
+            | GateKind::Tranif1
+            | GateKind::Rtranif0
+            | GateKind::Rtranif1 => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether this is a `pullup` or `pulldown` source.
+    pub fn is_pull(self) -> bool {
+        match self {
+            GateKind::Pullup | GateKind::Pulldown => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for GateKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            GateKind::And => "and",
+            GateKind::Nand => "nand",
+            GateKind::Or => "or",
+            GateKind::Nor => "nor",
+            GateKind::Xor => "xor",
+            GateKind::Xnor => "xnor",
+            GateKind::Buf => "buf",
+            GateKind::Not => "not",
+            GateKind::Bufif0 => "bufif0",
+            GateKind::Bufif1 => "bufif1",
+            GateKind::Notif0 => "notif0",
+            GateKind::Notif1 => "notif1",
+            GateKind::Nmos => "nmos",
+            GateKind::Pmos => "pmos",
+            GateKind::Rnmos => "rnmos",
+            GateKind::Rpmos => "rpmos",
+            GateKind::Cmos => "cmos",
+            GateKind::Rcmos => "rcmos",
+            GateKind::Tran => "tran",
+            GateKind::Rtran => "rtran",
+            GateKind::Tranif0 => "tranif0",
+            GateKind::Tranif1 => "tranif1",
+            GateKind::Rtranif0 => "rtranif0",
+            GateKind::Rtranif1 => "rtranif1",
+            GateKind::Pullup => "pullup",
+            GateKind::Pulldown => "pulldown",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// A modport declaration in an interface.
 ///
 /// For example `modport in (...), out (...);`.
@@ -2242,6 +2465,206 @@ pub enum PortConnMode<'a> {
     Connected(Expr<'a>),
 }
 
+/// A `let` declaration.
+///
+/// For example:
+/// ```verilog
+/// let max(a, b = 0) = a > b ? a : b;
+/// ```
+#[moore_derive::node]
+#[indefinite("let declaration")]
+#[definite("let declaration `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetDecl<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    pub args: Vec<LetArg<'a>>,
+    pub expr: Expr<'a>,
+}
+
+/// A formal argument of a `let` declaration.
+///
+/// Arguments declared without a type or as `untyped` carry an `ImplicitType`
+/// without sign or dimensions.
+#[moore_derive::node]
+#[indefinite("let argument")]
+#[definite("let argument `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LetArg<'a> {
+    pub ty: Type<'a>,
+    #[name]
+    pub name: Spanned<Name>,
+    pub default: Option<Expr<'a>>,
+}
+
+/// A covergroup declaration.
+///
+/// For example:
+/// ```verilog
+/// covergroup cg @(posedge clk);
+///     coverpoint opcode { bins alu[] = {[0:7]}; }
+/// endgroup
+/// ```
+#[moore_derive::node]
+#[indefinite("covergroup")]
+#[definite("covergroup `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Covergroup<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    pub event: Option<EventControl<'a>>,
+    pub points: Vec<Coverpoint<'a>>,
+    pub crosses: Vec<CoverCross<'a>>,
+}
+
+/// A coverpoint within a covergroup.
+///
+/// For example `lo: coverpoint addr[3:0] iff (valid) { bins b[] = {[0:3]}; }`.
+#[moore_derive::node]
+#[indefinite("coverpoint")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverpoint<'a> {
+    pub label: Option<Spanned<Name>>,
+    pub expr: Expr<'a>,
+    pub iff: Option<Expr<'a>>,
+    pub bins: Vec<CoverBins<'a>>,
+}
+
+/// A bins declaration within a coverpoint.
+///
+/// For example `bins low[4] = {[0:15]};` or `ignore_bins rest = default;`.
+#[moore_derive::node]
+#[indefinite("bins")]
+#[definite("bins `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverBins<'a> {
+    pub kind: CoverBinsKind,
+    #[name]
+    pub name: Spanned<Name>,
+    /// Whether an array of bins is declared, with an optional explicit size.
+    /// `None` for `bins x`, `Some(None)` for `bins x[]`, and `Some(Some(..))`
+    /// for `bins x[N]`.
+    pub array: Option<Option<Expr<'a>>>,
+    /// The values covered by the bins, or `None` for `default`.
+    pub values: Option<Vec<ValueRange<'a>>>,
+    pub iff: Option<Expr<'a>>,
+}
+
+/// The different kinds of bins.
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverBinsKind {
+    /// Regular `bins`.
+    Bins,
+    /// `illegal_bins`.
+    Illegal,
+    /// `ignore_bins`.
+    Ignore,
+}
+
+/// A cross coverage declaration within a covergroup.
+///
+/// For example `cross a, b;`.
+#[moore_derive::node]
+#[indefinite("cross")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverCross<'a> {
+    pub label: Option<Spanned<Name>>,
+    pub points: Vec<Spanned<Name>>,
+    pub iff: Option<Expr<'a>>,
+}
+
+/// A clocking block declaration.
+///
+/// For example:
+/// ```verilog
+/// default clocking cb @(posedge clk);
+///     default input #1step output #2;
+///     input a, b;
+///     output #1 c;
+///     input d = top.x;
+/// endclocking
+/// ```
+#[moore_derive::node]
+#[indefinite("clocking block")]
+#[definite("clocking block `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockingDecl<'a> {
+    /// Whether this is the default clocking block of the enclosing scope.
+    pub default: bool,
+    #[name]
+    pub name: Spanned<Name>,
+    pub event: EventControl<'a>,
+    /// The skew of inputs without an explicit skew.
+    pub default_input: Option<ClockingSkew<'a>>,
+    /// The skew of outputs without an explicit skew.
+    pub default_output: Option<ClockingSkew<'a>>,
+    pub items: Vec<ClockingItem<'a>>,
+}
+
+/// A group of signals declared in a clocking block with the same direction
+/// and skew.
+///
+/// For example `input #1step a, b = x;`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockingItem<'a> {
+    pub span: Span,
+    pub dir: ClockingDir,
+    pub input_skew: Option<ClockingSkew<'a>>,
+    pub output_skew: Option<ClockingSkew<'a>>,
+    pub signals: Vec<ClockingSignal<'a>>,
+}
+
+/// The direction of a clocking block signal.
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockingDir {
+    Input,
+    Output,
+    /// `input ... output ...`, which declares both an input and an output.
+    InputOutput,
+    Inout,
+}
+
+impl ClockingDir {
+    /// Check whether the signal is sampled by the clocking block.
+    pub fn is_input(self) -> bool {
+        self != ClockingDir::Output
+    }
+
+    /// Check whether the signal is driven by the clocking block.
+    pub fn is_output(self) -> bool {
+        self != ClockingDir::Input
+    }
+}
+
+/// The skew of a clocking block signal.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClockingSkew<'a> {
+    /// `#1step`, which samples the value right before the clocking event.
+    Step,
+    /// A delay such as `#2` or `#1ns`.
+    Delay(DelayControl<'a>),
+}
+
+/// A signal declared in a clocking block.
+///
+/// For example `a` or `d = top.x`.
+#[moore_derive::node]
+#[indefinite("clocking signal")]
+#[definite("clocking signal `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockingSignal<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    /// The expression the signal refers to. Without an explicit `= expr`, the
+    /// parser inserts an identifier referring to the signal of the same name
+    /// in the enclosing scope.
+    pub expr: Expr<'a>,
+}
+
 /// A DPI declaration such as `import "DPI-C"` or `export "DPI-C"`.
 #[moore_derive::node]
 #[indefinite("DPI declaration")]
@@ -3,10 +3,12 @@
 //! A lexical analyzer for SystemVerilog files, based on IEEE 1800-2009, section
 //! 5.
 
+use crate::ast::{NetType, Timeunit};
 use crate::cat::CatTokenKind;
 use crate::preproc::*;
 pub use crate::token::*;
 use moore_common::errors::*;
+use moore_common::lint::get_allow_table;
 use moore_common::name::*;
 use moore_common::source::*;
 
@@ -27,6 +29,16 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// The net type currently set by the `default_nettype directive.
+    pub fn default_nettype(&self) -> Option<NetType> {
+        self.input.default_nettype()
+    }
+
+    /// The time unit and precision currently set by the `timescale directive.
+    pub fn timescale(&self) -> Timeunit {
+        self.input.timescale()
+    }
+
     pub fn bump(&mut self) -> DiagResult2<()> {
         self.peek[0] = self.peek[1];
         self.peek[1] = self.peek[2];
@@ -319,6 +331,16 @@ impl<'a> Lexer<'a> {
                         self.bump()?; // eat the unit
                         return Ok((Literal(Time(value, frac, unit)), sp));
                     }
+                    // The `1step` keyword used in clocking skews.
+                    if self.peek[0].0 == CatTokenKind::Text
+                        && frac.is_none()
+                        && sp.extract() == "1"
+                        && self.peek[0].1.extract() == "step"
+                    {
+                        sp.expand(self.peek[0].1);
+                        self.bump()?; // eat the `step`
+                        return Ok((Keyword(Kw::OneStep), sp));
+                    }
                     if self.peek[0].0 == CatTokenKind::Text {
                         return Err(DiagBuilder2::fatal(format!(
                             "number literal `{}` may not directly be followed by letters `{}`",
@@ -406,7 +428,8 @@ impl<'a> Lexer<'a> {
     /// Skips all input tokens that are excluded from the language's syntax,
     /// i.e. whitespace, newlines, and comments. Note that during lexical
     /// analysis whitespace may still play a vital role, espceially when parsing
-    /// number literals or string constants.
+    /// number literals or string constants. Comments of the form
+    /// `// moore: allow(<lint>)` are recorded in the lint allow table.
     fn skip_noise(&mut self) -> DiagResult2<()> {
         loop {
             match (self.peek[0].0, self.peek[1].0) {
@@ -462,7 +485,9 @@ impl<'a> Lexer<'a> {
                 _ => (),
             }
             match self.peek[0].0 {
-                CatTokenKind::Whitespace | CatTokenKind::Newline | CatTokenKind::Comment => {
+                CatTokenKind::Whitespace | CatTokenKind::Newline => self.bump()?,
+                CatTokenKind::Comment => {
+                    get_allow_table().add_comment(self.peek[0].1);
                     self.bump()?
                 }
                 _ => return Ok(()),
@@ -634,15 +659,7 @@ impl<'a> Lexer<'a> {
     /// Try to parse the next text token as a time unit.
     fn try_time_unit(&mut self) -> Option<TimeUnit> {
         if self.peek[0].0 == CatTokenKind::Text {
-            match self.peek[0].1.extract().as_str() {
-                "s" => Some(TimeUnit::Second),
-                "ms" => Some(TimeUnit::MilliSecond),
-                "us" => Some(TimeUnit::MicroSecond),
-                "ns" => Some(TimeUnit::NanoSecond),
-                "ps" => Some(TimeUnit::PicoSecond),
-                "fs" => Some(TimeUnit::FemtoSecond),
-                _ => None,
-            }
+            TimeUnit::from_suffix(&self.peek[0].1.extract())
         } else {
             None
         }
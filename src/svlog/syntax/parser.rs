@@ -10,7 +10,14 @@ use crate::ast;
 use crate::ast::*;
 use crate::lexer::{Lexer, TokenAndSpan};
 use crate::token::*;
-use moore_common::{arenas::Alloc, errors::*, name::*, source::*, util::HasSpan};
+use moore_common::{
+    arenas::Alloc,
+    errors::*,
+    lint::{LintLevels, BLOCK_LABEL, TRAILING_COMMA, UNSUPPORTED},
+    name::*,
+    source::*,
+    util::HasSpan,
+};
 use std;
 use std::collections::VecDeque;
 
@@ -41,6 +48,8 @@ trait AbstractParser<'n> {
     fn last_span(&self) -> Span;
     fn add_diag(&mut self, diag: DiagBuilder2);
     fn severity(&self) -> Severity;
+    fn default_nettype(&self) -> Option<NetType>;
+    fn timescale(&self) -> Timeunit;
 
     fn try_eat_ident(&mut self) -> Option<(Name, Span)> {
         match self.peek(0) {
@@ -237,6 +246,10 @@ struct Parser<'a, 'n> {
     severity: Severity,
     consumed: usize,
     arena: &'n ast::Arena<'n>,
+    lints: &'a LintLevels,
+    /// The lint warnings reported so far, which are only emitted once the
+    /// entire file has been lexed and all `allow` comments are known.
+    pending_lints: Vec<DiagBuilder2>,
 }
 
 impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
@@ -279,6 +292,46 @@ impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
     }
 
     fn add_diag(&mut self, diag: DiagBuilder2) {
+        if diag.lint.is_some() {
+            self.pending_lints.push(diag);
+            return;
+        }
+        self.emit_diag(diag);
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn default_nettype(&self) -> Option<NetType> {
+        self.input.default_nettype()
+    }
+
+    fn timescale(&self) -> Timeunit {
+        self.input.timescale()
+    }
+}
+
+impl<'a, 'n> Parser<'a, 'n> {
+    fn new(input: Lexer<'a>, arena: &'n ast::Arena<'n>, lints: &'a LintLevels) -> Self {
+        Parser {
+            input: input,
+            queue: VecDeque::new(),
+            diagnostics: Vec::new(),
+            last_span: INVALID_SPAN,
+            severity: Severity::Note,
+            consumed: 0,
+            arena,
+            lints,
+            pending_lints: Vec::new(),
+        }
+    }
+
+    fn emit_diag(&mut self, diag: DiagBuilder2) {
+        let diag = match self.lints.apply(diag) {
+            Some(diag) => diag,
+            None => return,
+        };
         eprintln!("");
         eprintln!("{}", diag);
 
@@ -298,21 +351,14 @@ impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
         self.diagnostics.push(diag);
     }
 
-    fn severity(&self) -> Severity {
-        self.severity
-    }
-}
-
-impl<'a, 'n> Parser<'a, 'n> {
-    fn new(input: Lexer<'a>, arena: &'n ast::Arena<'n>) -> Self {
-        Parser {
-            input: input,
-            queue: VecDeque::new(),
-            diagnostics: Vec::new(),
-            last_span: INVALID_SPAN,
-            severity: Severity::Note,
-            consumed: 0,
-            arena,
+    /// Emit the lint warnings reported while parsing the file.
+    ///
+    /// This is deferred until the entire file has been lexed, such that an
+    /// `allow` comment at the end of a line suppresses lints reported for
+    /// the tokens before it.
+    fn emit_pending_lints(&mut self) {
+        for diag in std::mem::replace(&mut self.pending_lints, Vec::new()) {
+            self.emit_diag(diag);
         }
     }
 
@@ -407,7 +453,9 @@ where
         } else if p.try_eat(Comma) {
             if term.matches(p) {
                 let q = p.last_span();
-                p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(q));
+                p.add_diag(
+                    DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(q),
+                );
                 break;
             }
         } else {
@@ -622,9 +670,17 @@ impl<
     }
 }
 
-pub fn parse<'n>(input: Lexer, arena: &'n ast::Arena<'n>) -> Result<ast::SourceFile<'n>, ()> {
-    let mut p = Parser::new(input, arena);
+/// Parse a source file.
+///
+/// Lint warnings are reported according to `lints`.
+pub fn parse<'n>(
+    input: Lexer,
+    arena: &'n ast::Arena<'n>,
+    lints: &LintLevels,
+) -> Result<ast::SourceFile<'n>, ()> {
+    let mut p = Parser::new(input, arena, lints);
     let root = parse_source_text(&mut p);
+    p.emit_pending_lints();
     if p.is_error() {
         Err(())
     } else {
@@ -712,6 +768,8 @@ fn as_lifetime(tkn: Token) -> Option<Lifetime> {
 
 fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Interface<'n>> {
     let mut span = p.peek(0).1;
+    let default_nettype = p.default_nettype();
+    let timescale = p.timescale();
     p.require_reported(Keyword(Kw::Interface))?;
     let result = recovered(p, Keyword(Kw::Endinterface), |p| {
         // Eat the optional lifetime.
@@ -754,6 +812,9 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
             );
         }
 
+        // Eat the optional time unit and precision declarations.
+        let timeunits = parse_time_units(p)?;
+
         // Eat the items in the interface.
         let mut items = Vec::new();
         while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endinterface) && p.peek(0).0 != Eof {
@@ -772,6 +833,9 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
                 params: param_ports,
                 ports: ports,
                 items: items,
+                default_nettype,
+                timeunits,
+                timescale: timescale.clone(),
             },
         ))
     });
@@ -870,12 +934,22 @@ fn parse_parameter_port_list<'n>(
     })
 }
 
-/// Parse a module declaration, assuming that the leading `module` keyword has
-/// already been consumed.
+/// Parse a module, program, or checker declaration.
+///
+/// Programs and checkers share the structure of modules and are represented as
+/// such, with the `kind` field set to `ModuleKind::Program` or
+/// `ModuleKind::Checker`.
 fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Module<'n>> {
     let mut span = p.peek(0).1;
-    p.require_reported(Keyword(Kw::Module))?;
-    let result = recovered(p, Keyword(Kw::Endmodule), |p| {
+    let (kind, desc, kw, end_kw) = match p.peek(0).0 {
+        Keyword(Kw::Program) => (ModuleKind::Program, "program", Kw::Program, Kw::Endprogram),
+        Keyword(Kw::Checker) => (ModuleKind::Checker, "checker", Kw::Checker, Kw::Endchecker),
+        _ => (ModuleKind::Module, "module", Kw::Module, Kw::Endmodule),
+    };
+    let default_nettype = p.default_nettype();
+    let timescale = p.timescale();
+    p.require_reported(Keyword(kw))?;
+    let result = recovered(p, Keyword(end_kw), |p| {
         // Eat the optional lifetime.
         let lifetime = match as_lifetime(p.peek(0).0) {
             Some(l) => {
@@ -886,7 +960,7 @@ fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Modul
         };
 
         // Eat the module name.
-        let (name, name_sp) = p.eat_ident("module name")?;
+        let (name, name_sp) = p.eat_ident(&format!("{} name", desc))?;
 
         // TODO: Parse package import declarations.
         // Eat the optional package import declarations.
@@ -914,14 +988,17 @@ fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Modul
         if !p.try_eat(Semicolon) {
             let q = p.peek(0).1.end();
             p.add_diag(
-                DiagBuilder2::error(format!("Missing ; after header of module \"{}\"", name))
+                DiagBuilder2::error(format!("Missing ; after header of {} \"{}\"", desc, name))
                     .span(q),
             );
         }
 
+        // Eat the optional time unit and precision declarations.
+        let timeunits = parse_time_units(p)?;
+
         // Parse the module items.
         let mut items = Vec::new();
-        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endmodule) && p.peek(0).0 != Eof {
+        while !p.is_fatal() && p.peek(0).0 != Keyword(end_kw) && p.peek(0).0 != Eof {
             if p.try_eat(Semicolon) {
                 continue;
             }
@@ -932,25 +1009,30 @@ fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Modul
         Ok(Module::new(
             span,
             ModuleData {
+                kind,
                 lifetime,
                 name: Spanned::new(name, name_sp),
                 imports,
                 params,
                 ports,
                 items,
+                default_nettype,
+                timeunits,
+                timescale: timescale.clone(),
             },
         ))
     });
     let sp = p.peek(0).1;
-    p.require_reported(Keyword(Kw::Endmodule))?;
+    p.require_reported(Keyword(end_kw))?;
     if p.try_eat(Colon) {
-        p.eat_ident("module name")?;
+        p.eat_ident(&format!("{} name", desc))?;
     }
     result
 }
 
 fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Package<'n>> {
     let mut span = p.peek(0).1;
+    let timescale = p.timescale();
     p.require_reported(Keyword(Kw::Package))?;
     let result = recovered(p, Keyword(Kw::Endpackage), |p| {
         // Parse the optional lifetime.
@@ -966,11 +1048,8 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
         let (name, name_span) = p.eat_ident("package name")?;
         p.require_reported(Semicolon)?;
 
-        // TODO: Parse the optional timeunits declaration.
-        let timeunits = Timeunit {
-            unit: None,
-            prec: None,
-        };
+        // Parse the optional timeunits declaration.
+        let timeunits = parse_time_units(p)?;
 
         // Parse the package items.
         let mut items = Vec::new();
@@ -988,6 +1067,7 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
                 lifetime: lifetime,
                 name: Spanned::new(name, name_span),
                 timeunits: timeunits,
+                timescale: timescale.clone(),
                 items: items,
             },
         ))
@@ -999,15 +1079,481 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
     result
 }
 
-fn parse_program_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
-    p.require_reported(Keyword(Kw::Program))?;
-    let result = recovered(p, Keyword(Kw::Endprogram), |p| {
-        let q = p.peek(0).1;
-        p.add_diag(DiagBuilder2::error("Don't know how to parse program declarations").span(q));
-        Err(())
-    });
-    p.require_reported(Keyword(Kw::Endprogram))?;
-    result
+/// Parse a `let` declaration.
+///
+/// ```text
+/// let_declaration ::= "let" ident ["(" [let_port {"," let_port}] ")"] "=" expr ";"
+/// let_port ::= [data_type_or_implicit | "untyped"] ident ["=" expr]
+/// ```
+fn parse_let_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<LetDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Let))?;
+    let name = parse_identifier_name(p, "let name")?;
+    let args = try_flanked(p, Paren, |p| {
+        comma_list(p, CloseDelim(Paren), "let argument", parse_let_arg)
+    })?
+    .unwrap_or(Vec::new());
+    p.require_reported(Operator(Op::Assign))?;
+    let expr = parse_expr(p)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(LetDecl::new(span, LetDeclData { name, args, expr }))
+}
+
+fn parse_let_arg<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<LetArg<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Branch to parse arguments with explicit and implicit type. The `untyped`
+    // keyword is equivalent to omitting the type.
+    let (ty, (name, default)) = if p.try_eat(Keyword(Kw::Untyped)) {
+        let ty = parse_implicit_type(p)?;
+        (ty, tail(p)?)
+    } else {
+        let mut pp = ParallelParser::new();
+        pp.add("explicit type", |p| {
+            let ty = parse_explicit_type(p)?;
+            Ok((ty, tail(p)?))
+        });
+        pp.add("implicit type", |p| {
+            let ty = parse_implicit_type(p)?;
+            Ok((ty, tail(p)?))
+        });
+        pp.finish(p, "explicit or implicit type")?
+    };
+
+    // Parse the argument name and optional default, which must be followed by
+    // a "," or ")" to disambiguate explicit and implicit types.
+    fn tail<'n>(
+        p: &mut dyn AbstractParser<'n>,
+    ) -> ReportedResult<(Spanned<Name>, Option<Expr<'n>>)> {
+        let name = parse_identifier_name(p, "let argument name")?;
+        let default = if p.try_eat(Operator(Op::Assign)) {
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        match p.peek(0) {
+            (Comma, _) | (CloseDelim(Paren), _) => Ok((name, default)),
+            (_, sp) => {
+                p.add_diag(DiagBuilder2::error("expected , or ) after let argument").span(sp));
+                Err(())
+            }
+        }
+    }
+
+    span.expand(p.last_span());
+    Ok(LetArg::new(span, LetArgData { ty, name, default }))
+}
+
+/// Parse a covergroup declaration.
+///
+/// ```text
+/// covergroup_declaration ::=
+///     "covergroup" ident [clocking_event] ";"
+///     {coverage_option ";" | cover_point | cover_cross}
+///     "endgroup" [":" ident]
+/// ```
+fn parse_covergroup_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Covergroup<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Covergroup))?;
+    let name = parse_identifier_name(p, "covergroup name")?;
+    if p.peek(0).0 == OpenDelim(Paren) {
+        let sp = p.peek(0).1;
+        p.add_diag(DiagBuilder2::error("unsupported: covergroup arguments").span(sp));
+        return Err(());
+    }
+    let event = try_event_control(p)?;
+    p.require_reported(Semicolon)?;
+
+    // Parse the coverpoints and crosses.
+    let mut points = vec![];
+    let mut crosses = vec![];
+    while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endgroup) && p.peek(0).0 != Eof {
+        if try_coverage_option(p)? {
+            continue;
+        }
+        let mut item_span = p.peek(0).1;
+        let label = if p.is_ident() && p.peek(1).0 == Colon {
+            let label = parse_identifier_name(p, "coverpoint or cross label")?;
+            p.bump();
+            Some(label)
+        } else {
+            None
+        };
+        match p.peek(0) {
+            (Keyword(Kw::Coverpoint), _) => {
+                p.bump();
+                let expr = parse_expr(p)?;
+                let iff = try_iff(p)?;
+                let bins = if p.try_eat(Semicolon) {
+                    vec![]
+                } else {
+                    flanked(p, Brace, |p| {
+                        let mut bins = vec![];
+                        while p.peek(0).0 != CloseDelim(Brace) && p.peek(0).0 != Eof {
+                            if !try_coverage_option(p)? {
+                                bins.push(parse_cover_bins(p)?);
+                            }
+                        }
+                        Ok(bins)
+                    })?
+                };
+                item_span.expand(p.last_span());
+                points.push(Coverpoint::new(
+                    item_span,
+                    CoverpointData {
+                        label,
+                        expr,
+                        iff,
+                        bins,
+                    },
+                ));
+            }
+            (Keyword(Kw::Cross), _) => {
+                p.bump();
+                let mut names = vec![parse_identifier_name(p, "coverpoint name")?];
+                while p.try_eat(Comma) {
+                    names.push(parse_identifier_name(p, "coverpoint name")?);
+                }
+                let iff = try_iff(p)?;
+                if !p.try_eat(Semicolon) {
+                    flanked(p, Brace, |p| {
+                        if p.peek(0).0 != CloseDelim(Brace) {
+                            let sp = p.peek(0).1;
+                            p.add_diag(
+                                DiagBuilder2::error("unsupported: cross bins selection").span(sp),
+                            );
+                            return Err(());
+                        }
+                        Ok(())
+                    })?;
+                }
+                item_span.expand(p.last_span());
+                crosses.push(CoverCross::new(
+                    item_span,
+                    CoverCrossData {
+                        label,
+                        points: names,
+                        iff,
+                    },
+                ));
+            }
+            (tkn, sp) => {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "expected coverpoint or cross, but found `{}` instead",
+                        tkn
+                    ))
+                    .span(sp),
+                );
+                return Err(());
+            }
+        }
+    }
+
+    p.require_reported(Keyword(Kw::Endgroup))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("covergroup name")?;
+    }
+    span.expand(p.last_span());
+    Ok(Covergroup::new(
+        span,
+        CovergroupData {
+            name,
+            event,
+            points,
+            crosses,
+        },
+    ))
+}
+
+/// Parse a clocking block declaration, as per IEEE 1800-2017 section 14.3.
+///
+/// If the block is preceded by `default`, its span is passed in.
+///
+/// ```text
+/// clocking_declaration ::=
+///     ["default"] "clocking" ident clocking_event ";"
+///     {clocking_item}
+///     "endclocking" [":" ident]
+/// clocking_item ::=
+///     "default" default_skew ";"
+///     clocking_direction clocking_decl_assign {"," clocking_decl_assign} ";"
+/// clocking_decl_assign ::= ident ["=" expr]
+/// ```
+fn parse_clocking_decl<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    default: Option<Span>,
+) -> ReportedResult<ClockingDecl<'n>> {
+    let mut span = default.unwrap_or(p.peek(0).1);
+    p.require_reported(Keyword(Kw::Clocking))?;
+    if p.peek(0).0 == At {
+        let sp = p.peek(0).1;
+        p.add_diag(DiagBuilder2::error("unsupported: unnamed clocking block").span(sp));
+        return Err(());
+    }
+    let name = parse_identifier_name(p, "clocking block name")?;
+    let event = match try_event_control(p)? {
+        Some(x) => x,
+        None => {
+            let sp = p.peek(0).1;
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected clocking event after clocking block name `{}`",
+                    name
+                ))
+                .span(sp),
+            );
+            return Err(());
+        }
+    };
+    p.require_reported(Semicolon)?;
+
+    // Parse the default skews and signal declarations.
+    let mut default_input = None;
+    let mut default_output = None;
+    let mut items = vec![];
+    while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endclocking) && p.peek(0).0 != Eof {
+        let mut item_span = p.peek(0).1;
+        if p.try_eat(Keyword(Kw::Default)) {
+            let (dir, input_skew, output_skew) = parse_clocking_dir(p)?;
+            if dir == ClockingDir::Inout || (input_skew.is_none() && output_skew.is_none()) {
+                p.add_diag(
+                    DiagBuilder2::error("expected `input` or `output` skew after `default`")
+                        .span(item_span),
+                );
+                return Err(());
+            }
+            default_input = input_skew.or(default_input);
+            default_output = output_skew.or(default_output);
+            p.require_reported(Semicolon)?;
+            continue;
+        }
+        let (dir, input_skew, output_skew) = parse_clocking_dir(p)?;
+        let signals = comma_list_nonempty(p, Semicolon, "clocking signal", |p| {
+            let mut span = p.peek(0).1;
+            let name = parse_identifier_name(p, "clocking signal name")?;
+            let expr = if p.try_eat(Operator(Op::Assign)) {
+                parse_expr(p)?
+            } else {
+                Expr::new(name.span, IdentExpr(name))
+            };
+            span.expand(p.last_span());
+            Ok(ClockingSignal::new(span, ClockingSignalData { name, expr }))
+        })?;
+        p.require_reported(Semicolon)?;
+        item_span.expand(p.last_span());
+        items.push(ClockingItem {
+            span: item_span,
+            dir,
+            input_skew,
+            output_skew,
+            signals,
+        });
+    }
+
+    p.require_reported(Keyword(Kw::Endclocking))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("clocking block name")?;
+    }
+    span.expand(p.last_span());
+    Ok(ClockingDecl::new(
+        span,
+        ClockingDeclData {
+            default: default.is_some(),
+            name,
+            event,
+            default_input,
+            default_output,
+            items,
+        },
+    ))
+}
+
+/// Parse the direction and skews of a clocking block signal.
+///
+/// ```text
+/// clocking_direction ::=
+///     "input" [clocking_skew]
+///     "output" [clocking_skew]
+///     "input" [clocking_skew] "output" [clocking_skew]
+///     "inout"
+/// ```
+fn parse_clocking_dir<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<(
+    ClockingDir,
+    Option<ClockingSkew<'n>>,
+    Option<ClockingSkew<'n>>,
+)> {
+    match p.peek(0) {
+        (Keyword(Kw::Inout), _) => {
+            p.bump();
+            Ok((ClockingDir::Inout, None, None))
+        }
+        (Keyword(Kw::Input), _) => {
+            p.bump();
+            let input_skew = try_clocking_skew(p)?;
+            if p.try_eat(Keyword(Kw::Output)) {
+                let output_skew = try_clocking_skew(p)?;
+                Ok((ClockingDir::InputOutput, input_skew, output_skew))
+            } else {
+                Ok((ClockingDir::Input, input_skew, None))
+            }
+        }
+        (Keyword(Kw::Output), _) => {
+            p.bump();
+            let output_skew = try_clocking_skew(p)?;
+            Ok((ClockingDir::Output, None, output_skew))
+        }
+        (tkn, sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `input`, `output`, or `inout` in clocking block, but found `{}` \
+                     instead",
+                    tkn
+                ))
+                .span(sp),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Try to parse a clocking skew such as `#1step` or `#2ns`.
+fn try_clocking_skew<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<ClockingSkew<'n>>> {
+    match p.peek(0) {
+        (Hashtag, _) if p.peek(1).0 == Keyword(Kw::OneStep) => {
+            p.bump();
+            p.bump();
+            Ok(Some(ClockingSkew::Step))
+        }
+        (Keyword(Kw::Posedge), sp) | (Keyword(Kw::Negedge), sp) | (Keyword(Kw::Edge), sp) => {
+            p.add_diag(DiagBuilder2::error("unsupported: clocking skew with edge").span(sp));
+            Err(())
+        }
+        _ => Ok(try_delay_control(p)?.map(ClockingSkew::Delay)),
+    }
+}
+
+/// Parse a bins declaration within a coverpoint.
+///
+/// ```text
+/// bins_or_options ::=
+///     bins_keyword ident ["[" [expr] "]"] "=" "{" open_range_list "}" ["iff" "(" expr ")"] ";"
+///     bins_keyword ident ["[" "]"] "=" "default" ["iff" "(" expr ")"] ";"
+/// bins_keyword ::= "bins" | "illegal_bins" | "ignore_bins"
+/// ```
+fn parse_cover_bins<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<CoverBins<'n>> {
+    let mut span = p.peek(0).1;
+    let kind = match p.peek(0) {
+        (Keyword(Kw::Bins), _) => CoverBinsKind::Bins,
+        (Keyword(Kw::IllegalBins), _) => CoverBinsKind::Illegal,
+        (Keyword(Kw::IgnoreBins), _) => CoverBinsKind::Ignore,
+        (Keyword(Kw::Wildcard), sp) => {
+            p.add_diag(DiagBuilder2::error("unsupported: wildcard bins").span(sp));
+            return Err(());
+        }
+        (tkn, sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!("expected bins, but found `{}` instead", tkn)).span(sp),
+            );
+            return Err(());
+        }
+    };
+    p.bump();
+    let name = parse_identifier_name(p, "bins name")?;
+    let array = if p.try_eat(OpenDelim(Brack)) {
+        let size = if p.peek(0).0 == CloseDelim(Brack) {
+            None
+        } else {
+            Some(parse_expr(p)?)
+        };
+        p.require_reported(CloseDelim(Brack))?;
+        Some(size)
+    } else {
+        None
+    };
+    p.require_reported(Operator(Op::Assign))?;
+    let values = if p.try_eat(Keyword(Kw::Default)) {
+        None
+    } else {
+        Some(flanked(p, Brace, |p| {
+            comma_list_nonempty(p, CloseDelim(Brace), "range", parse_value_range)
+        })?)
+    };
+    let iff = try_iff(p)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(CoverBins::new(
+        span,
+        CoverBinsData {
+            kind,
+            name,
+            array,
+            values,
+            iff,
+        },
+    ))
+}
+
+/// Try to parse a coverage option such as `option.per_instance = 1;`.
+///
+/// Options are accepted for compatibility but have no effect. Returns whether
+/// an option was consumed.
+fn try_coverage_option<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<bool> {
+    let is_option = match p.peek(0).0 {
+        Ident(n) => {
+            (&*n.as_str() == "option" || &*n.as_str() == "type_option") && p.peek(1).0 == Period
+        }
+        _ => false,
+    };
+    if !is_option {
+        return Ok(false);
+    }
+    let mut span = p.peek(0).1;
+    p.bump();
+    p.bump();
+    p.eat_ident("coverage option name")?;
+    p.require_reported(Operator(Op::Assign))?;
+    parse_expr(p)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    p.add_diag(
+        DiagBuilder2::lint(
+            &UNSUPPORTED,
+            format!("unsupported: coverage option `{}`; ignored", span.extract()),
+        )
+        .span(span),
+    );
+    Ok(true)
+}
+
+/// Try to parse an `iff (<expr>)` guard.
+fn try_iff<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<Expr<'n>>> {
+    if p.try_eat(Keyword(Kw::Iff)) {
+        flanked(p, Paren, parse_expr).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parse a value range such as `42` or `[0:15]` in an `open_range_list`.
+fn parse_value_range<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ValueRange<'n>> {
+    if p.peek(0).0 == OpenDelim(Brack) {
+        p.require_reported(OpenDelim(Brack))?;
+        let mut sp = p.last_span();
+        let lo = parse_expr(p)?;
+        p.require_reported(Colon)?;
+        let hi = parse_expr(p)?;
+        p.require_reported(CloseDelim(Brack))?;
+        sp.expand(p.last_span());
+        Ok(ValueRange::Range { lo, hi, span: sp })
+    } else {
+        Ok(ValueRange::Single(parse_expr(p)?))
+    }
 }
 
 fn parse_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
@@ -1024,6 +1570,11 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         p.bump();
     }
 
+    // Gate and switch primitives are identified by their keyword.
+    if let Some(kind) = as_gate_kind(p.peek(0).0) {
+        return parse_gate_inst(p, kind).map(ItemData::GateInst);
+    }
+
     // First attempt the simple cases where a keyword reliably identifies the
     // following item.
     let class_follows = p.peek(1).0 == Keyword(Kw::Class);
@@ -1039,7 +1590,11 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         Keyword(Kw::Class) => return parse_class_decl(p).map(ItemData::ClassDecl),
         Keyword(Kw::Interface) => return parse_interface_decl(p).map(ItemData::InterfaceDecl),
         Keyword(Kw::Package) => return parse_package_decl(p).map(ItemData::PackageDecl),
-        Keyword(Kw::Program) => return parse_program_decl(p).map(ItemData::ProgramDecl),
+        Keyword(Kw::Program) => return parse_module_decl(p).map(ItemData::ProgramDecl),
+        Keyword(Kw::Checker) => return parse_module_decl(p).map(ItemData::CheckerDecl),
+        Keyword(Kw::Let) => return parse_let_decl(p).map(ItemData::LetDecl),
+        Keyword(Kw::Covergroup) => return parse_covergroup_decl(p).map(ItemData::CovergroupDecl),
+        Keyword(Kw::Clocking) => return parse_clocking_decl(p, None).map(ItemData::ClockingDecl),
 
         Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
             let decl = parse_param_decl(p, false)?;
@@ -1121,11 +1676,16 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         Keyword(Kw::Default) => {
             p.bump();
             let mut span = p.last_span();
-            if p.try_eat(Keyword(Kw::Clocking)) {
-                let name = p.eat_ident("clocking identifier")?;
-                p.require_reported(Semicolon)?;
-                span.expand(p.last_span());
-                return Ok(ItemData::Dummy);
+            if p.peek(0).0 == Keyword(Kw::Clocking) {
+                // `default clocking cb;` merely refers to a clocking block
+                // declared elsewhere.
+                if p.peek(2).0 == Semicolon {
+                    p.bump();
+                    p.eat_ident("clocking identifier")?;
+                    p.require_reported(Semicolon)?;
+                    return Ok(ItemData::Dummy);
+                }
+                return parse_clocking_decl(p, Some(span)).map(ItemData::ClockingDecl);
             }
             if p.try_eat(Keyword(Kw::Disable)) {
                 p.require_reported(Keyword(Kw::Iff))?;
@@ -1173,7 +1733,7 @@ fn parse_elab_system_task<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<
     };
     p.recover_balanced(&[Semicolon], true);
     span.expand(p.last_span());
-    p.add_diag(DiagBuilder2::warning("unsupported elaboration system task").span(span));
+    p.add_diag(DiagBuilder2::lint(&UNSUPPORTED, "unsupported elaboration system task").span(span));
     Ok(())
 }
 
@@ -1212,7 +1772,9 @@ fn parse_localparam_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<(
                 // gracefully.
                 if p.peek(0).0 == Semicolon {
                     // TODO: This should be an error in pedantic mode.
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
+                    p.add_diag(
+                        DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(sp),
+                    );
                     break;
                 }
             }
@@ -1675,8 +2237,17 @@ fn parse_type_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TypeKin
         Keyword(Kw::Virtual) => {
             p.bump();
             p.try_eat(Keyword(Kw::Interface));
-            let (name, _) = p.eat_ident("virtual interface name")?;
-            Ok(ast::VirtIntfType(name))
+            let (name, name_sp) = p.eat_ident("virtual interface name")?;
+            let modport = if p.try_eat(Period) {
+                let (name, sp) = p.eat_ident("modport name")?;
+                Some(Spanned::new(name, sp))
+            } else {
+                None
+            };
+            Ok(ast::VirtIntfType {
+                name: Spanned::new(name, name_sp),
+                modport,
+            })
         }
 
         // type_reference ::= `type` `(` expression `)`
@@ -2125,17 +2696,33 @@ fn parse_expr_suffix<'n>(
                 }
             }
             let args = flanked(p, Paren, parse_call_args)?;
-            let expr = Expr::new(
+            let mut expr = Expr::new(
                 Span::union(prefix.span, p.last_span()),
                 CallExpr(Box::new(prefix), args),
             );
+            // Calls to `randomize()` may be followed by inline constraints.
+            if p.peek(0).0 == Keyword(Kw::With) && p.peek(1).0 == OpenDelim(Brace) {
+                p.bump();
+                let items = flanked(p, Brace, |p| {
+                    repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+                })?;
+                expr = Expr::new(
+                    Span::union(expr.span, p.last_span()),
+                    InlineConstraintExpr(Box::new(expr), items),
+                );
+            }
             return parse_expr_suffix(p, expr, precedence);
         }
 
         // expr "." ident
         Period if precedence <= Precedence::Scope => {
             p.bump();
-            let name = parse_identifier_name(p, "member name")?;
+            // Constructors are called explicitly as `super.new(...)`.
+            let name = if p.try_eat(Keyword(Kw::New)) {
+                Spanned::new(get_name_table().intern("new", true), p.last_span())
+            } else {
+                parse_identifier_name(p, "member name")?
+            };
             let expr = Expr::new(
                 Span::union(prefix.span, p.last_span()),
                 MemberExpr {
@@ -2206,20 +2793,7 @@ fn parse_expr_suffix<'n>(
         Keyword(Kw::Inside) if precedence <= Precedence::Relational => {
             p.bump();
             let set = flanked(p, Brace, |p| {
-                comma_list_nonempty(p, CloseDelim(Brace), "range", |p| {
-                    if p.peek(0).0 == OpenDelim(Brack) {
-                        p.require_reported(OpenDelim(Brack))?;
-                        let mut sp = p.last_span();
-                        let lo = parse_expr(p)?;
-                        p.require_reported(Colon)?;
-                        let hi = parse_expr(p)?;
-                        p.require_reported(CloseDelim(Brack))?;
-                        sp.expand(p.last_span());
-                        Ok(ValueRange::Range { lo, hi, span: sp })
-                    } else {
-                        Ok(ValueRange::Single(parse_expr(p)?))
-                    }
-                })
+                comma_list_nonempty(p, CloseDelim(Brace), "range", parse_value_range)
             })?;
             let expr = Expr::new(
                 Span::union(prefix.span, p.last_span()),
@@ -2365,6 +2939,12 @@ fn parse_primary_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Expr
             return Ok(Expr::new(sp, ThisExpr));
         }
 
+        // `super`
+        Keyword(Kw::Super) => {
+            p.bump();
+            return Ok(Expr::new(sp, SuperExpr));
+        }
+
         // `$`
         Dollar => {
             p.bump();
@@ -2583,7 +3163,7 @@ fn parse_concat_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ExprD
     while p.try_eat(Comma) {
         if p.peek(0).0 == CloseDelim(Brace) {
             let q = p.peek(0).1;
-            p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(q));
+            p.add_diag(DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(q));
             break;
         }
         exprs.push(parse_expr_prec(p, Precedence::Min)?);
@@ -2604,7 +3184,9 @@ fn parse_expr_list<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Exp
             (Comma, sp) => {
                 p.bump();
                 if p.peek(0).0 == CloseDelim(Brace) {
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
+                    p.add_diag(
+                        DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(sp),
+                    );
                     break;
                 }
             }
@@ -2786,7 +3368,9 @@ fn parse_port_list<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Por
             (Comma, sp) => {
                 p.bump();
                 if p.peek(0).0 == CloseDelim(Paren) {
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
+                    p.add_diag(
+                        DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(sp),
+                    );
                     break;
                 }
             }
@@ -3559,26 +4143,7 @@ fn parse_stmt_kind<'n>(
             p.bump();
             let (expr, vars) = flanked(p, Paren, |p| {
                 let expr = parse_expr_prec(p, Precedence::Scope)?;
-                let vars = flanked(p, Brack, |p| {
-                    Ok(comma_list(p, CloseDelim(Brack), "loop variables", |p| {
-                        Ok(if p.peek(0).0 != Comma {
-                            Some(parse_identifier_name(p, "loop variable name")?)
-                        } else {
-                            None
-                        })
-                    })?
-                    .into_iter()
-                    .enumerate()
-                    .flat_map(|(i, name)| {
-                        name.map(|name| {
-                            ast::ForeachIndex::new(
-                                name.span,
-                                ast::ForeachIndexData { index: i, name },
-                            )
-                        })
-                    })
-                    .collect())
-                })?;
+                let vars = flanked(p, Brack, parse_foreach_indices)?;
                 Ok((expr, vars))
             })?;
             let stmt = Box::new(parse_stmt(p)?);
@@ -3658,6 +4223,24 @@ fn parse_stmt_kind<'n>(
             return Err(());
         }
 
+        // Event triggers, as per IEEE 1800-2017 section 15.5.1.
+        Operator(Op::LogicImpl) => {
+            p.bump();
+            let nonblocking = p.try_eat(Operator(Op::Gt));
+            let delay = if nonblocking {
+                try_delay_control(p)?
+            } else {
+                None
+            };
+            let target = parse_expr(p)?;
+            p.require_reported(Semicolon)?;
+            TriggerStmt {
+                target,
+                nonblocking,
+                delay,
+            }
+        }
+
         // Disable statements
         Keyword(Kw::Disable) => {
             p.bump();
@@ -3708,7 +4291,8 @@ fn parse_block<'n>(
         if let Some(existing) = *label {
             if name == existing {
                 p.add_diag(
-                    DiagBuilder2::warning(format!("Block {} labelled twice", name)).span(name_span),
+                    DiagBuilder2::lint(&BLOCK_LABEL, format!("Block {} labelled twice", name))
+                        .span(name_span),
                 );
             } else {
                 p.add_diag(
@@ -3927,7 +4511,8 @@ fn parse_case<'n>(
                         p.bump();
                         if p.try_eat(Colon) {
                             p.add_diag(
-                                DiagBuilder2::warning("superfluous trailing comma").span(sp),
+                                DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma")
+                                    .span(sp),
                             );
                             break;
                         }
@@ -4280,7 +4865,9 @@ fn parse_call_args<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Cal
             (Comma, sp) => {
                 p.bump();
                 if p.try_eat(CloseDelim(Paren)) {
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
+                    p.add_diag(
+                        DiagBuilder2::lint(&TRAILING_COMMA, "superfluous trailing comma").span(sp),
+                    );
                     break;
                 }
             }
@@ -4456,8 +5043,11 @@ fn parse_generate_block<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Ge
         if let Some(existing) = label {
             if existing.value == n.value {
                 p.add_diag(
-                    DiagBuilder2::warning(format!("Generate block {} labelled twice", n))
-                        .span(n.span),
+                    DiagBuilder2::lint(
+                        &BLOCK_LABEL,
+                        format!("Generate block {} labelled twice", n),
+                    )
+                    .span(n.span),
                 );
             } else {
                 p.add_diag(
@@ -4494,10 +5084,13 @@ fn parse_generate_block<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Ge
             }
         } else {
             p.add_diag(
-                DiagBuilder2::warning(format!(
-                    "Generate block has trailing label {}, but is missing leading label",
-                    n
-                ))
+                DiagBuilder2::lint(
+                    &BLOCK_LABEL,
+                    format!(
+                        "Generate block has trailing label {}, but is missing leading label",
+                        n
+                    ),
+                )
                 .span(n.span),
             );
         }
@@ -4658,6 +5251,7 @@ fn parse_class_item<'n>(
     let data = {
         let mut pp = ParallelParser::new();
         pp.add("class property", |p| {
+            let mut span = p.peek(0).1;
             let ty = parse_data_type(p)?;
             let names = comma_list_nonempty(
                 p,
@@ -4665,8 +5259,18 @@ fn parse_class_item<'n>(
                 "data declaration",
                 parse_variable_decl_assignment,
             )?;
+            span.expand(p.last_span());
             p.require_reported(Semicolon)?;
-            Ok(ClassItemData::Property)
+            Ok(ClassItemData::Property(ast::VarDecl::new(
+                span,
+                ast::VarDeclData {
+                    konst: false,
+                    var: false,
+                    lifetime: None,
+                    ty,
+                    names,
+                },
+            )))
         });
         if intf {
             pp.add("class function or task prototype", |p| {
@@ -4724,6 +5328,25 @@ fn parse_class_property<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Cl
     Err(())
 }
 
+/// Parse the loop variables of a `foreach`, like the `i, , k` in `a[i, , k]`.
+fn parse_foreach_indices<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<ast::ForeachIndex<'n>>> {
+    Ok(comma_list(p, CloseDelim(Brack), "loop variables", |p| {
+        Ok(if p.peek(0).0 != Comma {
+            Some(parse_identifier_name(p, "loop variable name")?)
+        } else {
+            None
+        })
+    })?
+    .into_iter()
+    .enumerate()
+    .flat_map(|(i, name)| {
+        name.map(|name| ast::ForeachIndex::new(name.span, ast::ForeachIndexData { index: i, name }))
+    })
+    .collect())
+}
+
 fn parse_constraint<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Constraint<'n>> {
     let mut span = p.peek(0).1;
 
@@ -4786,36 +5409,118 @@ fn parse_constraint_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<C
     let mut span = p.peek(0).1;
     let data = parse_constraint_item_data(p)?;
     span.expand(p.last_span());
-    Ok(ConstraintItem {
-        span: span,
-        data: data,
-    })
+    Ok(ConstraintItem::new(span, data))
 }
 
 fn parse_constraint_item_data<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<ConstraintItemData<'n>> {
-    // Handle the trivial cases that start with a keyword first.
+    // Handle the cases that start with a keyword first.
     if p.try_eat(Keyword(Kw::If)) {
-        let q = p.last_span();
-        p.add_diag(DiagBuilder2::error("Don't know how to parse `if` constraint items").span(q));
-        return Err(());
+        let cond = flanked(p, Paren, parse_expr)?;
+        let main = parse_constraint_set(p)?;
+        let alt = if p.try_eat(Keyword(Kw::Else)) {
+            parse_constraint_set(p)?
+        } else {
+            Vec::new()
+        };
+        return Ok(ConstraintItemData::If(cond, main, alt));
     }
 
     if p.try_eat(Keyword(Kw::Foreach)) {
-        let q = p.last_span();
-        p.add_diag(
-            DiagBuilder2::error("Don't know how to parse `foreach` constraint items").span(q),
-        );
-        return Err(());
+        let (expr, vars) = flanked(p, Paren, |p| {
+            let expr = parse_expr_prec(p, Precedence::Scope)?;
+            let vars = flanked(p, Brack, parse_foreach_indices)?;
+            Ok((expr, vars))
+        })?;
+        let items = parse_constraint_set(p)?;
+        return Ok(ConstraintItemData::Foreach(expr, vars, items));
     }
 
-    // If we arrive here, the item starts with an expression.
-    let expr = parse_expr(p)?;
+    if p.try_eat(Keyword(Kw::Solve)) {
+        let before = comma_list_nonempty(p, Keyword(Kw::Before), "random variable", parse_expr)?;
+        p.require_reported(Keyword(Kw::Before))?;
+        let after = comma_list_nonempty(p, Semicolon, "random variable", parse_expr)?;
+        p.require_reported(Semicolon)?;
+        return Ok(ConstraintItemData::SolveBefore(before, after));
+    }
+
+    match p.peek(0) {
+        (Keyword(kw @ Kw::Soft), sp)
+        | (Keyword(kw @ Kw::Unique), sp)
+        | (Keyword(kw @ Kw::Disable), sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!("unsupported: `{}` constraint items", kw.as_str()))
+                    .span(sp),
+            );
+            return Err(());
+        }
+        _ => (),
+    }
+
+    // If we arrive here, the item starts with an expression. Stop before any
+    // `->`, since the right-hand side of an implication is a constraint set
+    // rather than an expression.
+    let expr = parse_expr_prec(p, Precedence::Implication)?;
+    if p.try_eat(Operator(Op::LogicImpl)) {
+        let items = parse_constraint_set(p)?;
+        return Ok(ConstraintItemData::Implication(expr, items));
+    }
+    if p.try_eat(Keyword(Kw::Dist)) {
+        let items = flanked(p, Brace, |p| {
+            comma_list_nonempty(p, CloseDelim(Brace), "distribution item", parse_dist_item)
+        })?;
+        p.require_reported(Semicolon)?;
+        return Ok(ConstraintItemData::Dist(expr, items));
+    }
     p.require_reported(Semicolon)?;
     Ok(ConstraintItemData::Expr(expr))
 }
 
+/// Parse a single constraint item, or a list of items in braces.
+fn parse_constraint_set<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<ConstraintItem<'n>>> {
+    if p.peek(0).0 == OpenDelim(Brace) {
+        flanked(p, Brace, |p| {
+            repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+        })
+    } else {
+        Ok(vec![parse_constraint_item(p)?])
+    }
+}
+
+fn parse_dist_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<DistItem<'n>> {
+    let mut span = p.peek(0).1;
+    let range = parse_value_range(p)?;
+    let weight = if p.try_eat(Colon) {
+        let kind = match p.peek(0) {
+            (Operator(Op::Assign), _) => DistWeight::PerValue,
+            (Operator(Op::Div), _) => DistWeight::PerRange,
+            (tkn, sp) => {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "expected `:=` or `:/` weight, found `:{}` instead",
+                        tkn
+                    ))
+                    .span(sp),
+                );
+                return Err(());
+            }
+        };
+        p.bump();
+        Some((kind, parse_expr(p)?))
+    } else {
+        None
+    };
+    span.expand(p.last_span());
+    Ok(DistItem {
+        span,
+        range,
+        weight,
+    })
+}
+
 struct ParallelParser<'a, 'n, R: Clone> {
     branches: Vec<(
         String,
@@ -5025,6 +5730,14 @@ impl<'tp, 'n> AbstractParser<'n> for BranchParser<'tp, 'n> {
     fn severity(&self) -> Severity {
         self.severity
     }
+
+    fn default_nettype(&self) -> Option<NetType> {
+        self.parser.default_nettype()
+    }
+
+    fn timescale(&self) -> Timeunit {
+        self.parser.timescale()
+    }
 }
 
 fn parse_typedef<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Typedef<'n>> {
@@ -5917,6 +6630,136 @@ fn parse_inst<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Inst<'n
     ))
 }
 
+/// Parse a gate or switch primitive instantiation.
+///
+/// ```text
+/// gate_kind [drive_strength|pull_strength] [delay3] instance {"," instance} ";"
+/// instance: [ident [dimensions]] "(" expr {"," expr} ")"
+/// ```
+fn parse_gate_inst<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    kind: GateKind,
+) -> ReportedResult<ast::GateInst<'n>> {
+    let mut span = p.peek(0).1;
+    p.bump();
+
+    // Consume the optional drive or pull strength.
+    let strength = if p.peek(0).0 == OpenDelim(Paren) && as_drive_strength(p.peek(1).0).is_some() {
+        flanked(p, Paren, |p| {
+            let a = as_drive_strength(p.peek(0).0).unwrap();
+            p.bump();
+            if !p.try_eat(Comma) {
+                return Ok((a, None));
+            }
+            match as_drive_strength(p.peek(0).0) {
+                Some(b) => {
+                    p.bump();
+                    Ok((a, Some(b)))
+                }
+                None => {
+                    let q = p.peek(0).1;
+                    p.add_diag(DiagBuilder2::error("expected second drive strength").span(q));
+                    Err(())
+                }
+            }
+        })
+        .map(Some)?
+    } else {
+        None
+    };
+
+    // Consume the optional delays.
+    let delays = if p.try_eat(Hashtag) {
+        let (tkn, sp) = p.peek(0);
+        match tkn {
+            OpenDelim(Paren) => flanked(p, Paren, |p| {
+                comma_list_nonempty(p, CloseDelim(Paren), "delay value", |p| {
+                    parse_expr_prec(p, Precedence::MinTypMax)
+                })
+            })?,
+            Literal(Number(..)) | Literal(Time(..)) | Ident(..) => {
+                vec![parse_expr_first(p, Precedence::Max)?]
+            }
+            _ => {
+                p.add_diag(
+                    DiagBuilder2::error("expected delay value or expression after #").span(sp),
+                );
+                return Err(());
+            }
+        }
+    } else {
+        vec![]
+    };
+
+    // Consume the instances.
+    let names = comma_list_nonempty(p, Semicolon, "primitive instance", |p| {
+        let mut span = p.peek(0).1;
+        let (name, dims) = if p.is_ident() {
+            let name = parse_identifier_name(p, "instance name")?;
+            let (dims, _) = parse_optional_dimensions(p)?;
+            (Some(name), dims)
+        } else {
+            (None, vec![])
+        };
+        let terminals = flanked(p, Paren, |p| {
+            comma_list_nonempty(p, CloseDelim(Paren), "terminal", parse_expr)
+        })?;
+        span.expand(p.last_span());
+        Ok(ast::GateInstName::new(
+            span,
+            ast::GateInstNameData {
+                name,
+                dims,
+                terminals,
+            },
+        ))
+    })?;
+
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ast::GateInst::new(
+        span,
+        ast::GateInstData {
+            kind,
+            strength,
+            delays,
+            names,
+        },
+    ))
+}
+
+fn as_gate_kind(tkn: Token) -> Option<GateKind> {
+    match tkn {
+        Keyword(Kw::And) => Some(GateKind::And),
+        Keyword(Kw::Nand) => Some(GateKind::Nand),
+        Keyword(Kw::Or) => Some(GateKind::Or),
+        Keyword(Kw::Nor) => Some(GateKind::Nor),
+        Keyword(Kw::Xor) => Some(GateKind::Xor),
+        Keyword(Kw::Xnor) => Some(GateKind::Xnor),
+        Keyword(Kw::Buf) => Some(GateKind::Buf),
+        Keyword(Kw::Not) => Some(GateKind::Not),
+        Keyword(Kw::Bufif0) => Some(GateKind::Bufif0),
+        Keyword(Kw::Bufif1) => Some(GateKind::Bufif1),
+        Keyword(Kw::Notif0) => Some(GateKind::Notif0),
+        Keyword(Kw::Notif1) => Some(GateKind::Notif1),
+        Keyword(Kw::Nmos) => Some(GateKind::Nmos),
+        Keyword(Kw::Pmos) => Some(GateKind::Pmos),
+        Keyword(Kw::Rnmos) => Some(GateKind::Rnmos),
+        Keyword(Kw::Rpmos) => Some(GateKind::Rpmos),
+        Keyword(Kw::Cmos) => Some(GateKind::Cmos),
+        Keyword(Kw::Rcmos) => Some(GateKind::Rcmos),
+        Keyword(Kw::Tran) => Some(GateKind::Tran),
+        Keyword(Kw::Rtran) => Some(GateKind::Rtran),
+        Keyword(Kw::Tranif0) => Some(GateKind::Tranif0),
+        Keyword(Kw::Tranif1) => Some(GateKind::Tranif1),
+        Keyword(Kw::Rtranif0) => Some(GateKind::Rtranif0),
+        Keyword(Kw::Rtranif1) => Some(GateKind::Rtranif1),
+        Keyword(Kw::Pullup) => Some(GateKind::Pullup),
+        Keyword(Kw::Pulldown) => Some(GateKind::Pulldown),
+        _ => None,
+    }
+}
+
 fn parse_var_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::VarDecl<'n>> {
     let mut span = p.peek(0).1;
 
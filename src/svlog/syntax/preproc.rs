@@ -4,8 +4,11 @@
 //! tokens generated by a lexer and performs include and macro
 //! resolution.
 
+use crate::ast::{NetType, Timeunit};
 use crate::cat::*;
+use crate::token::{Lit, TimeUnit};
 use moore_common::errors::{DiagBuilder2, DiagResult2};
+use moore_common::name::get_name_table;
 use moore_common::source::*;
 use std::{collections::HashMap, fmt, path::Path, rc::Rc};
 
@@ -91,6 +94,28 @@ impl<'a> Preprocessor<'a> {
         }
     }
 
+    /// The net type currently set by the `default_nettype directive.
+    ///
+    /// Returns `None` if implicit nets have been disabled with
+    /// `` `default_nettype none``.
+    pub fn default_nettype(&self) -> Option<NetType> {
+        self.dirs.default_nettype
+    }
+
+    /// The time unit and precision currently set by the `timescale directive.
+    pub fn timescale(&self) -> Timeunit {
+        match self.dirs.timescale {
+            Some((unit, prec)) => Timeunit {
+                unit: Some(unit),
+                prec: Some(prec),
+            },
+            None => Timeunit {
+                unit: None,
+                prec: None,
+            },
+        }
+    }
+
     /// Advance to the next token in the input stream.
     fn bump(&mut self) {
         self.token = self.macro_stack.pop();
@@ -416,14 +441,49 @@ impl<'a> Preprocessor<'a> {
                 }
             }
 
-            // Ignore the "`timescale" directive for now.
             Directive::Timescale => {
-                while let Some((tkn, _)) = self.token {
+                // Gather the remaining tokens on the line.
+                let mut tokens = vec![];
+                while let Some((tkn, sp)) = self.token {
                     if tkn == Newline {
                         break;
                     }
+                    if tkn != Whitespace && tkn != Comment {
+                        tokens.push((tkn, sp));
+                    }
                     self.bump();
                 }
+                if self.is_inactive() {
+                    return Ok(());
+                }
+
+                // Parse the unit and precision.
+                let time_literal = |digits: Span, suffix: Span| {
+                    let value = get_name_table().intern(&digits.extract(), true);
+                    let unit = TimeUnit::from_suffix(&suffix.extract())?;
+                    Some(Spanned::new(
+                        Lit::Time(value, None, unit),
+                        Span::union(digits, suffix),
+                    ))
+                };
+                let parsed = match tokens.as_slice() {
+                    [(Digits, u), (Text, us), (Symbol('/'), _), (Digits, p), (Text, ps)] => {
+                        time_literal(*u, *us).and_then(|u| Some((u, time_literal(*p, *ps)?)))
+                    }
+                    _ => None,
+                };
+                match parsed {
+                    Some((unit, prec)) => {
+                        debug!("Set timescale to {:?} / {:?}", unit, prec);
+                        self.dirs.timescale = Some((unit, prec));
+                    }
+                    None => {
+                        return Err(DiagBuilder2::fatal(
+                            "expected time unit and precision after `timescale, e.g. `1ns/1ps`",
+                        )
+                        .span(span));
+                    }
+                }
                 return Ok(());
             }
 
@@ -485,16 +545,31 @@ impl<'a> Preprocessor<'a> {
                     };
 
                     // Store the nettype in the directive set.
-                    self.dirs.default_nettype = if tkn.1.extract() == "none" {
-                        None
-                    } else {
-                        Some(tkn)
+                    self.dirs.default_nettype = match tkn.1.extract().as_str() {
+                        "none" => None,
+                        "wire" => Some(NetType::Wire),
+                        "tri" => Some(NetType::Tri),
+                        "tri0" => Some(NetType::Tri0),
+                        "tri1" => Some(NetType::Tri1),
+                        "wand" => Some(NetType::WireAnd),
+                        "triand" => Some(NetType::TriAnd),
+                        "wor" => Some(NetType::WireOr),
+                        "trior" => Some(NetType::TriOr),
+                        "trireg" => Some(NetType::TriReg),
+                        "uwire" => Some(NetType::Uwire),
+                        other => {
+                            return Err(DiagBuilder2::fatal(format!(
+                                "`{}` is not a valid nettype for `default_nettype",
+                                other
+                            ))
+                            .span(tkn.1));
+                        }
                     };
                     debug!(
                         "Set default_nettype to `{}`",
                         self.dirs
                             .default_nettype
-                            .map(|(_, sp)| sp.extract())
+                            .map(|nt| nt.to_string())
                             .unwrap_or_else(|| "none".to_string())
                     );
                 }
@@ -1271,14 +1346,28 @@ enum Defcond {
     Disabled,
 }
 
-#[derive(Default)]
 struct Directives {
     celldefine: bool,
-    default_nettype: Option<TokenAndSpan>,
+    /// The net type of implicit nets. `None` if set to `none`.
+    default_nettype: Option<NetType>,
+    /// The time unit and precision set by `timescale.
+    timescale: Option<(Spanned<Lit>, Spanned<Lit>)>,
     keywords: Vec<KeywordsDirective>,
     unconnected_drive: Option<UnconnectedDrive>,
 }
 
+impl Default for Directives {
+    fn default() -> Self {
+        Directives {
+            celldefine: false,
+            default_nettype: Some(NetType::Wire),
+            timescale: None,
+            keywords: Vec::new(),
+            unconnected_drive: None,
+        }
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug)]
 enum KeywordsDirective {
@@ -140,6 +140,21 @@ pub enum TimeUnit {
     FemtoSecond,
 }
 
+impl TimeUnit {
+    /// Parse a time unit suffix such as `ns`.
+    pub fn from_suffix(s: &str) -> Option<TimeUnit> {
+        match s {
+            "s" => Some(TimeUnit::Second),
+            "ms" => Some(TimeUnit::MilliSecond),
+            "us" => Some(TimeUnit::MicroSecond),
+            "ns" => Some(TimeUnit::NanoSecond),
+            "ps" => Some(TimeUnit::PicoSecond),
+            "fs" => Some(TimeUnit::FemtoSecond),
+            _ => None,
+        }
+    }
+}
+
 /// Operator symbols.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Op {
@@ -411,6 +426,7 @@ macro_rules! declare_keywords {(
 
 declare_keywords! {
     // Keywords as per IEEE 1800-2009 Table B.1
+    (OneStep,              "1step")
     (AcceptOn,             "accept_on")
     (Alias,                "alias")
     (Always,               "always")
@@ -0,0 +1,200 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Time units and precisions.
+//!
+//! Every module, interface, program, and package has a time unit and a time
+//! precision. The unit determines how delays without an explicit unit such as
+//! `#10` are interpreted, and the precision determines to what granularity
+//! delays are rounded (see IEEE 1800-2017 §3.14).
+
+use crate::crate_prelude::*;
+use crate::syntax::token::{Lit, TimeUnit};
+use num::{BigInt, BigRational};
+
+/// The time unit assumed if nothing else is specified.
+const DEFAULT_UNIT: (u32, TimeUnit) = (1, TimeUnit::NanoSecond);
+
+/// The time precision assumed if nothing else is specified.
+const DEFAULT_PREC: (u32, TimeUnit) = (1, TimeUnit::PicoSecond);
+
+/// A time unit and precision.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeScale {
+    /// The time unit, in seconds.
+    pub unit: BigRational,
+    /// The time precision, in seconds.
+    pub prec: BigRational,
+}
+
+impl TimeScale {
+    /// Convert a value given in time units to seconds, rounded to the
+    /// precision.
+    pub fn scale(&self, value: &BigRational) -> BigRational {
+        self.round(&(value * &self.unit))
+    }
+
+    /// Round a time in seconds to the precision.
+    pub fn round(&self, time: &BigRational) -> BigRational {
+        (time / &self.prec).round() * &self.prec
+    }
+}
+
+impl std::fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} / {}", fmt_time(&self.unit), fmt_time(&self.prec))
+    }
+}
+
+/// Format a time in seconds using the largest unit that keeps it integral.
+fn fmt_time(time: &BigRational) -> String {
+    let mut value = time.clone();
+    for suffix in &["s", "ms", "us", "ns", "ps"] {
+        if value.is_integer() {
+            return format!("{}{}", value, suffix);
+        }
+        value = value * BigRational::from_integer(BigInt::from(1000));
+    }
+    format!("{}fs", value)
+}
+
+/// Convert a magnitude and unit to seconds.
+fn seconds(magnitude: u32, unit: TimeUnit) -> BigRational {
+    let exp = match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::MilliSecond => 3,
+        TimeUnit::MicroSecond => 6,
+        TimeUnit::NanoSecond => 9,
+        TimeUnit::PicoSecond => 12,
+        TimeUnit::FemtoSecond => 15,
+    };
+    BigRational::new(BigInt::from(magnitude), num::pow(BigInt::from(10), exp))
+}
+
+/// Find the module, interface, program, or package that contains a node.
+fn enclosing_element<'a>(node: &'a dyn ast::AnyNode<'a>) -> Option<&'a dyn ast::AnyNode<'a>> {
+    let mut next = node.get_parent();
+    while let Some(node) = next {
+        match node.as_all() {
+            ast::AllNode::Module(..) | ast::AllNode::Interface(..) | ast::AllNode::Package(..) => {
+                return Some(node)
+            }
+            _ => next = node.get_parent(),
+        }
+    }
+    None
+}
+
+/// Find the source file that contains a node.
+fn enclosing_source_file<'a>(node: &'a dyn ast::AnyNode<'a>) -> Option<&'a ast::SourceFile<'a>> {
+    let mut next = node.get_parent();
+    while let Some(node) = next {
+        match node.as_all() {
+            ast::AllNode::SourceFile(x) => return Some(x),
+            _ => next = node.get_parent(),
+        }
+    }
+    None
+}
+
+/// Determine the time unit and precision in effect at a node.
+pub fn time_scale_at<'a>(cx: &impl Context<'a>, node: &'a dyn ast::AnyNode<'a>) -> TimeScale {
+    match node.as_all() {
+        ast::AllNode::Module(..) | ast::AllNode::Interface(..) | ast::AllNode::Package(..) => {
+            cx.time_scale(Ref(node))
+        }
+        _ => match enclosing_element(node) {
+            Some(elem) => cx.time_scale(Ref(elem)),
+            None => TimeScale {
+                unit: seconds(DEFAULT_UNIT.0, DEFAULT_UNIT.1),
+                prec: seconds(DEFAULT_PREC.0, DEFAULT_PREC.1),
+            },
+        },
+    }
+}
+
+/// Determine the time unit and precision of a module, interface, program, or
+/// package.
+///
+/// Unit and precision are determined separately. An explicit `timeunit` or
+/// `timeprecision` declaration takes precedence. Otherwise, nested elements
+/// inherit from their parent, and top-level elements use the last
+/// `` `timescale`` directive, followed by a `timeunit` declaration in the
+/// compilation unit, followed by the default of 1ns / 1ps.
+#[moore_derive::query]
+pub(crate) fn time_scale<'a>(
+    cx: &impl Context<'a>,
+    Ref(node): Ref<'a, dyn ast::AnyNode<'a>>,
+) -> TimeScale {
+    let (timeunits, timescale) = match node.as_all() {
+        ast::AllNode::Module(x) => (&x.timeunits, Some(&x.timescale)),
+        ast::AllNode::Interface(x) => (&x.timeunits, Some(&x.timescale)),
+        ast::AllNode::Package(x) => (&x.timeunits, Some(&x.timescale)),
+        _ => bug_span!(node.span(), cx, "{} has no time scale", node),
+    };
+    let parent = enclosing_element(node).map(|p| cx.time_scale(Ref(p)));
+    let file = enclosing_source_file(node).map(|f| &f.timeunits);
+
+    // Determine the unit and precision separately.
+    let pick = |field: fn(&ast::Timeunit) -> Option<Spanned<Lit>>,
+                inherited: Option<&BigRational>,
+                default: (u32, TimeUnit)| {
+        let lit = field(timeunits);
+        if lit.is_none() {
+            if let Some(inherited) = inherited {
+                return inherited.clone();
+            }
+        }
+        let lit = lit
+            .or_else(|| timescale.and_then(field))
+            .or_else(|| file.and_then(field));
+        match lit.map(|lit| time_literal(cx, lit)) {
+            Some(Ok(x)) => x,
+            _ => seconds(default.0, default.1),
+        }
+    };
+    let unit = pick(|t| t.unit, parent.as_ref().map(|p| &p.unit), DEFAULT_UNIT);
+    let mut prec = pick(|t| t.prec, parent.as_ref().map(|p| &p.prec), DEFAULT_PREC);
+
+    // Ensure that the precision is at least as fine as the unit.
+    if prec > unit {
+        let span = timeunits
+            .prec
+            .or(timeunits.unit)
+            .map(|x| x.span)
+            .unwrap_or_else(|| node.human_span());
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "time precision {} is coarser than time unit {}",
+                fmt_time(&prec),
+                fmt_time(&unit)
+            ))
+            .span(span),
+        );
+        prec = unit.clone();
+    }
+
+    let scale = TimeScale { unit, prec };
+    debug!("Time scale of {:?} is {}", node, scale);
+    scale
+}
+
+/// Convert a time unit or precision literal to seconds.
+///
+/// Emits an error if the literal is not one of 1, 10, or 100 followed by a time
+/// unit.
+fn time_literal<'a>(cx: &impl Context<'a>, lit: Spanned<Lit>) -> Result<BigRational> {
+    let (magnitude, unit) = match lit.value {
+        Lit::Time(int, None, unit) => (int.to_string(), unit),
+        _ => (String::new(), TimeUnit::Second),
+    };
+    match magnitude.as_str() {
+        "1" | "10" | "100" => Ok(seconds(magnitude.parse().unwrap(), unit)),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error("time unit and precision must be 1, 10, or 100 of a unit")
+                    .span(lit.span),
+            );
+            Err(())
+        }
+    }
+}
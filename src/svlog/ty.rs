@@ -252,9 +252,10 @@ pub enum UnpackedCore<'a> {
     Chandle,
     /// An event.
     Event,
-    // TODO: Add virtual interfaces
-    // TODO: Add class types
-    // TODO: Add covergroups
+    /// A virtual interface.
+    VirtIntf(InterfaceType<'a>),
+    /// A class handle.
+    Class(ClassType<'a>),
     /// A named type.
     Named {
         /// How the user originally called the type.
@@ -273,6 +274,8 @@ pub enum UnpackedCore<'a> {
     Module(ModuleType<'a>),
     /// An interface instance.
     Interface(InterfaceType<'a>),
+    /// A covergroup instance.
+    Covergroup(CovergroupType<'a>),
 }
 
 /// An unpacked dimension.
@@ -364,6 +367,24 @@ pub struct InterfaceType<'a> {
     pub modport: Option<&'a ast::ModportName<'a>>,
 }
 
+/// A covergroup instance.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CovergroupType<'a> {
+    /// The AST node of the covergroup.
+    pub ast: &'a ast::Covergroup<'a>,
+    /// The parametrization of the scope the covergroup is declared in.
+    pub env: ParamEnv,
+}
+
+/// A class handle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassType<'a> {
+    /// The AST node of the class.
+    pub ast: &'a ast::ClassDecl<'a>,
+    /// The parametrization of the class.
+    pub env: ParamEnv,
+}
+
 /// A simple bit vector type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SbvType {
@@ -1370,7 +1391,10 @@ impl<'a> UnpackedType<'a> {
             | UnpackedCore::Chandle
             | UnpackedCore::Event
             | UnpackedCore::Module { .. }
-            | UnpackedCore::Interface { .. } => Domain::TwoValued,
+            | UnpackedCore::Interface { .. }
+            | UnpackedCore::VirtIntf { .. }
+            | UnpackedCore::Covergroup { .. }
+            | UnpackedCore::Class { .. } => Domain::TwoValued,
         }
     }
 
@@ -1390,7 +1414,10 @@ impl<'a> UnpackedType<'a> {
             | UnpackedCore::Chandle
             | UnpackedCore::Event
             | UnpackedCore::Module { .. }
-            | UnpackedCore::Interface { .. } => return None,
+            | UnpackedCore::Interface { .. }
+            | UnpackedCore::VirtIntf { .. }
+            | UnpackedCore::Covergroup { .. }
+            | UnpackedCore::Class { .. } => return None,
         };
         for &dim in &self.dims {
             match dim {
@@ -1425,11 +1452,24 @@ impl<'a> UnpackedType<'a> {
             .unwrap_or(false)
     }
 
+    /// Check if this type is `void`.
+    pub fn is_void(&self) -> bool {
+        match self.get_packed().map(|ty| &ty.resolve_full().core) {
+            Some(PackedCore::Void) => true,
+            _ => false,
+        }
+    }
+
     /// Check if this type is a string, like `string`.
     pub fn is_string(&self) -> bool {
         self.dims.is_empty() && self.resolve_full().core == UnpackedCore::String
     }
 
+    /// Check if this type is an event, like `event`.
+    pub fn is_event(&self) -> bool {
+        self.dims.is_empty() && self.resolve_full().core == UnpackedCore::Event
+    }
+
     /// Check if this type will coalesce to a scalar type in LLHD, like `i42`.
     pub fn coalesces_to_llhd_scalar(&self) -> bool {
         self.get_packed()
@@ -1573,6 +1613,35 @@ impl<'a> UnpackedType<'a> {
         }
     }
 
+    /// Get the interface underlying a virtual interface, or `None` if the type
+    /// is not a virtual interface.
+    pub fn get_virtual_interface(&self) -> Option<&InterfaceType<'a>> {
+        if self.dims.is_empty() {
+            self.resolve_full().core.get_virtual_interface()
+        } else {
+            None
+        }
+    }
+
+    /// Get the underlying covergroup, or `None` if the type is not a
+    /// covergroup.
+    pub fn get_covergroup(&self) -> Option<&CovergroupType<'a>> {
+        if self.dims.is_empty() {
+            self.resolve_full().core.get_covergroup()
+        } else {
+            None
+        }
+    }
+
+    /// Get the underlying class, or `None` if the type is not a class handle.
+    pub fn get_class(&self) -> Option<&ClassType<'a>> {
+        if self.dims.is_empty() {
+            self.resolve_full().core.get_class()
+        } else {
+            None
+        }
+    }
+
     /// Helper function to format this type around a declaration name.
     fn format_around(
         &self,
@@ -1669,6 +1738,9 @@ impl<'a> UnpackedCore<'a> {
             (Self::Ref { ty: a, .. }, Self::Ref { ty: b, .. }) => a.is_identical(b),
             (Self::Module(a), Self::Module(b)) => a == b,
             (Self::Interface(a), Self::Interface(b)) => a == b,
+            (Self::VirtIntf(a), Self::VirtIntf(b)) => a == b,
+            (Self::Covergroup(a), Self::Covergroup(b)) => a == b,
+            (Self::Class(a), Self::Class(b)) => a == b,
             _ => false,
         }
     }
@@ -1687,6 +1759,9 @@ impl<'a> UnpackedCore<'a> {
             (Self::Ref { ty: a, .. }, Self::Ref { ty: b, .. }) => a.is_strictly_identical(b),
             (Self::Module(a), Self::Module(b)) => a == b,
             (Self::Interface(a), Self::Interface(b)) => a == b,
+            (Self::VirtIntf(a), Self::VirtIntf(b)) => a == b,
+            (Self::Covergroup(a), Self::Covergroup(b)) => a == b,
+            (Self::Class(a), Self::Class(b)) => a == b,
             _ => false,
         }
     }
@@ -1732,6 +1807,37 @@ impl<'a> UnpackedCore<'a> {
             _ => None,
         }
     }
+
+    /// Get the interface underlying a virtual interface, or `None` if the type
+    /// is not a virtual interface.
+    pub fn get_virtual_interface(&self) -> Option<&InterfaceType<'a>> {
+        match *self {
+            UnpackedCore::VirtIntf(ref x) => Some(x),
+            UnpackedCore::Named { ty, .. } | UnpackedCore::Ref { ty, .. } => {
+                ty.get_virtual_interface()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the underlying covergroup, or `None` if the type is not a
+    /// covergroup.
+    pub fn get_covergroup(&self) -> Option<&CovergroupType<'a>> {
+        match *self {
+            UnpackedCore::Covergroup(ref x) => Some(x),
+            UnpackedCore::Named { ty, .. } | UnpackedCore::Ref { ty, .. } => ty.get_covergroup(),
+            _ => None,
+        }
+    }
+
+    /// Get the underlying class, or `None` if the type is not a class handle.
+    pub fn get_class(&self) -> Option<&ClassType<'a>> {
+        match *self {
+            UnpackedCore::Class(ref x) => Some(x),
+            UnpackedCore::Named { ty, .. } | UnpackedCore::Ref { ty, .. } => ty.get_class(),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<&'a PackedType<'a>> for UnpackedCore<'a> {
@@ -1764,6 +1870,18 @@ impl<'a> From<InterfaceType<'a>> for UnpackedCore<'a> {
     }
 }
 
+impl<'a> From<CovergroupType<'a>> for UnpackedCore<'a> {
+    fn from(inner: CovergroupType<'a>) -> Self {
+        Self::Covergroup(inner)
+    }
+}
+
+impl<'a> From<ClassType<'a>> for UnpackedCore<'a> {
+    fn from(inner: ClassType<'a>) -> Self {
+        Self::Class(inner)
+    }
+}
+
 impl Display for UnpackedCore<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -1779,6 +1897,12 @@ impl Display for UnpackedCore<'_> {
                 Some(y) => write!(f, "{}.{}", x.ast.name, y.name),
                 None => write!(f, "{}", x.ast.name),
             },
+            Self::VirtIntf(x) => match x.modport {
+                Some(y) => write!(f, "virtual {}.{}", x.ast.name, y.name),
+                None => write!(f, "virtual {}", x.ast.name),
+            },
+            Self::Covergroup(x) => write!(f, "{}", x.ast.name),
+            Self::Class(x) => write!(f, "{}", x.ast.name),
             Self::Named { name, .. } => write!(f, "{}", name),
             Self::Ref { span, .. } => write!(f, "{}", span.extract()),
         }
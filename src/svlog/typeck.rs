@@ -62,6 +62,9 @@ pub(crate) fn type_of<'a>(
             x => bug_span!(ast.span(), cx, "VarDeclName with weird parent {:?}", x),
         },
         ast::AllNode::ParamValueDecl(x) => return Ok(cx.type_of_value_param(Ref(x), env)),
+        ast::AllNode::SubroutinePort(x) => {
+            return Ok(cx.packed_type_from_ast(Ref(&x.ty), env, None))
+        }
         _ => (),
     };
 
@@ -101,6 +104,26 @@ pub(crate) fn type_of<'a>(
         HirNode::Package(_) => Ok(UnpackedType::make_void()),
         HirNode::Assign(_) => unreachable!("has no type: {:?}", hir),
         HirNode::Inst(hir) => Ok(cx.type_of_inst(Ref(hir), env)),
+        // Clocking blocks behave like events triggered after sampling, and
+        // their signals take on the type of the expression they refer to.
+        HirNode::ClockingBlock(_) => Ok(UnpackedType::make(cx, UnpackedCore::Event)),
+        HirNode::ClockVar(var) => Ok(cx.need_self_determined_type(var.expr, env)),
+        HirNode::LetArg(arg) => match arg.ty {
+            Some(ty) => Ok(cx.packed_type_from_ast(
+                Ref(cx
+                    .ast_for_id(ty)
+                    .as_all()
+                    .get_type()
+                    .expect("let argument type should be a type")),
+                env,
+                None,
+            )),
+            // Untyped arguments assume the type of the bound expression.
+            None => match cx.param_env_data(env).find_let_arg(arg.id) {
+                Some(actual) => Ok(cx.need_self_determined_type(actual.id(), actual.env())),
+                None => bug_span!(arg.span, cx, "{} not bound", arg.desc_full()),
+            },
+        },
         _ => {
             error!("{:#?}", hir);
             bug_span!(
@@ -113,6 +136,40 @@ pub(crate) fn type_of<'a>(
     }
 }
 
+/// Determine the type of a module name at the root of a hierarchical reference.
+///
+/// A reference such as `tb.c.x` within module `tb` starts at the current
+/// instance of `tb`. References rooted at any other module are not supported.
+fn type_of_hier_root<'a>(
+    cx: &impl Context<'a>,
+    module: &'a ast::Module<'a>,
+    expr: &'a hir::Expr<'a>,
+    env: ParamEnv,
+) -> Result<&'a UnpackedType<'a>> {
+    match crate::virtual_intf::enclosing_module(cx, expr.id) {
+        Some(current) if current.id() == module.id() => Ok(UnpackedType::make(
+            cx,
+            UnpackedCore::Module(ty::ModuleType { ast: module, env }),
+        )),
+        current => {
+            let mut d = DiagBuilder2::error(format!(
+                "unsupported: hierarchical reference rooted at module `{}`",
+                module.name
+            ))
+            .span(expr.span);
+            if let Some(current) = current {
+                d = d.add_note(format!(
+                    "Hierarchical references must start at an instance within `{0}`, or at `{0}` \
+                     itself",
+                    current.name
+                ));
+            }
+            cx.emit(d);
+            Err(())
+        }
+    }
+}
+
 /// Determine the type of an internal port.
 #[moore_derive::query]
 pub(crate) fn type_of_int_port<'a>(
@@ -373,6 +430,29 @@ pub(crate) fn type_of_inst<'a>(
     apply_unpacked_dims(cx, ty, &details.hir.ast.dims, env, details.hir.ast.span())
 }
 
+/// Determine the handle type of a class, optionally specialized by a `C #(...)`
+/// type.
+pub(crate) fn class_type<'a>(
+    cx: &impl Context<'a>,
+    ast: &'a ast::ClassDecl<'a>,
+    env: ParamEnv,
+    spec: Option<&'a ast::Type<'a>>,
+) -> &'a UnpackedType<'a> {
+    let hir = match cx.hir_of(ast.id()) {
+        Ok(HirNode::Class(x)) => x,
+        _ => return UnpackedType::make_error(),
+    };
+    let env = match cx.param_env(ParamEnvSource::ClassInst {
+        class: Ref(hir),
+        env,
+        spec: spec.map(Ref),
+    }) {
+        Ok(x) => x,
+        _ => return UnpackedType::make_error(),
+    };
+    UnpackedType::make(cx, UnpackedCore::Class(ty::ClassType { ast, env }))
+}
+
 /// Map an AST node to the type it represents.
 ///
 /// Returns `None` if the given AST node does not evaluate to a type.
@@ -407,6 +487,11 @@ pub(crate) fn map_to_type<'a>(
                 }),
             ))
         }
+        ast::AllNode::Covergroup(ast) => Some(UnpackedType::make(
+            cx,
+            UnpackedCore::Covergroup(ty::CovergroupType { ast, env }),
+        )),
+        ast::AllNode::ClassDecl(ast) => Some(class_type(cx, ast, env, None)),
         // The following is an ugly hack, and should actually never happen. But
         // as the HIR is implemented at the moment, certain parameter bindings
         // can bind expressions to type parameters.
@@ -732,31 +817,9 @@ pub(crate) fn packed_type_from_ast<'a>(
         } => {
             let inner_ty = cx.packed_type_from_ast(Ref(ty), env, None);
             if let Some(intf) = inner_ty.get_interface() {
-                // Resolve the modport name within the interface.
-                let def = match cx.resolve_hierarchical_or_error(name, intf.ast) {
-                    Ok(def) => def,
-                    _ => return UnpackedType::make_error(),
-                };
-
-                // Make sure that we have selected a modport.
-                let modport = match def.node {
-                    DefNode::Ast(node) => node.as_all().get_modport_name(),
-                    _ => None,
-                };
-                let modport = match modport {
-                    Some(x) => x,
-                    None => {
-                        cx.emit(
-                            DiagBuilder2::error(format!(
-                                "`{}` is not a modport of {}",
-                                name, intf.ast
-                            ))
-                            .span(name.span)
-                            .add_note(format!("`{}` was defined here:", name))
-                            .span(def.name.span),
-                        );
-                        return UnpackedType::make_error();
-                    }
+                let modport = match resolve_modport(cx, intf, name) {
+                    Ok(x) => x,
+                    Err(()) => return UnpackedType::make_error(),
                 };
 
                 // Package up a new interface type with the modport annotated.
@@ -801,7 +864,64 @@ pub(crate) fn packed_type_from_ast<'a>(
             }
         }
 
-        ast::VirtIntfType { .. } | ast::MailboxType | ast::SpecializedType(..) => {
+        // Specialized class types
+        ast::SpecializedType(ref inner, _) => {
+            let inner_ty = cx.packed_type_from_ast(Ref(inner), env, None);
+            if inner_ty.is_error() {
+                return inner_ty;
+            }
+            match inner_ty.get_class() {
+                Some(class) => return class_type(cx, class.ast, env, Some(ast)),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a class and cannot be specialized",
+                            inner.span().extract()
+                        ))
+                        .span(ast.span()),
+                    );
+                    return UnpackedType::make_error();
+                }
+            }
+        }
+
+        // Virtual interfaces
+        ast::VirtIntfType { name, modport } => {
+            let loc = cx.scope_location(ast);
+            let def = match cx.resolve_local_or_error(name, loc, false) {
+                Ok(def) => def,
+                Err(()) => return UnpackedType::make_error(),
+            };
+            let intf = match def.node {
+                DefNode::Ast(node) => cx.map_to_type(Ref(node), env),
+                _ => None,
+            };
+            let intf = match intf.and_then(|ty| ty.get_interface()) {
+                Some(x) => x,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("`{}` is not an interface", name))
+                            .span(name.span)
+                            .add_note(format!("`{}` was declared here:", name))
+                            .span(def.node.span()),
+                    );
+                    return UnpackedType::make_error();
+                }
+            };
+            let modport = match modport {
+                Some(name) => match resolve_modport(cx, intf, name) {
+                    Ok(x) => Some(x),
+                    Err(()) => return UnpackedType::make_error(),
+                },
+                None => None,
+            };
+            Unpacked(UnpackedCore::VirtIntf(ty::InterfaceType {
+                modport,
+                ..*intf
+            }))
+        }
+
+        ast::MailboxType => {
             bug_span!(ast.span(), cx, "type {:#1?} not implemented", ast.kind)
         }
     };
@@ -913,6 +1033,31 @@ fn packed_type_from_def<'a>(
     }
 }
 
+/// Resolve the name of a modport within an interface.
+fn resolve_modport<'a>(
+    cx: &impl Context<'a>,
+    intf: &ty::InterfaceType<'a>,
+    name: Spanned<Name>,
+) -> Result<&'a ast::ModportName<'a>> {
+    let def = cx.resolve_hierarchical_or_error(name, intf.ast)?;
+    let modport = match def.node {
+        DefNode::Ast(node) => node.as_all().get_modport_name(),
+        _ => None,
+    };
+    match modport {
+        Some(x) => Ok(x),
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` is not a modport of {}", name, intf.ast))
+                    .span(name.span)
+                    .add_note(format!("`{}` was defined here:", name))
+                    .span(def.name.span),
+            );
+            Err(())
+        }
+    }
+}
+
 // A simple enum that keeps either a packed or unpacked core type.
 enum PackedOrUnpacked<'a> {
     Packed(PackedCore<'a>),
@@ -1010,6 +1155,15 @@ pub(crate) fn type_of_expr<'a>(
     env: ParamEnv,
 ) -> &'a UnpackedType<'a> {
     let Ref(expr) = expr;
+
+    // References to `let` declarations assume the type of their expansion.
+    if let Some(expansion) = let_expansion(cx, expr, env) {
+        return match expansion {
+            Ok((body, env)) => cx.type_of_expr(Ref(body), env),
+            Err(()) => UnpackedType::make_error(),
+        };
+    }
+
     match expr.kind {
         // These expressions have a fully self-determined type.
         hir::ExprKind::IntConst { .. }
@@ -1030,8 +1184,18 @@ pub(crate) fn type_of_expr<'a>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Time)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealTime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Random(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Urandom(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::UrandomRange(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Finish(_))
+        | hir::ExprKind::StdRandomize(..)
+        | hir::ExprKind::InlineConstraint(..)
         | hir::ExprKind::Field(..)
         | hir::ExprKind::Index(..)
+        | hir::ExprKind::This
+        | hir::ExprKind::Super
         | hir::ExprKind::Assign { .. } => cx.need_self_determined_type(expr.id, env),
 
         // Unsized constants infer their type from the context if possible, and
@@ -1106,10 +1270,15 @@ pub(crate) fn type_of_expr<'a>(
         // Other things simply evaluate to their self-determined type.
         hir::ExprKind::Builtin(hir::BuiltinCall::Signed(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsigned(_))
-        | hir::ExprKind::FunctionCall(..) => cx.need_self_determined_type(expr.id, env),
+        | hir::ExprKind::FunctionCall(..)
+        | hir::ExprKind::MethodCall(..)
+        | hir::ExprKind::Let(..) => cx.need_self_determined_type(expr.id, env),
 
-        // Pattern expressions require a type context.
-        hir::ExprKind::PositionalPattern(..)
+        // Pattern expressions, constructor calls, and `null` require a type
+        // context.
+        hir::ExprKind::New(..)
+        | hir::ExprKind::Null
+        | hir::ExprKind::PositionalPattern(..)
         | hir::ExprKind::NamedPattern(..)
         | hir::ExprKind::RepeatPattern(..) => cx.need_type_context(expr.id, env).ty(),
     }
@@ -1143,39 +1312,45 @@ pub(crate) fn cast_expr_type<'a>(
     if cx.sess().has_verbosity(Verbosity::CASTS) && !cast.is_error() && !cast.casts.is_empty() {
         let mut d =
             DiagBuilder2::note(format!("cast: `{}` to `{}`", cast.init, cast.ty)).span(expr.span);
-        for (op, ty) in &cast.casts {
-            let msg = match *op {
-                CastOp::PackSBVT => format!("pack as simple bit vector type `{}`", ty),
-                CastOp::UnpackSBVT => format!("unpack simple bit vector type as `{}`", ty),
-                CastOp::Bool => format!("cast to boolean `{}`", ty),
-                CastOp::Sign(sign) => format!("sign cast to {} type `{}`", sign, ty),
-                CastOp::Range(_, signed) => format!(
-                    "{} size cast to `{}`",
-                    match signed {
-                        true => "sign-extended",
-                        false => "zero-extended",
-                    },
-                    ty
-                ),
-                CastOp::Domain(domain) => format!(
-                    "cast to {} `{}`",
-                    match domain {
-                        ty::Domain::TwoValued => "two-valued",
-                        ty::Domain::FourValued => "four-valued",
-                    },
-                    ty
-                ),
-                CastOp::PickModport => format!("implicitly picking modport `{}`", ty),
-                CastOp::PackString => format!("pack as string `{}`", ty),
-                CastOp::UnpackString => format!("unpack string as `{}`", ty),
-            };
-            d = d.add_note(msg);
+        for &(op, ty) in &cast.casts {
+            d = d.add_note(describe_cast_op(op, ty));
         }
         cx.emit(d);
     }
     cast
 }
 
+/// Describe a cast operation in a human-readable form.
+pub fn describe_cast_op(op: CastOp, ty: &ty::UnpackedType) -> String {
+    match op {
+        CastOp::PackSBVT => format!("pack as simple bit vector type `{}`", ty),
+        CastOp::UnpackSBVT => format!("unpack simple bit vector type as `{}`", ty),
+        CastOp::Bool => format!("cast to boolean `{}`", ty),
+        CastOp::Sign(sign) => format!("sign cast to {} type `{}`", sign, ty),
+        CastOp::Range(_, signed) => format!(
+            "{} size cast to `{}`",
+            match signed {
+                true => "sign-extended",
+                false => "zero-extended",
+            },
+            ty
+        ),
+        CastOp::Domain(domain) => format!(
+            "cast to {} `{}`",
+            match domain {
+                ty::Domain::TwoValued => "two-valued",
+                ty::Domain::FourValued => "four-valued",
+            },
+            ty
+        ),
+        CastOp::PickModport => format!("implicitly picking modport `{}`", ty),
+        CastOp::Upcast => format!("upcast to base class `{}`", ty),
+        CastOp::Virtual => format!("refer through virtual interface `{}`", ty),
+        CastOp::PackString => format!("pack as string `{}`", ty),
+        CastOp::UnpackString => format!("unpack string as `{}`", ty),
+    }
+}
+
 /// Get the cast type of an expression.
 fn cast_expr_type_inner<'gcx>(
     cx: &impl Context<'gcx>,
@@ -1253,6 +1428,38 @@ fn cast_expr_type_inner<'gcx>(
         }
     }
 
+    // Refer to interface instances through virtual interfaces.
+    if let TypeContext::Type(context) = context {
+        if let (Some(from), Some(to)) = (
+            inferred.resolve_full().core.get_interface(),
+            context.resolve_full().core.get_virtual_interface(),
+        ) {
+            if from.ast == to.ast {
+                trace!("  Referring to `{}` through `{}`", inferred, context);
+                cast.add_cast(CastOp::Virtual, context);
+                return cast;
+            }
+        }
+    }
+
+    // Cast class handles to handles of their base classes.
+    if let (Some(from), Some(to)) = (inferred.get_class(), context.ty().get_class()) {
+        if crate::class::is_derived_from(cx, from, to) {
+            trace!("  Upcasting `{}` to `{}`", inferred, context);
+            cast.add_cast(CastOp::Upcast, context.ty());
+            return cast;
+        }
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "cannot assign a handle of class `{}` to a handle of class `{}`",
+                inferred, context
+            ))
+            .span(expr.span)
+            .add_note(format!("`{}` does not extend `{}`", inferred, context)),
+        );
+        return ty::UnpackedType::make_error().into();
+    }
+
     // Cast strings to SBVTs.
     let inferred = match context.ty().get_simple_bit_vector() {
         Some(context_sbvt) if inferred.is_string() => {
@@ -1496,6 +1703,13 @@ fn self_determined_expr_type<'gcx>(
     expr: &'gcx hir::Expr<'gcx>,
     env: ParamEnv,
 ) -> Option<&'gcx UnpackedType<'gcx>> {
+    if let Some(expansion) = let_expansion(cx, expr, env) {
+        return match expansion {
+            Ok((body, env)) => cx.self_determined_type(body.id, env),
+            Err(()) => Some(UnpackedType::make_error()),
+        };
+    }
+
     match expr.kind {
         // Unsized constants fall back to their single bit equivalent.
         hir::ExprKind::UnsizedConst(_) => Some(UnpackedType::make_logic()),
@@ -1543,7 +1757,10 @@ fn self_determined_expr_type<'gcx>(
         // node.
         hir::ExprKind::Ident(_) | hir::ExprKind::Scope(..) => Some(
             cx.resolve_node(expr.id, env)
-                .and_then(|x| cx.type_of(x, env))
+                .and_then(|x| match cx.ast_for_id(x).as_all().get_module() {
+                    Some(module) => type_of_hier_root(cx, module, expr, env),
+                    None => cx.type_of(x, env),
+                })
                 .unwrap_or(UnpackedType::make_error()),
         ),
 
@@ -1669,10 +1886,31 @@ fn self_determined_expr_type<'gcx>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::CountOnes(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(..)) => {
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Random(_))
+        | hir::ExprKind::StdRandomize(..) => {
             Some(PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx))
         }
 
+        // `$urandom` and `$urandom_range` evaluate to an unsigned 32 bit
+        // integer. See IEEE 1800-2017 §18.13.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Urandom(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::UrandomRange(..)) => Some(
+            PackedType::make_sign(cx, ty::IntAtomType::Int, Sign::Unsigned, true).to_unpacked(cx),
+        ),
+
+        // `$finish` is a task and has no value.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Finish(_)) => Some(UnpackedType::make_void()),
+
+        // Calls to `randomize()` with inline constraints return whether the
+        // randomization succeeded, like the call itself.
+        hir::ExprKind::InlineConstraint(call, _) => cx.self_determined_type(call, env),
+
+        // The simulation time functions evaluate to a time value. Since `time`
+        // maps to physical time, we also use it for `$realtime`.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Time)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealTime) => Some(UnpackedType::make_time()),
+
         // These builtin functions evaluate to the bit type.
         hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_))
@@ -1683,7 +1921,10 @@ fn self_determined_expr_type<'gcx>(
         // Member field accesses resolve to the type of the member.
         hir::ExprKind::Field(target, name) => {
             let target_ty = cx.self_determined_type(target, env)?;
-            if let Some(intf) = target_ty.get_interface() {
+            let intf = target_ty
+                .get_interface()
+                .or_else(|| target_ty.get_virtual_interface());
+            if let Some(intf) = intf {
                 let def = cx.resolve_hierarchical_or_error(name, intf.ast).ok()?;
                 // If we are selecting a modport, just modify the type of the
                 // expression and let the implicit casting logic take care of
@@ -1718,6 +1959,55 @@ fn self_determined_expr_type<'gcx>(
                             .unwrap_or(UnpackedType::make_error()),
                     )
                 }
+            } else if let Some(class) = target_ty.get_class() {
+                // Property accesses resolve to the type of the property within
+                // the class that declares it.
+                match crate::class::find_property(cx, class, name.value).ok()? {
+                    Some(prop) => Some(
+                        cx.type_of(prop.member.id, prop.class.env)
+                            .unwrap_or(UnpackedType::make_error()),
+                    ),
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "class `{}` has no property `{}`",
+                                class.ast.name, name
+                            ))
+                            .span(name.span)
+                            .add_note(format!("`{}` declared here:", class.ast.name))
+                            .span(class.ast.name.span),
+                        );
+                        Some(UnpackedType::make_error())
+                    }
+                }
+            } else if let Some(block) = crate::clocking::clocking_block_of_expr(cx, target, env) {
+                // Signals of a clocking block.
+                Some(
+                    crate::clocking::resolve_clock_var(cx, block, name)
+                        .and_then(|var| cx.type_of(var.id, env))
+                        .unwrap_or(UnpackedType::make_error()),
+                )
+            } else if target_ty.is_event() {
+                // Events only provide the `triggered` property, which is a
+                // single bit.
+                if &*name.value.as_str() == "triggered" {
+                    Some(PackedType::make(cx, ty::IntVecType::Bit).to_unpacked(cx))
+                } else {
+                    cx.emit(
+                        DiagBuilder2::error(format!("event has no member `{}`", name))
+                            .span(name.span)
+                            .add_note("Events only have the `triggered` property."),
+                    );
+                    Some(UnpackedType::make_error())
+                }
+            } else if let Some(module) = target_ty.get_module() {
+                // Hierarchical references into module instances resolve to
+                // the type of the referenced node within that instance.
+                let def = cx.resolve_hierarchical_or_error(name, module.ast).ok()?;
+                Some(
+                    cx.type_of(def.node.id(), module.env)
+                        .unwrap_or(UnpackedType::make_error()),
+                )
             } else {
                 Some(
                     cx.resolve_field_access(expr.id, env)
@@ -1886,18 +2176,54 @@ fn self_determined_expr_type<'gcx>(
                         HirNode::Subroutine(s) => s,
                         _ => unreachable!(),
                     };
-                    match hir.retty {
-                        Some(retty_id) => Ok(cx.packed_type_from_ast(
-                            Ref(cx.ast_for_id(retty_id).as_all().get_type().unwrap()),
-                            env,
-                            None,
-                        )),
-                        None => Ok(UnpackedType::make_void()),
-                    }
+                    let env = crate::class::callee_env(cx, expr.id, target, env)?;
+                    Ok(subroutine_return_type(cx, hir, env))
                 })
                 .unwrap_or(UnpackedType::make_error()),
         ),
 
+        // `this` and `super` refer to the current object, as an instance of
+        // the enclosing class or its base class, respectively.
+        hir::ExprKind::This | hir::ExprKind::Super => {
+            let kw = match expr.kind {
+                hir::ExprKind::This => "this",
+                _ => "super",
+            };
+            let this = match crate::class::this_type(cx, expr.id, env) {
+                Some(x) => x,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("`{}` used outside of a class", kw))
+                            .span(expr.span),
+                    );
+                    return Some(UnpackedType::make_error());
+                }
+            };
+            if let hir::ExprKind::This = expr.kind {
+                return Some(this);
+            }
+            let class = this.get_class().unwrap();
+            match crate::class::base_class(cx, class) {
+                Ok(Some(base)) => Some(UnpackedType::make(cx, UnpackedCore::Class(base))),
+                Ok(None) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`super` used in class `{}`, which does not extend another class",
+                            class.ast.name
+                        ))
+                        .span(expr.span),
+                    );
+                    Some(UnpackedType::make_error())
+                }
+                Err(()) => Some(UnpackedType::make_error()),
+            }
+        }
+
+        // Method calls resolve to the method's return type.
+        hir::ExprKind::MethodCall(target, name, ref args) => {
+            Some(type_of_method_call(cx, expr, target, name, args, env))
+        }
+
         // Assignment expressions produce the value of the assigned variable as
         // their own value, which is basically the self-determined type of the
         // lhs, if available, and otherwise the rhs.
@@ -1909,6 +2235,126 @@ fn self_determined_expr_type<'gcx>(
     }
 }
 
+/// Determine the return type of a method call.
+///
+/// Besides class methods and the built-in `randomize()` of classes, only the
+/// built-in `sample()`, `get_coverage()`, and `get_inst_coverage()` methods of
+/// covergroups are supported at the moment.
+/// The coverage methods return the coverage as an integer percentage rather
+/// than a `real`.
+fn type_of_method_call<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx hir::Expr<'gcx>,
+    target: NodeId,
+    name: Spanned<Name>,
+    args: &[hir::CallArg],
+    env: ParamEnv,
+) -> &'gcx UnpackedType<'gcx> {
+    let ty = match cx.self_determined_type(target, env) {
+        Some(ty) if ty.is_error() => return ty,
+        Some(ty) => ty,
+        None => return UnpackedType::make_error(),
+    };
+    if let Some(class) = ty.get_class() {
+        if crate::constraint::is_randomize(name.value) {
+            if !args.is_empty() {
+                cx.emit(
+                    DiagBuilder2::error("unsupported: arguments to `randomize()`")
+                        .span(expr.span)
+                        .add_note("Only the `rand` and `randc` properties can be randomized"),
+                );
+                return UnpackedType::make_error();
+            }
+            return PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx);
+        }
+        return match find_class_method(cx, class, name) {
+            Ok(Some(method)) => {
+                let hir = match cx.hir_of(method.member.id) {
+                    Ok(HirNode::Subroutine(x)) => x,
+                    _ => return UnpackedType::make_error(),
+                };
+                subroutine_return_type(cx, hir, method.class.env)
+            }
+            // Classes without an explicit constructor have an implicit one.
+            Ok(None) => UnpackedType::make_void(),
+            Err(()) => UnpackedType::make_error(),
+        };
+    }
+    if ty.get_covergroup().is_none() {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`{}` has no method `{}`",
+                cx.span(target).extract(),
+                name
+            ))
+            .span(name.span)
+            .add_note(format!("`{}` has type `{}`", cx.span(target).extract(), ty)),
+        );
+        return UnpackedType::make_error();
+    }
+    let retty = match &*name.value.as_str() {
+        "sample" => UnpackedType::make_void(),
+        "get_coverage" | "get_inst_coverage" => {
+            PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx)
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("covergroup `{}` has no method `{}`", ty, name))
+                    .span(name.span),
+            );
+            return UnpackedType::make_error();
+        }
+    };
+    if !args.is_empty() {
+        cx.emit(DiagBuilder2::error(format!("`{}` takes no arguments", name)).span(expr.span));
+        return UnpackedType::make_error();
+    }
+    retty
+}
+
+/// Determine the return type of a subroutine.
+pub(crate) fn subroutine_return_type<'a>(
+    cx: &impl Context<'a>,
+    sub: &hir::Subroutine,
+    env: ParamEnv,
+) -> &'a UnpackedType<'a> {
+    match sub.retty {
+        Some(retty_id) => cx.packed_type_from_ast(
+            Ref(cx.ast_for_id(retty_id).as_all().get_type().unwrap()),
+            env,
+            None,
+        ),
+        None => UnpackedType::make_void(),
+    }
+}
+
+/// Look up a method of a class, or emit an error if there is none.
+///
+/// Returns `None` for the implicit constructor of a class that does not
+/// declare `new` itself.
+pub(crate) fn find_class_method<'a>(
+    cx: &impl Context<'a>,
+    class: &ty::ClassType<'a>,
+    name: Spanned<Name>,
+) -> Result<Option<crate::class::ClassMember<'a, hir::ClassMethod>>> {
+    match crate::class::find_method(cx, class, name.value)? {
+        Some(x) => Ok(Some(x)),
+        None if &*name.value.as_str() == "new" => Ok(None),
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "class `{}` has no method `{}`",
+                    class.ast.name, name
+                ))
+                .span(name.span)
+                .add_note(format!("`{}` declared here:", class.ast.name))
+                .span(class.ast.name.span),
+            );
+            Err(())
+        }
+    }
+}
+
 fn self_determined_sign_cast_type<'gcx>(
     cx: &impl Context<'gcx>,
     sign: Sign,
@@ -2036,10 +2482,25 @@ pub(crate) fn operation_type<'a>(
                 | hir::BinaryOp::Geq => {
                     let tlhs = cx.self_determined_type(lhs, env);
                     let trhs = cx.self_determined_type(rhs, env);
+                    let is_class = |t: Option<&UnpackedType>| {
+                        t.map(|t| t.get_class().is_some()).unwrap_or(false)
+                    };
                     if tlhs.map(|t| t.is_string()).unwrap_or(false)
                         && trhs.map(|t| t.is_string()).unwrap_or(false)
                     {
                         tlhs
+                    } else if is_class(tlhs) || is_class(trhs) {
+                        // Class handles are compared as handles of the more
+                        // general class, with `null` assuming the type of the
+                        // other handle.
+                        match (
+                            tlhs.and_then(|t| t.get_class()),
+                            trhs.and_then(|t| t.get_class()),
+                        ) {
+                            (Some(l), Some(r)) if crate::class::is_derived_from(cx, l, r) => trhs,
+                            (Some(_), _) => tlhs,
+                            _ => trhs,
+                        }
                     } else {
                         unify_operator_types(cx, env, tlhs.into_iter().chain(trhs.into_iter()))
                     }
@@ -2202,6 +2663,19 @@ pub(crate) fn need_operation_type<'a>(
     }
 }
 
+/// Determine the type of a subroutine argument.
+pub(crate) fn type_of_subroutine_arg<'a>(
+    cx: &impl Context<'a>,
+    arg: &hir::SubroutineArg,
+    env: ParamEnv,
+) -> &'a UnpackedType<'a> {
+    cx.packed_type_from_ast(
+        Ref(cx.ast_for_id(arg.ty).as_all().get_type().unwrap()),
+        env,
+        None,
+    )
+}
+
 /// Get the type context of a node.
 #[moore_derive::query]
 pub(crate) fn type_context<'a>(
@@ -2256,6 +2730,25 @@ pub(crate) fn type_context<'a>(
                 None
             }
         }
+        // The expansion of a `let` inherits the context of its call site.
+        HirNode::LetDecl(d) if d.expr == onto => {
+            let site = cx.param_env_data(env).find_let_arg(d.id)?;
+            cx.type_context(site.id(), site.env())
+        }
+        HirNode::LetArg(a) if a.default == Some(onto) && a.ty.is_some() => Some(
+            cx.type_of(hir_id, env)
+                .unwrap_or(UnpackedType::make_error())
+                .into(),
+        ),
+        // The arguments in an `extends` clause are passed to the constructor of
+        // the base class.
+        HirNode::Class(class) => {
+            let (_, ref args) = class.extends.as_ref()?;
+            let this = crate::class::this_type(cx, class.id, env)?;
+            let base = crate::class::base_class(cx, this.get_class()?).ok()??;
+            let method = crate::class::find_constructor(cx, &base).ok()??;
+            type_context_imposed_by_method(cx, onto, method.member.id, method.class.env, args)
+        }
         HirNode::Inst(inst) => {
             let details = cx.inst_details(Ref(inst), env).ok()?;
             details
@@ -2280,6 +2773,13 @@ pub(crate) fn type_context<'a>(
                 .and_then(|param_id| cx.type_of(param_id, details.inner_env).ok())
                 .map(Into::into)
         }
+        HirNode::Coverpoint(cp) if cp.iff == Some(onto) => Some(TypeContext::Bool),
+        HirNode::Gate(g) if !g.kind.is_bidirectional() && g.inputs.contains(&onto) => {
+            Some(UnpackedType::make_logic().into())
+        }
+        HirNode::Covergroup(cg) if cg.crosses.iter().any(|c| c.iff == Some(onto)) => {
+            Some(TypeContext::Bool)
+        }
         _ => None,
     }
 }
@@ -2441,6 +2941,10 @@ fn type_context_imposed_by_expr<'gcx>(
         // The ternary operator imposes a boolean context on its condition.
         hir::ExprKind::Ternary(cond, _, _) if onto == cond => Some(TypeContext::Bool),
 
+        // Casts to `void` discard the value, so the argument is left in its
+        // self-determined type.
+        hir::ExprKind::Cast(..) if cx.need_self_determined_type(expr.id, env).is_void() => None,
+
         // Static casts are *not* assignment-like contexts. See §10.8
         // "Assignment-like contexts". We use a trick here to get the implicit
         // casting logic to do the cast for us: we determine the type of the
@@ -2518,10 +3022,87 @@ fn type_context_imposed_by_expr<'gcx>(
         // Assignments impose their operation type as context.
         hir::ExprKind::Assign { .. } => Some(cx.need_operation_type(expr.id, env).into()),
 
+        // The seed of `$urandom` and the bounds of `$urandom_range` are
+        // unsigned 32 bit integers. The seed of `$random` is a variable and is
+        // left in its self-determined type.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Urandom(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::UrandomRange(..)) => {
+            Some(cx.need_self_determined_type(expr.id, env).into())
+        }
+
+        // The diagnostic level of `$finish` is an integer.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Finish(_)) => Some(
+            PackedType::make(cx, ty::IntAtomType::Int)
+                .to_unpacked(cx)
+                .into(),
+        ),
+
+        // Function calls impose the argument types onto the call arguments.
+        hir::ExprKind::FunctionCall(target, ref args) => {
+            let sub = match cx.hir_of(target).ok()? {
+                HirNode::Subroutine(s) => s,
+                _ => return None,
+            };
+            let (index, call_arg) = args
+                .iter()
+                .enumerate()
+                .find(|(_, arg)| arg.expr == Some(onto))?;
+            let arg = sub.find_arg(call_arg, index)?;
+            let env = crate::class::callee_env(cx, expr.id, target, env).ok()?;
+            Some(type_of_subroutine_arg(cx, arg, env).into())
+        }
+
+        // Class method calls and constructors impose the argument types onto
+        // the call arguments.
+        hir::ExprKind::MethodCall(target, name, ref args) if target != onto => {
+            let class = cx.self_determined_type(target, env)?.get_class()?.clone();
+            let method = find_class_method(cx, &class, name).ok()??;
+            type_context_imposed_by_method(cx, onto, method.member.id, method.class.env, args)
+        }
+        hir::ExprKind::New(ref args) => {
+            let ty = cx.need_type_context(expr.id, env).ty();
+            let class = ty.get_class()?;
+            let method = crate::class::find_constructor(cx, class).ok()??;
+            type_context_imposed_by_method(cx, onto, method.member.id, method.class.env, args)
+        }
+
+        // `let` references impose the argument types onto typed arguments.
+        hir::ExprKind::Let(_, ref bindings) => {
+            let &(arg, _) = bindings.iter().find(|&&(_, actual)| actual == onto)?;
+            match cx.hir_of(arg).ok()? {
+                HirNode::LetArg(a) if a.ty.is_some() => Some(
+                    cx.type_of(arg, env)
+                        .unwrap_or(UnpackedType::make_error())
+                        .into(),
+                ),
+                _ => None,
+            }
+        }
+
         _ => None,
     }
 }
 
+/// Determine the type context a class method imposes on its call arguments.
+fn type_context_imposed_by_method<'gcx>(
+    cx: &impl Context<'gcx>,
+    onto: NodeId,
+    method: NodeId,
+    env: ParamEnv,
+    args: &[hir::CallArg],
+) -> Option<TypeContext<'gcx>> {
+    let sub = match cx.hir_of(method).ok()? {
+        HirNode::Subroutine(s) => s,
+        _ => return None,
+    };
+    let (index, call_arg) = args
+        .iter()
+        .enumerate()
+        .find(|(_, arg)| arg.expr == Some(onto))?;
+    let arg = sub.find_arg(call_arg, index)?;
+    Some(type_of_subroutine_arg(cx, arg, env).into())
+}
+
 // Determine the type context a pattern expression imposes on its arguments.
 // Only call this for `onto` that are strictly outside of potential field index
 // expressions, i.e. not on the left of any `:` in the pattern.
@@ -2585,9 +3166,21 @@ fn type_context_imposed_by_stmt<'gcx>(
             }
         }
 
+        // Return statements impose the return type of the subroutine.
+        hir::StmtKind::Return(Some(expr)) if onto == expr => {
+            let sub = crate::class::enclosing_subroutine(cx, stmt.id)?;
+            match cx.hir_of(sub.id()).ok()? {
+                HirNode::Subroutine(sub) => Some(subroutine_return_type(cx, sub, env).into()),
+                _ => None,
+            }
+        }
+
         // If statements and do/while loops require a boolean condition.
         hir::StmtKind::If { cond, .. } if onto == cond => Some(TypeContext::Bool),
 
+        // Wait statements require a boolean condition.
+        hir::StmtKind::Wait { cond, .. } if onto == cond => Some(TypeContext::Bool),
+
         // Do/while loops require a boolean condition.
         hir::StmtKind::Loop { kind, .. } => {
             match kind {
@@ -2648,6 +3241,10 @@ pub enum CastOp {
     Domain(ty::Domain),
     /// Pick an interface's modport.
     PickModport,
+    /// Convert a class handle to a handle of one of its base classes.
+    Upcast,
+    /// Refer to an interface instance through a virtual interface.
+    Virtual,
     /// Pack a string into an SBVT.
     PackString,
     /// Unpack a string from an SBVT.
@@ -2794,3 +3391,31 @@ fn range_from_bounds_exprs<'a>(
     let offset = lo.to_isize().unwrap();
     Ok(ty::Range { size, dir, offset })
 }
+
+/// Determine the expansion of an expression that refers to a `let` declaration.
+///
+/// Returns the declaration's expression and the environment binding its
+/// arguments, or `None` if the expression is not a `let` reference.
+pub(crate) fn let_expansion<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx hir::Expr<'gcx>,
+    env: ParamEnv,
+) -> Option<Result<(&'gcx hir::Expr<'gcx>, ParamEnv)>> {
+    match expr.kind {
+        hir::ExprKind::Let(..) => (),
+        hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
+            match cx.resolve_node(expr.id, env).and_then(|x| cx.hir_of(x)) {
+                Ok(HirNode::LetDecl(..)) => (),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    }
+    Some(
+        cx.let_env(expr.id, env)
+            .and_then(|(body, env)| match cx.hir_of(body)? {
+                HirNode::Expr(x) => Ok((x, env)),
+                x => bug_span!(x.span(), cx, "let expansion is not an expression"),
+            }),
+    )
+}
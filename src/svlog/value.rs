@@ -154,6 +154,14 @@ pub fn make_int<'a>(ty: &'a UnpackedType<'a>, value: BigInt) -> ValueData<'a> {
     )
 }
 
+/// Create a new `null` class handle.
+pub fn make_null<'a>(ty: &'a UnpackedType<'a>) -> ValueData<'a> {
+    ValueData {
+        ty,
+        kind: ValueKind::Int(BigInt::zero(), BitVec::new(), BitVec::new()),
+    }
+}
+
 /// Create a new integer value with special bits.
 ///
 /// Panics if `ty` is not an integer type. Truncates the value to `ty`.
@@ -179,8 +187,11 @@ pub fn make_time<'a>(value: BigRational) -> ValueData<'a> {
 }
 
 /// Create a new struct value.
+///
+/// Events are represented as a struct of their trigger count and the time of
+/// their last trigger.
 pub fn make_struct<'a>(ty: &'a UnpackedType<'a>, fields: Vec<Value<'a>>) -> ValueData<'a> {
-    assert!(ty.dims().next().is_none() && ty.get_struct().is_some());
+    assert!(ty.dims().next().is_none() && (ty.get_struct().is_some() || ty.is_event()));
     ValueData {
         ty,
         kind: ValueKind::StructOrArray(fields),
@@ -549,10 +560,26 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         }
 
         mir::RvalueKind::Assignment { .. }
+        | mir::RvalueKind::Call { .. }
+        | mir::RvalueKind::CoverSample { .. }
+        | mir::RvalueKind::CoverCoverage(..)
+        | mir::RvalueKind::EventTriggered(..)
+        | mir::RvalueKind::Finish(..)
+        | mir::RvalueKind::SimTime(..)
+        | mir::RvalueKind::Random
+        | mir::RvalueKind::Urandom(..)
+        | mir::RvalueKind::UrandomRange { .. }
+        | mir::RvalueKind::This
+        | mir::RvalueKind::ClassNew(..)
+        | mir::RvalueKind::ClassProperty { .. }
+        | mir::RvalueKind::MethodCall { .. }
+        | mir::RvalueKind::Randomize { .. }
         | mir::RvalueKind::Var(_)
         | mir::RvalueKind::Port(_)
         | mir::RvalueKind::IntfSignal(..)
-        | mir::RvalueKind::Intf(..) => {
+        | mir::RvalueKind::Intf(..)
+        | mir::RvalueKind::Inst(..)
+        | mir::RvalueKind::HierSignal(..) => {
             cx.emit(DiagBuilder2::error("value is not constant").span(mir.span));
             cx.intern_value(make_error(mir.ty))
         }
@@ -896,6 +923,31 @@ pub(crate) fn type_default_value<'a>(cx: &impl Context<'a>, ty: &'a UnpackedType
         });
     }
 
+    // Handle covergroups, which are represented by their hit counters.
+    if let Some(cg) = ty.get_covergroup() {
+        return match cx.cover_model(Ref(cg.ast), cg.env) {
+            Ok(model) => cx.type_default_value(model.state_type(cx)),
+            Err(()) => cx.intern_value(make_error(UnpackedType::make_error())),
+        };
+    }
+
+    // Class handles start out as `null`.
+    if ty.get_class().is_some() {
+        return cx.intern_value(make_null(ty));
+    }
+
+    // Handle events, which have not been triggered yet.
+    if ty.is_event() {
+        let count_ty = ty::PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx);
+        return cx.intern_value(make_struct(
+            ty,
+            vec![
+                cx.intern_value(make_int(count_ty, Zero::zero())),
+                cx.intern_value(make_time(Zero::zero())),
+            ],
+        ));
+    }
+
     // Handle structs.
     if let Some(strukt) = ty.get_struct() {
         let fields = strukt
@@ -911,13 +963,13 @@ pub(crate) fn type_default_value<'a>(cx: &impl Context<'a>, ty: &'a UnpackedType
         let packed = packed;
         match packed.core {
             ty::PackedCore::IntVec(_) if packed.dims.len() <= 1 => {
-                return cx.intern_value(make_int(ty, Zero::zero()));
+                return cx.intern_value(make_null(ty));
             }
             ty::PackedCore::IntAtom(ty::IntAtomType::Time) if packed.dims.is_empty() => {
                 return cx.intern_value(make_time(Zero::zero()));
             }
             ty::PackedCore::IntAtom(_) if packed.dims.is_empty() => {
-                return cx.intern_value(make_int(ty, Zero::zero()));
+                return cx.intern_value(make_null(ty));
             }
             _ => (),
         }
@@ -0,0 +1,151 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Virtual interfaces.
+//!
+//! A virtual interface variable such as `virtual bus_if vif` refers to an
+//! interface instance. We only support the static case, where the variable is
+//! bound to the same instance throughout the simulation, such that the binding
+//! can be resolved during elaboration. Accesses through the variable are then
+//! lowered to accesses of the bound instance.
+
+use crate::crate_prelude::*;
+use crate::hir::{HirNode, Visitor};
+
+/// Determine the interface instance a virtual interface variable is bound to.
+///
+/// The variable is bound by its initializer, or by assignments within the
+/// procedures of the module it is declared in. Returns the expression that
+/// refers to the interface instance. Emits a diagnostic if the variable is
+/// never bound, or bound to different instances.
+#[moore_derive::query]
+pub(crate) fn virtual_intf_binding<'a>(
+    cx: &impl Context<'a>,
+    decl_id: NodeId,
+    env: ParamEnv,
+) -> Result<NodeId> {
+    let decl = match cx.hir_of(decl_id)? {
+        HirNode::VarDecl(x) => x,
+        _ => unreachable!(),
+    };
+
+    // Collect the initializer and all assignments to the variable.
+    let mut bindings = vec![];
+    bindings.extend(decl.init);
+    if let Some(module) = enclosing_module(cx, decl_id) {
+        let hir = cx.hir_of_module(module)?;
+        let mut collector = BindingCollector {
+            cx,
+            env,
+            decl_id,
+            bindings: &mut bindings,
+        };
+        for &id in &hir.block.procs {
+            collector.visit_node_with_id(id, false);
+        }
+    }
+    let binding = match bindings.first() {
+        Some(&x) => x,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "virtual interface `{}` is never bound to an interface instance",
+                    decl.name
+                ))
+                .span(decl.name.span)
+                .add_note(
+                    "Assign an interface instance to the variable in the module it is declared \
+                     in",
+                ),
+            );
+            return Err(());
+        }
+    };
+
+    // Make sure all bindings refer to the same instance.
+    let mut failed = false;
+    let first = cx.mir_rvalue(binding, env);
+    for &id in &bindings {
+        let value = cx.mir_rvalue(id, env);
+        if value.is_error() {
+            failed = true;
+        } else if value.get_intf().is_none() {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: virtual interface `{}` bound to `{}`",
+                    decl.name,
+                    value.span.extract()
+                ))
+                .span(value.span)
+                .add_note("Only interface instances and ports can be bound statically"),
+            );
+            failed = true;
+        } else if value.kind != first.kind {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: virtual interface `{}` bound to different interface instances",
+                    decl.name
+                ))
+                .span(value.span)
+                .add_note(format!("`{}` was previously bound here:", decl.name))
+                .span(first.span),
+            );
+            failed = true;
+            break;
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(binding)
+    }
+}
+
+/// Find the module a node is nested in, if any.
+pub(crate) fn enclosing_module<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+) -> Option<&'a ast::Module<'a>> {
+    let mut node = cx.ast_for_id(node_id);
+    loop {
+        if let Some(module) = node.as_all().get_module() {
+            return Some(module);
+        }
+        node = node.get_parent()?;
+    }
+}
+
+/// A visitor that collects the expressions assigned to a variable.
+struct BindingCollector<'a, 'b, C> {
+    cx: &'a C,
+    env: ParamEnv,
+    decl_id: NodeId,
+    bindings: &'b mut Vec<NodeId>,
+}
+
+impl<'a, 'b, 'gcx: 'a, C> Visitor<'gcx> for BindingCollector<'a, 'b, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        if let hir::StmtKind::Assign { lhs, rhs, .. } = stmt.kind {
+            let is_ident = match self.cx.hir_of(lhs) {
+                Ok(HirNode::Expr(hir::Expr {
+                    kind: hir::ExprKind::Ident(..),
+                    ..
+                })) => true,
+                _ => false,
+            };
+            if is_ident && self.cx.resolve_node(lhs, self.env) == Ok(self.decl_id) {
+                self.bindings.push(rhs);
+            }
+        }
+        hir::walk_stmt(self, stmt);
+    }
+
+    fn visit_var_decl(&mut self, _: &'gcx hir::VarDecl) {}
+}
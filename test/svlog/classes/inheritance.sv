@@ -0,0 +1,69 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §8 "Classes"
+
+class Base;
+    int x = 5;
+    static int count;
+    function new(int init);
+        x = init;
+        count = count + 1;
+    endfunction
+    virtual function int get();
+        return x;
+    endfunction
+    function void set(int v);
+        this.x = v;
+    endfunction
+endclass
+
+class Derived extends Base(7);
+    logic [7:0] y;
+    function new();
+        y = 8'd3;
+    endfunction
+    virtual function int get();
+        return super.get() + y;
+    endfunction
+endclass
+
+module foo;
+    initial begin
+        Base b;
+        Derived d;
+        int r;
+        d = new;
+        b = d;
+        r = b.get();
+        b.set(r);
+        if (b != null) r = b.x;
+    end
+endmodule
+
+// CHECK: func @Base.get (i64 %this) i32 {
+// CHECK:     %1 = call i32 @moore.class.get.Base.x (i64 %this)
+// CHECK:     ret i32 %1
+// CHECK: func @Derived.get (i64 %this) i32 {
+// CHECK:     %1 = call i32 @Base.get (i64 %this)
+// CHECK:     %3 = call i8 @moore.class.get.Derived.y (i64 %this)
+// CHECK: func @Base.new (i64 %this, i32 %init) void {
+// CHECK:     call void @moore.class.set.Base.x (i64 %this, i32 %1)
+// CHECK:     call void @moore.class.set.Base.x (i64 %this, i32 %init.var.ld)
+// CHECK:     %3 = call i32 @moore.class.get.Base.count (i64 %2)
+// CHECK: func @Derived.new (i64 %this) void {
+// CHECK:     %1 = const i32 7
+// CHECK:     call void @Base.new (i64 %this, i32 %1)
+// CHECK:     call void @moore.class.set.Derived.y (i64 %this, i8 %2)
+// CHECK: func @Base.set (i64 %this, i32 %v) void {
+// CHECK: proc %foo.initial.236.0 () -> () {
+// CHECK:     %5 = call i64 @moore.class.new (i32 %4)
+// CHECK:     call void @Derived.new (i64 %5)
+// CHECK:     %6 = call i32 @Base.get.virtual (i64 %b.ld)
+// CHECK:     call void @Base.set (i64 %b.ld1, i32 %r.ld)
+// CHECK:     %8 = neq i64 %b.ld2, %7
+// CHECK:     %11 = call i32 @moore.class.get.Base.x (i64 %b.ld3)
+// CHECK: func @Base.get.virtual (i64 %this) i32 {
+// CHECK:     %1 = call i32 @moore.class.id (i64 %this)
+// CHECK:     %3 = eq i32 %1, %2
+// CHECK:     %6 = call i32 @Derived.get (i64 %this)
+// CHECK:     %7 = call i32 @Base.get (i64 %this)
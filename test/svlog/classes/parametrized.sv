@@ -0,0 +1,37 @@
+// RUN: moore %s -e foo
+
+class Box #(type T = int, int N = 2);
+    T data;
+    static function int width();
+        return N;
+    endfunction
+    function T get();
+        return data;
+    endfunction
+endclass
+
+module foo;
+    initial begin
+        Box #(logic [7:0], 4) b;
+        Box c;
+        int w;
+        logic [7:0] v;
+        b = new;
+        c = new();
+        v = b.get();
+        w = b.width() + c.get() + Box::width();
+    end
+endmodule
+
+// CHECK: func @Box.param4.new (i64 %this) void {
+// CHECK:     call void @moore.class.set.Box.param4.data (i64 %this, i8 %1)
+// CHECK: func @Box.param2.new (i64 %this) void {
+// CHECK:     call void @moore.class.set.Box.param2.data (i64 %this, i32 %1)
+// CHECK: func @Box.param4.get (i64 %this) i8 {
+// CHECK: func @Box.param4.width () i32 {
+// CHECK:     %1 = const i32 4
+// CHECK: func @Box.param2.width () i32 {
+// CHECK:     %1 = const i32 2
+// CHECK:     %9 = call i8 @Box.param4.get (i64 %b.ld)
+// CHECK:     %10 = call i32 @Box.param4.width ()
+// CHECK:     %13 = call i32 @Box.param2.width ()
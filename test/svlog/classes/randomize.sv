@@ -0,0 +1,35 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §18 "Constrained random value generation"
+
+class Packet;
+    rand bit [7:0] addr;
+    rand bit [3:0] len;
+    randc bit [1:0] kind;
+    constraint c_addr { addr inside {[8'h10:8'h1f], 8'h40}; }
+    constraint c_len { addr < 8'h30 -> len != 0; }
+    constraint c_order { solve addr before len; }
+    function void post_randomize(); endfunction
+endclass
+
+module foo;
+    initial begin
+        Packet p;
+        int ok;
+        p = new;
+        ok = p.randomize() with { len == 2; };
+    end
+endmodule
+
+// CHECK:     %7 = call i8 @moore.class.get.Packet.addr (i64 %p.ld)
+// CHECK:     %10 = const i32 1000
+// CHECK:     br %15, %randomize_draw, %randomize_failure
+// CHECK: randomize_success:
+// CHECK:     call void @Packet.post_randomize (i64 %p.ld1)
+// CHECK: randomize_failure:
+// CHECK:     call void @moore.class.set.Packet.addr (i64 %p.ld, i8 %7)
+// CHECK:     %21 = call i64 @moore.rand.below (i32 %19, i64 %20)
+// CHECK:     %29 = mux [2 x i64] %28, i1 %27
+// CHECK:     call void @moore.class.set.Packet.addr (i64 %p.ld, i8 %30)
+// CHECK:     %41 = call i64 @moore.rand.cyclic.Packet.kind (i32 %39, i64 %p.ld, i64 %40)
+// CHECK:     br %57, %randomize_try1, %randomize_success
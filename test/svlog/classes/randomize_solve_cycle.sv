@@ -0,0 +1,17 @@
+// RUN: moore %s -e foo
+// FAIL
+
+class A;
+    rand bit [3:0] a, b;
+    constraint c { solve a before b; solve b before a; }
+endclass
+
+module foo;
+    initial begin
+        A x;
+        x = new;
+        void'(x.randomize());
+    end
+endmodule
+
+// CHECK-ERR: circular `solve ... before` constraints
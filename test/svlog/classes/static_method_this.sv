@@ -0,0 +1,16 @@
+// RUN: moore %s -e foo
+// FAIL
+
+class A;
+    int x;
+    static function int get();
+        return x;
+    endfunction
+endclass
+
+module foo;
+    int y;
+    initial y = A::get();
+endmodule
+
+// CHECK-ERR: non-static property `x` used in a static method
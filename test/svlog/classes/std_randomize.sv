@@ -0,0 +1,21 @@
+// RUN: moore %s -e foo
+
+module foo;
+    initial begin
+        int x, y, ok;
+        ok = std::randomize(x, y) with { x < y; x > 0; y < 10; };
+    end
+endmodule
+
+// CHECK:     %x.ld = ld i32* %x
+// CHECK:     %6 = var i32 %x.ld
+// CHECK:     st i32* %ok, %10
+// CHECK: randomize_success:
+// CHECK:     st i32* %x, %13
+// CHECK:     %17 = const i64 8
+// CHECK:     %18 = call i64 @moore.rand.below (i32 %16, i64 %17)
+// CHECK:     %19 = const i64 1
+// CHECK:     %23 = const i64 8
+// CHECK:     %25 = const i64 2
+// CHECK:     %31 = slt i32 %29, %30
+// CHECK:     br %32, %randomize_try, %randomize_success
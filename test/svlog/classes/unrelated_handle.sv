@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+// FAIL
+
+class A;
+endclass
+
+class B;
+endclass
+
+module foo;
+    initial begin
+        A a;
+        B b;
+        a = b;
+    end
+endmodule
+
+// CHECK-ERR: cannot assign a handle of class `B` to a handle of class `A`
@@ -0,0 +1,36 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §19 "Functional coverage"
+
+module foo (input logic clk, input logic [1:0] a, input logic b);
+    covergroup cg @(posedge clk);
+        cp_a: coverpoint a {
+            bins low = {0, 1};
+            bins high = {[2:3]};
+        }
+        coverpoint b;
+        axb: cross cp_a, b;
+    endgroup
+    cg cg_inst = new;
+    int pct;
+    always @(negedge clk) begin
+        cg_inst.sample();
+        pct = cg_inst.get_coverage();
+    end
+endmodule
+
+// CHECK: proc %foo.cover.40.0 (i1$ %clk, i2$ %a, i1$ %b) -> ([8 x i32]$ %cg_inst) {
+// CHECK:     %counters = var [8 x i32] %1
+// CHECK:     %cp_a.low = and i1 %13, %5
+// CHECK:     %cp_a.high = and i1 %18, %5
+// CHECK:     %25 = and i1 %23, %b.auto\5b0\5d
+// CHECK:     %54 = [i32 %32, %35, %38, %41, %44, %47, %50, %53]
+// CHECK:     st [8 x i32]* %counters, %54
+// CHECK:     drv [8 x i32]$ %cg_inst, %54, %55
+// CHECK: proc %foo.always.88.0 (i1$ %clk, i2$ %a, i1$ %b) -> ([8 x i32]$ %cg_inst, i32$ %pct) {
+// CHECK:     drv [8 x i32]$ %cg_inst, %54, %55
+// CHECK:     %coverage = udiv i32 %101, %102
+// CHECK:     drv i32$ %pct, %coverage, %103
+// CHECK: entity @foo (i1$ %clk, i2$ %a, i1$ %b) -> () {
+// CHECK:     %cg_inst = sig [8 x i32] %8
+// CHECK:     inst %foo.cover.40.0 (i1$ %clk, i2$ %a, i1$ %b) -> ([8 x i32]$ %cg_inst)
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input logic clk, input logic [1:0] a);
+    covergroup cg @(posedge clk);
+        cp_a: coverpoint a;
+        cross cp_a, cp_b;
+    endgroup
+    cg cg_inst = new;
+endmodule
+
+// CHECK-ERR: `cp_b` is not a coverpoint of covergroup `cg`
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input logic clk, input logic [1:0] a);
+    covergroup cg;
+        coverpoint a;
+    endgroup
+    cg cg_inst = new;
+    always @(posedge clk) cg_inst.start();
+endmodule
+
+// CHECK-ERR: covergroup `cg` has no method `start`
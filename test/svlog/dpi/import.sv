@@ -0,0 +1,29 @@
+// RUN: moore %s -e foo
+
+import "DPI-C" function int add(int a, int b);
+import "DPI-C" c_log = function void log_value(input bit [7:0] value);
+
+module foo;
+    import "DPI-C" pure function longint scale(longint x, shortint factor);
+    import "DPI-C" function bit [3:0] nibble(bit [11:0] x, logic signed [4:0] y);
+    int x, y;
+    bit [3:0] n;
+    longint z;
+    initial begin
+        x = add(1, 2);
+        y = add(.b(x), .a(3));
+        log_value(x);
+        z = scale(x, 4);
+        n = nibble(12'h123, -5'sd3);
+    end
+endmodule
+
+// CHECK: declare @add (i32, i32) i32
+// CHECK: declare @c_log (i8) void
+// CHECK: declare @scale (i64, i16) i64
+// CHECK: declare @nibble (i16, i8) i8
+// CHECK: call i32 @add (i32 %2, i32 %3)
+// CHECK: call void @c_log (i8 %9)
+// CHECK: %20 = inss i16 %19, i12 %18, 0, 12
+// CHECK: %28 = call i8 @nibble (i16 %20, i8 %27)
+// CHECK: %29 = exts i4, i8 %28, 0, 4
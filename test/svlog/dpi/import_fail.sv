@@ -0,0 +1,21 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    import "DPI-C" function void f(string s);
+    import "DPI-C" function void g(output int x);
+    import "DPI-C" function int h(int a);
+    int x;
+    initial begin
+        f("hello");
+        g(x);
+        x = h(1, 2);
+    end
+    export "DPI-C" task bar;
+    function void bar; endfunction
+endmodule
+
+// CHECK-ERR: type `string` cannot cross the DPI boundary of `f`
+// CHECK-ERR: argument `x` of DPI import `g` is not an input
+// CHECK-ERR: too many arguments to function `h`; expected 1
+// CHECK-ERR: `bar` is a function, but exported as a task
@@ -0,0 +1,57 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §28 "Gate-level and switch-level modeling"
+
+module foo (input a, b, c, en, output y, z, t);
+    wire w;
+    and g0 (w, a, b, c);
+    nand #1ns g1 (y, w, a);
+    xnor (z, a, b);
+    bufif1 (t, a, en);
+    notif0 (t, b, en);
+    cmos (t, a, en, b);
+    pulldown (t);
+    wire od;
+    bufif1 (strong0, highz1) (od, a, en);
+    pullup (od);
+endmodule
+
+// CHECK: entity @foo (i1$ %a, i1$ %b, i1$ %c, i1$ %en) -> (i1$ %y, i1$ %z, i1$ %t) {
+// CHECK:     %2 = and i1 %a.prb, %b.prb
+// CHECK:     %3 = and i1 %2, %c.prb
+// CHECK:     drv i1$ %w, %3, %4
+// CHECK:     %5 = and i1 %w.prb, %a.prb1
+// CHECK:     %6 = not i1 %5
+// CHECK:     %7 = const time 1ns
+// CHECK:     drv i1$ %y, %6, %7
+// CHECK:     %8 = xor i1 %a.prb2, %b.prb1
+// CHECK:     %9 = not i1 %8
+// CHECK:     drv i1$ %z, %9, %10
+// CHECK:     %t.drv0 = sig i1 %12
+// CHECK:     %15 = mux [2 x i1] %14, i1 %en.prb
+// CHECK:     drv i1$ %t.drv0, %15, %11
+// CHECK:     %16 = not i1 %en.prb1
+// CHECK:     %17 = not i1 %b.prb2
+// CHECK:     %t.drv1 = sig i1 %19
+// CHECK:     %22 = mux [2 x i1] %21, i1 %16
+// CHECK:     drv i1$ %t.drv1, %22, %18
+// CHECK:     %23 = not i1 %b.prb3
+// CHECK:     %24 = or i1 %en.prb2, %23
+// CHECK:     %t.drv2 = sig i1 %26
+// CHECK:     %29 = mux [2 x i1] %28, i1 %24
+// CHECK:     drv i1$ %t.drv2, %29, %25
+// CHECK:     %t.drv3 = sig i1 %32
+// CHECK:     drv i1$ %t.drv3, %30, %31
+// CHECK:     %33 = not i1 %a.prb5
+// CHECK:     %34 = and i1 %en.prb3, %33
+// CHECK:     %od.drv0 = sig i1 %36
+// CHECK:     %39 = mux [2 x i1] %38, i1 %34
+// CHECK:     drv i1$ %od.drv0, %39, %35
+// CHECK:     %od.drv1 = sig i1 %42
+// CHECK:     drv i1$ %od.drv1, %40, %41
+// CHECK:     %45 = or i1 %43, %44
+// CHECK:     %47 = or i1 %45, %46
+// CHECK:     %49 = or i1 %47, %48
+// CHECK:     drv i1$ %t, %49, %50
+// CHECK:     %53 = and i1 %51, %52
+// CHECK:     drv i1$ %od, %53, %54
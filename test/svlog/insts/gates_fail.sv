@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input a, en, output t);
+    bufif1 (t, a);
+endmodule
+
+// CHECK-ERR: `bufif1` requires 3 terminals, but 2 given
@@ -0,0 +1,10 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input a, en);
+    tri0 t;
+    bufif1 (t, a, en);
+    pullup (t);
+endmodule
+
+// CHECK-ERR: unsupported: `pullup` driving `tri0` net `t`
@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input a, b, output y);
+    and (weak0, weak1) (y, a, b);
+endmodule
+
+// CHECK-ERR: unsupported: `weak0` strength of `and`
@@ -0,0 +1,30 @@
+// RUN: moore %s -e tb
+
+module core (input logic clk);
+    int pc_q;
+    bit halted;
+    always_ff @(posedge clk) pc_q <= pc_q + 1;
+endmodule
+
+module dut (input logic clk);
+    core u_core(clk);
+endmodule
+
+module tb;
+    logic clk;
+    int pc;
+    dut d(clk);
+    initial begin
+        pc = d.u_core.pc_q;
+        d.u_core.halted = 1;
+    end
+endmodule
+
+// CHECK: entity @core.param2.hier0 (i1$ %clk) -> (i32$ %pc_q, i1$ %halted) {
+// CHECK: entity @dut.param1.hier0 (i1$ %clk) -> (i32$ %u_core.pc_q, i1$ %u_core.halted) {
+// CHECK:     inst @core.param2.hier0 (i1$ %clk) -> (i32$ %u_core.pc_q, i1$ %u_core.halted)
+// CHECK: proc %tb.initial.119.0 (i32$ %d.u_core.pc_q) -> (i32$ %pc, i1$ %d.u_core.halted) {
+// CHECK:     %d.u_core.pc_q.prb = prb i32$ %d.u_core.pc_q
+// CHECK:     drv i1$ %d.u_core.halted,
+// CHECK: entity @tb () -> () {
+// CHECK:     inst @dut.param1.hier0 (i1$ %clk) -> (i32$ %d.u_core.pc_q, i1$ %d.u_core.halted)
@@ -0,0 +1,16 @@
+// RUN: moore %s -e tb
+// FAIL
+
+module core;
+    int pc_q;
+endmodule
+
+module tb;
+    int pc;
+    core c();
+    initial begin
+        pc = c.pc_d;
+    end
+endmodule
+
+// CHECK-ERR: `pc_d` not found
@@ -0,0 +1,29 @@
+// RUN: moore %s -e tb
+
+module core;
+    int pc_q;
+    bit halted;
+endmodule
+
+module dut;
+    core u_core();
+endmodule
+
+module tb;
+    int pc, count;
+    core c();
+    dut d();
+    initial begin
+        pc = tb.c.pc_q;
+        tb.d.u_core.halted = 1;
+        tb.count = pc;
+    end
+endmodule
+
+// CHECK: entity @dut.param2.hier0 () -> (i1$ %u_core.halted) {
+// CHECK: proc %tb.initial.111.0 (i32$ %c.pc_q) -> (i32$ %pc, i32$ %count, i1$ %d.u_core.halted) {
+// CHECK:     %c.pc_q.prb = prb i32$ %c.pc_q
+// CHECK:     drv i1$ %d.u_core.halted, %3, %4
+// CHECK:     drv i32$ %count, %pc.shadow.ld, %5
+// CHECK:     inst @core.param1.hier0 () -> (i32$ %c.pc_q)
+// CHECK:     inst @dut.param2.hier0 () -> (i1$ %d.u_core.halted)
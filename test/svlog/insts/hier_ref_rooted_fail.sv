@@ -0,0 +1,15 @@
+// RUN: moore %s -e tb
+// FAIL
+
+module core;
+    int pc_q;
+    int pc;
+    initial pc = tb.c.pc_q;
+endmodule
+
+module tb;
+    core c();
+endmodule
+
+// CHECK-ERR: error: unsupported: hierarchical reference rooted at module `tb`
+// CHECK-ERR: = note: Hierarchical references must start at an instance within `core`, or at `core` itself
@@ -0,0 +1,31 @@
+// RUN: moore %s -e foo -O0
+
+module foo;
+  bar x();
+  virtual bar vif;
+  task send(input logic [31:0] d);
+    vif.data <= d;
+    vif.valid <= 1;
+  endtask
+  initial begin
+    vif = x;
+    send(42);
+  end
+endmodule
+
+interface bar;
+  logic [31:0] data;
+  logic valid;
+endinterface
+
+// CHECK: proc %foo.initial.85.0 () -> (i32$ %x.data, i1$ %x.valid) {
+// CHECK:     %send.d = var i32 %1
+// CHECK:     drv i32$ %x.data, %send.d.ld, %2
+// CHECK:     drv i1$ %x.valid, %3, %4
+// CHECK: }
+
+// CHECK: entity @foo () -> () {
+// CHECK:     %x.data = sig i32 %0
+// CHECK:     %x.valid = sig i1 %1
+// CHECK:     inst %foo.initial.85.0 () -> (i32$ %x.data, i1$ %x.valid)
+// CHECK: }
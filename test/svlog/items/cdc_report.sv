@@ -0,0 +1,55 @@
+// RUN: moore %s -e top --cdc-report -
+
+module top (input logic clk_a, clk_b, rst_n, input logic [7:0] d, output logic [7:0] q, r, output logic f);
+    logic [7:0] data_q;
+    logic flag_q;
+    tx i_tx (.clk(clk_a), .rst_n, .d, .data_q, .flag_q);
+    rx i_rx (.clk(clk_b), .data_q, .flag_q, .q, .r, .f);
+endmodule
+
+module tx (input logic clk, rst_n, input logic [7:0] d, output logic [7:0] data_q, output logic flag_q);
+    always_ff @(posedge clk or negedge rst_n) begin
+        if (!rst_n) begin
+            data_q <= 0;
+            flag_q <= 0;
+        end else begin
+            data_q <= d;
+            flag_q <= ~flag_q;
+        end
+    end
+endmodule
+
+module rx (input logic clk, input logic [7:0] data_q, input logic flag_q, output logic [7:0] q, r, output logic f);
+    // Two-flop synchronizer.
+    logic sync_q, sync2_q, prev_q;
+    always_ff @(posedge clk) begin
+        sync_q <= flag_q;
+        sync2_q <= sync_q;
+        prev_q <= sync2_q;
+    end
+    assign f = sync2_q;
+
+    // Data sampled when the synchronized flag toggles.
+    always_ff @(posedge clk) if (sync2_q != prev_q) r <= data_q;
+
+    // Data sampled without synchronization.
+    always_ff @(posedge clk) q <= data_q + 1;
+endmodule
+
+// CHECK: Clock domains:
+// CHECK:   top.clk_a
+// CHECK:     top.i_tx.data_q
+// CHECK:     top.i_tx.flag_q
+// CHECK:   top.clk_b
+// CHECK:     top.i_rx.prev_q
+// CHECK:     top.i_rx.q
+// CHECK:     top.i_rx.r
+// CHECK:     top.i_rx.sync2_q
+// CHECK:     top.i_rx.sync_q
+// CHECK: Crossings:
+// CHECK:   top.i_tx.flag_q (top.clk_a) -> top.i_rx.sync_q (top.clk_b): synchronized
+// CHECK:   top.i_tx.data_q (top.clk_a) -> top.i_rx.r (top.clk_b): synchronized enable
+// CHECK:   top.i_tx.data_q (top.clk_a) -> top.i_rx.q (top.clk_b): unsynchronized
+
+// CHECK-ERR: warning: `top.i_rx.q` in clock domain `top.clk_b` samples `top.i_tx.data_q` from clock domain `top.clk_a` without a synchronizer
+// CHECK-ERR: = note: Source register assigned here:
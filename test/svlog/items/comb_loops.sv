@@ -0,0 +1,49 @@
+// RUN: moore %s -e foo
+
+module foo (input logic a, b, output logic [5:0] z);
+    // Loop through a continuous assignment and a procedure.
+    logic x, y;
+    assign x = y & a;
+    always_comb y = x | b;
+
+    // Loop through primitives.
+    wire q, qn;
+    nor n0 (q, a, qn);
+    nor n1 (qn, b, q);
+
+    // Loop through a single assignment.
+    wire w;
+    assign w = w & a;
+
+    // Distinct bits and fields do not form loops.
+    logic [3:0] c;
+    assign c[0] = a;
+    for (genvar i = 0; i < 3; i++) begin
+        assign c[i+1] = c[i] ^ b;
+    end
+    struct packed { logic u, v; } s;
+    assign s.u = s.v;
+    assign s.v = a;
+
+    // Variables in a procedure only depend on their assignments.
+    logic t, u, v;
+    always_comb begin
+        t = a;
+        t = t | b;
+        u = t;
+        if (v) t = 0;
+    end
+    assign v = u;
+
+    // Flip-flops break loops.
+    logic p;
+    always_ff @(posedge a) p <= p ^ b;
+
+    assign z = {c[3], s.u, t, x, p, q};
+endmodule
+
+// CHECK-ERR: warning: combinational loop through variable `x` and variable `y`
+// CHECK-ERR: = note: Continues through `always_comb` procedure here:
+// CHECK-ERR: warning: combinational loop through net `w`
+// CHECK-ERR: warning: combinational loop through net `q` and net `qn`
+// CHECK-ERR: = note: Continues through `nor` instance `n1` here:
@@ -0,0 +1,23 @@
+// RUN: moore %s -e foo -Wundriven -Wunread -Wunconnected
+
+module foo (input logic a, b, output z);
+    wire n;
+    logic u;
+    tri0 p;
+    assign u = n;
+    bar i0 (.x(a), .y(p));
+    for (genvar i = 0; i < 2; i++) begin : g
+        logic t;
+        assign t = b;
+    end
+endmodule
+
+module bar (input x, y, k, output o);
+    assign o = x & y & k;
+endmodule
+
+// CHECK-ERR: warning: input `k` of instance `i0` is unconnected
+// CHECK-ERR: warning: port `z` is never driven
+// CHECK-ERR: warning: net `n` is never driven
+// CHECK-ERR: warning: variable `u` is never read
+// CHECK-ERR: warning: variable `t` is never read
@@ -0,0 +1,24 @@
+// RUN: moore %s -e top
+
+// See IEEE 1800-2017 §6.10 "Implicit declarations"
+
+module foo (input logic a, output logic z);
+    assign z = a;
+endmodule
+
+`default_nettype tri
+module top (input logic a, output logic y);
+    foo i0 (a, n0);
+    foo i1 (.a(n0), .z(n1));
+    assign y = n1;
+    assign n2 = a;
+endmodule
+`default_nettype wire
+
+// CHECK: entity @top (i1$ %a) -> (i1$ %y) {
+// CHECK:     %n0 = sig i1 %0
+// CHECK:     %n1 = sig i1 %1
+// CHECK:     %n2 = sig i1 %2
+// CHECK:     drv i1$ %n2, %a.prb, %4
+// CHECK:     inst @foo.param1 (i1$ %a) -> (i1$ %n0)
+// CHECK:     inst @foo.param1 (i1$ %n0) -> (i1$ %n1)
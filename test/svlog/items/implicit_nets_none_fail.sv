@@ -0,0 +1,14 @@
+// RUN: moore %s -e top
+// FAIL
+
+`default_nettype none
+module foo (input logic a, output logic z);
+    assign z = a;
+endmodule
+
+module top (input logic a);
+    foo i0 (a, n0);
+endmodule
+`default_nettype wire
+
+// CHECK-ERR: `n0` not declared, and `default_nettype none forbids implicit nets
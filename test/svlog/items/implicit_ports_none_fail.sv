@@ -0,0 +1,9 @@
+// RUN: moore %s -e foo
+// FAIL
+
+`default_nettype none
+module foo (input logic a, input b);
+endmodule
+`default_nettype wire
+
+// CHECK-ERR: port `b` has no net type, but `default_nettype none is in effect
@@ -0,0 +1,26 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §11.12 "Let construct"
+
+package pkg;
+    let max(a, b) = a > b ? a : b;
+endpackage
+
+module foo;
+    bit valid, ready;
+    let valid_and_ready = valid && ready;
+    let inc(logic [7:0] x, int step = 1) = x + step;
+    logic [7:0] a, b, c, d;
+    bit q;
+    assign q = valid_and_ready;
+    assign b = inc(a);
+    assign c = inc(.x(a), .step(2));
+    assign d = pkg::max(a, b);
+endmodule
+
+// CHECK: entity @foo () -> () {
+// CHECK:     %11 = and i1 %8, %10
+// CHECK:     drv i1$ %q, %11, %12
+// CHECK:     %15 = add i32 %zext, %14
+// CHECK:     %20 = add i32 %zext1, %19
+// CHECK:     %23 = ugt i8 %a.prb2, %b.prb
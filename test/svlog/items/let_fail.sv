@@ -0,0 +1,10 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    logic [7:0] a, b;
+    let add(x, y) = x + y;
+    assign b = add(a);
+endmodule
+
+// CHECK-ERR: missing argument `y` in reference to let declaration `add`
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+
+module foo (input logic a, b);
+    logic y;
+    int x;
+    assign y = a;
+    assign y = b;
+    bar i0 (x);
+    bar i1 (x);
+endmodule
+
+module bar (output int k);
+endmodule
+
+// CHECK-ERR: warning: variable `y` has multiple drivers
+// CHECK-ERR: = note: Also driven by continuous assignment here:
+// CHECK-ERR: warning: variable `x` has multiple drivers
+// CHECK-ERR: = note: Reported by lint `multiple-drivers`, which can be disabled with `-W no-multiple-drivers` or `// moore: allow(multiple-drivers)`
@@ -0,0 +1,23 @@
+// RUN: moore %s -e foo -Werror=multiple-drivers
+// FAIL
+
+module foo (input logic a, b, c, output logic [3:0] y);
+    logic [3:0] v;
+    logic [1:0] w;
+    int x;
+    assign v[1:0] = {a, b};
+    assign v[3:2] = {b, a};
+    assign y = v;
+    assign y[0] = c;
+    assign w = {a, b};
+    always_comb w[0] = c;
+    bar i0 (x);
+    bar i1 (x);
+endmodule
+
+module bar (output int k);
+endmodule
+
+// CHECK-ERR: error: port `y` has multiple drivers
+// CHECK-ERR: error: variable `w` has multiple drivers
+// CHECK-ERR: error: variable `x` has multiple drivers
@@ -0,0 +1,35 @@
+// RUN: moore %s -e top
+
+module foo (output logic y);
+    assign y = 1;
+endmodule
+
+module top (input logic a, b, en);
+    wand wa;
+    wor wo;
+    tri1 t1;
+    supply0 gnd;
+    tri bus;
+    assign wa = a;
+    assign wa = b;
+    assign wo = en ? a : 'z;
+    foo i0 (wo);
+    assign t1 = en ? a : 1'bz;
+    assign gnd = a;
+    assign bus = en ? a : 'z;
+    assign bus = en ? 'z : b;
+endmodule
+
+// CHECK: %t1 = sig i1 %3
+// CHECK: drv i1$ %wa.drv0, %a.prb, %6
+// CHECK: drv i1$ %wa.drv1, %b.prb, %8
+// CHECK: %16 = mux [2 x i1] %15, i1 %11
+// CHECK: drv i1$ %wo.drv0, %16, %12
+// CHECK: drv i1$ %bus if %26, %a.prb4, %27
+// CHECK: drv i1$ %bus if %30, %b.prb1, %31
+// CHECK: inst @foo.param1 () -> (i1$ %wo.drv1)
+// CHECK: %35 = and i1 %33, %34
+// CHECK: drv i1$ %wa, %35, %36
+// CHECK: %39 = or i1 %37, %38
+// CHECK: drv i1$ %wo, %39, %40
+// CHECK: drv i1$ %t1, %41, %42
@@ -0,0 +1,29 @@
+// RUN: moore %s -e top
+
+`timescale 1ns/1ps
+module top;
+    time t;
+    logic a;
+    foo i0 (a);
+    initial begin
+        #5 a = 1;
+        #1.5ps a = 0;
+        a <= #3 1;
+        t = $time;
+    end
+endmodule
+`timescale 1us/10ns
+
+module foo (output logic a);
+    timeunit 10ps;
+    timeprecision 10ps;
+    initial #5 a = 1;
+    initial #1.234ns a = 0;
+endmodule
+
+// CHECK: const time 50ps
+// CHECK: const time 1.230ns
+// CHECK: const time 5ns
+// CHECK: const time 2ps
+// CHECK: const time 3ns
+// CHECK: call time @moore.time
@@ -0,0 +1,11 @@
+// RUN: moore %s -e top
+// FAIL
+
+module top;
+    timeunit 1ns;
+    timeprecision 10ns;
+    logic a;
+    initial #1 a = 1;
+endmodule
+
+// CHECK-ERR: time precision 10ns is coarser than time unit 1ns
@@ -0,0 +1,10 @@
+// RUN: moore %s -e top
+// FAIL
+
+module top (input logic a, b);
+    uwire u;
+    assign u = a;
+    assign u = b;
+endmodule
+
+// CHECK-ERR: `uwire` net `u` has multiple drivers
@@ -0,0 +1,19 @@
+// RUN: moore %s -e foo
+
+// See IEEE 1800-2017 §17 "Checkers"
+
+checker stable_check (logic [7:0] sig, bit clk);
+    logic [7:0] prev;
+    let changed = sig != prev;
+    always_ff @(posedge clk) if (changed) prev <= sig;
+endchecker : stable_check
+
+module foo;
+    bit clk;
+    logic [7:0] data;
+    stable_check c(data, clk);
+endmodule
+
+// CHECK: entity @stable_check.param1 (i8$ %sig, i1$ %clk) -> () {
+// CHECK: entity @foo () -> () {
+// CHECK:     inst @stable_check.param1 (i8$ %data, i1$ %clk) -> ()
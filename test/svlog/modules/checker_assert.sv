@@ -0,0 +1,20 @@
+// RUN: moore %s -e foo
+
+// Concurrent assertions in checkers are not supported yet.
+
+checker stable_check (logic [7:0] sig, bit clk);
+    logic [7:0] prev;
+    always_ff @(posedge clk) prev <= sig;
+    assert property (@(posedge clk) sig == prev);
+endchecker
+
+module foo;
+    bit clk;
+    logic [7:0] data;
+    stable_check c(data, clk);
+endmodule
+
+// CHECK: entity @stable_check.param1 (i8$ %sig, i1$ %clk) -> () {
+// CHECK-ERR: warning: unsupported: concurrent assertion; ignored
+// CHECK-ERR: --> checker_assert.sv:8:5-50:
+// CHECK-ERR: = note: Only the variables and procedures of a checker are elaborated; its assertions are not checked
@@ -0,0 +1,14 @@
+// RUN: moore %s -e foo
+// FAIL
+
+checker bad_check (bit clk);
+    bit x;
+    always @(posedge clk) x = ~x;
+endchecker
+
+module foo;
+    bit clk;
+    bad_check c(clk);
+endmodule
+
+// CHECK-ERR: `always` procedure in checker
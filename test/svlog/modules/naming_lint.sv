@@ -0,0 +1,30 @@
+// RUN: moore %s -e foo_top -Wnaming
+
+module foo_top #(parameter int WIDTH = 8, parameter int Depth = 2) (
+    input  logic clk_i, rst_ni, data,
+    output logic [Depth-1:0] q_o, out
+);
+    typedef logic [Depth-1:0] word_t;
+    typedef logic [Depth-1:0] word;
+    localparam int unsigned NumWords = 1;
+    word_t data_q, data_r;
+    always_ff @(posedge clk_i or negedge rst_ni) begin
+        if (!rst_ni) begin
+            data_q <= '0;
+            data_r <= '0;
+        end else begin
+            data_q <= {Depth{data}};
+            data_r <= data_q;
+        end
+    end
+    assign q_o = data_q;
+    assign out = data_r;
+endmodule
+
+// CHECK-ERR: warning: input `data` does not follow the naming convention
+// CHECK-ERR: = note: Names of input ports must match `[a-z][a-z0-9_]*_n?i`
+// CHECK-ERR: warning: output `out` does not follow the naming convention
+// CHECK-ERR: warning: parameter `WIDTH` does not follow the naming convention
+// CHECK-ERR: = note: Names of parameters must match `[A-Z]([a-z0-9]+[A-Z]?)*`
+// CHECK-ERR: warning: type `word` does not follow the naming convention
+// CHECK-ERR: warning: register `data_r` does not follow the naming convention
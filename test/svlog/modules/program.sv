@@ -0,0 +1,24 @@
+// RUN: moore %s -e tb
+
+// See IEEE 1800-2017 §24 "Programs"
+
+program test (input bit clk, output int count);
+    initial begin
+        count = 0;
+        repeat (4) begin
+            @(posedge clk);
+            count = count + 1;
+        end
+    end
+endprogram : test
+
+module tb;
+    bit clk;
+    int count;
+    test t(clk, count);
+    initial repeat (8) #1ns clk = ~clk;
+endmodule
+
+// CHECK: entity @test.param1 (i1$ %clk) -> (i32$ %count) {
+// CHECK: entity @tb () -> () {
+// CHECK:     inst @test.param1 (i1$ %clk) -> (i32$ %count)
@@ -0,0 +1,13 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module bar;
+endmodule
+
+program foo;
+    bit clk;
+    always #1ns clk = ~clk;
+    // CHECK-ERR: error: `always` procedure in program
+    bar b();
+    // CHECK-ERR: error: instance of `bar` in program
+endprogram
@@ -0,0 +1,33 @@
+// RUN: moore %s
+
+// See IEEE 1800-2017 §28 "Gate-level and switch-level modeling"
+
+module foo (input a, b, c, d, output y);
+    wire w0, w1, w2;
+    and g0 (y, a, b);
+    nand (strong0, strong1) #1ns g1 (w0, a, b, c), g2 (w1, c, d);
+    or #(1ns, 2ns) (w2, a, b);
+    xor (w2, a, b);
+    xnor (w2, a, b);
+    buf (w0, w1, a);
+    not (w0, a);
+    bufif0 (w0, a, b);
+    bufif1 (weak1, weak0) (w0, a, b);
+    notif0 #(1ns, 2ns, 3ns) (w0, a, b);
+    notif1 (w0, a, b);
+    nmos (w0, a, b);
+    pmos (w0, a, b);
+    rnmos (w0, a, b);
+    rpmos (w0, a, b);
+    cmos (w0, a, b, c);
+    rcmos (w0, a, b, c);
+    tran (w0, w1);
+    rtran (w0, w1);
+    tranif0 (w0, w1, a);
+    tranif1 (w0, w1, a);
+    rtranif0 (w0, w1, a);
+    rtranif1 (w0, w1, a);
+    pullup (w0);
+    pulldown (pull0) (w1);
+    pullup (pull0, pull1) p0 (w2);
+endmodule
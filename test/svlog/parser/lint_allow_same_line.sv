@@ -0,0 +1,10 @@
+// RUN: moore %s -e foo -Werror=trailing-comma
+
+module foo (input logic a, b, output logic [1:0] y);
+    assign y = {a, b,}; // moore: allow(trailing-comma)
+endmodule
+
+// CHECK: entity @foo (i1$ %a, i1$ %b) -> (i2$ %y) {
+// CHECK:     %concat = inss i2 %1, i1 %a.prb, 1, 1
+// CHECK:     drv i2$ %y, %concat, %2
+// CHECK: }
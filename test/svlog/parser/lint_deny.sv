@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo -Werror=trailing-comma
+// FAIL
+
+module foo;
+    bar #(.W(1),) i_bar();
+endmodule
+
+module bar #(parameter int W = 1);
+endmodule
+
+// CHECK-ERR: error: superfluous trailing comma
+// CHECK-ERR: = note: Denied by lint `trailing-comma`
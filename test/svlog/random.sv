@@ -0,0 +1,29 @@
+// RUN: moore %s -e foo
+
+module foo;
+    int seed, a;
+    int unsigned b;
+    initial begin
+        a = $random(seed);
+        a = $random;
+        b = $urandom(42);
+        b = $urandom_range(10, 3);
+    end
+endmodule
+
+// CHECK:     %3 = eq i32 %seed.shadow.ld, %2
+// CHECK:     %4 = const i32 259341593
+// CHECK:     %7 = const i32 69069
+// CHECK:     %8 = smul i32 %6, %7
+// CHECK:     %10 = add i32 %8, %9
+// CHECK:     drv i32$ %seed, %10, %11
+// CHECK:     %18 = shr i32 %10, i32 %17, i32 %16
+// CHECK:     %22 = const i32 2155871744
+// CHECK:     %30 = sub i32 %21, %29
+// CHECK:     drv i32$ %a, %30, %31
+// CHECK:     %33 = call i32 @moore.random (i32 %32)
+// CHECK:     call void @moore.urandom.seed (i32 %36)
+// CHECK:     %37 = call i32 @moore.urandom (i32 %35)
+// CHECK:     %42 = ult i32 %40, %41
+// CHECK:     %52 = call i64 @moore.urandom.below (i32 %39, i64 %51)
+// CHECK:     %54 = add i32 %53, %44
@@ -0,0 +1,34 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input logic clk, input logic [1:0] s, input logic a, output logic y, z, q, r);
+    always_comb begin
+        if (a) y = 1;
+        unique case (s)
+            0: z = 0;
+            1: z = 1;
+        endcase
+    end
+    always_comb begin
+        if (a) r = 1;
+        else if (s == 0) r = 0;
+    end
+    always_ff q = a;
+    always_ff @(posedge clk) begin
+        logic t;
+        t = a;
+        q <= t;
+        r = 1;
+    end
+    always_comb y <= a;
+endmodule
+
+// CHECK-ERR: port `y` is not assigned on all paths through `always_comb` procedure; latch inferred
+// CHECK-ERR: port `r` is not assigned on all paths through `always_comb` procedure; latch inferred
+// CHECK-ERR: `always_ff` procedure is not triggered by a clock edge
+// CHECK-ERR: blocking assignment in `always_ff` procedure
+// CHECK-ERR: blocking assignment in `always_ff` procedure
+// CHECK-ERR: nonblocking assignment in `always_comb` procedure
+// CHECK-ERR: port `y` is written by more than one procedure
+// CHECK-ERR: port `r` is written by more than one procedure
+// CHECK-ERR: port `q` is written by more than one procedure
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+
+module foo #(parameter int N = 4) (input logic [1:0] s, input logic [N-1:0] a, output logic y, output logic [N-1:0] z);
+    always_comb begin
+        casez (s)
+            2'b1?: y = a[0];
+            2'b01: y = a[1];
+            2'b00: y = a[2];
+        endcase
+    end
+    always_comb begin
+        for (int unsigned i = 0; i < N; i++) begin
+            z[i] = a[N-1-i];
+        end
+    end
+endmodule
+
+// CHECK-NOT: latch inferred
@@ -0,0 +1,52 @@
+// RUN: moore %s -e foo
+
+module foo (input logic [2:0] sel, input logic [1:0] mode, input logic [3:0] req, output logic y);
+    typedef enum logic [1:0] { IDLE, BUSY, DONE, ERR } state_t;
+    state_t state;
+
+    always_comb begin
+        y = 0;
+        case (state)
+            IDLE: y = 1;
+            BUSY: y = 0;
+        endcase
+        case (sel)
+            0, 1: y = 1;
+            1: y = 0;
+            default: y = 0;
+        endcase
+        casez (req)
+            4'b1???: y = 1;
+            4'b11??: y = 0;
+            4'b0???: y = 0;
+        endcase
+        unique casez (req)
+            4'b1???: y = 1;
+            4'b?1??: y = 0;
+            4'b00??: y = 1;
+        endcase
+        case (mode)
+            3'b100: y = 1;
+            2'b01, 2'b10, 2'b11: y = 0;
+        endcase
+        // moore: allow(incomplete-case)
+        case (sel)
+            0, 1, 2: y = 1;
+        endcase
+    end
+endmodule
+
+// CHECK-ERR: warning: case statement does not handle all values of `state`
+// CHECK-ERR: = note: Unhandled values: `DONE`, `ERR`
+// CHECK-ERR: = note: Add items for these values or a `default` item
+// CHECK-ERR: warning: case item is unreachable
+// CHECK-ERR: --> case_lint.sv:15:13-14:
+// CHECK-ERR: = note: Earlier items already match all its values:
+// CHECK-ERR: --> case_lint.sv:14:13-17:
+// CHECK-ERR: --> case_lint.sv:20:13-20:
+// CHECK-ERR: warning: case item overlaps with an earlier item in `unique` case statement
+// CHECK-ERR: = note: Both items match `4'b1100`:
+// CHECK-ERR: --> case_lint.sv:29:13-19:
+// CHECK-ERR: = note: The item matches no value of type `logic [1:0]`
+// CHECK-ERR: warning: case statement does not handle all values of `mode`
+// CHECK-ERR: = note: Unhandled values: `2'b00`
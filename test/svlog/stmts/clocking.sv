@@ -0,0 +1,32 @@
+// RUN: moore %s -e foo
+
+module foo;
+    bit clk;
+    bit [7:0] a, b, c, x;
+    clocking cb @(posedge clk);
+        input #1step a;
+        input #2 c;
+        output #3 b;
+    endclocking
+    always @(cb) begin
+        x <= cb.a + cb.c;
+        cb.b <= x;
+    end
+endmodule
+
+// CHECK: proc %foo.clocking.26.0 (i1$ %clk, i8$ %a, i8$ %cb.c.skewed) -> ({i32, time}$ %cb, i8$ %cb.a, i8$ %cb.c) {
+// CHECK:     drv i8$ %cb.a, %a.prb, %4
+// CHECK:     %5 = prb i8$ %cb.c.skewed
+// CHECK:     drv i8$ %cb.c, %5, %4
+// CHECK:     %12 = {i32 %9, time %11}
+// CHECK:     drv {i32, time}$ %cb, %12, %4
+// CHECK: proc %foo.always.75.0 (i8$ %cb.a, i8$ %cb.c, {i32, time}$ %cb) -> (i8$ %b, i8$ %x) {
+// CHECK:     %impledge = neq {i32, time} %cb.prb, %cb.prb1
+// CHECK:     %5 = const time 3ns
+// CHECK:     drv i8$ %b, %x.shadow.ld, %5
+// CHECK: entity @foo () -> () {
+// CHECK:     %cb = sig {i32, time} %7
+// CHECK:     %cb.c.skewed = sig i8 %9
+// CHECK:     %10 = const time 2ns
+// CHECK:     drv i8$ %cb.c.skewed, %c.prb, %10
+// CHECK:     inst %foo.clocking.26.0 (i1$ %clk, i8$ %a, i8$ %cb.c.skewed) -> ({i32, time}$ %cb, i8$ %cb.a, i8$ %cb.c)
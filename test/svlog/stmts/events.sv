@@ -0,0 +1,32 @@
+// RUN: moore %s -e foo
+
+module foo;
+    event e;
+    bit t;
+    initial begin
+        -> e;
+        ->> #1 e;
+    end
+    initial begin
+        @e;
+        wait (e.triggered) t = 1;
+    end
+endmodule
+
+// CHECK: proc %foo.initial.20.0 () -> ({i32, time}$ %e) {
+// CHECK:     %2 = extf i32, {i32, time} %e.shadow.ld, 0
+// CHECK:     %4 = add i32 %2, %3
+// CHECK:     %6 = call time @moore.time (time %5)
+// CHECK:     %7 = {i32 %4, time %6}
+// CHECK:     drv {i32, time}$ %e, %7, %8
+// CHECK:     %15 = add time %13, %14
+// CHECK:     %16 = {i32 %11, time %15}
+// CHECK:     drv {i32, time}$ %e, %16, %14
+// CHECK: wait_check:
+// CHECK:     %7 = eq time %2, %6
+// CHECK:     %8 = and i1 %4, %7
+// CHECK: wait:
+// CHECK:     wait %wait_check, %e
+// CHECK: entity @foo () -> () {
+// CHECK:     %2 = {i32 %0, time %1}
+// CHECK:     %e = sig {i32, time} %2
@@ -0,0 +1,20 @@
+// RUN: moore %s -e foo
+
+module foo;
+    int count;
+    initial begin
+        #10 count = 1;
+        $finish;
+        count = 2;
+    end
+    final count = count + 1;
+endmodule
+
+// CHECK: proc %foo.initial.31.0 () -> (i32$ %count) {
+// CHECK:     call void @moore.finish (i32 %5)
+// CHECK:     halt
+// CHECK: finished:
+// CHECK: proc %foo.final.47.0 () -> (i32$ %count) {
+// CHECK:     call void @moore.final ()
+// CHECK:     wait %body
+// CHECK: body:
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    bit a;
+    final begin
+        #1 a = 1;
+        $finish(2);
+    end
+endmodule
+
+// CHECK-ERR: timing control in `final` procedure
@@ -0,0 +1,32 @@
+// RUN: moore %s -e foo
+
+module foo (input logic [1:0] a, input logic b, c, output logic [1:0] z);
+    always_comb begin
+        unique if (b) z = 0;
+        else if (c) z = 1;
+        priority case (a)
+            0: z = 2;
+            1, 2: z = 3;
+        endcase
+        unique0 case (a)
+            0: z = 1;
+            default: z = 0;
+        endcase
+    end
+endmodule
+
+// CHECK:     %10 = or i1 %7, %9
+// CHECK:     %11 = or i1 %8, %4
+// CHECK:     %12 = not i1 %11
+// CHECK:     br %12, %violation_exit, %violation
+// CHECK:     call void @moore.violation (i32 %13, i32 %14, i32 %15)
+// CHECK:     br %10, %violation_exit1, %violation1
+// CHECK:     call void @moore.violation (i32 %16, i32 %17, i32 %18)
+// CHECK:     br %2, %if_false, %if_true
+// CHECK:     %33 = not i1 %32
+// CHECK:     br %33, %violation_exit2, %violation2
+// CHECK:     call void @moore.violation (i32 %38, i32 %39, i32 %40)
+// CHECK:     br %48, %violation_exit3, %violation3
+// CHECK:     %55 = const i32 1
+// CHECK:     %56 = const i32 11
+// CHECK:     call void @moore.violation (i32 %55, i32 %56, i32 %57)
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo -Wtruncation
+
+module foo (input logic [15:0] a, output logic [7:0] x, y, z);
+    assign x = a;
+    // moore: allow(truncation)
+    assign y = a;
+    assign z = a; /* moore: allow(truncation, sign) */
+    bar #(.W(1),) i_bar();
+endmodule
+
+module bar #(parameter int W = 1);
+endmodule
+
+// CHECK-ERR: warning: superfluous trailing comma
+// CHECK-ERR: = note: Reported by lint `trailing-comma`, which can be disabled with `-W no-trailing-comma` or `// moore: allow(trailing-comma)`
+// CHECK-ERR: warning: `a` implicitly truncated from 16 to 8 bits
+// CHECK-ERR: --> lint_allow.sv:4:16-17:
+// CHECK-ERR: = note: Reported by lint `truncation`, which can be disabled with `-W no-truncation` or `// moore: allow(truncation)`
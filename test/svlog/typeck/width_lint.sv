@@ -0,0 +1,19 @@
+// RUN: moore %s -e foo -Wall -Wno-extension
+
+module foo (input logic [15:0] a, input logic signed [7:0] s);
+    logic [7:0] y, z, q, u;
+    logic [15:0] w;
+    assign y = a;
+    assign z = 300;
+    assign q = 8'hff;
+    assign u = s;
+    assign w = y;
+    bar b(.x(a[3:0]));
+endmodule
+
+module bar (input logic [3:0] x);
+endmodule
+
+// CHECK-ERR: warning: `a` implicitly truncated from 16 to 8 bits
+// CHECK-ERR: warning: unsized literal `300` does not fit into 8 bits of `logic unsigned [7:0]`
+// CHECK-ERR: warning: `s` implicitly converted from signed to unsigned